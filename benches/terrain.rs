@@ -0,0 +1,143 @@
+//! Benchmarks for generating terrain meshes and colliders.
+//!
+//! Run with `cargo bench --bench terrain`. Everything uses
+//! [`TEST_TERRAIN`], which is about 20,000 km in radius,
+//! with 8 subdivisions.
+//!
+//! Rough timings to expect from an optimized build on a desktop CPU:
+//!
+//! - Generating every LoD level takes a few milliseconds, as each of the
+//!   9 levels samples 512 points of noise with 8 octaves each.
+//! - Building a mesh out of already generated LoD levels is a matter of
+//!   copying up to 512 vertices per level and estimating their normals,
+//!   so it takes tens of microseconds at most. Stitch blending only
+//!   touches a handful of vertices per level, so it should barely show.
+//! - Collider decomposition is by far the slowest part, at a few
+//!   milliseconds per collider. V-HACD's voxelization takes up a fixed
+//!   share of that, with the rest growing with the amount of vertices
+//!   under the vessel, from about a dozen under a 10 m vessel to a few
+//!   hundred under a 250 m one. Raising the concavity setting trades
+//!   how closely the collider follows the terrain for fewer convex
+//!   pieces, and so less time spent decomposing.
+//!
+//! These are ballpark figures to spot regressions by orders of magnitude,
+//! not targets. Compare against a baseline from the same machine for
+//! anything finer than that.
+
+use core::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use hack_club_space_program::{
+    components::main_game::celestial::Terrain,
+    resources::simulation::{TerrainColliderConfig, TerrainMeshConfig},
+    test_util::{TEST_TERRAIN, TerrainLods, terrain_collider_under},
+};
+
+/// The angle the camera looks at the terrain from, in radians.
+///
+/// This is away from the +X axis, so that LoD level 1 wraps around
+/// the end of LoD level 0.
+const FOCUS: f64 = 0.1;
+
+fn lod_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("terrain_lods");
+
+    for subdivs in [0, 4, TEST_TERRAIN.subdivs] {
+        let terrain = Terrain {
+            subdivs,
+            ..TEST_TERRAIN
+        };
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(subdivs),
+            &terrain,
+            |b, &terrain| {
+                b.iter(|| TerrainLods::new(black_box(terrain), black_box(FOCUS)));
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn mesh_buffers(c: &mut Criterion) {
+    let lods = TerrainLods::new(TEST_TERRAIN, FOCUS);
+    let stitch_blend = TerrainMeshConfig::default().stitch_blend;
+
+    let mut group = c.benchmark_group("terrain_mesh");
+
+    let levels = [None]
+        .into_iter()
+        .chain((0..=TEST_TERRAIN.subdivs).step_by(2).map(Some));
+    for max_level in levels {
+        let id = max_level.map_or_else(|| "min".to_owned(), |level| level.to_string());
+
+        group.bench_with_input(BenchmarkId::new("blended", &id), &max_level, |b, &level| {
+            b.iter(|| lods.build_mesh(black_box(level), stitch_blend));
+        });
+        group.bench_with_input(
+            BenchmarkId::new("unblended", &id),
+            &max_level,
+            |b, &level| {
+                b.iter(|| lods.build_mesh(black_box(level), 0));
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn collider_decomposition(c: &mut Criterion) {
+    let config = TerrainColliderConfig::default();
+
+    let mut group = c.benchmark_group("terrain_collider");
+    // Each decomposition takes a while, so don't wait for too many
+    group.sample_size(20);
+
+    for vessel_size in [10.0, 50.0, 250.0] {
+        let (_, verts) = terrain_collider_under(TEST_TERRAIN, vessel_size, &config);
+
+        group.bench_with_input(
+            BenchmarkId::new("vessel_size", format!("{vessel_size}m/{verts}verts")),
+            &vessel_size,
+            |b, &size| b.iter(|| terrain_collider_under(TEST_TERRAIN, black_box(size), &config)),
+        );
+    }
+
+    for subdivs in [6, TEST_TERRAIN.subdivs, 10] {
+        let terrain = Terrain {
+            subdivs,
+            ..TEST_TERRAIN
+        };
+        let (_, verts) = terrain_collider_under(terrain, 50.0, &config);
+
+        group.bench_with_input(
+            BenchmarkId::new("subdivs", format!("{subdivs}/{verts}verts")),
+            &terrain,
+            |b, &terrain| b.iter(|| terrain_collider_under(black_box(terrain), 50.0, &config)),
+        );
+    }
+
+    for concavity in [0.005, config.concavity, 0.1] {
+        let config = TerrainColliderConfig {
+            concavity,
+            ..config
+        };
+
+        group.bench_with_input(
+            BenchmarkId::new("concavity", concavity),
+            &config,
+            |b, config| b.iter(|| terrain_collider_under(TEST_TERRAIN, 50.0, black_box(config))),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    lod_generation,
+    mesh_buffers,
+    collider_decomposition
+);
+criterion_main!(benches);
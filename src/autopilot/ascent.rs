@@ -0,0 +1,152 @@
+//! Launch aids.
+
+use bevy::{math::DVec2, prelude::*};
+
+use crate::autopilot::{AutopilotStatus, ThrustCommand};
+
+/// A launch autopilot that burns at full throttle while pitching
+/// over from straight up towards the horizon, until the vessel's
+/// apoapsis is high enough.
+///
+/// The vessel pitches over counterclockwise around its parent body,
+/// i.e. the same way the body spins by default.
+///
+/// While the active vessel has this component, it flies itself instead of
+/// following the player's inputs. The component gets removed once done,
+/// or straight away if the vessel has no
+/// [`Engine`][crate::components::main_game::vessel::Engine] to burn with.
+#[derive(Clone, Copy, Component, Debug, PartialEq, Reflect)]
+#[reflect(Component, Debug, PartialEq, Clone)]
+pub struct GravityTurn {
+    /// The altitude at which to start pitching over, in meters.
+    pub start_altitude: f64,
+    /// The altitude at which to reach `target_angle`, in meters.
+    pub end_altitude: f64,
+    /// The pitch to end up at, in radians away from straight up.
+    ///
+    /// A pitch of π/2 is flat along the horizon.
+    pub target_angle: f64,
+    /// The apoapsis altitude at which to cut the engines, in meters.
+    pub target_apoapsis: f64,
+}
+
+impl GravityTurn {
+    /// Gets the commanded pitch at the given altitude, in radians
+    /// away from straight up.
+    ///
+    /// The pitch goes linearly from zero at `start_altitude`
+    /// to `target_angle` at `end_altitude`.
+    #[must_use]
+    pub fn pitch_at(&self, altitude: f64) -> f64 {
+        let band = self.end_altitude - self.start_altitude;
+        let progress = if band > 0.0 {
+            ((altitude - self.start_altitude) / band).clamp(0.0, 1.0)
+        } else if altitude < self.start_altitude {
+            0.0
+        } else {
+            1.0
+        };
+
+        self.target_angle * progress
+    }
+
+    /// Decides how to burn this tick.
+    ///
+    /// `up` is the unit vector pointing from the parent body's center
+    /// to the vessel, and `apoapsis` is the altitude of the vessel's
+    /// current apoapsis, which is infinite when escaping.
+    #[must_use]
+    pub fn step(
+        &self,
+        altitude: f64,
+        apoapsis: f64,
+        up: DVec2,
+    ) -> (ThrustCommand, AutopilotStatus) {
+        if apoapsis >= self.target_apoapsis {
+            return (ThrustCommand::IDLE, AutopilotStatus::Done);
+        }
+
+        let (sin, cos) = self.pitch_at(altitude).sin_cos();
+        let command = ThrustCommand {
+            direction: up * cos + up.perp() * sin,
+            throttle: 1.0,
+        };
+
+        (command, AutopilotStatus::Active)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f64::consts::FRAC_PI_2;
+
+    const TURN: GravityTurn = GravityTurn {
+        start_altitude: 1000.0,
+        end_altitude: 41_000.0,
+        target_angle: 1.2,
+        target_apoapsis: 80_000.0,
+    };
+
+    #[test]
+    fn pitch_interpolates_across_band() {
+        for (altitude, expected) in [
+            (-50.0, 0.0),
+            (0.0, 0.0),
+            (1000.0, 0.0),
+            (11_000.0, 0.3),
+            (21_000.0, 0.6),
+            (31_000.0, 0.9),
+            (41_000.0, 1.2),
+            (100_000.0, 1.2),
+        ] {
+            let pitch = TURN.pitch_at(altitude);
+            assert!(
+                (pitch - expected).abs() < 1e-12,
+                "pitch of {pitch} at {altitude} m, expected {expected}"
+            );
+        }
+
+        let mut previous = 0.0;
+        for altitude in (0..50).map(|i| f64::from(i) * 1000.0) {
+            let pitch = TURN.pitch_at(altitude);
+            assert!(pitch >= previous, "pitch went back up at {altitude} m");
+            previous = pitch;
+        }
+
+        let sudden = GravityTurn {
+            end_altitude: TURN.start_altitude,
+            ..TURN
+        };
+        assert!(sudden.pitch_at(999.0).abs() < 1e-12);
+        assert!((sudden.pitch_at(1000.0) - 1.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn burns_towards_pitch() {
+        // On the +X side of the body, where counterclockwise is +Y
+        let (command, status) = TURN.step(21_000.0, 30_000.0, DVec2::X);
+
+        assert_eq!(status, AutopilotStatus::Active);
+        assert!((command.throttle - 1.0).abs() < 1e-12);
+        assert!((command.direction.length() - 1.0).abs() < 1e-12);
+        assert!((DVec2::X.angle_to(command.direction) - 0.6).abs() < 1e-12);
+
+        let flat = GravityTurn {
+            target_angle: FRAC_PI_2,
+            ..TURN
+        };
+        let (command, _) = flat.step(50_000.0, 30_000.0, DVec2::Y);
+        assert!((command.direction - DVec2::NEG_X).length() < 1e-12);
+    }
+
+    #[test]
+    fn cuts_engines_at_target_apoapsis() {
+        let (command, status) = TURN.step(30_000.0, 80_000.0, DVec2::Y);
+        assert_eq!(status, AutopilotStatus::Done);
+        assert_eq!(command, ThrustCommand::IDLE);
+
+        let (_, status) = TURN.step(30_000.0, f64::INFINITY, DVec2::Y);
+        assert_eq!(status, AutopilotStatus::Done);
+    }
+}
@@ -0,0 +1,177 @@
+//! Landing aids.
+
+use bevy::{math::DVec2, prelude::*};
+
+use crate::autopilot::{AutopilotStatus, ThrustCommand};
+
+/// A pre-landing autopilot that nulls the vessel's horizontal
+/// (surface-tangential) velocity while holding its altitude.
+///
+/// While the active vessel has this component, it flies itself instead of
+/// following the player's inputs. The component gets removed once done,
+/// or once the vessel can't burn anymore, e.g. because it has no
+/// [`Engine`][crate::components::main_game::vessel::Engine].
+#[derive(Clone, Copy, Component, Debug, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+pub struct KillHorizontalVelocity {
+    /// How aggressively velocity errors are corrected, in 1/s.
+    ///
+    /// The autopilot aims for an acceleration of `-gain * velocity`
+    /// on both axes.
+    pub gain: f64,
+    /// The horizontal speed, in m/s, below which the autopilot is done.
+    pub threshold: f64,
+}
+
+impl Default for KillHorizontalVelocity {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            threshold: 0.1,
+        }
+    }
+}
+
+/// The state of a vessel relative to the surface below it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SurfaceState {
+    /// The unit vector pointing from the parent body's center to the vessel.
+    pub up: DVec2,
+    /// The vessel's velocity relative to the surface below it, in m/s.
+    pub velocity: DVec2,
+    /// The magnitude of the parent body's gravity at the vessel, in m/s².
+    pub gravity: f64,
+}
+
+impl SurfaceState {
+    /// Gets the vertical (radial) component of the surface velocity.
+    #[must_use]
+    pub fn vertical_speed(&self) -> f64 {
+        self.velocity.dot(self.up)
+    }
+
+    /// Gets the horizontal (surface-tangential) part of the surface velocity.
+    #[must_use]
+    pub fn horizontal_velocity(&self) -> DVec2 {
+        self.velocity - self.up * self.vertical_speed()
+    }
+}
+
+impl KillHorizontalVelocity {
+    /// Decides how to burn this tick.
+    ///
+    /// `max_accel` is the acceleration the vessel gets at full throttle,
+    /// in m/s², and `has_fuel` is whether the vessel can still burn at all.
+    ///
+    /// If the vessel can't both hold altitude and kill its horizontal speed,
+    /// holding altitude is prioritized.
+    #[must_use]
+    pub fn step(
+        &self,
+        surface: SurfaceState,
+        max_accel: f64,
+        has_fuel: bool,
+    ) -> (ThrustCommand, AutopilotStatus) {
+        let horizontal = surface.horizontal_velocity();
+
+        if horizontal.length() < self.threshold {
+            return (ThrustCommand::IDLE, AutopilotStatus::Done);
+        }
+
+        if !has_fuel || max_accel <= 0.0 {
+            return (ThrustCommand::IDLE, AutopilotStatus::GaveUp);
+        }
+
+        let hold_accel = self
+            .gain
+            .mul_add(-surface.vertical_speed(), surface.gravity);
+        let vertical_accel = surface.up * hold_accel;
+        let horizontal_accel = -horizontal * self.gain;
+
+        let vertical_len = vertical_accel.length();
+        let horizontal_budget = (max_accel * max_accel - vertical_len * vertical_len)
+            .max(0.0)
+            .sqrt();
+
+        let accel = if vertical_len >= max_accel {
+            vertical_accel.normalize_or_zero() * max_accel
+        } else {
+            vertical_accel + horizontal_accel.clamp_length_max(horizontal_budget)
+        };
+
+        let command = ThrustCommand {
+            direction: accel.normalize_or_zero(),
+            throttle: (accel.length() / max_accel).clamp(0.0, 1.0),
+        };
+
+        (command, AutopilotStatus::Active)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f64 = 1.0 / 60.0;
+    const GRAVITY: f64 = 9.81;
+
+    /// Simulates a point mass above flat ground until the autopilot
+    /// stops, returning the final surface state and autopilot status.
+    fn simulate(
+        velocity: DVec2,
+        max_accel: f64,
+        mut fuel_ticks: u32,
+        ticks: u32,
+    ) -> (SurfaceState, AutopilotStatus) {
+        let autopilot = KillHorizontalVelocity::default();
+        let mut surface = SurfaceState {
+            up: DVec2::Y,
+            velocity,
+            gravity: GRAVITY,
+        };
+
+        for _ in 0..ticks {
+            let (command, status) = autopilot.step(surface, max_accel, fuel_ticks > 0);
+
+            if status != AutopilotStatus::Active {
+                return (surface, status);
+            }
+
+            if command.throttle > 0.0 {
+                fuel_ticks -= 1;
+            }
+
+            let thrust = command.direction * command.throttle * max_accel;
+            surface.velocity += (thrust - surface.up * GRAVITY) * DT;
+
+            assert!(
+                surface.vertical_speed().abs() < 5.0,
+                "vertical speed got out of control: {}",
+                surface.vertical_speed()
+            );
+        }
+
+        (surface, AutopilotStatus::Active)
+    }
+
+    #[test]
+    fn kills_horizontal_velocity() {
+        let (surface, status) = simulate(DVec2::new(40.0, -2.0), 30.0, u32::MAX, 60 * 30);
+
+        assert_eq!(status, AutopilotStatus::Done);
+        assert!(surface.horizontal_velocity().length() < 0.1);
+        assert!(surface.vertical_speed().abs() < 0.5);
+    }
+
+    #[test]
+    fn gives_up_without_fuel() {
+        let (surface, status) = simulate(DVec2::new(-40.0, 1.0), 30.0, 60, 120);
+
+        assert_eq!(status, AutopilotStatus::GaveUp);
+        assert!(surface.horizontal_velocity().length() > 0.1);
+
+        let (command, status) = KillHorizontalVelocity::default().step(surface, 30.0, false);
+        assert_eq!(command, ThrustCommand::IDLE);
+        assert_eq!(status, AutopilotStatus::GaveUp);
+    }
+}
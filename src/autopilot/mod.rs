@@ -0,0 +1,37 @@
+//! Simple controllers that fly a vessel on the player's behalf.
+
+use bevy::math::DVec2;
+
+pub mod ascent;
+pub mod landing;
+
+/// What an autopilot wants the vessel's engines to do this tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ThrustCommand {
+    /// The unit vector the vessel should point its engines' thrust towards.
+    ///
+    /// Zero if the autopilot doesn't care about the vessel's facing.
+    pub direction: DVec2,
+    /// The throttle to burn at, in the range 0..=1.
+    pub throttle: f64,
+}
+
+impl ThrustCommand {
+    /// A command to not burn at all.
+    pub const IDLE: Self = Self {
+        direction: DVec2::ZERO,
+        throttle: 0.0,
+    };
+}
+
+/// The state an autopilot is in after a tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AutopilotStatus {
+    /// The autopilot is still working towards its goal.
+    Active,
+    /// The autopilot has reached its goal.
+    Done,
+    /// The autopilot can't reach its goal, e.g. because
+    /// the vessel ran out of fuel.
+    GaveUp,
+}
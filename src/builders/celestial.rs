@@ -1,10 +1,18 @@
-use crate::components::main_game::{
-    camera::Focusable,
-    celestial::{CelestialBody, Terrain},
-    frames::{RigidSpaceVelocity, RootSpaceLinearVelocity, RootSpacePosition},
+use crate::{
+    components::main_game::{
+        camera::Focusable,
+        celestial::{CelestialBody, Terrain},
+        frames::{RigidSpaceVelocity, RootSpaceAngle, RootSpaceLinearVelocity, RootSpacePosition},
+        relations::{CelestialParent, RailMode},
+    },
+    consts::{
+        CELESTIAL_COLLISION_GROUP, DEFAULT_SURFACE_FRICTION, DEFAULT_SURFACE_RESTITUTION,
+        terrain::MAX_LOD_LEVEL,
+    },
 };
 use bevy::{math::DVec2, prelude::*, sprite_render::Material2d};
 use bevy_rapier2d::prelude::*;
+use keplerian_sim::Orbit2D;
 
 /// Recommended additional components:
 /// - [`CelestialParent`][crate::components::relations::CelestialParent]
@@ -17,17 +25,96 @@ pub struct CelestialBodyBuilder<M: Material2d> {
     pub angle: f32,
     pub mesh: Mesh2d,
     pub material: MeshMaterial2d<M>,
+    /// The material of the body's surface.
+    ///
+    /// Use [`CelestialSurface::default`] unless the body needs
+    /// a rougher, smoother or bouncier surface than usual.
+    pub surface: CelestialSurface,
+}
+
+/// The material of a celestial body's surface.
+///
+/// The surface material is kept in [`Friction`] and [`Restitution`]
+/// rather than in the [`Collider`] itself, so it stays the same
+/// when the terrain collider gets rebuilt.
+#[derive(Bundle, Clone, Copy, Debug)]
+pub struct CelestialSurface {
+    pub friction: Friction,
+    pub restitution: Restitution,
+}
+
+impl CelestialSurface {
+    /// Creates a surface with the given friction
+    /// and restitution (i.e. bounciness) coefficients.
+    #[must_use]
+    pub const fn new(friction: f32, restitution: f32) -> Self {
+        Self {
+            friction: Friction::new(friction),
+            restitution: Restitution::coefficient(restitution),
+        }
+    }
+}
+
+impl Default for CelestialSurface {
+    /// A surface with [`DEFAULT_SURFACE_FRICTION`]
+    /// and [`DEFAULT_SURFACE_RESTITUTION`].
+    fn default() -> Self {
+        Self::new(DEFAULT_SURFACE_FRICTION, DEFAULT_SURFACE_RESTITUTION)
+    }
 }
 
 impl<M: Material2d> CelestialBodyBuilder<M> {
+    /// Sets up a body from its real-world radius, in meters,
+    /// and mass, in kilograms.
+    ///
+    /// The mesh and material are left as the default handles,
+    /// so set them afterwards if the body should be drawn.
+    /// The surface gets the default friction and restitution.
+    ///
+    /// # Output
+    /// The builder, along with the [`CelestialParent`] and
+    /// [`RailMode::Orbit`] to insert alongside the built body
+    /// if it orbits around `parent_orbit`'s parent.
     #[must_use]
-    pub(crate) const fn base_bundle() -> impl Bundle {
+    pub fn from_physical(
+        name: impl Into<Name>,
+        radius: f64,
+        mass: f64,
+        parent_orbit: Option<(Entity, Orbit2D)>,
+    ) -> (Self, Option<(CelestialParent, RailMode)>) {
+        let builder = Self {
+            name: name.into(),
+            #[expect(clippy::cast_possible_truncation)]
+            radius: radius as f32,
+            mass,
+            angle: 0.0,
+            mesh: Mesh2d::default(),
+            material: MeshMaterial2d::default(),
+            surface: CelestialSurface::default(),
+        };
+
+        let orbit = parent_orbit
+            .map(|(entity, orbit)| (CelestialParent { entity }, RailMode::Orbit(orbit)));
+
+        (builder, orbit)
+    }
+
+    #[must_use]
+    pub(crate) const fn base_bundle(surface: CelestialSurface) -> impl Bundle {
         (
             RigidBody::KinematicPositionBased,
             RootSpacePosition(DVec2::ZERO),
             RootSpaceLinearVelocity(DVec2::ZERO),
-            Friction::new(0.7),
+            surface,
             Focusable,
+            CollisionGroups {
+                memberships: CELESTIAL_COLLISION_GROUP,
+                filters: Group::ALL,
+            },
+            SolverGroups {
+                memberships: CELESTIAL_COLLISION_GROUP,
+                filters: Group::ALL,
+            },
         )
     }
 
@@ -49,12 +136,13 @@ impl<M: Material2d> CelestialBodyBuilder<M> {
             }),
             self.mesh,
             self.material,
-            Self::base_bundle(),
+            Self::base_bundle(self.surface),
             RigidSpaceVelocity {
                 // TODO: Celestial rotation
                 angvel: 0.0,
                 linvel: Vec2::NAN,
             },
+            RootSpaceAngle(f64::from(self.angle)),
             Transform::from_rotation(Quat::from_rotation_z(self.angle)),
         )
     }
@@ -65,9 +153,17 @@ impl<M: Material2d> CelestialBodyBuilder<M> {
         (self.shared_components(), Collider::ball(radius))
     }
 
+    /// # Panics
+    /// Panics if the terrain's subdivisions go past [`MAX_LOD_LEVEL`].
     #[must_use]
     #[expect(clippy::cast_possible_truncation)]
     pub fn build_with_terrain(self, terrain: Terrain) -> impl Bundle {
+        assert!(
+            terrain.subdivs <= MAX_LOD_LEVEL,
+            "terrain has {} subdivisions, but at most {MAX_LOD_LEVEL} are supported",
+            terrain.subdivs
+        );
+
         (
             self.shared_components(),
             terrain,
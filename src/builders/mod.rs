@@ -1,4 +1,5 @@
 pub(crate) mod button;
 pub mod camera;
 pub mod celestial;
+pub mod solar_system;
 pub mod vessel;
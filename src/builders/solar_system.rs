@@ -0,0 +1,349 @@
+//! Declarative descriptions of whole solar systems.
+//!
+//! Rather than spawning each body by hand, describe the hierarchy as
+//! a [`SystemConfig`] and let [`SystemConfig::spawn`] wire up the
+//! [`CelestialParent`][crate::components::main_game::relations::CelestialParent]
+//! links and [`RailMode::Orbit`][crate::components::main_game::relations::RailMode::Orbit]s.
+
+use core::{error::Error, fmt::Display};
+
+use bevy::{math::DVec2, prelude::*, sprite_render::Material2d};
+use keplerian_sim::Orbit2D;
+
+use crate::{
+    builders::celestial::CelestialBodyBuilder,
+    components::main_game::{celestial::Terrain, frames::RootSpacePosition},
+    consts::terrain::MAX_LOD_LEVEL,
+    orbit::{orbit_from_elements, sphere_of_influence},
+    resources::simulation::GravityConstants,
+};
+
+/// The orbit of a body around its parent, as classical elements.
+///
+/// See [`orbit_from_elements`] for what each element means.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitSpec {
+    /// The semi-major axis, in meters.
+    pub semi_major_axis: f64,
+    /// The eccentricity, which must be below 1 for the orbit to be closed.
+    pub eccentricity: f64,
+    /// The angle of the periapsis from the +X axis, in radians.
+    pub arg_pe: f64,
+    /// The mean anomaly at a simulation time of zero, in radians.
+    pub mean_anomaly: f64,
+}
+
+impl OrbitSpec {
+    #[must_use]
+    pub const fn periapsis(self) -> f64 {
+        self.semi_major_axis * (1.0 - self.eccentricity)
+    }
+
+    #[must_use]
+    pub const fn apoapsis(self) -> f64 {
+        self.semi_major_axis * (1.0 + self.eccentricity)
+    }
+
+    /// Builds the orbit around a parent with the given
+    /// gravitational parameter.
+    #[must_use]
+    pub fn to_orbit(self, mu: f64) -> Orbit2D {
+        orbit_from_elements(
+            self.semi_major_axis,
+            self.eccentricity,
+            self.arg_pe,
+            self.mean_anomaly,
+            mu,
+        )
+    }
+}
+
+/// A celestial body in a [`SystemConfig`], along with every body
+/// orbiting it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BodySpec {
+    pub name: String,
+    /// The radius, in meters.
+    pub radius: f64,
+    /// The mass, in kilograms.
+    pub mass: f64,
+    /// Where the body starts in root space, in meters.
+    ///
+    /// Only top-level bodies can be placed like this, as the rest get
+    /// placed along their orbit. Top-level bodies without a position
+    /// start at the origin.
+    pub position: Option<DVec2>,
+    /// The orbit around the parent body.
+    ///
+    /// This must be set for every body with a parent,
+    /// and left unset for the top-level ones.
+    pub orbit: Option<OrbitSpec>,
+    /// The body's terrain, or [`None`] for a perfectly round body.
+    pub terrain: Option<Terrain>,
+    /// The bodies orbiting this one.
+    pub children: Vec<Self>,
+}
+
+/// A hierarchy of celestial bodies to spawn when entering the game.
+///
+/// While this resource exists, it gets spawned in
+/// [`GameScene::InGame`][crate::resources::scene::GameScene::InGame]
+/// instead of the [default][Self::default] system. Either way, the
+/// player's vessel starts out orbiting the first top-level body.
+#[derive(Clone, Debug, PartialEq, Resource)]
+pub struct SystemConfig {
+    /// The top-level bodies, which don't orbit anything.
+    pub bodies: Vec<BodySpec>,
+}
+
+impl Default for SystemConfig {
+    /// A single Earth-sized body with terrain, at the origin.
+    fn default() -> Self {
+        const RADIUS: f64 = 6_371_137.0;
+
+        Self {
+            bodies: vec![BodySpec {
+                name: "Body".into(),
+                radius: RADIUS,
+                mass: 5.972_168e24,
+                position: None,
+                orbit: None,
+                terrain: Some(Terrain {
+                    seed: 2401,
+                    octaves: 6,
+                    frequency: 400.0,
+                    gain: 0.4,
+                    lacunarity: 0.6,
+                    offset: RADIUS,
+                    multiplier: RADIUS * 0.001,
+                    subdivs: 6,
+                }),
+                children: Vec::new(),
+            }],
+        }
+    }
+}
+
+/// Why a [`SystemConfig`] can't be spawned.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SystemConfigError {
+    /// The body's radius or mass isn't a positive, finite number.
+    InvalidSize { body: String },
+    /// The body has a parent, but no orbit around it.
+    MissingOrbit { body: String, parent: String },
+    /// The body is at the top level, but has an orbit.
+    OrbitWithoutParent { body: String },
+    /// The body has a parent, but also a position of its own.
+    PositionWithParent { body: String, parent: String },
+    /// The body's orbit isn't a closed orbit with a positive,
+    /// finite semi-major axis.
+    OpenOrbit { body: String },
+    /// The body's periapsis is close enough to its parent for the two to touch.
+    IntersectsParent { body: String, parent: String },
+    /// The body's apoapsis is outside its parent's sphere of influence.
+    OutsideSoi {
+        body: String,
+        parent: String,
+        apoapsis: f64,
+        soi: f64,
+    },
+    /// The body's terrain has more subdivisions than supported.
+    TooManySubdivisions { body: String, subdivs: u8 },
+}
+
+impl Display for SystemConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidSize { body } => {
+                write!(f, "{body} must have a positive, finite radius and mass")
+            }
+            Self::MissingOrbit { body, parent } => {
+                write!(f, "{body} is a child of {parent}, but has no orbit")
+            }
+            Self::OrbitWithoutParent { body } => {
+                write!(f, "{body} has an orbit, but no parent to orbit")
+            }
+            Self::PositionWithParent { body, parent } => {
+                write!(
+                    f,
+                    "{body} has a position, but gets placed along its orbit around {parent}"
+                )
+            }
+            Self::OpenOrbit { body } => write!(f, "{body} must be on a closed orbit"),
+            Self::IntersectsParent { body, parent } => {
+                write!(f, "{body} gets close enough to {parent} to touch it")
+            }
+            Self::OutsideSoi {
+                body,
+                parent,
+                apoapsis,
+                soi,
+            } => write!(
+                f,
+                "{body} reaches {apoapsis} m from {parent}, \
+                 past its sphere of influence of {soi} m"
+            ),
+            Self::TooManySubdivisions { body, subdivs } => write!(
+                f,
+                "{body}'s terrain has {subdivs} subdivisions, \
+                 but at most {MAX_LOD_LEVEL} are supported"
+            ),
+        }
+    }
+}
+
+impl Error for SystemConfigError {}
+
+impl BodySpec {
+    /// Checks this body and its children, given its parent and the
+    /// radius of the parent's sphere of influence.
+    fn validate(&self, parent: Option<(&Self, f64)>) -> Result<(), SystemConfigError> {
+        let body = || self.name.clone();
+
+        let valid_size = |value: f64| value.is_finite() && value > 0.0;
+        if !valid_size(self.radius) || !valid_size(self.mass) {
+            return Err(SystemConfigError::InvalidSize { body: body() });
+        }
+
+        if let Some(terrain) = self.terrain
+            && terrain.subdivs > MAX_LOD_LEVEL
+        {
+            return Err(SystemConfigError::TooManySubdivisions {
+                body: body(),
+                subdivs: terrain.subdivs,
+            });
+        }
+
+        if let Some((parent, _)) = parent
+            && self.position.is_some()
+        {
+            return Err(SystemConfigError::PositionWithParent {
+                body: body(),
+                parent: parent.name.clone(),
+            });
+        }
+
+        let soi = match (parent, self.orbit) {
+            (None, None) => f64::INFINITY,
+            (None, Some(_)) => return Err(SystemConfigError::OrbitWithoutParent { body: body() }),
+            (Some((parent, _)), None) => {
+                return Err(SystemConfigError::MissingOrbit {
+                    body: body(),
+                    parent: parent.name.clone(),
+                });
+            }
+            (Some((parent, parent_soi)), Some(orbit)) => {
+                if !(orbit.semi_major_axis.is_finite() && orbit.semi_major_axis > 0.0)
+                    || !(0.0..1.0).contains(&orbit.eccentricity)
+                {
+                    return Err(SystemConfigError::OpenOrbit { body: body() });
+                }
+
+                if orbit.periapsis() <= parent.radius + self.radius {
+                    return Err(SystemConfigError::IntersectsParent {
+                        body: body(),
+                        parent: parent.name.clone(),
+                    });
+                }
+
+                if orbit.apoapsis() > parent_soi {
+                    return Err(SystemConfigError::OutsideSoi {
+                        body: body(),
+                        parent: parent.name.clone(),
+                        apoapsis: orbit.apoapsis(),
+                        soi: parent_soi,
+                    });
+                }
+
+                sphere_of_influence(orbit.semi_major_axis, self.mass, parent.mass)
+            }
+        };
+
+        self.children
+            .iter()
+            .try_for_each(|child| child.validate(Some((self, soi))))
+    }
+
+    fn spawn<M: Material2d>(
+        &self,
+        parent: Option<(Entity, f64)>,
+        commands: &mut Commands,
+        constants: &GravityConstants,
+        visuals: &mut impl FnMut(&Self) -> (Mesh2d, MeshMaterial2d<M>),
+        spawned: &mut Vec<Entity>,
+    ) {
+        let parent_orbit = parent
+            .zip(self.orbit)
+            .map(|((entity, mu), orbit)| (entity, orbit.to_orbit(mu)));
+
+        let (builder, orbit_components) = CelestialBodyBuilder::<M>::from_physical(
+            self.name.clone(),
+            self.radius,
+            self.mass,
+            parent_orbit,
+        );
+        let (mesh, material) = visuals(self);
+        let builder = CelestialBodyBuilder {
+            mesh,
+            material,
+            ..builder
+        };
+
+        let mut entity = match self.terrain {
+            Some(terrain) => commands.spawn(builder.build_with_terrain(terrain)),
+            None => commands.spawn(builder.build_without_terrain()),
+        };
+        if let Some(orbit_components) = orbit_components {
+            entity.insert(orbit_components);
+        }
+        if let Some(position) = self.position {
+            entity.insert(RootSpacePosition(position));
+        }
+
+        let entity = entity.id();
+        spawned.push(entity);
+
+        let mu = self.mass * constants.gravitational_constant;
+        for child in &self.children {
+            child.spawn(Some((entity, mu)), commands, constants, visuals, spawned);
+        }
+    }
+}
+
+impl SystemConfig {
+    /// Checks that every body can be spawned as described.
+    ///
+    /// # Errors
+    /// Returns the first problem found, going depth-first.
+    pub fn validate(&self) -> Result<(), SystemConfigError> {
+        self.bodies.iter().try_for_each(|body| body.validate(None))
+    }
+
+    /// Spawns every body in the config, after checking that they're
+    /// valid with [`validate`][Self::validate].
+    ///
+    /// `visuals` gets called once per body to get its mesh and material.
+    /// Bodies with terrain need a mesh of their own, as the terrain gets
+    /// generated into it.
+    ///
+    /// # Output
+    /// The spawned bodies, in the same depth-first order as the config.
+    ///
+    /// # Errors
+    /// Returns the first problem found, in which case nothing gets spawned.
+    pub fn spawn<M: Material2d>(
+        &self,
+        commands: &mut Commands,
+        constants: &GravityConstants,
+        mut visuals: impl FnMut(&BodySpec) -> (Mesh2d, MeshMaterial2d<M>),
+    ) -> Result<Vec<Entity>, SystemConfigError> {
+        self.validate()?;
+
+        let mut spawned = Vec::new();
+        for body in &self.bodies {
+            body.spawn(None, commands, constants, &mut visuals, &mut spawned);
+        }
+
+        Ok(spawned)
+    }
+}
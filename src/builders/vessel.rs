@@ -1,12 +1,32 @@
-use crate::components::main_game::{
-    camera::Focusable,
-    frames::{RigidSpaceVelocity, RootSpaceLinearVelocity, RootSpacePosition},
-    relations::{CelestialParent, RailMode},
-    vessel::Vessel,
+use crate::{
+    components::main_game::{
+        camera::Focusable,
+        frames::{
+            RigidSpaceVelocity, RootSpaceAngle, RootSpaceAngularVelocity, RootSpaceLinearVelocity,
+            RootSpacePosition,
+        },
+        relations::{CelestialParent, RailMode},
+        vessel::{Debris, DragProfile, Vessel},
+    },
+    consts::{CELESTIAL_COLLISION_GROUP, VESSEL_COLLISION_GROUP},
 };
 use bevy::{prelude::*, sprite_render::Material2d};
 use bevy_rapier2d::prelude::*;
 
+/// The collision groups every vessel starts out with.
+const fn collision_bundle() -> impl Bundle {
+    (
+        CollisionGroups {
+            memberships: VESSEL_COLLISION_GROUP,
+            filters: CELESTIAL_COLLISION_GROUP,
+        },
+        SolverGroups {
+            memberships: VESSEL_COLLISION_GROUP,
+            filters: CELESTIAL_COLLISION_GROUP,
+        },
+    )
+}
+
 #[derive(Clone, Debug)]
 pub struct VesselBuilder<M: Material2d> {
     pub name: Name,
@@ -23,6 +43,9 @@ pub struct VesselBuilder<M: Material2d> {
 }
 
 impl<M: Material2d> VesselBuilder<M> {
+    /// Vessels only collide with celestial bodies to begin with. With
+    /// [`PhysicsConfig::vessel_collisions`][crate::resources::simulation::PhysicsConfig::vessel_collisions]
+    /// set, they get to collide with each other once loaded.
     #[must_use]
     pub(crate) const fn base_bundle() -> impl Bundle {
         (
@@ -32,6 +55,7 @@ impl<M: Material2d> VesselBuilder<M> {
             Restitution::coefficient(0.02),
             Ccd { enabled: true },
             Focusable,
+            collision_bundle(),
         )
     }
 
@@ -40,14 +64,25 @@ impl<M: Material2d> VesselBuilder<M> {
     /// For the on-rails version, see [`build_on_rails`][Self::build_on_rails].
     #[must_use]
     pub fn build_rigid(self) -> impl Bundle {
+        let drag = DragProfile::from_aabb(
+            self.collider.raw.compute_local_aabb(),
+            DragProfile::DEFAULT_DRAG_COEFFICIENT,
+        );
+
         (
+            drag,
             self.name,
             self.collider,
             self.mass,
+            ReadMassProperties::default(),
             self.parent,
             self.rail_mode,
-            self.position,
-            self.linvel,
+            (
+                self.position,
+                self.linvel,
+                RootSpaceAngle(f64::from(self.angle)),
+                RootSpaceAngularVelocity(f64::from(self.angvel)),
+            ),
             RigidSpaceVelocity {
                 angvel: self.angvel,
                 linvel: Vec2::NAN,
@@ -67,3 +102,54 @@ impl<M: Material2d> VesselBuilder<M> {
         (self.build_rigid(), RigidBodyDisabled)
     }
 }
+
+/// A lightweight piece of [`Debris`], such as a spent stage.
+///
+/// Unlike a full vessel, debris has no drag, can't be focused,
+/// and doesn't use continuous collision detection. It still falls and
+/// lands like any other vessel, and goes on rails the same way.
+#[derive(Clone, Debug)]
+pub struct DebrisBuilder<M: Material2d> {
+    pub name: Name,
+    pub collider: Collider,
+    pub mass: AdditionalMassProperties,
+    pub parent: CelestialParent,
+    pub position: RootSpacePosition,
+    pub linvel: RootSpaceLinearVelocity,
+    pub mesh: Mesh2d,
+    pub material: MeshMaterial2d<M>,
+    pub angvel: f32,
+    pub angle: f32,
+}
+
+impl<M: Material2d> DebrisBuilder<M> {
+    /// Builds the debris as a loaded rigid body, which gets put on
+    /// rails once it's far enough from the active vessel.
+    #[must_use]
+    pub fn build(self) -> impl Bundle {
+        (
+            (Debris, Vessel, RigidBody::Dynamic, collision_bundle()),
+            Friction::coefficient(0.9),
+            Restitution::coefficient(0.02),
+            self.name,
+            self.collider,
+            self.mass,
+            ReadMassProperties::default(),
+            self.parent,
+            RailMode::None,
+            (
+                self.position,
+                self.linvel,
+                RootSpaceAngle(f64::from(self.angle)),
+                RootSpaceAngularVelocity(f64::from(self.angvel)),
+            ),
+            RigidSpaceVelocity {
+                angvel: self.angvel,
+                linvel: Vec2::NAN,
+            },
+            Transform::from_rotation(Quat::from_rotation_z(self.angle)),
+            self.mesh,
+            self.material,
+        )
+    }
+}
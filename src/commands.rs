@@ -0,0 +1,367 @@
+//! Custom commands for planning and changing things in the simulation.
+
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+use keplerian_sim::{Orbit2D, OrbitTrait2D};
+
+use crate::{
+    components::main_game::{
+        camera::{FocusTransition, SimCamera},
+        celestial::GravitationalParameter,
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::{CelestialParent, RailMode},
+        vessel::Vessel,
+    },
+    consts::FOCUS_TRANSITION_DURATION,
+    orbit::{
+        current_orbit,
+        elements::OrbitEdit,
+        maneuver::{circularize_at_radius_node, circularize_node},
+    },
+    systems::main_game::frame_sync::{post_rapier_frame_switch, update_active_vessel_resource},
+};
+
+/// Plans a burn that makes a vessel's orbit circular, replacing
+/// the vessel's [`ManeuverNode`][crate::orbit::maneuver::ManeuverNode], if any.
+///
+/// Nothing gets planned if the vessel isn't on an orbit,
+/// or if the orbit can't be made circular there.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlanCircularization {
+    /// The distance from the parent's center, in meters, at which to
+    /// make the orbit circular.
+    ///
+    /// If this is [`None`], the burn happens at the next apoapsis.
+    pub radius: Option<f64>,
+}
+
+impl EntityCommand for PlanCircularization {
+    fn apply(self, mut entity: EntityWorldMut) {
+        let id = entity.id();
+        let now = entity.world().resource::<Time<Fixed>>().elapsed_secs_f64();
+
+        let Some(orbit) = entity.get::<RailMode>().and_then(RailMode::as_orbit) else {
+            warn!("Cannot circularize {id}, as it isn't on an orbit");
+            return;
+        };
+
+        let mu = orbit.get_gravitational_parameter();
+        let node = match self.radius {
+            Some(radius) => circularize_at_radius_node(&orbit, radius, mu, now),
+            None => circularize_node(&orbit, mu, now),
+        };
+
+        let Some(node) = node else {
+            warn!("Cannot circularize {id}, as its orbit never gets there");
+            return;
+        };
+
+        entity.insert(node);
+    }
+}
+
+/// Smoothly moves the active simulation camera over to an entity,
+/// zooming so that it fills `frame_radius` of the viewport.
+///
+/// Celestial bodies get framed by their base radius, and anything
+/// else by its collider. This replaces any [`FocusTransition`]
+/// already in progress.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FocusOn {
+    /// The entity to move the camera to.
+    pub entity: Entity,
+    /// The fraction of the smaller dimension of the viewport
+    /// that the entity's diameter should take up.
+    pub frame_radius: f64,
+    /// Whether to attach the camera to the entity once it arrives,
+    /// rather than leaving it detached there.
+    pub attach: bool,
+}
+
+impl Command for FocusOn {
+    fn apply(self, world: &mut World) {
+        let camera = world
+            .query_filtered::<(Entity, &Camera), With<SimCamera>>()
+            .iter(world)
+            .find(|(_, camera)| camera.is_active)
+            .map(|(entity, _)| entity);
+
+        let Some(camera) = camera else {
+            warn!(
+                "Cannot focus on {}, as there's no active camera",
+                self.entity
+            );
+            return;
+        };
+
+        world.entity_mut(camera).insert(FocusTransition::new(
+            self.entity,
+            self.frame_radius,
+            self.attach,
+            FOCUS_TRANSITION_DURATION,
+        ));
+    }
+}
+
+/// Puts a vessel on an orbit around its current parent, right away.
+///
+/// The vessel's [`RailMode`] gets replaced, and its root-space state
+/// vectors get moved onto the orbit as of the last fixed tick. Rigid
+/// space and everything on screen get moved along with it, so the
+/// vessel doesn't spend a frame in its old spot, even if it's the
+/// [`ActiveVessel`][crate::resources::simulation::ActiveVessel].
+///
+/// This is meant for debugging and cheats, so the orbit isn't checked
+/// for making sense, e.g. whether it goes through the parent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SetOrbit {
+    /// The vessel to move.
+    pub vessel: Entity,
+    /// The new orbit, relative to the vessel's parent.
+    pub orbit: Orbit2D,
+}
+
+impl Command for SetOrbit {
+    fn apply(self, world: &mut World) {
+        let Self { vessel, orbit } = self;
+
+        if world.get::<Vessel>(vessel).is_none() {
+            warn!("Cannot set the orbit of {vessel}, as it isn't a vessel");
+            return;
+        }
+
+        let Some(&CelestialParent { entity: parent }) = world.get::<CelestialParent>(vessel) else {
+            warn!("Cannot set the orbit of {vessel}, as it has no parent");
+            return;
+        };
+
+        let (Some(&parent_pos), Some(&parent_vel)) = (
+            world.get::<RootSpacePosition>(parent),
+            world.get::<RootSpaceLinearVelocity>(parent),
+        ) else {
+            warn!("Cannot set the orbit of {vessel}, as its parent {parent} isn't anywhere");
+            return;
+        };
+
+        // The state vectors always match the end of the last fixed tick
+        let now = world.resource::<Time<Fixed>>().elapsed_secs_f64();
+        let sv = orbit.get_state_vectors_at_time(now);
+
+        world.entity_mut(vessel).insert((
+            RailMode::Orbit(orbit),
+            RootSpacePosition(parent_pos.0 + sv.position),
+            RootSpaceLinearVelocity(parent_vel.0 + sv.velocity),
+        ));
+
+        if let Err(err) = world.run_system_once(update_active_vessel_resource) {
+            warn!("Couldn't move rigid space along with {vessel}: {err}");
+        }
+
+        let has_camera = world
+            .query_filtered::<&Camera, With<SimCamera>>()
+            .iter(world)
+            .any(|camera| camera.is_active);
+        if has_camera && let Err(err) = world.run_system_once(post_rapier_frame_switch) {
+            warn!("Couldn't redraw {vessel} on its new orbit: {err}");
+        }
+    }
+}
+
+/// Changes a single element of a vessel's orbit right away,
+/// keeping the rest of it as it is, using [`SetOrbit`].
+///
+/// Vessels on an orbit rail get that orbit edited, and loaded vessels get
+/// the orbit they're currently on. See
+/// [`OrbitElements`][crate::orbit::elements::OrbitElements] for which
+/// elements each edit holds constant.
+///
+/// Nothing happens to vessels that aren't on an orbit, e.g. landed
+/// ones, or if the edit doesn't make a valid orbit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EditOrbit {
+    /// The vessel whose orbit to change.
+    pub vessel: Entity,
+    /// The element to change.
+    pub edit: OrbitEdit,
+}
+
+impl EditOrbit {
+    /// Gets the orbit the vessel is on as of the last fixed tick.
+    fn orbit_of(world: &World, vessel: Entity) -> Option<Orbit2D> {
+        match *world.get::<RailMode>(vessel)? {
+            RailMode::Orbit(orbit) => Some(orbit),
+            RailMode::Surface(_) => None,
+            RailMode::None => {
+                let parent = world.get::<CelestialParent>(vessel)?.entity;
+
+                current_orbit(
+                    *world.get::<RootSpacePosition>(vessel)?,
+                    *world.get::<RootSpaceLinearVelocity>(vessel)?,
+                    (
+                        *world.get::<RootSpacePosition>(parent)?,
+                        *world.get::<RootSpaceLinearVelocity>(parent)?,
+                    ),
+                    world.get::<GravitationalParameter>(parent)?.0,
+                    world.resource::<Time<Fixed>>().elapsed_secs_f64(),
+                )
+            }
+        }
+    }
+}
+
+impl Command for EditOrbit {
+    fn apply(self, world: &mut World) {
+        let Self { vessel, edit } = self;
+
+        let Some(orbit) = Self::orbit_of(world, vessel) else {
+            warn!("Cannot edit the orbit of {vessel}, as it isn't on one");
+            return;
+        };
+
+        let Some(orbit) = edit.apply(&orbit) else {
+            warn!("Cannot edit the orbit of {vessel}, as {edit:?} doesn't fit it");
+            return;
+        };
+
+        SetOrbit { vessel, orbit }.apply(world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{orbit::maneuver::ManeuverNode, resources::simulation::ActiveVessel};
+    use bevy::math::DVec2;
+    use keplerian_sim::StateVectors2D;
+
+    #[test]
+    fn orbit_gets_set() {
+        let mut world = World::new();
+        let mut time = Time::<Fixed>::default();
+        time.advance_by(core::time::Duration::from_secs(1000));
+        world.insert_resource(time);
+
+        let parent_pos = DVec2::new(3e8, -2e8);
+        let parent_vel = DVec2::new(-500.0, 800.0);
+        let parent = world
+            .spawn((
+                RootSpacePosition(parent_pos),
+                RootSpaceLinearVelocity(parent_vel),
+            ))
+            .id();
+        let vessel = world
+            .spawn((
+                Vessel,
+                CelestialParent { entity: parent },
+                RootSpacePosition(parent_pos + DVec2::new(7e6, 0.0)),
+                RootSpaceLinearVelocity(parent_vel),
+            ))
+            .id();
+        world.insert_resource(ActiveVessel {
+            entity: vessel,
+            prev_tick_parent: parent,
+            prev_tick_position: RootSpacePosition(parent_pos),
+            prev_tick_velocity: RootSpaceLinearVelocity(parent_vel),
+        });
+
+        let orbit = StateVectors2D {
+            position: DVec2::new(0.0, 8e6),
+            velocity: DVec2::new(-7500.0, 100.0),
+        }
+        .to_cached_orbit(3.986e14, 0.0);
+
+        world.commands().queue(SetOrbit { vessel, orbit });
+        world.flush();
+
+        let sv = orbit.get_state_vectors_at_time(1000.0);
+        let pos = world.get::<RootSpacePosition>(vessel).unwrap().0;
+        let vel = world.get::<RootSpaceLinearVelocity>(vessel).unwrap().0;
+        assert!((pos - (parent_pos + sv.position)).length() < 1e-6);
+        assert!((vel - (parent_vel + sv.velocity)).length() < 1e-9);
+        assert_eq!(world.get::<RailMode>(vessel), Some(&RailMode::Orbit(orbit)));
+
+        // Rigid space follows the active vessel
+        let active = world.resource::<ActiveVessel>();
+        assert_eq!(active.prev_tick_position.0, pos);
+        assert_eq!(active.prev_tick_velocity.0, vel);
+    }
+
+    #[test]
+    fn orbit_gets_edited() {
+        let mut world = World::new();
+        world.init_resource::<Time<Fixed>>();
+
+        let parent = world
+            .spawn((
+                RootSpacePosition(DVec2::ZERO),
+                RootSpaceLinearVelocity(DVec2::ZERO),
+            ))
+            .id();
+        let orbit = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 8000.0),
+        }
+        .to_cached_orbit(3.986e14, 0.0);
+        let vessel = world
+            .spawn((
+                Vessel,
+                CelestialParent { entity: parent },
+                RailMode::Orbit(orbit),
+                RootSpacePosition(DVec2::new(7e6, 0.0)),
+                RootSpaceLinearVelocity(DVec2::new(0.0, 8000.0)),
+            ))
+            .id();
+
+        world.commands().queue(EditOrbit {
+            vessel,
+            edit: OrbitEdit::Apoapsis(3e7),
+        });
+        // Doesn't fit, so the orbit stays as it is
+        world.commands().queue(EditOrbit {
+            vessel,
+            edit: OrbitEdit::Apoapsis(1e6),
+        });
+        world.flush();
+
+        let edited = world
+            .get::<RailMode>(vessel)
+            .and_then(RailMode::as_orbit)
+            .expect("vessel should still be on an orbit");
+        assert!((edited.get_apoapsis() / 3e7 - 1.0).abs() < 1e-9);
+        assert!((edited.get_periapsis() / orbit.get_periapsis() - 1.0).abs() < 1e-9);
+
+        let sv = edited.get_state_vectors_at_time(0.0);
+        let pos = world.get::<RootSpacePosition>(vessel).unwrap().0;
+        assert!((pos - sv.position).length() < 1e-6);
+    }
+
+    #[test]
+    fn circularization_gets_planned() {
+        let mut world = World::new();
+        world.init_resource::<Time<Fixed>>();
+
+        let orbit = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 9000.0),
+        }
+        .to_cached_orbit(3.986e14, 0.0);
+
+        let vessel = world.spawn(RailMode::Orbit(orbit)).id();
+        let landed = world.spawn(RailMode::None).id();
+
+        world
+            .commands()
+            .entity(vessel)
+            .queue(PlanCircularization { radius: None });
+        world
+            .commands()
+            .entity(landed)
+            .queue(PlanCircularization { radius: None });
+        world.flush();
+
+        let node = *world
+            .get::<ManeuverNode>(vessel)
+            .expect("node should be planned");
+        assert!(node.apply(&orbit).get_eccentricity() < 1e-6);
+        assert!(world.get::<ManeuverNode>(landed).is_none());
+    }
+}
@@ -1,8 +1,16 @@
-use crate::components::main_game::frames::RootSpacePosition;
-use bevy::{math::DVec2, prelude::*};
-use core::ops::Deref;
+use crate::{
+    components::main_game::frames::RootSpacePosition,
+    consts::controls::{MAX_ZOOM, MIN_ZOOM},
+};
+use bevy::{ecs::query::QueryEntityError, math::DVec2, prelude::*};
+use core::{
+    f64::consts::{FRAC_PI_2, PI, TAU},
+    ops::Deref,
+};
+use keplerian_sim::{Orbit2D, OrbitTrait2D};
 
-#[derive(Clone, Copy, Component)]
+#[derive(Clone, Copy, Component, Reflect)]
+#[reflect(Component, Default, Clone)]
 pub enum SimCameraOffset {
     Attached {
         entity: Entity,
@@ -13,6 +21,15 @@ pub enum SimCameraOffset {
 }
 
 impl SimCameraOffset {
+    /// Moves the root-space positions kept in this offset along with
+    /// the root-space origin, which moved to `new_origin`.
+    pub(crate) fn rebase(&mut self, new_origin: DVec2) {
+        match self {
+            Self::Attached { last_known_pos, .. } => last_known_pos.0 -= new_origin,
+            Self::Detached(pos) => pos.0 -= new_origin,
+        }
+    }
+
     #[must_use]
     pub fn immutably(&self) -> SimCameraOffsetReference<'_> {
         SimCameraOffsetReference::Immutable(self)
@@ -22,6 +39,65 @@ impl SimCameraOffset {
     pub fn mutably(&mut self) -> SimCameraOffsetReference<'_> {
         SimCameraOffsetReference::Mutable(self)
     }
+
+    /// Detaches the camera, keeping it at its current root position.
+    ///
+    /// Does nothing if the camera is already detached.
+    pub fn detach(&mut self, query: Query<&RootSpacePosition>) {
+        self.detach_with(|entity| query.get(entity).ok().copied());
+    }
+
+    /// Attaches the camera to `entity`, setting the offset such that
+    /// the camera stays at its current root position.
+    ///
+    /// # Errors
+    /// Errors if `entity` has no [`RootSpacePosition`], in which case
+    /// the camera is left untouched.
+    pub fn attach(
+        &mut self,
+        entity: Entity,
+        query: Query<&RootSpacePosition>,
+    ) -> Result<(), QueryEntityError> {
+        let target_pos = *query.get(entity)?;
+        self.attach_with(entity, target_pos, |entity| query.get(entity).ok().copied());
+        Ok(())
+    }
+
+    /// Gets the current root position of the camera, using `lookup`
+    /// to get the position of the attached entity.
+    #[must_use]
+    fn root_position_with(
+        &self,
+        lookup: impl FnOnce(Entity) -> Option<RootSpacePosition>,
+    ) -> RootSpacePosition {
+        match *self {
+            Self::Attached {
+                entity,
+                last_known_pos,
+                offset,
+            } => RootSpacePosition(lookup(entity).unwrap_or(last_known_pos).0 + offset),
+            Self::Detached(pos) => pos,
+        }
+    }
+
+    fn detach_with(&mut self, lookup: impl FnOnce(Entity) -> Option<RootSpacePosition>) {
+        *self = Self::Detached(self.root_position_with(lookup));
+    }
+
+    fn attach_with(
+        &mut self,
+        entity: Entity,
+        target_pos: RootSpacePosition,
+        lookup: impl FnOnce(Entity) -> Option<RootSpacePosition>,
+    ) {
+        let current_pos = self.root_position_with(lookup);
+
+        *self = Self::Attached {
+            entity,
+            last_known_pos: target_pos,
+            offset: current_pos.0 - target_pos.0,
+        };
+    }
 }
 
 impl Default for SimCameraOffset {
@@ -94,19 +170,379 @@ impl SimCameraOffsetReference<'_> {
     }
 }
 
-#[derive(Clone, Copy, Component)]
+#[derive(Clone, Copy, Component, Reflect)]
+#[reflect(Component, Clone)]
 pub struct SimCameraZoom(pub f64);
 
+impl SimCameraZoom {
+    /// Gets the zoom at which a circle with the given radius, in meters,
+    /// takes up `fill` of the smaller dimension of the viewport.
+    #[must_use]
+    pub fn fitting(radius: f64, viewport_size: Vec2, fill: f64) -> Self {
+        let half_extent = f64::from(viewport_size.min_element()) / 2.0;
+
+        Self((fill * half_extent / radius).clamp(MIN_ZOOM, MAX_ZOOM))
+    }
+
+    /// Gets the zoom at which the whole orbit, centered on its
+    /// parent, takes up `fill` of the smaller dimension of the viewport.
+    ///
+    /// Open orbits have no apoapsis to frame, so twice the current
+    /// `distance` from the parent gets framed instead.
+    #[must_use]
+    pub fn framing_orbit(orbit: &Orbit2D, distance: f64, viewport_size: Vec2, fill: f64) -> Self {
+        let radius = if orbit.get_eccentricity() < 1.0 {
+            orbit.get_apoapsis()
+        } else {
+            2.0 * distance
+        };
+
+        Self::fitting(radius, viewport_size, fill)
+    }
+}
+
+/// Makes the simulation camera smoothly zoom to frame
+/// its focus whenever the focus switches.
+///
+/// This is opt-in; cameras without this component keep
+/// their zoom when switching focus.
+#[derive(Clone, Copy, Component, Debug, PartialEq)]
+pub struct AutoZoom {
+    /// The fraction of the viewport the focus should take up.
+    pub fill: f64,
+    /// How quickly the zoom approaches its target, in e-folds per second.
+    pub rate: f64,
+    pub(crate) last_focus: Option<Entity>,
+    pub(crate) target: Option<f64>,
+    /// Whether to also ease an attached camera's offset
+    /// back to zero while zooming.
+    pub(crate) recenter: bool,
+}
+
+impl AutoZoom {
+    #[must_use]
+    pub const fn new(fill: f64, rate: f64) -> Self {
+        Self {
+            fill,
+            rate,
+            last_focus: None,
+            target: None,
+            recenter: false,
+        }
+    }
+}
+
+impl Default for AutoZoom {
+    fn default() -> Self {
+        Self::new(0.5, 4.0)
+    }
+}
+
 impl Default for SimCameraZoom {
     fn default() -> Self {
         Self(1.0)
     }
 }
 
+/// Animates the simulation camera's zoom and rotation
+/// back to their defaults when they get reset.
+///
+/// Zoom gets eased in log-space, and rotation goes
+/// whichever way around is shorter.
+#[derive(Clone, Copy, Component, Debug, PartialEq)]
+pub struct CameraEasing {
+    /// How long easing back to the default takes, in seconds.
+    pub duration: f64,
+    pub(crate) zoom: Option<Ease>,
+    pub(crate) rotation: Option<Ease>,
+}
+
+impl CameraEasing {
+    #[must_use]
+    pub const fn new(duration: f64) -> Self {
+        Self {
+            duration,
+            zoom: None,
+            rotation: None,
+        }
+    }
+
+    /// Starts easing from `zoom` back to a zoom of 1.
+    pub(crate) fn reset_zoom(&mut self, zoom: f64) {
+        self.zoom = Some(Ease::new(zoom.ln(), 0.0));
+    }
+
+    /// Starts easing from `rotation`, in radians, back to no rotation.
+    pub(crate) fn reset_rotation(&mut self, rotation: f64) {
+        let rotation = (rotation + PI).rem_euclid(TAU) - PI;
+        self.rotation = Some(Ease::new(rotation, 0.0));
+    }
+}
+
+impl Default for CameraEasing {
+    fn default() -> Self {
+        Self::new(0.25)
+    }
+}
+
+/// Smoothly moves the simulation camera over to an entity,
+/// zooming so that the entity fills part of the viewport.
+///
+/// This gets added by the
+/// [`FocusOn`][crate::commands::FocusOn] command, and gets
+/// removed once the camera has arrived.
+#[derive(Clone, Copy, Component, Debug, PartialEq)]
+pub struct FocusTransition {
+    /// The entity being moved to.
+    pub target: Entity,
+    /// The fraction of the viewport the target should take up.
+    pub fill: f64,
+    /// Whether to attach the camera to the target once it arrives,
+    /// rather than leaving it detached there.
+    pub attach: bool,
+    /// How long the transition takes, in seconds.
+    pub duration: f64,
+    pub(crate) progress: Option<FocusProgress>,
+}
+
+impl FocusTransition {
+    #[must_use]
+    pub const fn new(target: Entity, fill: f64, attach: bool, duration: f64) -> Self {
+        Self {
+            target,
+            fill,
+            attach,
+            duration,
+            progress: None,
+        }
+    }
+}
+
+/// How far along a [`FocusTransition`] is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct FocusProgress {
+    /// Where the camera started from.
+    ///
+    /// The target may move during the transition, so the camera
+    /// goes the eased fraction of the way to wherever it is now.
+    pub(crate) from: RootSpacePosition,
+    /// The fraction of the way to the target.
+    pub(crate) travel: Ease,
+    /// The zoom, in log-space.
+    pub(crate) zoom: Ease,
+}
+
+impl FocusProgress {
+    /// Starts moving from the camera's current position and zoom.
+    pub(crate) fn new(from: RootSpacePosition, zoom: f64, target_zoom: f64) -> Self {
+        Self {
+            from,
+            travel: Ease::new(0.0, 1.0),
+            zoom: Ease::new(zoom.ln(), target_zoom.ln()),
+        }
+    }
+}
+
+/// A value being eased between two others.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Ease {
+    from: f64,
+    to: f64,
+    /// How long this has been easing for, in seconds.
+    elapsed: f64,
+}
+
+impl Ease {
+    const fn new(from: f64, to: f64) -> Self {
+        Self {
+            from,
+            to,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Advances the easing by `delta` seconds, out of `duration` in total.
+    ///
+    /// # Output
+    /// The eased value, and whether the easing has finished.
+    pub(crate) fn advance(&mut self, delta: f64, duration: f64) -> (f64, bool) {
+        self.elapsed += delta;
+
+        let t = if duration > 0.0 {
+            (self.elapsed / duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let t = t * t * 2.0f64.mul_add(-t, 3.0);
+
+        ((self.to - self.from).mul_add(t, self.from), t >= 1.0)
+    }
+}
+
+/// Which way the simulation camera keeps itself rotated.
+///
+/// Rotating the camera manually switches back to [`Free`][Self::Free].
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+pub enum CameraOrientationMode {
+    /// The camera only rotates when told to.
+    #[default]
+    Free,
+    /// The active vessel's velocity, relative to its parent body,
+    /// points up on screen.
+    Prograde,
+    /// The surface of the active vessel's parent body is
+    /// straight down on screen.
+    SurfaceUp,
+}
+
+impl CameraOrientationMode {
+    /// Gets the mode to switch to when cycling through them.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Free => Self::Prograde,
+            Self::Prograde => Self::SurfaceUp,
+            Self::SurfaceUp => Self::Free,
+        }
+    }
+
+    /// Gets the counterclockwise camera rotation, in radians, for this mode,
+    /// given the active vessel's position and velocity relative to its parent.
+    ///
+    /// Returns [`None`] in [`Free`][Self::Free] mode, or when the
+    /// direction to point up is undefined.
+    #[must_use]
+    pub fn rotation(self, rel_pos: DVec2, rel_vel: DVec2) -> Option<f64> {
+        let up = match self {
+            Self::Free => return None,
+            Self::Prograde => rel_vel,
+            Self::SurfaceUp => rel_pos,
+        };
+
+        // The camera's +Y axis is up on screen
+        (up != DVec2::ZERO).then(|| up.to_angle() - FRAC_PI_2)
+    }
+}
+
 #[derive(Clone, Copy, Component)]
-#[require(SimCameraOffset, SimCameraZoom)]
+#[require(SimCameraOffset, SimCameraZoom, CameraEasing, CameraOrientationMode)]
 pub(crate) struct SimCamera;
 
 /// Component to mark an object as focusable by the camera.
 #[derive(Clone, Copy, Component)]
 pub(crate) struct Focusable;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn entity(index: u32) -> Entity {
+        Entity::from_raw_u32(index).unwrap()
+    }
+
+    /// Stands in for a `Query<&RootSpacePosition>`.
+    fn positions() -> HashMap<Entity, RootSpacePosition> {
+        HashMap::from([
+            (entity(1), RootSpacePosition(DVec2::new(1e9, -3e8))),
+            (entity(2), RootSpacePosition(DVec2::new(-42.0, 7.5))),
+        ])
+    }
+
+    #[test]
+    fn zoom_to_fit() {
+        let viewport = Vec2::new(1280.0, 720.0);
+
+        let small = SimCameraZoom::fitting(10.0, viewport, 0.5);
+        let large = SimCameraZoom::fitting(1e7, viewport, 0.5);
+
+        assert!((small.0 - 18.0).abs() < 1e-9);
+        assert!((large.0 - 1.8e-5).abs() < 1e-15);
+        assert!((SimCameraZoom::fitting(1e-9, viewport, 1.0).0 - MAX_ZOOM).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn zoom_to_frame_orbit() {
+        use keplerian_sim::StateVectors2D;
+
+        let viewport = Vec2::new(1280.0, 720.0);
+        let mu = 3.986e14;
+
+        let closed = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 8500.0),
+        }
+        .to_cached_orbit(mu, 0.0);
+        let zoom = SimCameraZoom::framing_orbit(&closed, 7e6, viewport, 0.5);
+        let expected = SimCameraZoom::fitting(closed.get_apoapsis(), viewport, 0.5);
+        assert!((zoom.0 - expected.0).abs() < 1e-15);
+        assert!(zoom.0 < SimCameraZoom::fitting(7e6, viewport, 0.5).0);
+
+        let open = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 15000.0),
+        }
+        .to_cached_orbit(mu, 0.0);
+        let zoom = SimCameraZoom::framing_orbit(&open, 9e6, viewport, 0.5);
+        let expected = SimCameraZoom::fitting(1.8e7, viewport, 0.5);
+        assert!((zoom.0 - expected.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn detach_keeps_position() {
+        let positions = positions();
+        let lookup = |entity| positions.get(&entity).copied();
+
+        let cases = [
+            SimCameraOffset::Attached {
+                entity: entity(1),
+                last_known_pos: RootSpacePosition(DVec2::ZERO),
+                offset: DVec2::new(12.0, -5.0),
+            },
+            // Unknown entity; falls back to the last known position
+            SimCameraOffset::Attached {
+                entity: entity(3),
+                last_known_pos: RootSpacePosition(DVec2::new(5.0, 5.0)),
+                offset: DVec2::new(1.0, 2.0),
+            },
+            SimCameraOffset::Detached(RootSpacePosition(DVec2::new(3.0, 4.0))),
+        ];
+
+        for mut offset in cases {
+            let before = offset.root_position_with(lookup);
+            offset.detach_with(lookup);
+            let after = offset.root_position_with(lookup);
+
+            assert!(matches!(offset, SimCameraOffset::Detached(_)));
+            assert_eq!(before, after);
+        }
+    }
+
+    #[test]
+    fn attach_keeps_position() {
+        let positions = positions();
+        let lookup = |entity| positions.get(&entity).copied();
+
+        let cases = [
+            SimCameraOffset::Detached(RootSpacePosition(DVec2::new(3.0, 4.0))),
+            SimCameraOffset::Attached {
+                entity: entity(1),
+                last_known_pos: RootSpacePosition(DVec2::ZERO),
+                offset: DVec2::new(12.0, -5.0),
+            },
+        ];
+
+        for mut offset in cases {
+            let before = offset.root_position_with(lookup);
+            offset.attach_with(entity(2), positions[&entity(2)], lookup);
+            let after = offset.root_position_with(lookup);
+
+            assert!(matches!(
+                offset,
+                SimCameraOffset::Attached { entity: e, .. } if e == entity(2)
+            ));
+            assert!((before.0 - after.0).length() < 1e-6);
+        }
+    }
+}
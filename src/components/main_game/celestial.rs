@@ -1,8 +1,18 @@
-use bevy::prelude::*;
+use bevy::{
+    ecs::{lifecycle::HookContext, query::QueryData, world::DeferredWorld},
+    prelude::*,
+};
 use bevy_rapier2d::prelude::RigidBody;
+use core::f64::consts::TAU;
+
+use crate::{
+    components::main_game::relations::SoiMembers, resources::simulation::GravityConstants,
+    terrain::TerrainGen,
+};
 
 /// The terrain parameters of a celestial body.
-#[derive(Clone, Copy, Component, Debug, Default)]
+#[derive(Clone, Copy, Component, Debug, Default, Reflect)]
+#[reflect(Component, Default, Debug, Clone)]
 #[require(CelestialBody)]
 pub struct Terrain {
     /// The seed given to the noise generator.
@@ -20,11 +30,16 @@ pub struct Terrain {
     /// The multiplier to give to the noise generator output.
     pub multiplier: f64,
     /// The amount of subdivisions for mesh generation.
+    ///
+    /// This is the finest LoD level the terrain gets generated at,
+    /// and must be at most [`MAX_LOD_LEVEL`][crate::consts::terrain::MAX_LOD_LEVEL].
     pub subdivs: u8,
 }
 
-#[derive(Clone, Copy, Component)]
-#[require(RigidBody::KinematicPositionBased)]
+#[derive(Clone, Copy, Component, Reflect)]
+#[reflect(Component, Clone)]
+#[require(RigidBody::KinematicPositionBased, GravitationalParameter, SoiMembers)]
+#[component(on_insert = init_gravitational_parameter)]
 pub(crate) struct CelestialBody {
     /// The "base radius" of a celestial body.
     ///
@@ -38,6 +53,15 @@ pub(crate) struct CelestialBody {
     pub(crate) mass: f64,
 }
 
+impl CelestialBody {
+    /// Calculates the standard gravitational parameter (μ)
+    /// of this body from its mass, in m^3 s^-2.
+    #[must_use]
+    pub(crate) fn gravitational_parameter(self, constants: &GravityConstants) -> f64 {
+        constants.gravitational_constant * self.mass
+    }
+}
+
 impl Default for CelestialBody {
     fn default() -> Self {
         Self {
@@ -46,3 +70,199 @@ impl Default for CelestialBody {
         }
     }
 }
+
+/// Works out the [`GravitationalParameter`] of a celestial body as soon
+/// as it gets inserted, so it's never left at zero until the next fixed tick.
+///
+/// Uses the default [`GravityConstants`] if the resource doesn't exist yet.
+fn init_gravitational_parameter(mut world: DeferredWorld, ctx: HookContext) {
+    let constants = world
+        .get_resource::<GravityConstants>()
+        .copied()
+        .unwrap_or_default();
+    let Some(body) = world.get::<CelestialBody>(ctx.entity).copied() else {
+        return;
+    };
+
+    if let Some(mut mu) = world.get_mut::<GravitationalParameter>(ctx.entity) {
+        mu.0 = body.gravitational_parameter(&constants);
+    }
+}
+
+/// The standard gravitational parameter (μ) of a celestial body,
+/// in m^3 s^-2.
+///
+/// Gets worked out when the [`CelestialBody`] is inserted, then kept
+/// in sync with the body's mass and the [`GravityConstants`] every
+/// fixed tick.
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+pub struct GravitationalParameter(pub f64);
+
+/// A cached terrain generator for a body's [`Terrain`], used
+/// to sample the surface without setting up a new generator each time.
+///
+/// Kept in sync with the body's [`Terrain`] every fixed tick.
+#[derive(Component)]
+pub struct TerrainSampler(TerrainGen);
+
+impl TerrainSampler {
+    #[must_use]
+    pub(crate) fn new(terrain: Terrain) -> Self {
+        Self(TerrainGen::new(terrain))
+    }
+
+    /// Gets the distance from the body's center to its surface at the
+    /// given angle, in meters.
+    ///
+    /// `theta` is the counterclockwise angle from the body's own +X axis,
+    /// in radians, so the body's rotation needs to be subtracted from
+    /// root-space angles first.
+    #[must_use]
+    pub fn surface_radius(&self, theta: f64) -> f64 {
+        self.0.get_terrain_altitude(theta)
+    }
+}
+
+/// Query data for sampling the surface of a celestial body,
+/// e.g. `bodies.get(body)?.surface_radius(theta)`.
+#[derive(QueryData)]
+pub struct SurfaceData {
+    body: &'static CelestialBody,
+    terrain: Option<&'static Terrain>,
+    sampler: Option<&'static TerrainSampler>,
+}
+
+impl SurfaceDataItem<'_, '_> {
+    /// Gets the distance from the body's center to its surface at the
+    /// given angle, in meters.
+    ///
+    /// Bodies without a [`Terrain`] are perfect circles of their base radius.
+    /// See [`TerrainSampler::surface_radius`] for what `theta` is.
+    #[must_use]
+    pub fn surface_radius(&self, theta: f64) -> f64 {
+        match (self.sampler, self.terrain) {
+            (Some(sampler), _) => sampler.surface_radius(theta),
+            // The sampler doesn't get added until the next fixed tick
+            (None, Some(&terrain)) => TerrainSampler::new(terrain).surface_radius(theta),
+            (None, None) => f64::from(self.body.base_radius),
+        }
+    }
+}
+
+/// The atmosphere of a celestial body.
+///
+/// The air density falls off exponentially with altitude,
+/// and is cut off entirely above `max_altitude`.
+#[derive(Clone, Copy, Component, Debug, PartialEq, Reflect)]
+#[reflect(Component, Debug, PartialEq, Clone)]
+#[require(CelestialBody)]
+pub struct Atmosphere {
+    /// The altitude over which the density falls by a factor of e, in meters.
+    pub scale_height: f64,
+    /// The air density at an altitude of zero, in kg/m^3.
+    pub sea_level_density: f64,
+    /// The altitude above which there is no atmosphere, in meters.
+    pub max_altitude: f64,
+}
+
+impl Atmosphere {
+    /// Gets the air density at the given altitude, in kg/m^3.
+    ///
+    /// Returns zero above `max_altitude`.
+    #[must_use]
+    pub fn density_at(self, altitude: f64) -> f64 {
+        if altitude > self.max_altitude {
+            return 0.0;
+        }
+
+        self.sea_level_density * (-altitude / self.scale_height).exp()
+    }
+}
+
+/// How fast a celestial body spins around its axis.
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(RotationPeriod)]
+pub struct CelestialSpin {
+    /// The counterclockwise angular velocity, in radians per second.
+    pub angular_velocity: f64,
+}
+
+impl CelestialSpin {
+    /// Gets the time it takes for the body to do a full rotation
+    /// (i.e. the length of a sidereal day), in seconds.
+    ///
+    /// Returns [`None`] if the body doesn't spin.
+    #[must_use]
+    pub fn rotation_period(self) -> Option<f64> {
+        (self.angular_velocity != 0.0).then(|| TAU / self.angular_velocity.abs())
+    }
+}
+
+/// A readout of [`CelestialSpin::rotation_period`].
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+pub struct RotationPeriod(pub Option<f64>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_period() {
+        let earthlike = CelestialSpin {
+            angular_velocity: TAU / 86400.0,
+        };
+        let retrograde = CelestialSpin {
+            angular_velocity: -TAU / 3600.0,
+        };
+
+        assert!((earthlike.rotation_period().unwrap() - 86400.0).abs() < 1e-6);
+        assert!((retrograde.rotation_period().unwrap() - 3600.0).abs() < 1e-6);
+        assert_eq!(CelestialSpin::default().rotation_period(), None);
+    }
+
+    #[test]
+    fn gravitational_parameter_set_on_spawn() {
+        let mut world = World::new();
+        world.insert_resource(GravityConstants {
+            gravitational_constant: 2.0,
+        });
+
+        let body = world
+            .spawn(CelestialBody {
+                base_radius: 1.0,
+                mass: 300.0,
+            })
+            .id();
+        assert_eq!(
+            world.get::<GravitationalParameter>(body),
+            Some(&GravitationalParameter(600.0))
+        );
+
+        world.entity_mut(body).insert(CelestialBody {
+            base_radius: 1.0,
+            mass: 50.0,
+        });
+        assert_eq!(
+            world.get::<GravitationalParameter>(body),
+            Some(&GravitationalParameter(100.0))
+        );
+    }
+
+    #[test]
+    fn atmosphere_density() {
+        let atmosphere = Atmosphere {
+            scale_height: 8500.0,
+            sea_level_density: 1.225,
+            max_altitude: 70_000.0,
+        };
+
+        assert!((atmosphere.density_at(0.0) - 1.225).abs() < 1e-12);
+        assert!((atmosphere.density_at(8500.0) - 1.225 / core::f64::consts::E).abs() < 1e-12);
+        assert!(atmosphere.density_at(-100.0) > 1.225);
+        assert!(atmosphere.density_at(69_999.0) > 0.0);
+        assert!(atmosphere.density_at(70_001.0).abs() < f64::EPSILON);
+    }
+}
@@ -3,9 +3,13 @@
 //! Root Space converts into Rigid Space position (with its own rotation)
 //! Root Space position + Rigid Space rotation + Camera offset = Camera Space transform
 
-use crate::components::main_game::camera::SimCameraZoom;
+use crate::{
+    components::main_game::camera::SimCameraZoom,
+    math::{quat_to_rot, rot_to_quat},
+};
 use bevy::{math::DVec2, prelude::*};
 use bevy_rapier2d::prelude::*;
+use keplerian_sim::StateVectors2D;
 use std::fmt::Display;
 
 macro_rules! wrapper {
@@ -26,13 +30,98 @@ macro_rules! wrapper {
     };
 }
 
+/// Arithmetic for root-space vectors.
+///
+/// Subtracting two of them gives the plain vector between them,
+/// which can then be added back onto one of them.
+macro_rules! vector_ops {
+    ($( $outer:ident ),* $(,)?) => {
+        $(
+            impl ::core::ops::Sub for $outer {
+                type Output = DVec2;
+                fn sub(self, rhs: Self) -> DVec2 {
+                    self.0 - rhs.0
+                }
+            }
+            impl ::core::ops::Add<DVec2> for $outer {
+                type Output = Self;
+                fn add(self, rhs: DVec2) -> Self {
+                    Self(self.0 + rhs)
+                }
+            }
+            impl ::core::ops::Sub<DVec2> for $outer {
+                type Output = Self;
+                fn sub(self, rhs: DVec2) -> Self {
+                    Self(self.0 - rhs)
+                }
+            }
+            impl ::core::ops::AddAssign<DVec2> for $outer {
+                fn add_assign(&mut self, rhs: DVec2) {
+                    self.0 += rhs;
+                }
+            }
+            impl ::core::ops::SubAssign<DVec2> for $outer {
+                fn sub_assign(&mut self, rhs: DVec2) {
+                    self.0 -= rhs;
+                }
+            }
+        )*
+    };
+}
+
 /// Coordinates relative to root body.
 ///
 /// Used for orbital physics and as source of truth.
-#[derive(Clone, Copy, Component, Debug, PartialEq)]
+#[derive(Clone, Copy, Component, Debug, PartialEq, Reflect)]
+#[reflect(Component, Debug, PartialEq, Clone)]
 pub struct RootSpacePosition(pub DVec2);
 
 impl RootSpacePosition {
+    /// Gets the state vectors of something at this position moving at
+    /// `vel`, relative to a parent at `parent_pos` moving at `parent_vel`.
+    #[must_use]
+    pub fn relative_to(
+        self,
+        vel: RootSpaceLinearVelocity,
+        parent_pos: RootSpacePosition,
+        parent_vel: RootSpaceLinearVelocity,
+    ) -> StateVectors2D {
+        StateVectors2D {
+            position: self - parent_pos,
+            velocity: vel - parent_vel,
+        }
+    }
+
+    /// Gets the distance to `other`, in meters.
+    #[must_use]
+    pub fn distance_to(self, other: Self) -> f64 {
+        self.0.distance(other.0)
+    }
+
+    /// Gets the squared distance to `other`, in square meters.
+    ///
+    /// Cheaper than [`distance_to`][Self::distance_to] when only
+    /// comparing distances.
+    #[must_use]
+    pub fn distance_squared_to(self, other: Self) -> f64 {
+        self.0.distance_squared(other.0)
+    }
+
+    /// Gets the unit vector pointing from this position to `other`.
+    ///
+    /// Returns zero if both positions are the same.
+    #[must_use]
+    pub fn direction_to(self, other: Self) -> DVec2 {
+        (other.0 - self.0).normalize_or_zero()
+    }
+
+    /// Linearly interpolates between this position and `other`,
+    /// where a `t` of 0 gives this position and 1 gives `other`.
+    #[must_use]
+    pub fn lerp(self, other: Self, t: f64) -> Self {
+        Self(self.0.lerp(other.0, t))
+    }
+
     #[must_use]
     pub(crate) fn to_rigid_space_position(
         self,
@@ -69,7 +158,8 @@ impl Display for RootSpacePosition {
 /// Coordinates relative to root body.
 ///
 /// Used for orbital physics and as source of truth.
-#[derive(Clone, Copy, Component, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
 pub struct RootSpaceLinearVelocity(pub DVec2);
 
 impl RootSpaceLinearVelocity {
@@ -89,6 +179,55 @@ impl Display for RootSpaceLinearVelocity {
     }
 }
 
+/// Counterclockwise rotation relative to root body, in radians.
+///
+/// Used as source of truth, like [`RootSpacePosition`].
+/// Rigid space doesn't rotate relative to root space,
+/// so this only differs from the rigid-space rotation in precision.
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+pub struct RootSpaceAngle(pub f64);
+
+impl RootSpaceAngle {
+    #[must_use]
+    pub(crate) fn from_rigid_space_rotation(rotation: Quat) -> Self {
+        Self(quat_to_rot(rotation))
+    }
+
+    #[must_use]
+    pub(crate) fn to_rigid_space_rotation(self) -> Quat {
+        rot_to_quat(self.0)
+    }
+}
+
+impl Display for RootSpaceAngle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.7e}rad@root", self.0)
+    }
+}
+
+/// Counterclockwise angular velocity relative to root body, in rad/s.
+///
+/// Used as source of truth, like [`RootSpaceLinearVelocity`].
+/// On-rails vessels keep spinning at this rate.
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+pub struct RootSpaceAngularVelocity(pub f64);
+
+impl RootSpaceAngularVelocity {
+    #[must_use]
+    #[expect(clippy::cast_possible_truncation)]
+    pub(crate) fn to_rigid_space_angular_velocity(self) -> f32 {
+        self.0 as f32
+    }
+}
+
+impl Display for RootSpaceAngularVelocity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.7e}rad/s@root", self.0)
+    }
+}
+
 /// Coordinates relative to active vessel.
 ///
 /// Single precision, and unscaled. Used to be transformed to [`RigidSpaceTransform`].
@@ -156,6 +295,8 @@ pub trait RigidSpaceVelocityImpl {
         self,
         active_vessel_vel: RootSpaceLinearVelocity,
     ) -> RootSpaceLinearVelocity;
+
+    fn to_root_space_angular_velocity(self) -> RootSpaceAngularVelocity;
 }
 
 impl RigidSpaceVelocityImpl for RigidSpaceVelocity {
@@ -166,17 +307,29 @@ impl RigidSpaceVelocityImpl for RigidSpaceVelocity {
         let linvel = DVec2::new(f64::from(self.linvel.x), f64::from(self.linvel.y));
         RootSpaceLinearVelocity(active_vessel_vel.0 + linvel)
     }
+
+    fn to_root_space_angular_velocity(self) -> RootSpaceAngularVelocity {
+        RootSpaceAngularVelocity(f64::from(self.angvel))
+    }
 }
 
 /// Coordinates relative to camera.
 ///
 /// Single precision, and scaled to camera zoom amount.
-#[derive(Clone, Copy, Component, Debug, PartialEq)]
+#[derive(Clone, Copy, Component, Debug, PartialEq, Reflect)]
+#[reflect(Component, Debug, PartialEq, Clone)]
 pub struct CameraSpaceTransform(pub Transform);
 
+vector_ops! {
+    RootSpacePosition,
+    RootSpaceLinearVelocity,
+}
+
 wrapper! {
     RootSpacePosition: DVec2,
     RootSpaceLinearVelocity: DVec2,
+    RootSpaceAngle: f64,
+    RootSpaceAngularVelocity: f64,
     RigidSpacePosition: Vec2,
     RigidSpaceTransform: Transform,
     RigidSpaceLinearVelocity: Vec2,
@@ -185,10 +338,12 @@ wrapper! {
 
 #[cfg(test)]
 mod tests {
-    use bevy::math::{DVec2, Vec2};
+    use bevy::math::{DVec2, Quat, Vec2};
+    use core::f64::consts::{PI, TAU};
 
     use crate::components::main_game::frames::{
-        RigidSpaceVelocity, RigidSpaceVelocityImpl as _, RootSpaceLinearVelocity, RootSpacePosition,
+        RigidSpaceVelocity, RigidSpaceVelocityImpl as _, RootSpaceAngle, RootSpaceAngularVelocity,
+        RootSpaceLinearVelocity, RootSpacePosition,
     };
 
     #[test]
@@ -230,4 +385,111 @@ mod tests {
             ROOTSPACE_VEL
         );
     }
+
+    #[test]
+    fn root_rigid_orientation_conversion() {
+        const ROOTSPACE_ANGLE: RootSpaceAngle = RootSpaceAngle(1.25);
+        const ROOTSPACE_ANGVEL: RootSpaceAngularVelocity = RootSpaceAngularVelocity(-0.5);
+
+        let rigid = ROOTSPACE_ANGLE.to_rigid_space_rotation();
+
+        assert!(rigid.angle_between(Quat::from_rotation_z(1.25)) < 1e-6);
+        assert!(
+            (RootSpaceAngle::from_rigid_space_rotation(rigid).0 - ROOTSPACE_ANGLE.0).abs() < 1e-6
+        );
+
+        let rigid_angvel = ROOTSPACE_ANGVEL.to_rigid_space_angular_velocity();
+
+        assert!((rigid_angvel + 0.5).abs() < f32::EPSILON);
+
+        let rigid_full = RigidSpaceVelocity {
+            linvel: Vec2::ZERO,
+            angvel: rigid_angvel,
+        };
+
+        assert_eq!(
+            rigid_full.to_root_space_angular_velocity(),
+            ROOTSPACE_ANGVEL
+        );
+    }
+
+    #[test]
+    fn root_rigid_orientation_wraparound() {
+        for angle in [PI, -PI, PI - 1e-3, -PI + 1e-3, TAU + 0.5] {
+            let rigid = RootSpaceAngle(angle).to_rigid_space_rotation();
+            let back = RootSpaceAngle::from_rigid_space_rotation(rigid).0;
+
+            assert!(
+                ((back - angle + PI).rem_euclid(TAU) - PI).abs() < 1e-6,
+                "{back} isn't equivalent to {angle}"
+            );
+        }
+    }
+
+    #[test]
+    fn root_space_arithmetic() {
+        let pos = RootSpacePosition(DVec2::new(5.0, 9.0));
+        let other_pos = RootSpacePosition(DVec2::new(-4.0, -3.0));
+
+        assert_eq!(pos - other_pos, DVec2::new(9.0, 12.0));
+        assert_eq!(other_pos + (pos - other_pos), pos);
+        assert_eq!(pos - DVec2::new(5.0, 9.0), RootSpacePosition(DVec2::ZERO));
+
+        let mut vel = RootSpaceLinearVelocity(DVec2::new(1.0, 2.0));
+        vel += DVec2::new(3.0, 4.0);
+        assert_eq!(vel, RootSpaceLinearVelocity(DVec2::new(4.0, 6.0)));
+        vel -= DVec2::new(4.0, 0.0);
+        assert_eq!(vel, RootSpaceLinearVelocity(DVec2::new(0.0, 6.0)));
+        assert_eq!(
+            vel - RootSpaceLinearVelocity(DVec2::new(1.0, 1.0)),
+            DVec2::new(-1.0, 5.0)
+        );
+        assert_eq!(
+            vel + DVec2::X,
+            RootSpaceLinearVelocity(DVec2::new(1.0, 6.0))
+        );
+    }
+
+    #[test]
+    fn distance_and_direction() {
+        let a = RootSpacePosition(DVec2::new(1e9, 2.0));
+        let b = RootSpacePosition(DVec2::new(1e9 + 3.0, 6.0));
+
+        assert!((a.distance_to(b) - 5.0).abs() < 1e-9);
+        assert!((b.distance_to(a) - 5.0).abs() < 1e-9);
+        assert!((a.distance_squared_to(b) - 25.0).abs() < 1e-6);
+        assert!((a.direction_to(b) - DVec2::new(0.6, 0.8)).length() < 1e-9);
+        assert!((b.direction_to(a) - DVec2::new(-0.6, -0.8)).length() < 1e-9);
+
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert!(
+            a.lerp(b, 0.5)
+                .distance_to(RootSpacePosition(DVec2::new(1e9 + 1.5, 4.0)))
+                < 1e-9
+        );
+
+        // No direction to go in, but no NaN either
+        assert!(a.distance_to(a).abs() < f64::EPSILON);
+        assert_eq!(a.direction_to(a), DVec2::ZERO);
+    }
+
+    #[test]
+    fn relative_state_vectors() {
+        let parent_pos = RootSpacePosition(DVec2::new(1e9, -2e9));
+        let parent_vel = RootSpaceLinearVelocity(DVec2::new(3e4, 1e3));
+
+        let pos = RootSpacePosition(DVec2::new(1e9 + 7e6, -2e9));
+        let vel = RootSpaceLinearVelocity(DVec2::new(3e4, 1e3 + 7500.0));
+
+        let sv = pos.relative_to(vel, parent_pos, parent_vel);
+
+        assert_eq!(sv.position, DVec2::new(7e6, 0.0));
+        assert_eq!(sv.velocity, DVec2::new(0.0, 7500.0));
+
+        // Relative to itself, it's at rest at the origin
+        let sv = pos.relative_to(vel, pos, vel);
+        assert_eq!(sv.position, DVec2::ZERO);
+        assert_eq!(sv.velocity, DVec2::ZERO);
+    }
 }
@@ -0,0 +1,324 @@
+use bevy::{math::DVec2, prelude::*};
+use core::f64::consts::{PI, TAU};
+use keplerian_sim::{Orbit2D, OrbitTrait2D};
+
+/// How many points get cached for each orbit by default.
+///
+/// This is enough for an orbit spanning a large monitor
+/// to still look smooth.
+pub(crate) const ORBIT_MESH_POINTS: u32 = 1024;
+
+/// The fewest points an orbit gets cached with,
+/// however few get asked for.
+const MIN_ORBIT_MESH_POINTS: u32 = 3;
+
+/// How many points get drawn at the least for each orbit,
+/// however small it is on screen.
+const MIN_DRAWN_POINTS: usize = 16;
+
+/// How long each drawn segment of an orbit should be on screen,
+/// in logical pixels.
+const DRAWN_SEGMENT_LENGTH: f64 = 4.0;
+
+/// How much an orbit's shape can change, relative to its size,
+/// before its cached points need resampling.
+///
+/// Loaded vessels get their orbit rewritten every tick, with their
+/// shape wobbling a little from the gravity integration. Changes this
+/// small are well under a pixel even with the orbit filling the screen.
+const ORBIT_MESH_TOLERANCE: f64 = 1e-5;
+
+/// How much of an open orbit's possible true anomaly range to sample,
+/// as the asymptotes themselves are infinitely far away.
+const OPEN_ORBIT_ANOMALY_FRACTION: f64 = 0.95;
+
+/// Points along the orbit in an entity's
+/// [`RailMode`][crate::components::main_game::relations::RailMode],
+/// cached for drawing it on the map.
+///
+/// The points are relative to the entity's parent, so they stay valid
+/// as the camera moves and zooms, and only need regenerating when the
+/// orbit itself changes.
+#[derive(Clone, Component, Debug, Default, PartialEq)]
+pub(crate) struct OrbitMesh {
+    /// The sampled points, relative to the parent, in meters.
+    points: Vec<DVec2>,
+    /// Whether the last point connects back to the first one.
+    closed: bool,
+    /// The size of the bounding box around all the points, in meters.
+    extent: f64,
+    /// The shape of the orbit the points got sampled from.
+    shape: OrbitShape,
+}
+
+/// The parts of an orbit that decide where its line goes,
+/// leaving out where along it the orbiter is.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct OrbitShape {
+    /// The distance from the parent to the periapsis, in meters.
+    periapsis: f64,
+    /// The eccentricity, pointing towards the periapsis.
+    ///
+    /// Unlike the argument of periapsis, this stays steady
+    /// for nearly circular orbits.
+    eccentricity: DVec2,
+}
+
+impl OrbitShape {
+    fn of(orbit: &Orbit2D) -> Self {
+        let towards_periapsis = orbit
+            .get_state_vectors_at_true_anomaly(0.0)
+            .position
+            .normalize_or_zero();
+
+        Self {
+            periapsis: orbit.get_periapsis(),
+            eccentricity: towards_periapsis * orbit.get_eccentricity(),
+        }
+    }
+}
+
+impl OrbitMesh {
+    /// Samples `points` points along the orbit, or
+    /// [`MIN_ORBIT_MESH_POINTS`] if that's fewer.
+    ///
+    /// Closed orbits get sampled evenly by eccentric anomaly, rather than
+    /// by mean anomaly (i.e. time), which would bunch the points up
+    /// around apoapsis. This keeps the gap between the drawn line and
+    /// the true orbit about the same all the way around, even at the
+    /// tight turn around periapsis of an eccentric orbit.
+    ///
+    /// Open orbits get sampled by true anomaly up to
+    /// close to their asymptotes.
+    #[must_use]
+    pub(crate) fn from_orbit(orbit: &Orbit2D, points: u32) -> Self {
+        let points = points.max(MIN_ORBIT_MESH_POINTS);
+        let eccentricity = orbit.get_eccentricity();
+        let shape = OrbitShape::of(orbit);
+
+        if eccentricity < 1.0 {
+            let points = (0..points)
+                .map(|i| {
+                    let anomaly = TAU * f64::from(i) / f64::from(points);
+                    orbit
+                        .get_state_vectors_at_eccentric_anomaly(anomaly)
+                        .position
+                })
+                .collect();
+
+            return Self::new(points, true, shape);
+        }
+
+        let max_anomaly = (-1.0 / eccentricity).acos() * OPEN_ORBIT_ANOMALY_FRACTION;
+        let last = points - 1;
+
+        let points = (0..=last)
+            .map(|i| {
+                let t = f64::from(i) / f64::from(last);
+                let anomaly = max_anomaly * 2.0f64.mul_add(t, -1.0);
+                orbit.get_state_vectors_at_true_anomaly(anomaly).position
+            })
+            .collect();
+
+        Self::new(points, false, shape)
+    }
+
+    fn new(points: Vec<DVec2>, closed: bool, shape: OrbitShape) -> Self {
+        let (min, max) = points.iter().fold(
+            (DVec2::INFINITY, DVec2::NEG_INFINITY),
+            |(min, max), &point| (min.min(point), max.max(point)),
+        );
+
+        Self {
+            points,
+            closed,
+            extent: (max - min).max_element().max(0.0),
+            shape,
+        }
+    }
+
+    /// Whether the points still follow `orbit` closely enough,
+    /// i.e. whether it's about the same shape as the orbit
+    /// they got sampled from.
+    #[must_use]
+    pub(crate) fn follows(&self, orbit: &Orbit2D) -> bool {
+        let shape = OrbitShape::of(orbit);
+
+        (shape.periapsis - self.shape.periapsis).abs()
+            <= ORBIT_MESH_TOLERANCE * self.shape.periapsis
+            && shape.eccentricity.distance(self.shape.eccentricity) <= ORBIT_MESH_TOLERANCE
+    }
+
+    /// Gets the points worth drawing for an orbit that's
+    /// `screen_size` logical pixels across on screen.
+    ///
+    /// Smaller orbits skip over more of the cached points. For closed
+    /// orbits, the first point gets repeated at the end to close the loop.
+    pub(crate) fn decimated(&self, screen_size: f64) -> impl Iterator<Item = DVec2> {
+        let stride = self.stride(screen_size);
+        let closing = self.closed.then(|| self.points.first().copied()).flatten();
+
+        self.points.iter().copied().step_by(stride).chain(closing)
+    }
+
+    /// Gets how many cached points to step over for each drawn one.
+    #[must_use]
+    #[expect(clippy::cast_possible_truncation)]
+    #[expect(clippy::cast_sign_loss)]
+    fn stride(&self, screen_size: f64) -> usize {
+        let len = self.points.len();
+        // Roughly the perimeter of the orbit on screen
+        let wanted = (screen_size * PI / DRAWN_SEGMENT_LENGTH).max(0.0) as usize;
+        let wanted = wanted.clamp(MIN_DRAWN_POINTS, len.max(1));
+
+        (len / wanted).max(1)
+    }
+
+    /// Gets the size of the bounding box around all the points, in meters.
+    #[must_use]
+    pub(crate) const fn extent(&self) -> f64 {
+        self.extent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keplerian_sim::StateVectors2D;
+
+    const MU: f64 = 3.986e14;
+
+    #[test]
+    fn closed_orbit_mesh() {
+        let orbit = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 8500.0),
+        }
+        .to_cached_orbit(MU, 0.0);
+
+        let mesh = OrbitMesh::from_orbit(&orbit, ORBIT_MESH_POINTS);
+        assert_eq!(mesh.points.len(), ORBIT_MESH_POINTS as usize);
+
+        for point in &mesh.points {
+            let radius = point.length();
+            assert!(radius >= orbit.get_periapsis() * (1.0 - 1e-9));
+            assert!(radius <= orbit.get_apoapsis() * (1.0 + 1e-9));
+        }
+
+        // A tiny orbit only gets the minimum, a huge one gets every point
+        let small: Vec<_> = mesh.decimated(1.0).collect();
+        assert_eq!(small.len(), MIN_DRAWN_POINTS + 1);
+        assert_eq!(small.first(), small.last());
+
+        let large: Vec<_> = mesh.decimated(1e6).collect();
+        assert_eq!(large.len(), ORBIT_MESH_POINTS as usize + 1);
+
+        let medium = mesh.decimated(200.0).count();
+        assert!(medium > small.len() && medium < large.len());
+    }
+
+    #[test]
+    fn mesh_follows_same_shaped_orbit() {
+        let orbit = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 8500.0),
+        }
+        .to_cached_orbit(MU, 0.0);
+        let mesh = OrbitMesh::from_orbit(&orbit, ORBIT_MESH_POINTS);
+
+        // The same orbit, just further along it
+        let later = orbit
+            .get_state_vectors_at_time(1000.0)
+            .to_cached_orbit(MU, 1000.0);
+        assert!(mesh.follows(&later));
+
+        let faster = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 8600.0),
+        }
+        .to_cached_orbit(MU, 0.0);
+        assert!(!mesh.follows(&faster));
+
+        // Nearly circular orbits have no steady argument of periapsis
+        let speed = (MU / 7e6).sqrt();
+        let circular = |offset: f64| {
+            StateVectors2D {
+                position: DVec2::new(7e6, 0.0),
+                velocity: DVec2::new(offset, speed),
+            }
+            .to_cached_orbit(MU, 0.0)
+        };
+        let mesh = OrbitMesh::from_orbit(&circular(1e-3), ORBIT_MESH_POINTS);
+        assert!(mesh.follows(&circular(-1e-3)));
+    }
+
+    #[test]
+    fn open_orbit_mesh() {
+        let orbit = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 15000.0),
+        }
+        .to_cached_orbit(MU, 0.0);
+
+        let mesh = OrbitMesh::from_orbit(&orbit, ORBIT_MESH_POINTS);
+        let points: Vec<_> = mesh.decimated(1e6).collect();
+
+        // Not closed, so the ends don't meet up
+        assert_eq!(points.len(), ORBIT_MESH_POINTS as usize);
+        assert!(points.iter().all(|point| point.is_finite()));
+        assert!((points[0] - points[points.len() - 1]).length() > 1e7);
+        assert!(mesh.extent() > 1e7);
+    }
+
+    /// Gets how far the orbit strays from the drawn line between
+    /// each pair of consecutive points, at most, in meters.
+    fn max_chord_error(orbit: &Orbit2D, points: u32) -> f64 {
+        let mesh = OrbitMesh::from_orbit(orbit, points);
+        let step = TAU / f64::from(points);
+
+        (0..points)
+            .map(|i| {
+                let start = mesh.points[i as usize];
+                let end = mesh.points[((i + 1) % points) as usize];
+                let middle = orbit
+                    .get_state_vectors_at_eccentric_anomaly(step * (f64::from(i) + 0.5))
+                    .position;
+
+                let chord = end - start;
+                let t = ((middle - start).dot(chord) / chord.length_squared()).clamp(0.0, 1.0);
+                middle.distance(start + chord * t)
+            })
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn eccentric_orbit_stays_smooth() {
+        let orbit = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 10_500.0),
+        }
+        .to_cached_orbit(MU, 0.0);
+        assert!(orbit.get_eccentricity() > 0.9);
+
+        let semi_major_axis = orbit.get_semi_major_axis();
+
+        for points in [64, 256, ORBIT_MESH_POINTS] {
+            // The sagitta of an arc of an ellipse spanning Δ in eccentric
+            // anomaly is about aΔ²/8, wherever on the ellipse it is
+            let step = TAU / f64::from(points);
+            let tolerance = 1.1 * semi_major_axis * step * step / 8.0;
+
+            let error = max_chord_error(&orbit, points);
+            assert!(
+                error < tolerance,
+                "{points} points strayed by {error} m, expected under {tolerance} m"
+            );
+        }
+
+        // Far below the periapsis altitude, even with the default
+        let error = max_chord_error(&orbit, ORBIT_MESH_POINTS);
+        assert!(error < 1e-3 * orbit.get_periapsis());
+
+        assert_eq!(OrbitMesh::from_orbit(&orbit, 0).points.len(), 3);
+    }
+}
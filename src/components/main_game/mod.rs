@@ -1,8 +1,10 @@
 pub mod camera;
 pub mod celestial;
 pub mod frames;
+#[cfg(feature = "not-headless")]
+pub(crate) mod map;
 pub mod relations;
 pub(crate) mod terrain;
 #[cfg(feature = "not-headless")]
 pub(crate) mod ui;
-pub(crate) mod vessel;
+pub mod vessel;
@@ -1,11 +1,15 @@
 use core::{error::Error, fmt::Display};
 
-use bevy::prelude::*;
+use bevy::{math::DVec2, prelude::*};
 use derive_more::{Deref, IsVariant};
 use keplerian_sim::{Orbit2D, OrbitTrait2D};
+use serde::{Deserialize, Serialize};
+
+use crate::orbit::{ApsisTarget, elements::OrbitElements, orbital_period, time_to_apsis};
 
 /// Marks this entity's relation with a parent celestial body.
-#[derive(Clone, Copy, Component, Debug)]
+#[derive(Clone, Copy, Component, Debug, Reflect)]
+#[reflect(Component, Debug, Clone)]
 #[require(RailMode)]
 #[relationship(relationship_target = CelestialChildren)]
 pub struct CelestialParent {
@@ -13,7 +17,13 @@ pub struct CelestialParent {
     pub entity: Entity,
 }
 
-#[derive(Component, Deref)]
+/// The [`CelestialParent`] this entity had the last time
+/// sphere of influence transitions were checked.
+#[derive(Clone, Copy, Component, Debug, PartialEq, Eq)]
+pub(crate) struct PrevCelestialParent(pub(crate) Entity);
+
+#[derive(Component, Deref, Reflect)]
+#[reflect(Component)]
 #[relationship_target(relationship = CelestialParent, linked_spawn)]
 pub struct CelestialChildren(Vec<Entity>);
 
@@ -24,8 +34,44 @@ impl CelestialChildren {
     }
 }
 
+/// The vessels within a celestial body's sphere of influence,
+/// i.e. the ones whose [`CelestialParent`] is this body.
+///
+/// Unlike [`CelestialChildren`], this leaves out the bodies orbiting
+/// this one. It gets brought up to date at the end of every fixed tick,
+/// after vessels have moved between spheres of influence.
+#[derive(Clone, Component, Debug, Default, Deref, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+pub struct SoiMembers(pub(crate) Vec<Entity>);
+
+/// Marks this entity as a part of a multi-part vessel,
+/// rigidly attached to the vessel's root part.
+///
+/// See [`VesselPart`][crate::components::main_game::vessel::VesselPart].
+#[derive(Clone, Copy, Component, Debug, Reflect)]
+#[reflect(Component, Debug, Clone)]
+#[relationship(relationship_target = ChildObjects)]
+pub struct ParentBody {
+    #[relationship]
+    pub entity: Entity,
+}
+
+/// The parts attached to a vessel's root part.
+#[derive(Component, Deref, Reflect)]
+#[reflect(Component)]
+#[relationship_target(relationship = ParentBody, linked_spawn)]
+pub struct ChildObjects(Vec<Entity>);
+
 /// How this entity behaves on-rails.
-#[derive(Clone, Copy, Component, Debug, Default, PartialEq, IsVariant)]
+///
+/// [`Orbit2D`] can't be reflected, so this gets reflected as an opaque
+/// value, which serializes orbits as their [`OrbitElements`].
+#[derive(
+    Clone, Copy, Component, Debug, Default, PartialEq, IsVariant, Reflect, Serialize, Deserialize,
+)]
+#[reflect(opaque)]
+#[reflect(Component, Default, Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(from = "SerializedRailMode", into = "SerializedRailMode")]
 pub enum RailMode {
     /// When on-rails, the object should stay static in terms of root-space
     /// coordinates.
@@ -55,6 +101,48 @@ impl RailMode {
             _ => None,
         }
     }
+
+    /// Gets the period of the orbit in this rail, in seconds.
+    ///
+    /// Returns [`None`] if this rail isn't an orbit, or if the orbit is open.
+    #[must_use]
+    pub fn orbital_period(&self) -> Option<f64> {
+        self.as_orbit().as_ref().and_then(orbital_period)
+    }
+
+    /// Gets the time from the simulation time `now` until the orbit
+    /// in this rail next passes through the given apsis.
+    ///
+    /// Returns [`None`] if this rail isn't an orbit, or if the orbit
+    /// will never pass through that apsis again.
+    #[must_use]
+    pub fn time_to_apsis(&self, now: f64, apsis: ApsisTarget) -> Option<f64> {
+        self.as_orbit()
+            .and_then(|orbit| time_to_apsis(&orbit, now, apsis))
+    }
+
+    /// Gets the position and velocity relative to the parent body
+    /// that this rail puts its object at, at the simulation time `time`.
+    ///
+    /// `body_rotation` is the parent's counterclockwise angular velocity,
+    /// in rad/s, which surface attachments get carried along at. The
+    /// attachment's angle itself gets used as-is.
+    ///
+    /// [`RailMode::None`] has no state of its own, so it gives zeroes.
+    #[must_use]
+    pub fn relative_state_at(&self, time: f64, body_rotation: f64) -> (DVec2, DVec2) {
+        match self {
+            Self::None => (DVec2::ZERO, DVec2::ZERO),
+            Self::Orbit(o) => {
+                let sv = o.get_state_vectors_at_time(time);
+                (sv.position, sv.velocity)
+            }
+            Self::Surface(a) => {
+                let position = DVec2::from_angle(a.angle) * a.radius;
+                (position, position.perp() * body_rotation)
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -102,8 +190,38 @@ impl Display for RailMode {
     }
 }
 
+/// [`RailMode`], with its orbit swapped out for plain elements
+/// that can be (de)serialized.
+#[derive(Serialize, Deserialize)]
+enum SerializedRailMode {
+    None,
+    Orbit(OrbitElements),
+    Surface(SurfaceAttachment),
+}
+
+impl From<RailMode> for SerializedRailMode {
+    fn from(value: RailMode) -> Self {
+        match value {
+            RailMode::None => Self::None,
+            RailMode::Orbit(o) => Self::Orbit(OrbitElements::of(&o)),
+            RailMode::Surface(a) => Self::Surface(a),
+        }
+    }
+}
+
+impl From<SerializedRailMode> for RailMode {
+    fn from(value: SerializedRailMode) -> Self {
+        match value {
+            SerializedRailMode::None => Self::None,
+            SerializedRailMode::Orbit(elements) => Self::Orbit(elements.to_orbit()),
+            SerializedRailMode::Surface(a) => Self::Surface(a),
+        }
+    }
+}
+
 /// Denotes an attachment of a vessel relative to a body's surface.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Debug, PartialEq, Clone)]
 pub struct SurfaceAttachment {
     /// The angle from the +x axis line that this
     /// vessel is landed on.
@@ -111,3 +229,127 @@ pub struct SurfaceAttachment {
     /// How far away from the planetary core this vessel is landed on.
     pub radius: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::DVec2;
+    use keplerian_sim::StateVectors2D;
+
+    #[test]
+    fn rail_orbital_period() {
+        const MU: f64 = 3.986e14;
+
+        let circular = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, (MU / 7e6).sqrt()),
+        }
+        .to_cached_orbit(MU, 0.0);
+        let escape = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 15000.0),
+        }
+        .to_cached_orbit(MU, 0.0);
+
+        let period = RailMode::Orbit(circular).orbital_period().unwrap();
+        assert!((period - 5828.5).abs() < 1.0, "got {period}");
+
+        assert_eq!(RailMode::Orbit(escape).orbital_period(), None);
+        assert_eq!(RailMode::None.orbital_period(), None);
+        assert_eq!(
+            RailMode::Surface(SurfaceAttachment {
+                angle: 0.0,
+                radius: 7e6,
+            })
+            .orbital_period(),
+            None
+        );
+    }
+
+    #[test]
+    fn rail_relative_state() {
+        const MU: f64 = 3.986e14;
+
+        assert_eq!(
+            RailMode::None.relative_state_at(1234.0, 1.0),
+            (DVec2::ZERO, DVec2::ZERO)
+        );
+
+        let sv = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 8000.0),
+        };
+        let orbit = sv.to_cached_orbit(MU, 100.0);
+        let (position, velocity) = RailMode::Orbit(orbit).relative_state_at(100.0, 1.0);
+        assert!(position.distance(sv.position) < 1e-3);
+        assert!(velocity.distance(sv.velocity) < 1e-6);
+
+        let later = orbit.get_state_vectors_at_time(700.0);
+        let (position, velocity) = RailMode::Orbit(orbit).relative_state_at(700.0, 0.0);
+        assert!(position.distance(later.position) < 1e-6);
+        assert!(velocity.distance(later.velocity) < 1e-9);
+
+        let landed = RailMode::Surface(SurfaceAttachment {
+            angle: core::f64::consts::FRAC_PI_2,
+            radius: 6e6,
+        });
+        let (position, velocity) = landed.relative_state_at(0.0, 0.0);
+        assert!(position.distance(DVec2::new(0.0, 6e6)) < 1e-6);
+        assert_eq!(velocity, DVec2::ZERO);
+
+        // Spinning counterclockwise carries the top of the body towards -X
+        let (spun_position, velocity) = landed.relative_state_at(5000.0, 1e-4);
+        assert!(spun_position.distance(position) < 1e-6);
+        assert!(velocity.distance(DVec2::new(-600.0, 0.0)) < 1e-6);
+    }
+
+    #[test]
+    fn rail_reflect_round_trip() {
+        use bevy::reflect::{
+            TypeRegistry,
+            serde::{ReflectDeserializer, ReflectSerializer},
+        };
+        use serde::de::DeserializeSeed;
+
+        const MU: f64 = 3.986e14;
+
+        let mut registry = TypeRegistry::new();
+        registry.register::<RailMode>();
+
+        let round_trip = |rail: RailMode| {
+            let serialized = ron::to_string(&ReflectSerializer::new(&rail, &registry)).unwrap();
+            let mut deserializer = ron::Deserializer::from_str(&serialized).unwrap();
+            let value = ReflectDeserializer::new(&registry)
+                .deserialize(&mut deserializer)
+                .unwrap();
+            <RailMode as FromReflect>::from_reflect(value.as_partial_reflect())
+                .unwrap_or_else(|| panic!("{serialized} didn't deserialize into a RailMode"))
+        };
+
+        assert_eq!(round_trip(RailMode::None), RailMode::None);
+
+        let landed = RailMode::Surface(SurfaceAttachment {
+            angle: 1.25,
+            radius: 6.4e6,
+        });
+        assert_eq!(round_trip(landed), landed);
+
+        let orbit = StateVectors2D {
+            position: DVec2::new(5e6, 5e6),
+            velocity: DVec2::new(-6500.0, 2500.0),
+        }
+        .to_cached_orbit(MU, 300.0);
+        let Some(restored) = round_trip(RailMode::Orbit(orbit)).as_orbit() else {
+            panic!("an orbit should deserialize into an orbit");
+        };
+        for time in [0.0, 300.0, 4000.0] {
+            let expected = orbit.get_state_vectors_at_time(time);
+            let actual = restored.get_state_vectors_at_time(time);
+            assert!(
+                actual.position.distance(expected.position) < 1e-3,
+                "{actual:?} != {expected:?} at {time} s"
+            );
+            assert!(actual.velocity.distance(expected.velocity) < 1e-6);
+        }
+    }
+}
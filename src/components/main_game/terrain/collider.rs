@@ -1,4 +1,4 @@
-use bevy::prelude::*;
+use bevy::{math::DVec2, prelude::*};
 use core::ops::Range;
 
 use crate::terrain::TerrainPoint;
@@ -8,3 +8,8 @@ pub(crate) struct PrevIndexRanges(pub(crate) Box<[Range<u32>]>);
 
 #[derive(Clone, Component, Debug, PartialEq)]
 pub(crate) struct PrevColliderPoints(pub(crate) Vec<TerrainPoint>);
+
+/// The rigid-space position of the celestial body at the time
+/// its terrain collider was last built.
+#[derive(Clone, Copy, Component, Debug, PartialEq)]
+pub(crate) struct PrevColliderOrigin(pub(crate) DVec2);
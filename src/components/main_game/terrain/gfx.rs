@@ -80,17 +80,21 @@ impl LodVectors {
         }
     }
 
-    /// The index buffer for minimal quality rendering (far away)
+    /// Creates a triangle fan index buffer for a ring of `RING` vertices
+    /// starting at index 1, around a center vertex at index 0.
     #[expect(clippy::cast_possible_truncation)]
-    const fn create_min_index_buffer() -> [u16; (MIN_LOD_VERTS as usize - 1) * 3] {
+    const fn create_ring_index_buffer<const RING: usize, const LEN: usize>() -> [u16; LEN] {
+        assert!(LEN == RING * 3);
+        assert!(RING < u16::MAX as usize);
+
         let mut arr = [0u16; _];
 
         let mut index = 1usize;
 
-        while index < MIN_LOD_VERTS as usize {
+        while index <= RING {
             arr[3 * index - 2] = index as u16;
             arr[3 * index - 1] = match index + 1 {
-                val if val == MIN_LOD_VERTS as usize => 1,
+                val if val > RING => 1,
                 val => val as u16,
             };
 
@@ -100,23 +104,13 @@ impl LodVectors {
         arr
     }
 
-    #[expect(clippy::cast_possible_truncation)]
-    const fn create_zeroth_index_buffer() -> [u16; (LOD_VERTS as usize - 1) * 3] {
-        let mut arr = [0u16; _];
-
-        let mut index = 1usize;
-
-        while index < LOD_VERTS as usize {
-            arr[3 * index - 2] = index as u16;
-            arr[3 * index - 1] = match index + 1 {
-                val if val == LOD_VERTS as usize => 1,
-                val => val as u16,
-            };
-
-            index += 1;
-        }
+    /// The index buffer for minimal quality rendering (far away)
+    const fn create_min_index_buffer() -> [u16; MIN_LOD_VERTS as usize * 3] {
+        Self::create_ring_index_buffer::<{ MIN_LOD_VERTS as usize }, _>()
+    }
 
-        arr
+    const fn create_zeroth_index_buffer() -> [u16; LOD_VERTS as usize * 3] {
+        Self::create_ring_index_buffer::<{ LOD_VERTS as usize }, _>()
     }
 
     /// Creates a very minimal vertex and index buffer
@@ -129,14 +123,17 @@ impl LodVectors {
         // using the constructors.
         let vecs = unsafe { self.0.first().unwrap_unchecked() };
 
-        let vertices = (0..MIN_LOD_VERTS)
-            .map(|i| vecs[(i * LOD_VERTS_PER_MIN) as usize].gfx_tf_downcast(shift, zoom))
+        // +1 vert in the center of the body
+        let points: Vec<_> = core::iter::once(TerrainPoint(DVec2::ZERO))
+            .chain((0..MIN_LOD_VERTS).map(|i| vecs[(i * LOD_VERTS_PER_MIN) as usize]))
             .collect();
 
-        Buffers {
-            vertices,
-            indices: Indices::U16(Vec::from(const { Self::create_min_index_buffer() })),
-        }
+        Buffers::from_fan(
+            &points,
+            Indices::U16(Vec::from(const { Self::create_min_index_buffer() })),
+            shift,
+            zoom,
+        )
     }
 
     /// Creates a vertex and index buffer from the vectors for just the zeroth `LoD`.
@@ -147,17 +144,65 @@ impl LodVectors {
             return Buffers::empty();
         };
 
-        Buffers {
-            vertices: vecs
-                .iter()
-                .map(|v| v.gfx_tf_downcast(shift, zoom))
-                .collect(),
-            indices: Indices::U16(Vec::from(const { Self::create_zeroth_index_buffer() })),
+        // +1 vert in the center of the body
+        let points: Vec<_> = core::iter::once(TerrainPoint(DVec2::ZERO))
+            .chain(vecs.iter().copied())
+            .collect();
+
+        Buffers::from_fan(
+            &points,
+            Indices::U16(Vec::from(const { Self::create_zeroth_index_buffer() })),
+            shift,
+            zoom,
+        )
+    }
+
+    /// Gets a LoD level's vertices, with `blend` vertices at each end
+    /// pulled onto the coarser level's outline.
+    ///
+    /// The coarser level samples the terrain too sparsely to follow the
+    /// finer level's bumps, so the two disagree where they get stitched
+    /// together. The vertex at each end gets put right on the coarser
+    /// level's outline, and the ones after it ease back into their own shape.
+    ///
+    /// # Unchecked Operation
+    /// This function assumes you have updated the `LoD` vectors
+    /// up to `level`.
+    #[must_use]
+    fn blended_level(
+        &self,
+        level: NonZeroU8,
+        focus: f64,
+        blend: u32,
+    ) -> [TerrainPoint; LOD_VERTS as usize] {
+        let mut verts = self.0[level.get() as usize];
+        let coarse = &self.0[level.get() as usize - 1];
+        let start = lod_level_index(level, focus);
+        let blend = blend.min(LOD_VERTS / 2);
+
+        for i in 0..blend {
+            let weight = f64::from(i) / f64::from(blend);
+
+            for fine in [i, LOD_VERTS - 1 - i] {
+                let coarse_idx = start + (fine / LOD_DIVISIONS) as usize;
+                let frac = f64::from(fine % LOD_DIVISIONS) / f64::from(LOD_DIVISIONS);
+                let outline = coarse[coarse_idx % LOD_VERTS as usize]
+                    .0
+                    .lerp(coarse[(coarse_idx + 1) % LOD_VERTS as usize].0, frac);
+
+                let vert = &mut verts[fine as usize];
+                vert.0 = outline.lerp(vert.0, weight);
+            }
         }
+
+        verts
     }
 
     /// Creates a vertex buffer from the vectors.
     ///
+    /// `blend` is how many vertices at each end of every LoD level
+    /// get blended into the coarser level, see [`Self::blended_level`].
+    ///
     /// # Unchecked Operation
     /// This function assumes you have updated the `LoD` vectors.
     #[must_use]
@@ -165,6 +210,7 @@ impl LodVectors {
         &self,
         focus: f64,
         max_level: NonZeroU8,
+        blend: u32,
     ) -> Box<[TerrainPoint]> {
         const LOD_0_USED_VERTS_COUNT: u32 = LOD_VERTS * (LOD_DIVISIONS - 1) / LOD_DIVISIONS - 1;
         const SKIP_VERTS_AMOUNT: usize = (LOD_VERTS / LOD_DIVISIONS + 1) as usize;
@@ -196,24 +242,24 @@ impl LodVectors {
             LOD_0_USED_VERTS_COUNT as usize,
         );
 
+        // We already clamped the max_level at the beginning of the function,
+        // so every level here is loaded. Level N ends up at index N - 1.
+        let levels: Vec<_> = (1..=max_level)
+            .map(|level| self.blended_level(NonZeroU8::new(level).unwrap(), focus, blend))
+            .collect();
+
         for level in 1..max_level {
-            // SAFETY: We already clamped the max_level at the beginning
-            // of the function.
-            let verts = unsafe { self.0.get_unchecked(level as usize) };
+            let verts = &levels[level as usize - 1];
 
             let next_start = lod_level_index(NonZeroU8::new(level + 1).unwrap(), focus);
 
             vertices.extend_from_slice(&verts[0..next_start]);
         }
 
-        // SAFETY: We already clamped the max_level at the beginning
-        // of the function.
-        vertices.extend_from_slice(unsafe { self.0.get_unchecked(max_level as usize) });
+        vertices.extend_from_slice(&levels[max_level as usize - 1]);
 
         for level in (1..max_level).rev() {
-            // SAFETY: We already clamped the max_level at the beginning
-            // of the function.
-            let verts = unsafe { self.0.get_unchecked(level as usize) };
+            let verts = &levels[level as usize - 1];
 
             let next_start = lod_level_index(NonZeroU8::new(level + 1).unwrap(), focus);
 
@@ -229,56 +275,29 @@ impl LodVectors {
     fn create_index_buffer_inner_16(len: u16) -> Indices {
         debug_assert!(len >= 3);
 
-        let mut buf = Box::new_uninit_slice((len as usize - 1) * 3);
+        let mut buf = Vec::with_capacity((len as usize - 1) * 3);
 
         for i in 1..len - 1 {
-            let offset = 3 * (i - 1) as usize;
-
-            unsafe {
-                buf.get_unchecked_mut(offset).write(0);
-                buf.get_unchecked_mut(offset + 1).write(i);
-                buf.get_unchecked_mut(offset + 2).write(i + 1);
-            }
+            buf.extend_from_slice(&[0, i, i + 1]);
         }
+        buf.extend_from_slice(&[0, len - 1, 1]);
 
-        let last = 3 * (len - 2) as usize;
-
-        unsafe {
-            buf.get_unchecked_mut(last).write(0);
-            buf.get_unchecked_mut(last + 1).write(len - 1);
-            buf.get_unchecked_mut(last + 2).write(1);
-        }
-
-        let buf = unsafe { buf.assume_init() };
-
-        Indices::U16(buf.into())
+        Indices::U16(buf)
     }
 
     /// `len` is the length of the vertex buffer and must be >= 3
     #[cold]
     fn create_index_buffer_inner_32(len: u32) -> Indices {
-        let mut buf = Box::new_uninit_slice((len as usize - 1) * 3);
-
-        for i in 1..len - 1 {
-            let offset = 3 * (i - 1) as usize;
-
-            unsafe {
-                buf.get_unchecked_mut(offset).write(0);
-                buf.get_unchecked_mut(offset + 1).write(i);
-                buf.get_unchecked_mut(offset + 2).write(i + 1);
-            }
-        }
+        debug_assert!(len >= 3);
 
-        let last = 3 * (len - 2) as usize;
+        let mut buf = Vec::with_capacity((len as usize - 1) * 3);
 
-        unsafe {
-            buf.get_unchecked_mut(last).write(0);
-            buf.get_unchecked_mut(last + 1).write(len - 1);
-            buf.get_unchecked_mut(last + 2).write(1);
+        for i in 1..len - 1 {
+            buf.extend_from_slice(&[0, i, i + 1]);
         }
+        buf.extend_from_slice(&[0, len - 1, 1]);
 
-        let buf = unsafe { buf.assume_init() };
-        Indices::U32(buf.into())
+        Indices::U32(buf)
     }
 
     /// Create an index buffer from the given vertex buffer.
@@ -304,17 +323,14 @@ impl LodVectors {
         &self,
         focus: f64,
         max_level: NonZeroU8,
+        blend: u32,
         shift: DVec2,
         zoom: SimCameraZoom,
     ) -> Buffers {
-        let vertices: Vec<Vec3> = self
-            .create_unshifted_vertex_buffer(focus, max_level)
-            .into_iter()
-            .map(|point| point.gfx_tf_downcast(shift, zoom))
-            .collect();
-        let indices = Self::create_index_buffer(vertices.len());
+        let points = self.create_unshifted_vertex_buffer(focus, max_level, blend);
+        let indices = Self::create_index_buffer(points.len());
 
-        Buffers { vertices, indices }
+        Buffers::from_fan(&points, indices, shift, zoom)
     }
 
     /// Creates a vertex and index buffer from the vectors.
@@ -324,6 +340,9 @@ impl LodVectors {
     /// This is reserved for when the camera is zoomed very far out or is very
     /// far away.
     ///
+    /// `blend` is how many vertices at each end of every LoD level
+    /// get blended into the coarser level, see [`Self::blended_level`].
+    ///
     /// # Unchecked Operation
     /// This function assumes you have updated the `LoD` vectors.
     #[must_use]
@@ -331,15 +350,20 @@ impl LodVectors {
         &self,
         focus: f64,
         max_level: Option<u8>,
+        blend: u32,
         shift: DVec2,
         zoom: SimCameraZoom,
     ) -> Buffers {
         match max_level {
             None => self.create_min_buffer(shift, zoom),
             Some(0) => self.create_zeroth_buffer(shift, zoom),
-            Some(max_level) => {
-                self.create_buffers_inner(focus, NonZeroU8::new(max_level).unwrap(), shift, zoom)
-            }
+            Some(max_level) => self.create_buffers_inner(
+                focus,
+                NonZeroU8::new(max_level).unwrap(),
+                blend,
+                shift,
+                zoom,
+            ),
         }
     }
 }
@@ -375,25 +399,23 @@ fn partial_wrapping_copy<T: Clone, const M: usize>(
 mod tests {
     use core::f64::consts::TAU;
 
-    use crate::components::main_game::celestial::Terrain;
+    use crate::{components::main_game::celestial::Terrain, consts::terrain::TEST_TERRAIN};
 
     use super::*;
     use bevy::mesh::Indices;
 
-    const TEST_TERRAIN: Terrain = Terrain {
-        seed: 0xabcba,
-        octaves: 8,
-        frequency: 1.0,
-        gain: 0.5,
-        lacunarity: 2.0,
-        offset: 20e6,
-        multiplier: 10.0,
-        subdivs: 8,
-    };
+    /// Builds a triangle fan around vertex 0 for a vertex buffer of
+    /// `len` vertices, one triangle at a time.
+    fn expected_fan(len: u32) -> Vec<u32> {
+        (1..len)
+            .flat_map(|cur| {
+                let next = if cur + 1 == len { 1 } else { cur + 1 };
+                [0, cur, next]
+            })
+            .collect()
+    }
 
     #[test]
-    #[ignore = "takes a few dozen secs"]
-    #[expect(clippy::cast_possible_truncation)]
     fn test_index_buffer() {
         let buf = LodVectors::create_index_buffer_inner_16(7);
         assert_eq!(
@@ -401,47 +423,95 @@ mod tests {
             Indices::U16(vec![0, 1, 2, 0, 2, 3, 0, 3, 4, 0, 4, 5, 0, 5, 6, 0, 6, 1])
         );
 
-        for i in 3..u16::MAX {
-            let buf = LodVectors::create_index_buffer_inner_16(i);
-            let Indices::U16(buf) = buf else {
+        let lens_16 = (3..64)
+            .chain([MIN_LOD_VERTS, 255, 256, 257, 1000])
+            .chain([u16::MAX - 1, u16::MAX]);
+
+        for len in lens_16 {
+            let Indices::U16(buf) = LodVectors::create_index_buffer_inner_16(len) else {
                 panic!("buf isn't u16")
             };
+            let buf: Vec<u32> = buf.into_iter().map(u32::from).collect();
 
-            for (tri, slice) in buf.chunks(3).enumerate() {
-                let tri = tri as u16;
-                let [zero, cur, next] = [slice[0], slice[1], slice[2]];
-                assert_eq!(zero, 0);
-                assert_eq!(cur, tri + 1);
-
-                if tri + 2 == i {
-                    assert_eq!(next, 1);
-                } else {
-                    assert_eq!(next, tri + 2);
-                }
-            }
+            assert_eq!(buf, expected_fan(len.into()), "{len} vertices");
         }
 
-        for i in u32::from(u16::MAX)..70000 {
-            let buf = LodVectors::create_index_buffer_inner_32(i);
-            let Indices::U32(buf) = buf else {
+        for len in [3, 7, u32::from(u16::MAX) + 1, 70000] {
+            let Indices::U32(buf) = LodVectors::create_index_buffer_inner_32(len) else {
                 panic!("buf isn't u32")
             };
 
-            for (tri, slice) in buf.chunks(3).enumerate() {
-                let tri = tri as u32;
-                let [zero, cur, next] = [slice[0], slice[1], slice[2]];
-                assert_eq!(zero, 0);
-                assert_eq!(cur, tri + 1);
+            assert_eq!(buf, expected_fan(len), "{len} vertices");
+        }
+    }
 
-                if tri + 2 == i {
-                    assert_eq!(next, 1);
-                } else {
-                    assert_eq!(next, tri + 2);
-                }
-            }
+    /// Checks that `buf` is a triangle fan around vertex 0
+    /// covering a ring of `ring` vertices exactly once.
+    fn assert_ring_fan(buf: &[u16], ring: usize) {
+        assert_eq!(buf.len(), ring * 3, "expected one triangle per ring vertex");
+
+        for (tri, slice) in buf.chunks(3).enumerate() {
+            let expected_next = if tri + 1 == ring { 1 } else { tri + 2 };
+
+            assert_eq!(
+                [slice[0], slice[1], slice[2]].map(usize::from),
+                [0, tri + 1, expected_next],
+                "triangle {tri} of a {ring}-vertex ring"
+            );
         }
     }
 
+    #[test]
+    fn test_ring_index_buffers() {
+        assert_ring_fan(&LodVectors::create_ring_index_buffer::<3, 9>(), 3);
+        assert_ring_fan(&LodVectors::create_ring_index_buffer::<8, 24>(), 8);
+        assert_ring_fan(&LodVectors::create_ring_index_buffer::<20, 60>(), 20);
+        assert_ring_fan(&LodVectors::create_min_index_buffer(), MIN_LOD_VERTS.into());
+        assert_ring_fan(
+            &LodVectors::create_zeroth_index_buffer(),
+            LOD_VERTS as usize,
+        );
+    }
+
+    #[test]
+    fn test_min_buffer() {
+        const _: () = assert!(
+            MIN_LOD_VERTS as u32 != LOD_VERTS,
+            "this test is meant to exercise decimation"
+        );
+        const STRIDE: usize = (LOD_VERTS / MIN_LOD_VERTS as u32) as usize;
+
+        let terrain = TerrainGen::new(TEST_TERRAIN);
+        let vectors = LodVectors::new(&terrain);
+        let shift = DVec2::new(3.0, -4.0);
+        let zoom = SimCameraZoom(0.5);
+
+        let min = vectors.create_buffers(0.0, None, 0, shift, zoom);
+        let zeroth = vectors.create_buffers(0.0, Some(0), 0, shift, zoom);
+
+        assert_eq!(min.vertices.len(), usize::from(MIN_LOD_VERTS) + 1);
+        assert_eq!(zeroth.vertices.len(), LOD_VERTS as usize + 1);
+
+        let center = TerrainPoint(DVec2::ZERO).gfx_tf_downcast(shift, zoom);
+        assert_eq!(min.vertices[0], center);
+        assert_eq!(zeroth.vertices[0], center);
+
+        for (i, vertex) in min.vertices[1..].iter().enumerate() {
+            assert_eq!(*vertex, zeroth.vertices[i * STRIDE + 1]);
+            assert_eq!(*vertex, vectors[0][i * STRIDE].gfx_tf_downcast(shift, zoom));
+        }
+
+        let Indices::U16(min_indices) = min.indices else {
+            panic!("min indices aren't u16")
+        };
+        let Indices::U16(zeroth_indices) = zeroth.indices else {
+            panic!("zeroth indices aren't u16")
+        };
+
+        assert_ring_fan(&min_indices, MIN_LOD_VERTS.into());
+        assert_ring_fan(&zeroth_indices, LOD_VERTS as usize);
+    }
+
     #[test]
     fn test_partial_wrapping_copy() {
         fn slow_pwc<T: Clone, const M: usize>(
@@ -502,6 +572,7 @@ mod tests {
             let old_buffers = vectors.create_buffers(
                 focus,
                 Some(TEST_TERRAIN.subdivs),
+                8,
                 DVec2::ZERO,
                 SimCameraZoom(1.0),
             );
@@ -516,6 +587,7 @@ mod tests {
             let new_buffers = vectors.create_buffers(
                 focus,
                 Some(TEST_TERRAIN.subdivs),
+                8,
                 DVec2::ZERO,
                 SimCameraZoom(1.0),
             );
@@ -548,12 +620,14 @@ mod tests {
             let full_buffers = full_vectors.create_buffers(
                 focus,
                 Some(TEST_TERRAIN.subdivs),
+                8,
                 DVec2::ZERO,
                 SimCameraZoom(1.0),
             );
             let lazy_buffers = lazy_vectors.create_buffers(
                 focus,
                 Some(TEST_TERRAIN.subdivs),
+                8,
                 DVec2::ZERO,
                 SimCameraZoom(1.0),
             );
@@ -580,4 +654,98 @@ mod tests {
             assert_eq!(full_buffers.indices, lazy_buffers.indices);
         }
     }
+
+    #[test]
+    #[expect(clippy::cast_precision_loss)]
+    fn stitches_are_watertight() {
+        const ITERS: usize = 16;
+        const BLEND: u32 = 8;
+        const TOLERANCE: f64 = 1e-6;
+        const VERTS: usize = LOD_VERTS as usize;
+
+        let terrain = TerrainGen::new(TEST_TERRAIN);
+        let mut unblended_gap: f64 = 0.0;
+
+        for i in 0..ITERS {
+            let focus = i as f64 * TAU / ITERS as f64;
+            let vectors = LodVectors::new_full(&terrain, TEST_TERRAIN.subdivs, focus);
+
+            for level in (1..=TEST_TERRAIN.subdivs).map(|level| NonZeroU8::new(level).unwrap()) {
+                let raw = &vectors[level.get() as usize];
+                let coarse = &vectors[level.get() as usize - 1];
+                let blended = vectors.blended_level(level, focus, BLEND);
+
+                // The first vertex sits on a coarse vertex, and the last one
+                // three quarters of the way between two of them
+                let start = lod_level_index(level, focus);
+                let end = start + LOD_VERTS_PER_DIVISION as usize - 1;
+                let head = coarse[start].0;
+                let tail = coarse[end % VERTS]
+                    .0
+                    .lerp(coarse[(end + 1) % VERTS].0, 0.75);
+
+                for (expected, vertex, side) in [
+                    (head, blended[0].0, "start"),
+                    (tail, blended[VERTS - 1].0, "end"),
+                ] {
+                    assert!(
+                        vertex.distance(expected) < TOLERANCE,
+                        "gap of {} m at the {side} of level {level}, focus {focus}",
+                        vertex.distance(expected)
+                    );
+                }
+                unblended_gap = unblended_gap.max(raw[VERTS - 1].0.distance(tail));
+
+                let untouched = BLEND as usize..VERTS - BLEND as usize;
+                assert_eq!(
+                    blended[untouched.clone()],
+                    raw[untouched],
+                    "vertices away from the stitches shouldn't move"
+                );
+            }
+
+            let max_level = NonZeroU8::new(TEST_TERRAIN.subdivs).unwrap();
+            let blended = vectors.create_unshifted_vertex_buffer(focus, max_level, BLEND);
+            let unblended = vectors.create_unshifted_vertex_buffer(focus, max_level, 0);
+            assert_eq!(blended.len(), unblended.len());
+        }
+
+        assert!(
+            unblended_gap > TOLERANCE,
+            "the terrain should be bumpy enough to leave gaps without blending"
+        );
+    }
+
+    #[test]
+    fn circular_terrain_normals_point_outward() {
+        let terrain = TerrainGen::new(Terrain {
+            multiplier: 0.0,
+            ..TEST_TERRAIN
+        });
+        let shift = DVec2::new(-2e7, 5e6);
+        let zoom = SimCameraZoom(1e-6);
+
+        for (focus, max_level) in [
+            (0.0, None),
+            (1.0, Some(0)),
+            (2.5, Some(TEST_TERRAIN.subdivs)),
+        ] {
+            let vectors = LodVectors::new_full(&terrain, TEST_TERRAIN.subdivs, focus);
+            let buffers = vectors.create_buffers(focus, max_level, 0, shift, zoom);
+
+            assert_eq!(buffers.normals.len(), buffers.vertices.len());
+            assert_eq!(buffers.normals[0], Vec3::Z, "the center faces the camera");
+
+            let center = buffers.vertices[0];
+            for (vertex, normal) in buffers.vertices.iter().zip(&buffers.normals).skip(1) {
+                let radial = (*vertex - center).normalize();
+                // Neighbors are unevenly spaced where LoD levels meet,
+                // which skews the estimated tangent a bit
+                assert!(
+                    normal.distance(radial) < 1e-2,
+                    "normal {normal} at {vertex} isn't radial ({radial}), max level {max_level:?}"
+                );
+            }
+        }
+    }
 }
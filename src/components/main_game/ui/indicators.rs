@@ -0,0 +1,11 @@
+use bevy::prelude::*;
+
+use crate::resources::scene::GameScene;
+
+/// The distance text next to an edge indicator.
+#[derive(Clone, Copy, Component, Debug, PartialEq, Eq)]
+#[require(DespawnOnExit::<GameScene>(GameScene::InGame), Node)]
+pub(crate) struct EdgeIndicatorLabel {
+    /// The off-screen entity the indicator points towards.
+    pub(crate) target: Entity,
+}
@@ -1,4 +1,6 @@
 pub(crate) mod altimeter;
 pub(crate) mod controls;
+#[cfg(feature = "not-headless")]
+pub(crate) mod indicators;
 pub(crate) mod oribar;
 pub(crate) mod speedometer;
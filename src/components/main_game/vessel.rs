@@ -1,4 +1,719 @@
-use bevy::prelude::*;
+use bevy::{math::DVec2, prelude::*};
+use bevy_rapier2d::{
+    prelude::{Collider, ExternalForce},
+    rapier::prelude::Aabb,
+};
+
+use crate::consts::{SETTLED_SPEED, SETTLED_TICKS};
 
 #[derive(Clone, Copy, Component)]
+#[require(OrbitalVelocity, SurfaceVelocity, LandedState, VesselInput)]
 pub(crate) struct Vessel;
+
+/// An input axis that eases towards where the player wants it,
+/// rather than snapping there as soon as a key gets pressed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Default, Debug, PartialEq, Clone)]
+pub struct SmoothedAxis {
+    /// The value actually being used.
+    pub current: f64,
+    /// The value being eased towards.
+    pub target: f64,
+}
+
+impl SmoothedAxis {
+    /// Moves the current value towards the target by at most `max_delta`.
+    pub fn approach(&mut self, max_delta: f64) {
+        let diff = self.target - self.current;
+        self.current += diff.clamp(-max_delta, max_delta);
+    }
+}
+
+/// The player's control inputs for a vessel, smoothed over time
+/// according to [`InputSmoothing`][crate::resources::controls::InputSmoothing].
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+pub struct VesselInput {
+    /// How hard to burn, in the range 0..=1.
+    pub throttle: SmoothedAxis,
+    /// How hard to turn, in the range -1..=1.
+    ///
+    /// Positive values turn counterclockwise.
+    pub rotation: SmoothedAxis,
+    /// Whether precision mode is on, which makes
+    /// the inputs change slower and turn less hard.
+    pub precision: bool,
+}
+
+/// A vessel's velocity relative to its parent body's spinning surface,
+/// decomposed into vertical and horizontal components.
+///
+/// Only kept up-to-date for loaded vessels.
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+pub struct SurfaceVelocity {
+    /// The velocity away from the parent's center, in m/s.
+    ///
+    /// Negative when descending.
+    pub vertical: f64,
+    /// The velocity along the surface, in m/s.
+    ///
+    /// Positive when moving counterclockwise around the parent.
+    pub horizontal: f64,
+}
+
+impl SurfaceVelocity {
+    /// Decomposes a velocity relative to the parent, given the position
+    /// relative to the parent and the parent's counterclockwise angular
+    /// velocity, in radians per second.
+    #[must_use]
+    pub fn from_relative(rel_pos: DVec2, rel_vel: DVec2, angular_velocity: f64) -> Self {
+        let surface_vel = angular_velocity * rel_pos.perp();
+        let vel = OrbitalVelocity::from_relative(rel_pos, rel_vel - surface_vel);
+
+        Self {
+            vertical: vel.radial,
+            horizontal: vel.prograde,
+        }
+    }
+}
+
+/// How long a vessel has been resting on its parent body's surface.
+///
+/// Once a vessel has been touching the surface slower than
+/// [`SETTLED_SPEED`] for [`SETTLED_TICKS`] ticks, it counts as settled,
+/// and its [`SurfaceAttachment`][crate::components::main_game::relations::SurfaceAttachment]
+/// gets kept as-is instead of following the jitter of the contacts.
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Eq)]
+pub(crate) struct LandedState {
+    /// How many ticks in a row the vessel has been resting.
+    calm_ticks: u32,
+}
+
+impl LandedState {
+    /// Records a tick of touching the surface at the given
+    /// speed relative to the parent body, in m/s.
+    pub(crate) fn record(&mut self, rel_speed: f64) {
+        self.calm_ticks = if rel_speed < SETTLED_SPEED {
+            self.calm_ticks.saturating_add(1)
+        } else {
+            0
+        };
+    }
+
+    /// Gets whether the vessel has been resting long enough
+    /// for its attachment to be frozen.
+    #[must_use]
+    pub(crate) const fn is_settled(self) -> bool {
+        self.calm_ticks >= SETTLED_TICKS
+    }
+}
+
+/// A vessel's velocity relative to its parent body, decomposed
+/// into prograde and radial components.
+///
+/// Only kept up-to-date for loaded vessels.
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+pub struct OrbitalVelocity {
+    /// The velocity perpendicular to the parent direction, in m/s.
+    ///
+    /// Positive when moving counterclockwise around the parent.
+    pub prograde: f64,
+    /// The velocity along the parent direction, in m/s.
+    ///
+    /// Positive when moving away from the parent (radial-out).
+    pub radial: f64,
+}
+
+impl OrbitalVelocity {
+    /// Decomposes a velocity relative to the parent, given the
+    /// position relative to the parent.
+    #[must_use]
+    pub fn from_relative(rel_pos: DVec2, rel_vel: DVec2) -> Self {
+        let radial_dir = rel_pos.normalize_or_zero();
+
+        Self {
+            prograde: rel_vel.dot(radial_dir.perp()),
+            radial: rel_vel.dot(radial_dir),
+        }
+    }
+}
+
+/// The directions a vessel is going relative to its parent body,
+/// for drawing markers on a flight HUD.
+///
+/// All directions are unit vectors in root space.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OrbitalMarkers {
+    /// Along the velocity relative to the parent.
+    ///
+    /// [`None`] if the vessel isn't moving relative to the parent.
+    pub prograde: Option<DVec2>,
+    /// Against the velocity relative to the parent.
+    ///
+    /// [`None`] if the vessel isn't moving relative to the parent.
+    pub retrograde: Option<DVec2>,
+    /// Perpendicular to prograde, towards the parent.
+    pub radial_in: Option<DVec2>,
+    /// Perpendicular to prograde, away from the parent.
+    pub radial_out: Option<DVec2>,
+}
+
+impl OrbitalMarkers {
+    /// Gets the markers from the position and velocity relative to the parent.
+    ///
+    /// Without any relative velocity, radial-in and radial-out point
+    /// straight towards and away from the parent instead. When moving
+    /// straight towards or away from the parent, radial-out gets picked
+    /// to be on the counterclockwise side of prograde.
+    #[must_use]
+    pub fn from_relative(rel_pos: DVec2, rel_vel: DVec2) -> Self {
+        let prograde = rel_vel.try_normalize();
+
+        let radial_out = match prograde {
+            Some(prograde) => {
+                let perp = prograde.perp();
+                let side = if rel_pos.dot(perp) < 0.0 { -1.0 } else { 1.0 };
+                Some(perp * side)
+            }
+            None => rel_pos.try_normalize(),
+        };
+
+        Self {
+            prograde,
+            retrograde: prograde.map(|dir| -dir),
+            radial_in: radial_out.map(|dir| -dir),
+            radial_out,
+        }
+    }
+}
+
+/// A part of a vessel.
+///
+/// A multi-part vessel is made of a root part, which is the entity with
+/// the [`Vessel`] and rigid body components, and other parts that point to
+/// the root part using a [`ParentBody`][crate::components::main_game::relations::ParentBody].
+/// Only the root part gets simulated; the other parts don't have a rigid
+/// body of their own and simply follow the root part around.
+///
+/// # Colliders
+/// Parts have their shape stored here instead of having a [`Collider`]
+/// component, as Rapier would otherwise treat them as standalone colliders.
+/// The root part's collider is a compound of every part's shape,
+/// including its own.
+///
+/// # Mass
+/// The root part's [`AdditionalMassProperties`][bevy_rapier2d::prelude::AdditionalMassProperties]
+/// is set to the sum of every part's mass, including its own, placed at
+/// their combined center of mass. Each part's mass is assumed to be spread
+/// evenly over its shape.
+#[derive(Clone, Component, Debug)]
+pub struct VesselPart {
+    /// The position of this part relative to the root part, in meters.
+    ///
+    /// This is ignored for the root part itself.
+    pub offset: Vec2,
+    /// The counterclockwise rotation of this part relative to the
+    /// root part, in radians.
+    ///
+    /// This is ignored for the root part itself.
+    pub angle: f32,
+    /// The shape of this part, relative to the part's own origin.
+    pub shape: Collider,
+    /// The mass of this part, in kilograms.
+    pub mass: f32,
+}
+
+impl VesselPart {
+    /// Gets the transform of this part relative to the root part.
+    #[must_use]
+    pub fn local_transform(&self) -> Transform {
+        Transform::from_translation(self.offset.extend(0.0))
+            .with_rotation(Quat::from_rotation_z(self.angle))
+    }
+}
+
+/// A spot on a vessel part where another vessel can dock.
+///
+/// Two vessels dock when one port of each gets close enough, facing
+/// each other, at a low enough relative speed. The lighter vessel then
+/// becomes a set of parts of the heavier one.
+#[derive(Clone, Copy, Component, Debug, PartialEq)]
+pub struct DockingPort {
+    /// The position of the port relative to the part it's on, in meters.
+    pub offset: Vec2,
+    /// The counterclockwise angle, in radians, from the part's +X axis
+    /// to the direction the port faces.
+    pub facing: f32,
+}
+
+/// Marks the root part of a vessel that's docked to another one.
+///
+/// While docked, this part is attached to the other vessel's root part
+/// through a [`ParentBody`][crate::components::main_game::relations::ParentBody],
+/// just like any other part.
+#[derive(Clone, Component, Debug)]
+pub(crate) struct DockedVessel {
+    /// This vessel's docking port.
+    pub(crate) port: Entity,
+    /// The docking port of the vessel this one is docked to.
+    pub(crate) other_port: Entity,
+    /// The parts that were attached to this part before docking.
+    pub(crate) parts: Vec<Entity>,
+}
+
+/// Marks a vessel as debris, e.g. a spent stage or a broken-off part.
+///
+/// Debris gets loaded in a smaller radius around the active vessel than
+/// other vessels, and gets despawned when there's more of it than the
+/// [`DebrisLimit`][crate::resources::simulation::DebrisLimit] allows.
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+pub struct Debris;
+
+/// How hard a vessel can hit something before it gets destroyed.
+///
+/// Vessels without this component never get destroyed.
+#[derive(Clone, Copy, Component, Debug, PartialEq, Reflect)]
+#[reflect(Component, Debug, PartialEq, Clone)]
+#[require(ImpactSensor)]
+pub struct CrashTolerance {
+    /// The highest impact speed, in m/s, that the vessel survives.
+    pub max_impact_speed: f64,
+}
+
+/// Remembers a vessel's velocity from right before the physics step,
+/// so that sudden changes caused by collisions can be noticed.
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq)]
+pub(crate) struct ImpactSensor {
+    /// The rigid-space linear velocity before the current physics step,
+    /// if it's been recorded yet.
+    pub(crate) pre_step_linvel: Option<Vec2>,
+}
+
+/// How much a vessel gets slowed down by air resistance.
+///
+/// The drag gets applied through the vessel's [`ExternalForce`].
+#[derive(Clone, Copy, Component, Debug, PartialEq, Reflect)]
+#[reflect(Component, Debug, PartialEq, Clone)]
+#[require(ExternalForce)]
+pub struct DragProfile {
+    /// The dimensionless drag coefficient.
+    pub drag_coefficient: f64,
+    /// The cross-section the air pushes against, in meters.
+    ///
+    /// As the simulation is 2D, this is a length rather than an area.
+    pub reference_area: f64,
+}
+
+impl DragProfile {
+    /// The drag coefficient given to vessels by default.
+    pub const DEFAULT_DRAG_COEFFICIENT: f64 = 0.8;
+
+    /// Creates a drag profile using the larger side of a
+    /// collider's AABB as the reference area.
+    #[must_use]
+    pub fn from_aabb(aabb: Aabb, drag_coefficient: f64) -> Self {
+        let width = aabb.maxs.x - aabb.mins.x;
+        let height = aabb.maxs.y - aabb.mins.y;
+
+        Self {
+            drag_coefficient,
+            reference_area: f64::from(width.max(height)),
+        }
+    }
+
+    /// Gets the drag force, in newtons, on a vessel moving through
+    /// air of the given density with the given velocity relative to the air.
+    ///
+    /// The force always opposes the relative velocity.
+    #[must_use]
+    pub fn drag_force(self, density: f64, rel_vel: DVec2) -> DVec2 {
+        -0.5 * density * rel_vel.length() * rel_vel * self.drag_coefficient * self.reference_area
+    }
+}
+
+/// Lets a vessel push itself forwards, driven by its
+/// [`VesselInput::throttle`].
+///
+/// The thrust always points along the vessel's raw forward direction,
+/// which is +Y when it has no rotation.
+/// Vessels without this component can't speed up on their own.
+#[derive(Clone, Copy, Component, Debug, PartialEq, Reflect)]
+#[reflect(Component, Debug, PartialEq, Clone)]
+pub struct Engine {
+    /// The thrust at full throttle, in N.
+    pub max_thrust: f32,
+    /// How much longer the engine can burn at full throttle, in seconds.
+    ///
+    /// Lower throttles use the fuel up proportionally slower, and
+    /// engines without any fuel left don't thrust at all.
+    /// Engines with infinite fuel never run out.
+    pub fuel: f64,
+}
+
+impl Engine {
+    /// Gets whether the engine has any fuel left to burn.
+    #[must_use]
+    pub fn has_fuel(self) -> bool {
+        self.fuel > 0.0
+    }
+
+    /// Gets the thrust, in N, to apply for the given throttle input.
+    ///
+    /// The input is in the range 0..=1. Inputs outside of that range
+    /// never thrust backwards, nor get more than
+    /// [`max_thrust`][Self::max_thrust] out of the engine.
+    /// Engines without fuel give no thrust.
+    #[must_use]
+    pub fn commanded_thrust(self, throttle: f64) -> f64 {
+        if !self.has_fuel() {
+            return 0.0;
+        }
+
+        let max_thrust = f64::from(self.max_thrust.max(0.0));
+
+        (throttle * max_thrust).clamp(0.0, max_thrust)
+    }
+
+    /// Uses up the fuel for burning at the given throttle
+    /// input for `dt` seconds.
+    pub fn burn(&mut self, throttle: f64, dt: f64) {
+        self.fuel = throttle.clamp(0.0, 1.0).mul_add(-dt, self.fuel).max(0.0);
+    }
+}
+
+/// Lets a vessel turn itself by spinning up a wheel inside it,
+/// driven by its [`VesselInput::rotation`].
+///
+/// As the simulation is 2D, there's only the one axis to turn around.
+/// Vessels without this component can't turn on their own.
+#[derive(Clone, Copy, Component, Debug, PartialEq, Reflect)]
+#[reflect(Component, Debug, PartialEq, Clone)]
+pub struct ReactionWheel {
+    /// The most torque the wheel can apply either way, in N·m.
+    pub max_torque: f32,
+}
+
+impl ReactionWheel {
+    /// Gets the torque, in N·m, to apply for the given rotation input.
+    ///
+    /// The input is in the range -1..=1, with positive values turning
+    /// counterclockwise. Inputs outside of that range still never
+    /// get more than [`max_torque`][Self::max_torque] out of the wheel.
+    #[must_use]
+    pub fn commanded_torque(self, rotation: f64) -> f64 {
+        let max_torque = f64::from(self.max_torque.max(0.0));
+
+        (rotation * max_torque).clamp(-max_torque, max_torque)
+    }
+}
+
+/// Makes a vessel's [`ReactionWheel`] saturate, losing torque
+/// when it keeps turning the same way for too long.
+///
+/// Every bit of torque the wheel puts into the vessel gets stored as
+/// angular momentum in the wheel, and the closer that gets to the
+/// [`capacity`][Self::capacity], the less torque is left for turning
+/// further that way. Turning the other way always gets the full torque,
+/// and unloads the wheel as it goes.
+#[derive(Clone, Copy, Component, Debug, PartialEq, Reflect)]
+#[reflect(Component, Debug, PartialEq, Clone)]
+pub struct WheelSaturation {
+    /// How much angular momentum, in N·m·s, the wheel can store.
+    pub capacity: f64,
+    /// How fast the stored angular momentum bleeds off on its own,
+    /// in N·m, e.g. through magnetorquers.
+    pub desaturation_rate: f64,
+    /// The angular momentum the wheel is storing, in N·m·s.
+    ///
+    /// Positive after turning the vessel counterclockwise.
+    pub stored: f64,
+}
+
+impl WheelSaturation {
+    /// Creates an empty wheel saturation model.
+    #[must_use]
+    pub const fn new(capacity: f64, desaturation_rate: f64) -> Self {
+        Self {
+            capacity,
+            desaturation_rate,
+            stored: 0.0,
+        }
+    }
+
+    /// Gets the share of the wheel's torque, in the range 0..=1,
+    /// that's left for turning the same way as `torque`.
+    #[must_use]
+    pub fn available_fraction(self, torque: f64) -> f64 {
+        if self.capacity <= 0.0 {
+            return 0.0;
+        }
+
+        let load = (self.stored * torque.signum() / self.capacity).clamp(0.0, 1.0);
+
+        1.0 - load
+    }
+
+    /// Limits `torque` to what the wheel has left, then stores
+    /// the angular momentum it puts in over `dt` seconds.
+    ///
+    /// Returns the torque that actually gets applied, in N·m.
+    pub fn apply(&mut self, torque: f64, dt: f64) -> f64 {
+        let torque = torque * self.available_fraction(torque);
+        let capacity = self.capacity.max(0.0);
+        let bleed = self.desaturation_rate.max(0.0) * dt;
+
+        self.stored = torque.mul_add(dt, self.stored).clamp(-capacity, capacity);
+        self.stored -= self.stored.clamp(-bleed, bleed);
+
+        torque
+    }
+}
+
+/// The part of a vessel that it is being "controlled from".
+///
+/// Vessels without this component are controlled from their
+/// raw [`Transform`] facing.
+#[derive(Clone, Copy, Component, Debug, Default, PartialEq)]
+pub(crate) struct ControlPoint {
+    /// The counterclockwise angle, in radians, between the vessel's
+    /// raw forward direction and the control point's forward direction.
+    pub(crate) forward_offset_angle: f64,
+}
+
+impl ControlPoint {
+    /// Gets the rotation of the control point, given the
+    /// raw rotation of the vessel.
+    #[must_use]
+    pub(crate) fn effective_rotation(self, raw_rotation: f64) -> f64 {
+        raw_rotation + self.forward_offset_angle
+    }
+
+    /// Gets the unit vector the control point is facing towards,
+    /// given the raw rotation of the vessel.
+    ///
+    /// A vessel's forward direction is +Y when it has no rotation.
+    #[must_use]
+    pub(crate) fn forward(self, raw_rotation: f64) -> DVec2 {
+        DVec2::from_angle(self.effective_rotation(raw_rotation)).perp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f64::consts::{FRAC_PI_2, TAU};
+
+    #[test]
+    fn control_point_offset() {
+        let default = ControlPoint::default();
+        let offset = ControlPoint {
+            forward_offset_angle: FRAC_PI_2,
+        };
+
+        assert!((default.forward(0.0) - DVec2::Y).length() < 1e-12);
+        assert!((offset.forward(0.0) - DVec2::NEG_X).length() < 1e-12);
+        assert!((offset.forward(FRAC_PI_2) - DVec2::NEG_Y).length() < 1e-12);
+        assert!((offset.effective_rotation(1.0) - (1.0 + FRAC_PI_2)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn orbital_velocity_circular() {
+        for i in 0..16 {
+            let angle = f64::from(i) * TAU / 16.0;
+            let rel_pos = DVec2::from_angle(angle) * 7e6;
+            let rel_vel = DVec2::from_angle(angle).perp() * 7500.0;
+
+            let ccw = OrbitalVelocity::from_relative(rel_pos, rel_vel);
+            assert!((ccw.prograde - 7500.0).abs() < 1e-9);
+            assert!(ccw.radial.abs() < 1e-9);
+
+            let cw = OrbitalVelocity::from_relative(rel_pos, -rel_vel);
+            assert!((cw.prograde + 7500.0).abs() < 1e-9);
+            assert!(cw.radial.abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn drag_opposes_velocity() {
+        let profile = DragProfile {
+            drag_coefficient: 0.5,
+            reference_area: 2.0,
+        };
+        let rel_vel = DVec2::new(30.0, -40.0);
+
+        let force = profile.drag_force(1.2, rel_vel);
+        assert!((force.length() - 0.5 * 1.2 * 2500.0 * 0.5 * 2.0).abs() < 1e-9);
+        assert!((force.normalize() + rel_vel.normalize()).length() < 1e-12);
+
+        assert_eq!(profile.drag_force(0.0, rel_vel), DVec2::ZERO);
+        assert_eq!(profile.drag_force(1.2, DVec2::ZERO), DVec2::ZERO);
+    }
+
+    #[test]
+    fn engine_thrust_is_clamped() {
+        let engine = Engine {
+            max_thrust: 200.0,
+            fuel: f64::INFINITY,
+        };
+
+        assert!((engine.commanded_thrust(0.25) - 50.0).abs() < 1e-9);
+        assert!((engine.commanded_thrust(1.0) - 200.0).abs() < 1e-9);
+        assert!((engine.commanded_thrust(2.0) - 200.0).abs() < 1e-9);
+        assert!(engine.commanded_thrust(-1.0).abs() < 1e-9);
+        assert!(engine.commanded_thrust(0.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn engine_burns_fuel() {
+        let mut engine = Engine {
+            max_thrust: 200.0,
+            fuel: 2.0,
+        };
+
+        engine.burn(0.5, 1.0);
+        assert!((engine.fuel - 1.5).abs() < 1e-9);
+        assert!((engine.commanded_thrust(1.0) - 200.0).abs() < 1e-9);
+
+        // Throttling past full doesn't burn any faster
+        engine.burn(3.0, 1.0);
+        assert!((engine.fuel - 0.5).abs() < 1e-9);
+
+        engine.burn(1.0, 10.0);
+        assert!(engine.fuel.abs() < 1e-9);
+        assert!(!engine.has_fuel());
+        assert!(engine.commanded_thrust(1.0).abs() < 1e-9);
+
+        let mut unlimited = Engine {
+            max_thrust: 200.0,
+            fuel: f64::INFINITY,
+        };
+        unlimited.burn(1.0, 1e9);
+        assert!(unlimited.has_fuel());
+    }
+
+    #[test]
+    fn wheel_torque_is_clamped() {
+        let wheel = ReactionWheel { max_torque: 50.0 };
+
+        assert!((wheel.commanded_torque(0.5) - 25.0).abs() < 1e-9);
+        assert!((wheel.commanded_torque(-1.0) + 50.0).abs() < 1e-9);
+        assert!((wheel.commanded_torque(3.0) - 50.0).abs() < 1e-9);
+        assert!((wheel.commanded_torque(-1e6) + 50.0).abs() < 1e-9);
+        assert!(wheel.commanded_torque(0.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wheel_saturates_and_unloads() {
+        const DT: f64 = 0.1;
+
+        let mut saturation = WheelSaturation::new(100.0, 0.0);
+
+        // Turning one way for long enough uses up nearly all the torque
+        let first = saturation.apply(50.0, DT);
+        assert!((first - 50.0).abs() < 1e-9);
+        for _ in 0..1000 {
+            saturation.apply(50.0, DT);
+        }
+        assert!(saturation.apply(50.0, DT) < 1.0);
+        assert!(saturation.stored <= saturation.capacity);
+
+        // Turning back gets the full torque, and frees some up again
+        assert!((saturation.apply(-50.0, DT) + 50.0).abs() < 1e-9);
+        for _ in 0..10 {
+            saturation.apply(-50.0, DT);
+        }
+        assert!(saturation.apply(50.0, DT) > 10.0);
+
+        // Left alone, the wheel desaturates on its own
+        let mut saturation = WheelSaturation {
+            stored: 100.0,
+            ..WheelSaturation::new(100.0, 20.0)
+        };
+        for _ in 0..50 {
+            saturation.apply(0.0, DT);
+        }
+        assert!(saturation.stored.abs() < 1e-9);
+        assert!((saturation.available_fraction(50.0) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn orbital_markers() {
+        // Orbiting clockwise, while climbing a bit
+        let rel_pos = DVec2::new(7e6, 0.0);
+        let rel_vel = DVec2::new(100.0, -7000.0);
+
+        let markers = OrbitalMarkers::from_relative(rel_pos, rel_vel);
+        let prograde = markers.prograde.unwrap();
+        let radial_out = markers.radial_out.unwrap();
+
+        assert!((prograde - rel_vel.normalize()).length() < 1e-12);
+        assert_eq!(markers.retrograde, Some(-prograde));
+        assert_eq!(markers.radial_in, Some(-radial_out));
+        assert!(prograde.dot(radial_out).abs() < 1e-12);
+        assert!(
+            radial_out.dot(rel_pos) > 0.0,
+            "radial-out should point away from the parent"
+        );
+        assert!((radial_out.length() - 1.0).abs() < 1e-12);
+
+        // Not moving, so there's no prograde to show
+        let markers = OrbitalMarkers::from_relative(rel_pos, DVec2::ZERO);
+        assert_eq!(markers.prograde, None);
+        assert_eq!(markers.retrograde, None);
+        assert_eq!(markers.radial_out, Some(DVec2::X));
+        assert_eq!(markers.radial_in, Some(-DVec2::X));
+    }
+
+    #[test]
+    fn surface_velocity_straight_down() {
+        let rel_pos = DVec2::new(-3e5, 4e5);
+        let up = rel_pos.normalize();
+        // Spinning along with the surface, while falling at 80 m/s
+        let angular_velocity = 1e-4;
+        let rel_vel = angular_velocity * rel_pos.perp() - up * 80.0;
+
+        let vel = SurfaceVelocity::from_relative(rel_pos, rel_vel, angular_velocity);
+        assert!(vel.horizontal.abs() < 1e-9);
+        assert!((vel.vertical + 80.0).abs() < 1e-9);
+
+        // Falling straight down in the non-spinning frame means
+        // the surface slides by underneath
+        let vel = SurfaceVelocity::from_relative(rel_pos, -up * 80.0, angular_velocity);
+        assert!((vel.horizontal + 50.0).abs() < 1e-9);
+        assert!((vel.vertical + 80.0).abs() < 1e-9);
+
+        let resting = SurfaceVelocity::from_relative(rel_pos, DVec2::ZERO, 0.0);
+        assert_eq!(resting, SurfaceVelocity::default());
+    }
+
+    #[test]
+    fn landed_state_settles() {
+        let mut state = LandedState::default();
+
+        for _ in 1..SETTLED_TICKS {
+            state.record(SETTLED_SPEED / 2.0);
+        }
+        assert!(!state.is_settled());
+
+        state.record(SETTLED_SPEED / 2.0);
+        assert!(state.is_settled());
+
+        // A single bump starts the count over
+        state.record(SETTLED_SPEED * 2.0);
+        assert!(!state.is_settled());
+        assert_eq!(state, LandedState::default());
+    }
+
+    #[test]
+    fn orbital_velocity_radial_fall() {
+        let rel_pos = DVec2::new(3e5, -4e5);
+        let rel_vel = rel_pos.normalize() * -120.0;
+
+        let vel = OrbitalVelocity::from_relative(rel_pos, rel_vel);
+        assert!(vel.prograde.abs() < 1e-9);
+        assert!((vel.radial + 120.0).abs() < 1e-9);
+    }
+}
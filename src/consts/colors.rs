@@ -69,6 +69,17 @@ pub(crate) const SPEEDOMETER_VSPD: Color = hex_to_color(b"#ef8b39");
 pub(crate) const SPEEDOMETER_TSPD: Color = hex_to_color(b"#91be83");
 pub(crate) const SPEEDOMETER_DOTS: Color = SPEEDOMETER_TSPD;
 
+pub(crate) const MAP_ORBIT: Color = scheme::PRIMARY;
+pub(crate) const MAP_ORBIT_SUBORBITAL: Color = Color::Srgba(Srgba::new(0.9, 0.3, 0.25, 1.0));
+pub(crate) const MAP_ORBIT_ESCAPE: Color = Color::Srgba(Srgba::new(0.35, 0.7, 0.95, 1.0));
+pub(crate) const MAP_SOI: Color = Color::Srgba(Srgba::new(0.6, 0.6, 0.6, 0.35));
+
+pub(crate) const MARKER_PROGRADE: Color = Color::Srgba(Srgba::new(0.85, 0.85, 0.2, 0.9));
+pub(crate) const MARKER_RADIAL: Color = Color::Srgba(Srgba::new(0.3, 0.8, 0.9, 0.9));
+pub(crate) const EDGE_INDICATOR: Color = Color::Srgba(Srgba::new(0.9, 0.9, 0.9, 0.8));
+
+pub(crate) const DEBUG_VELOCITY: Color = Color::Srgba(Srgba::new(0.95, 0.45, 0.9, 0.9));
+
 pub(crate) mod icons {
     use crate::consts::colors::hex_to_color;
     use bevy::color::Color;
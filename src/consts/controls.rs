@@ -1,6 +1,6 @@
 #![cfg_attr(not(feature = "not-headless"), expect(dead_code))]
 
-use bevy::input::keyboard::KeyCode;
+use bevy::input::{keyboard::KeyCode, mouse::MouseButton};
 
 /// The keys a user can press to activate a selected button.
 pub(crate) const ACTIVATION_KEYCODES: [KeyCode; 3] =
@@ -33,12 +33,24 @@ pub(crate) const KB_MODE_SWITCH_TO_MENU_MODE: [KeyCode; 1] = [KeyCode::KeyM];
 pub(crate) const KB_MODE_SWITCH_TO_VESSEL_MODE: [KeyCode; 1] = [KeyCode::KeyV];
 pub(crate) const KB_MODE_SWITCH_TO_CAM_MODE: [KeyCode; 1] = [KeyCode::KeyC];
 
+/// Switches between the flight view and the orbital map view.
+pub(crate) const KB_TOGGLE_VIEW_MODE: [KeyCode; 1] = [KeyCode::Tab];
+/// Pauses or resumes the simulation.
+pub(crate) const KB_TOGGLE_PAUSE: [KeyCode; 1] = [KeyCode::Space];
+/// Steps down to the next slower time warp rate.
+pub(crate) const KB_WARP_SLOWER: [KeyCode; 1] = [KeyCode::Comma]; // "<"
+/// Steps up to the next faster time warp rate.
+pub(crate) const KB_WARP_FASTER: [KeyCode; 1] = [KeyCode::Period]; // ">"
+/// Cycles through the presets of debug overlays.
+pub(crate) const KB_DEBUG_CYCLE_OVERLAY: [KeyCode; 1] = [KeyCode::F3];
+
 pub(crate) const KB_CAM_SLOW_MOD: [KeyCode; 2] = [KeyCode::ControlLeft, KeyCode::ControlRight];
 pub(crate) const KB_CAM_FAST_MOD: [KeyCode; 2] = [KeyCode::ShiftLeft, KeyCode::ShiftRight];
 
 pub(crate) const KB_CAM_ROT_LEFT: [KeyCode; 1] = [KeyCode::KeyQ];
 pub(crate) const KB_CAM_ROT_RIGHT: [KeyCode; 1] = [KeyCode::KeyE];
 pub(crate) const KB_CAM_ROT_RESET: [KeyCode; 1] = [KeyCode::KeyR];
+pub(crate) const KB_CAM_CYCLE_ORIENTATION: [KeyCode; 1] = [KeyCode::KeyO];
 
 pub(crate) const KB_CAM_MOV_UP: [KeyCode; 2] = [KeyCode::KeyW, KeyCode::ArrowUp];
 pub(crate) const KB_CAM_MOV_DOWN: [KeyCode; 2] = [KeyCode::KeyS, KeyCode::ArrowDown];
@@ -52,5 +64,27 @@ pub(crate) const KB_CAM_ZOOM_RESET: [KeyCode; 2] = [KeyCode::Digit0, KeyCode::Nu
 
 pub(crate) const KB_CAM_SWITCH_PREV: [KeyCode; 1] = [KeyCode::BracketLeft];
 pub(crate) const KB_CAM_SWITCH_NEXT: [KeyCode; 1] = [KeyCode::BracketRight];
+/// Detaches the camera, or attaches it to the closest focusable entity.
+pub(crate) const KB_CAM_TOGGLE_ATTACH: [KeyCode; 1] = [KeyCode::KeyF]; // "Follow"
+
+/// Dragging the map with these buttons held pans the detached camera.
+pub(crate) const MB_CAM_PAN: [MouseButton; 1] = [MouseButton::Left];
+
+/// Clicking on a celestial body or vessel with these buttons
+/// focuses the camera on it.
+pub(crate) const MB_CAM_FOCUS: [MouseButton; 1] = [MouseButton::Left];
+
+/// How far the cursor can move, in logical pixels, between pressing
+/// and releasing [`MB_CAM_FOCUS`] for it to count as a click
+/// rather than a drag.
+pub(crate) const CLICK_MAX_DRAG: f32 = 4.0;
+
+pub(crate) const KB_VESSEL_THROTTLE_UP: [KeyCode; 2] = [KeyCode::ShiftLeft, KeyCode::ShiftRight];
+pub(crate) const KB_VESSEL_THROTTLE_DOWN: [KeyCode; 2] =
+    [KeyCode::ControlLeft, KeyCode::ControlRight];
+pub(crate) const KB_VESSEL_ROT_LEFT: [KeyCode; 1] = [KeyCode::KeyA];
+pub(crate) const KB_VESSEL_ROT_RIGHT: [KeyCode; 1] = [KeyCode::KeyD];
+/// Toggles precision mode, which makes vessel inputs gentler.
+pub(crate) const KB_VESSEL_PRECISION: [KeyCode; 1] = [KeyCode::CapsLock];
 
 pub(crate) const KB_MENU_SWITCH_ALTIMETER_MODE: [KeyCode; 1] = [KeyCode::KeyA];
@@ -41,3 +41,55 @@ pub(crate) static FLUENT_LANGUAGE_LOADER: LazyLock<FluentLanguageLoader> =
     LazyLock::new(|| fluent_language_loader!());
 
 pub const GRAVITY_MIN_RADIUS: f64 = 1e-9;
+
+/// The speed, in m/s, relative to its parent body, below which a
+/// vessel touching the surface counts as resting on it.
+pub const SETTLED_SPEED: f64 = 0.05;
+
+/// How many fixed ticks in a row a vessel needs to rest on the surface
+/// before its [`SurfaceAttachment`][crate::components::main_game::relations::SurfaceAttachment]
+/// stops getting recomputed.
+pub const SETTLED_TICKS: u32 = 16;
+
+/// The smallest change in velocity, in m/s, during a single physics
+/// step that counts as a [`VesselImpact`][crate::messages::crash::VesselImpact].
+///
+/// This is well above what gravity can do in a single step, so that
+/// vessels resting on the surface don't keep sending impacts.
+pub const MIN_IMPACT_SPEED: f64 = 1.0;
+
+/// The friction coefficient of a celestial body's surface, unless its
+/// builder gives it a different
+/// [`CelestialSurface`][crate::builders::celestial::CelestialSurface].
+///
+/// Friction coefficients get averaged between the two colliders
+/// in contact, so this also depends on the vessel's own friction.
+pub const DEFAULT_SURFACE_FRICTION: f32 = 0.7;
+
+/// The restitution coefficient of a celestial body's surface, unless its
+/// builder gives it a different
+/// [`CelestialSurface`][crate::builders::celestial::CelestialSurface].
+pub const DEFAULT_SURFACE_RESTITUTION: f32 = 0.0;
+
+/// The collision group every vessel is in.
+pub const VESSEL_COLLISION_GROUP: Group = Group::GROUP_1;
+
+/// The collision group every celestial body is in.
+pub const CELESTIAL_COLLISION_GROUP: Group = Group::GROUP_2;
+
+/// How long a [`FocusOn`][crate::commands::FocusOn] transition takes,
+/// in seconds.
+pub const FOCUS_TRANSITION_DURATION: f64 = 0.5;
+
+/// The highest time warp rate used when warping to a point in time.
+pub const MAX_WARP_TO_RATE: f64 = 10_000.0;
+
+/// The time warp rates the player can step between, slowest first.
+pub const WARP_RATES: [f64; 8] = [1.0, 5.0, 10.0, 50.0, 100.0, 1000.0, 10_000.0, 100_000.0];
+
+/// How far around its parent body the active vessel may move, in radians,
+/// per fixed timestep's worth of real time while time warping.
+///
+/// Past this, the warp rate gets stepped down, so that the vessel doesn't
+/// skip past most of its periapsis in a handful of frames.
+pub const MAX_WARP_ANGLE_PER_TICK: f64 = 0.01;
@@ -1,3 +1,6 @@
+#[cfg(any(test, feature = "test-util"))]
+use crate::components::main_game::celestial::Terrain;
+
 /// The amount of vertices to use for the extremely-zoomed-out mesh.
 ///
 /// Is at most [`LOD_VERTS`].
@@ -16,6 +19,38 @@ pub(crate) const LOD_DIVISIONS: u32 = 4;
 /// coarser division's verts.
 pub(crate) const LOD_VERTS_PER_DIVISION: u32 = LOD_VERTS / LOD_DIVISIONS;
 
+/// A bumpy terrain about the size of a planet, for tests and benchmarks.
+///
+/// It has enough subdivisions for every LoD level to have
+/// bumps of its own, which makes the stitches between levels show.
+#[cfg(any(test, feature = "test-util"))]
+pub const TEST_TERRAIN: Terrain = Terrain {
+    seed: 0xabcba,
+    octaves: 8,
+    frequency: 1.0,
+    gain: 0.5,
+    lacunarity: 2.0,
+    offset: 20e6,
+    multiplier: 10.0,
+    subdivs: 8,
+};
+
+/// The highest LoD level a [`Terrain`][crate::components::main_game::celestial::Terrain]
+/// can be subdivided to.
+///
+/// This is the highest level whose vertex count still fits in a [`u32`].
+pub(crate) const MAX_LOD_LEVEL: u8 = {
+    let mut level = 0;
+    let mut verts = LOD_VERTS as u64;
+
+    while verts * (LOD_DIVISIONS as u64) <= u32::MAX as u64 {
+        verts *= LOD_DIVISIONS as u64;
+        level += 1;
+    }
+
+    level
+};
+
 const _LOD_ASSERTIONS: () = {
     assert!(MIN_LOD_VERTS as u32 <= LOD_VERTS);
     assert!(LOD_VERTS.is_multiple_of(LOD_DIVISIONS));
@@ -24,4 +59,6 @@ const _LOD_ASSERTIONS: () = {
     assert!((LOD_VERTS as u128) < isize::MAX as u128);
     assert!(LOD_VERTS < i32::MAX as u32);
     assert!(LOD_VERTS < u16::MAX as u32);
+    assert!(LOD_DIVISIONS > 1);
+    assert!(LOD_VERTS as u64 * (LOD_DIVISIONS as u64).pow(MAX_LOD_LEVEL as u32) <= u32::MAX as u64);
 };
@@ -1,13 +1,19 @@
 pub(crate) mod assets;
+pub mod autopilot;
 pub mod builders;
+pub mod commands;
 pub mod components;
 pub mod consts;
 pub mod macros;
 pub(crate) mod math;
+pub mod messages;
+pub mod orbit;
 pub mod plugins;
 pub mod resources;
 pub(crate) mod systems;
 pub(crate) mod terrain;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 #[cfg(target_family = "wasm")]
 pub mod web;
 
@@ -1,4 +1,6 @@
 use bevy::math::Quat;
+use bevy_rapier2d::prelude::AdditionalMassProperties;
+use core::f64::consts::{PI, TAU};
 
 /// Gets the rotation of the quaternion, assuming the
 /// quaternion stays in the 2D XY plane.
@@ -10,11 +12,48 @@ pub(crate) fn quat_to_rot(quat: Quat) -> f64 {
     2.0 * f64::from(quat.z).atan2(f64::from(quat.w))
 }
 
+/// Gets a quaternion that rotates by the given angle around the Z axis.
+///
+/// This is the inverse of [`quat_to_rot`].
+#[must_use]
+#[expect(clippy::cast_possible_truncation)]
+pub(crate) fn rot_to_quat(angle: f64) -> Quat {
+    Quat::from_rotation_z(angle as f32)
+}
+
+/// Turns `current` towards `target` by at most `max_delta`, going
+/// whichever way around is shorter.
+///
+/// All angles are in radians. `max_delta` must not be negative.
+///
+/// # Output
+/// The output is not wrapped, so it stays close to `current`
+/// rather than jumping by a full turn at the ±π boundary.
+#[must_use]
+pub(crate) fn rotate_toward(current: f64, target: f64, max_delta: f64) -> f64 {
+    let diff = (target - current + PI).rem_euclid(TAU) - PI;
+
+    current + diff.clamp(-max_delta, max_delta)
+}
+
+/// Gets the total mass, in kilograms, of a rigid body.
+///
+/// With [`AdditionalMassProperties::MassProperties`], only the mass gets
+/// used. Gravity treats bodies as point masses, so the center of mass
+/// offset and the angular inertia get ignored.
+#[must_use]
+pub(crate) fn body_mass(props: &AdditionalMassProperties) -> f64 {
+    match props {
+        AdditionalMassProperties::Mass(mass) => f64::from(*mass),
+        AdditionalMassProperties::MassProperties(props) => f64::from(props.mass),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use bevy::math::{Quat, Vec3};
-    use core::f64::consts::TAU;
+    use bevy::math::{Quat, Vec2, Vec3};
+    use bevy_rapier2d::prelude::MassProperties;
 
     #[test]
     #[expect(clippy::cast_precision_loss)]
@@ -53,4 +92,72 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    #[expect(clippy::cast_precision_loss)]
+    fn test_rot_to_quat_round_trip() {
+        const ITERS: usize = 1024;
+
+        for i in 0..ITERS {
+            let angle = -PI + TAU * i as f64 / ITERS as f64;
+            let round_trip = quat_to_rot(rot_to_quat(angle));
+
+            assert!(
+                ((round_trip - angle + PI).rem_euclid(TAU) - PI).abs() < 1e-6,
+                "{round_trip} isn't near {angle}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rotate_toward() {
+        // Within reach, so it lands right on the target
+        assert!((rotate_toward(0.0, 0.5, 1.0) - 0.5).abs() < 1e-12);
+        assert!((rotate_toward(0.0, -0.5, 1.0) + 0.5).abs() < 1e-12);
+
+        // Out of reach, so it only turns by the max delta
+        assert!((rotate_toward(0.0, 2.0, 0.25) - 0.25).abs() < 1e-12);
+        assert!((rotate_toward(0.0, -2.0, 0.25) + 0.25).abs() < 1e-12);
+
+        // Already there
+        assert!((rotate_toward(1.0, 1.0 + TAU, 0.25) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_rotate_toward_wraparound() {
+        // Just below +π to just above -π is a short step counterclockwise,
+        // not almost a full turn clockwise
+        let current = PI - 0.1;
+        let target = -PI + 0.1;
+
+        let turned = rotate_toward(current, target, 0.05);
+        assert!((turned - (current + 0.05)).abs() < 1e-12);
+
+        let arrived = rotate_toward(current, target, 1.0);
+        assert!((arrived - (PI + 0.1)).abs() < 1e-12);
+
+        // And the same the other way around
+        let turned = rotate_toward(target, current, 0.05);
+        assert!((turned - (target - 0.05)).abs() < 1e-12);
+
+        // Angles that are multiple turns apart still take the short way
+        let turned = rotate_toward(3.0 * TAU + 0.1, -0.1, 0.05);
+        assert!((turned - (3.0 * TAU + 0.05)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn body_mass_of_both_variants() {
+        let plain = AdditionalMassProperties::Mass(12.5);
+        assert!((body_mass(&plain) - 12.5).abs() < 1e-12);
+
+        let offset = AdditionalMassProperties::MassProperties(MassProperties {
+            local_center_of_mass: Vec2::new(3.0, -4.0),
+            mass: 7.25,
+            principal_inertia: 100.0,
+        });
+        assert!((body_mass(&offset) - 7.25).abs() < 1e-12);
+
+        let empty = AdditionalMassProperties::MassProperties(MassProperties::default());
+        assert!(body_mass(&empty).abs() < f64::EPSILON);
+    }
 }
@@ -0,0 +1,30 @@
+use bevy::prelude::*;
+
+/// Sent when a loaded vessel's velocity suddenly changes
+/// because it hit something.
+///
+/// Only vessels with a
+/// [`CrashTolerance`][crate::components::main_game::vessel::CrashTolerance]
+/// get checked for impacts.
+#[derive(Clone, Copy, Debug, Message, PartialEq)]
+pub struct VesselImpact {
+    /// The vessel that hit something.
+    pub vessel: Entity,
+    /// How much the vessel's velocity changed during
+    /// the physics step, in m/s.
+    pub speed: f64,
+}
+
+/// Sent when a vessel hits something harder than its
+/// [`CrashTolerance`][crate::components::main_game::vessel::CrashTolerance]
+/// allows, and gets destroyed.
+///
+/// The vessel, along with all of its parts, has already been
+/// despawned by the time this gets read.
+#[derive(Clone, Copy, Debug, Message, PartialEq)]
+pub struct VesselDestroyed {
+    /// The destroyed vessel.
+    pub vessel: Entity,
+    /// The speed of the impact that destroyed it, in m/s.
+    pub impact_speed: f64,
+}
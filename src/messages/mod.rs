@@ -0,0 +1,5 @@
+pub mod crash;
+pub mod parts;
+pub mod relations;
+pub mod telemetry;
+pub mod warp;
@@ -0,0 +1,29 @@
+use bevy::{math::DVec2, prelude::*};
+
+/// Requests detaching a vessel part from its vessel, turning
+/// it into a vessel of its own.
+///
+/// Ignored if the part isn't attached to a loaded vessel.
+#[derive(Clone, Copy, Debug, Message, PartialEq)]
+pub struct Stage {
+    /// The part to detach.
+    pub part: Entity,
+    /// The impulse pushing the part away from the rest
+    /// of the vessel, in root space, in N·s.
+    ///
+    /// The rest of the vessel gets the opposite impulse.
+    pub impulse: DVec2,
+}
+
+/// Requests undocking two docked vessels, turning them back
+/// into independent vessels.
+///
+/// Ignored if the port isn't docked.
+#[derive(Clone, Copy, Debug, Message, PartialEq)]
+pub struct Undock {
+    /// Either of the two docking ports holding the vessels together.
+    pub port: Entity,
+    /// The impulse pushing the vessels apart along the
+    /// direction the ports face, in N·s.
+    pub impulse: f64,
+}
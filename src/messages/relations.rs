@@ -0,0 +1,34 @@
+use bevy::prelude::*;
+
+/// Sent when an entity's [`CelestialParent`][crate::components::main_game::relations::CelestialParent]
+/// changes, i.e. when it moves from one sphere of influence to another.
+///
+/// This is sent exactly once per transition, for vessels as well as for
+/// celestial bodies that get a new parent.
+#[derive(Clone, Copy, Debug, Message, PartialEq, Eq)]
+pub struct SoiChanged {
+    /// The entity that changed parents.
+    ///
+    /// Despite the name, this may also be a celestial body.
+    pub vessel: Entity,
+    /// The old parent.
+    pub from: Entity,
+    /// The new parent.
+    pub to: Entity,
+}
+
+/// Moves a vessel to a different
+/// [`CelestialParent`][crate::components::main_game::relations::CelestialParent],
+/// regardless of which sphere of influence it's actually in.
+///
+/// The vessel keeps its root-space position and velocity, and its
+/// [`RailMode`][crate::components::main_game::relations::RailMode]
+/// gets replaced with its orbit around the new parent. Like any other
+/// parent change, this makes a [`SoiChanged`] get sent.
+#[derive(Clone, Copy, Debug, Message, PartialEq, Eq)]
+pub struct Reparent {
+    /// The vessel to move.
+    pub vessel: Entity,
+    /// The celestial body to make the vessel's new parent.
+    pub new_parent: Entity,
+}
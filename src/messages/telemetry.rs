@@ -0,0 +1,44 @@
+use bevy::{math::DVec2, prelude::*};
+
+use crate::components::main_game::{
+    frames::{RootSpaceLinearVelocity, RootSpacePosition},
+    relations::RailMode,
+    vessel::{OrbitalVelocity, SurfaceVelocity},
+};
+
+/// A snapshot of the active vessel's state, sent every fixed tick
+/// for external observers such as telemetry dashboards.
+///
+/// Only sent while the
+/// [`TelemetryEnabled`][crate::resources::simulation::TelemetryEnabled]
+/// resource exists.
+#[derive(Clone, Copy, Debug, Message, PartialEq)]
+pub struct TelemetryFrame {
+    /// The simulation time this snapshot was taken at, in seconds.
+    pub time: f64,
+    /// The active vessel.
+    pub vessel: Entity,
+    /// The active vessel's parent body.
+    pub parent: Entity,
+    /// The active vessel's position.
+    pub position: RootSpacePosition,
+    /// The active vessel's velocity.
+    pub velocity: RootSpaceLinearVelocity,
+    /// The active vessel's position relative to its parent.
+    pub rel_position: DVec2,
+    /// The active vessel's velocity relative to its parent.
+    pub rel_velocity: DVec2,
+    /// The active vessel's velocity in prograde/radial components.
+    pub orbital_velocity: OrbitalVelocity,
+    /// The active vessel's rail, including its orbit if it has one.
+    pub rail_mode: RailMode,
+    /// The active vessel's altitude above its parent's sea level, in meters.
+    pub altitude: f64,
+    /// The active vessel's velocity relative to its parent's spinning surface.
+    pub surface_velocity: SurfaceVelocity,
+    /// Whether the active vessel is touching its parent's surface.
+    pub landed: bool,
+    /// How long the active vessel's engine can still burn at full
+    /// throttle, in seconds, or 0 if it has no engine.
+    pub fuel: f64,
+}
@@ -0,0 +1,16 @@
+use bevy::prelude::*;
+
+use crate::orbit::ApsisTarget;
+
+/// Requests warping time until a vessel reaches the next
+/// apoapsis or periapsis of its orbit.
+///
+/// Ignored if the vessel doesn't have an orbit, or if it will
+/// never reach the target apsis.
+#[derive(Clone, Copy, Debug, Message, PartialEq, Eq)]
+pub struct WarpTo {
+    /// The vessel whose orbit to follow.
+    pub vessel: Entity,
+    /// Which apsis to stop at.
+    pub target: ApsisTarget,
+}
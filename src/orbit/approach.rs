@@ -0,0 +1,166 @@
+//! Finding when two objects orbiting the same parent get closest to each other.
+
+use bevy::math::DVec2;
+use keplerian_sim::{Orbit2D, OrbitTrait2D};
+
+use crate::orbit::orbital_period;
+
+/// How many samples to take per revolution of the faster orbit.
+const SAMPLES_PER_PERIOD: f64 = 128.0;
+
+/// The most samples to take over the whole scan, so that orbits
+/// with wildly different periods don't take forever to scan.
+const MAX_SAMPLES: f64 = 16384.0;
+
+/// How many samples to take when neither orbit is closed.
+const OPEN_SAMPLES: f64 = 1024.0;
+
+/// How many golden-section steps to refine the closest sample with.
+const REFINE_STEPS: u32 = 64;
+
+/// The closest two objects get to each other within a scan.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClosestApproach {
+    /// The simulation time of the closest approach, in seconds.
+    pub time: f64,
+    /// How far apart the objects are at that time, in meters.
+    pub separation: f64,
+    /// Where each object is at that time, relative to their shared parent.
+    ///
+    /// Useful for drawing a marker on both orbits.
+    pub positions: [DVec2; 2],
+}
+
+impl ClosestApproach {
+    fn at(orbits: [&Orbit2D; 2], time: f64) -> Self {
+        let positions = orbits.map(|orbit| orbit.get_state_vectors_at_time(time).position);
+
+        Self {
+            time,
+            separation: positions[0].distance(positions[1]),
+            positions,
+        }
+    }
+}
+
+/// Gets how long to scan two orbits for to find their closest approach.
+///
+/// Closed orbits line up the same way again after their synodic
+/// period, so scanning past that wouldn't find anything new.
+fn scan_window(a: &Orbit2D, b: &Orbit2D, max_duration: f64) -> f64 {
+    let window = match (orbital_period(a), orbital_period(b)) {
+        (Some(period_a), Some(period_b)) => {
+            let synodic = 1.0 / (1.0 / period_a - 1.0 / period_b).abs();
+
+            if synodic.is_finite() {
+                synodic.max(period_a).max(period_b)
+            } else {
+                period_a
+            }
+        }
+        _ => max_duration,
+    };
+
+    window.min(max_duration)
+}
+
+/// Finds when two objects orbiting the same parent will be closest
+/// to each other, starting from the simulation time `now`.
+///
+/// Both orbits get sampled over their synodic period, so that every
+/// relative alignment gets seen once, or over at most `max_duration`
+/// seconds if that's shorter or either orbit is open. The closest
+/// sample then gets refined with a golden-section search.
+///
+/// `max_duration` needs to be finite if either orbit is open.
+#[must_use]
+pub fn closest_approach(a: &Orbit2D, b: &Orbit2D, now: f64, max_duration: f64) -> ClosestApproach {
+    let orbits = [a, b];
+    let window = scan_window(a, b, max_duration.max(0.0));
+
+    let shortest_period = [a, b]
+        .into_iter()
+        .filter_map(orbital_period)
+        .reduce(f64::min);
+    let samples = shortest_period
+        .map_or(OPEN_SAMPLES, |period| {
+            (window / period * SAMPLES_PER_PERIOD).ceil()
+        })
+        .clamp(1.0, MAX_SAMPLES);
+    let step = window / samples;
+
+    #[expect(clippy::cast_possible_truncation)]
+    #[expect(clippy::cast_sign_loss)]
+    let best = (0..=samples as u32)
+        .map(|i| ClosestApproach::at(orbits, step.mul_add(f64::from(i), now)))
+        .min_by(|x, y| x.separation.total_cmp(&y.separation))
+        .unwrap_or_else(|| ClosestApproach::at(orbits, now));
+
+    // The true minimum is somewhere between the neighbouring samples
+    refine(orbits, (best.time - step).max(now), best.time + step)
+}
+
+/// Narrows down the time of closest approach between `start` and `end`
+/// with a golden-section search.
+fn refine(orbits: [&Orbit2D; 2], mut start: f64, mut end: f64) -> ClosestApproach {
+    let ratio = (5.0f64.sqrt() - 1.0) / 2.0;
+    let separation = |time| ClosestApproach::at(orbits, time).separation;
+
+    for _ in 0..REFINE_STEPS {
+        let left = (end - start).mul_add(-ratio, end);
+        let right = (end - start).mul_add(ratio, start);
+
+        if separation(left) < separation(right) {
+            end = right;
+        } else {
+            start = left;
+        }
+    }
+
+    ClosestApproach::at(orbits, (start + end) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orbit::{mean_motion, orbit_from_elements};
+    use core::f64::consts::FRAC_PI_2;
+
+    const MU: f64 = 3.986e14;
+
+    #[test]
+    fn circular_orbits_line_up() {
+        let inner = orbit_from_elements(7e6, 0.0, 0.0, 0.0, MU);
+        let outer = orbit_from_elements(8e6, 0.0, 0.0, FRAC_PI_2, MU);
+
+        let approach = closest_approach(&inner, &outer, 0.0, f64::INFINITY);
+
+        // The inner orbit catches up a quarter turn on the outer one
+        let expected_time = FRAC_PI_2 / (mean_motion(&inner) - mean_motion(&outer));
+        assert!((approach.separation - 1e6).abs() < 1.0);
+        assert!(
+            (approach.time / expected_time - 1.0).abs() < 1e-3,
+            "got {}, expected {expected_time}",
+            approach.time
+        );
+
+        let [inner_pos, outer_pos] = approach.positions;
+        assert!(inner_pos.angle_to(outer_pos).abs() < 1e-3);
+    }
+
+    #[test]
+    fn flyby_beats_brute_force() {
+        let circular = orbit_from_elements(1e7, 0.0, 0.0, 0.0, MU);
+        let flyby = orbit_from_elements(-2e7, 1.4, 2.0, -1.0, MU);
+        let max_duration = 4e4;
+
+        let approach = closest_approach(&circular, &flyby, 0.0, max_duration);
+
+        let brute_force = (0..=100_000)
+            .map(|i| ClosestApproach::at([&circular, &flyby], f64::from(i) * 0.4).separation)
+            .fold(f64::INFINITY, f64::min);
+
+        assert!((0.0..=max_duration).contains(&approach.time));
+        assert!(approach.separation <= brute_force + 1.0);
+    }
+}
@@ -0,0 +1,389 @@
+//! Changing single orbital elements of an existing orbit,
+//! e.g. for setting up scenarios.
+//!
+//! Every edit keeps the mean anomaly at a simulation time of zero, the
+//! gravitational parameter, and which way the orbit goes around the
+//! parent the same. Beyond that, each edit documents which of the other
+//! elements it holds constant, as the periapsis, apoapsis, eccentricity
+//! and semi-major axis can't all be changed independently. Edits that
+//! change the semi-major axis also change the period, so the object
+//! ends up somewhere else along the orbit at any time other than the
+//! moments it passes through the periapsis.
+
+use bevy::math::DVec2;
+use keplerian_sim::{Orbit2D, OrbitTrait2D, StateVectors2D};
+use serde::{Deserialize, Serialize};
+
+use crate::orbit::mean_motion;
+
+/// The elements of an orbit, in the form edits get applied to.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OrbitElements {
+    /// The distance from the parent's center to the periapsis, in meters.
+    pub periapsis: f64,
+    /// The eccentricity, which is 1 or more for open orbits.
+    pub eccentricity: f64,
+    /// The counterclockwise angle of the periapsis from the +X axis, in radians.
+    pub arg_pe: f64,
+    /// The mean anomaly at a simulation time of zero.
+    pub mean_anomaly: f64,
+    /// The gravitational parameter of the parent.
+    pub mu: f64,
+    /// Whether the orbit goes clockwise around the parent.
+    pub clockwise: bool,
+}
+
+impl OrbitElements {
+    /// Gets the elements of an orbit.
+    #[must_use]
+    pub fn of(orbit: &Orbit2D) -> Self {
+        let mean_anomaly = orbit.get_mean_anomaly_at_epoch();
+
+        // Reading the periapsis off the orbit itself works out
+        // the same whichever way it goes around
+        let sv = orbit.get_state_vectors_at_time(-mean_anomaly / mean_motion(orbit));
+
+        Self {
+            periapsis: orbit.get_periapsis(),
+            eccentricity: orbit.get_eccentricity(),
+            arg_pe: sv.position.to_angle(),
+            mean_anomaly,
+            mu: orbit.get_gravitational_parameter(),
+            clockwise: sv.position.perp_dot(sv.velocity) < 0.0,
+        }
+    }
+
+    /// Builds the orbit with these elements.
+    ///
+    /// Parabolic orbits (an eccentricity of exactly 1) aren't supported,
+    /// as they have no finite semi-major axis.
+    #[must_use]
+    pub fn to_orbit(self) -> Orbit2D {
+        let speed = (self.mu * (1.0 + self.eccentricity) / self.periapsis).sqrt();
+        let direction = DVec2::from_angle(self.arg_pe);
+        let turn = if self.clockwise { -1.0 } else { 1.0 };
+
+        // Passing through the periapsis at the time that makes
+        // the mean anomaly at a time of zero come out right
+        let semi_major_axis = self.semi_major_axis();
+        let mean_motion = (self.mu / semi_major_axis.abs().powi(3)).sqrt();
+
+        StateVectors2D {
+            position: direction * self.periapsis,
+            velocity: direction.perp() * speed * turn,
+        }
+        .to_cached_orbit(self.mu, -self.mean_anomaly / mean_motion)
+    }
+
+    /// Gets the semi-major axis, in meters, which is negative for open orbits.
+    #[must_use]
+    pub fn semi_major_axis(self) -> f64 {
+        self.periapsis / (1.0 - self.eccentricity)
+    }
+
+    /// Gets the distance from the parent's center to the apoapsis, in meters.
+    ///
+    /// Returns [`None`] for open orbits, as they have no apoapsis.
+    #[must_use]
+    pub fn apoapsis(self) -> Option<f64> {
+        (self.eccentricity < 1.0)
+            .then(|| self.periapsis * (1.0 + self.eccentricity) / (1.0 - self.eccentricity))
+    }
+
+    /// Changes the periapsis.
+    ///
+    /// Closed orbits keep their apoapsis, so the eccentricity and
+    /// semi-major axis change to fit. Open orbits keep their eccentricity
+    /// instead, as they have no apoapsis to keep.
+    ///
+    /// Returns [`None`] if the periapsis isn't positive,
+    /// or would be further out than the apoapsis.
+    #[must_use]
+    pub fn with_periapsis(self, periapsis: f64) -> Option<Self> {
+        if periapsis.is_nan() || periapsis <= 0.0 {
+            return None;
+        }
+
+        let eccentricity = match self.apoapsis() {
+            Some(apoapsis) if periapsis > apoapsis => return None,
+            Some(apoapsis) => (apoapsis - periapsis) / (apoapsis + periapsis),
+            None => self.eccentricity,
+        };
+
+        Some(Self {
+            periapsis,
+            eccentricity,
+            ..self
+        })
+    }
+
+    /// Changes the apoapsis, keeping the periapsis.
+    ///
+    /// The eccentricity and semi-major axis change to fit.
+    ///
+    /// Returns [`None`] if the apoapsis would be closer in than the
+    /// periapsis. Open orbits can be closed this way, but an infinite
+    /// apoapsis doesn't open an orbit up, see
+    /// [`with_eccentricity`][Self::with_eccentricity] for that.
+    #[must_use]
+    pub fn with_apoapsis(self, apoapsis: f64) -> Option<Self> {
+        if !apoapsis.is_finite() || apoapsis < self.periapsis {
+            return None;
+        }
+
+        Some(Self {
+            eccentricity: (apoapsis - self.periapsis) / (apoapsis + self.periapsis),
+            ..self
+        })
+    }
+
+    /// Changes the eccentricity, keeping the periapsis.
+    ///
+    /// The apoapsis and semi-major axis change to fit, and an
+    /// eccentricity above 1 opens the orbit up.
+    ///
+    /// Returns [`None`] for negative eccentricities, and for an
+    /// eccentricity of exactly 1, as in [`to_orbit`][Self::to_orbit].
+    #[must_use]
+    pub fn with_eccentricity(self, eccentricity: f64) -> Option<Self> {
+        if eccentricity.is_nan() || eccentricity < 0.0 {
+            return None;
+        }
+
+        let edited = Self {
+            eccentricity,
+            ..self
+        };
+
+        edited.semi_major_axis().is_finite().then_some(edited)
+    }
+
+    /// Turns the orbit around the parent so that its periapsis
+    /// is at `arg_pe`, keeping everything else.
+    #[must_use]
+    pub fn with_arg_pe(self, arg_pe: f64) -> Self {
+        Self { arg_pe, ..self }
+    }
+}
+
+/// A change to a single element of an orbit.
+///
+/// See the methods of [`OrbitElements`] for which other elements each
+/// of these holds constant.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OrbitEdit {
+    /// Sets the periapsis, in meters.
+    Periapsis(f64),
+    /// Sets the apoapsis, in meters.
+    Apoapsis(f64),
+    /// Sets the eccentricity.
+    Eccentricity(f64),
+    /// Sets the counterclockwise angle of the periapsis
+    /// from the +X axis, in radians.
+    ArgPe(f64),
+}
+
+impl OrbitEdit {
+    /// Gets a copy of `orbit` with this edit applied.
+    ///
+    /// Returns [`None`] if the edit doesn't make a valid orbit.
+    #[must_use]
+    pub fn apply(self, orbit: &Orbit2D) -> Option<Orbit2D> {
+        let elements = OrbitElements::of(orbit);
+
+        let edited = match self {
+            Self::Periapsis(periapsis) => elements.with_periapsis(periapsis),
+            Self::Apoapsis(apoapsis) => elements.with_apoapsis(apoapsis),
+            Self::Eccentricity(eccentricity) => elements.with_eccentricity(eccentricity),
+            Self::ArgPe(arg_pe) => Some(elements.with_arg_pe(arg_pe)),
+        };
+
+        edited.map(OrbitElements::to_orbit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orbit::orbital_period;
+
+    const MU: f64 = 3.986e14;
+
+    fn elliptic_orbit(clockwise: bool) -> Orbit2D {
+        let turn = if clockwise { -1.0 } else { 1.0 };
+
+        StateVectors2D {
+            position: DVec2::new(-3e6, 6e6),
+            velocity: DVec2::new(-7000.0, -2000.0) * turn,
+        }
+        .to_cached_orbit(MU, 50.0)
+    }
+
+    fn assert_close(actual: f64, expected: f64, what: &str) {
+        assert!(
+            (actual - expected).abs() <= 1e-8 * expected.abs().max(1.0),
+            "{what} should be {expected}, got {actual}"
+        );
+    }
+
+    /// Checks that the orbit has the elements in `expected`,
+    /// reading them off the orbit itself.
+    fn assert_elements(orbit: &Orbit2D, expected: OrbitElements) {
+        let actual = OrbitElements::of(orbit);
+
+        assert_close(actual.periapsis, expected.periapsis, "periapsis");
+        assert_close(actual.eccentricity, expected.eccentricity, "eccentricity");
+        assert_close(
+            (actual.arg_pe - expected.arg_pe).sin(),
+            0.0,
+            "arg_pe difference",
+        );
+        assert!((actual.arg_pe - expected.arg_pe).cos() > 0.0);
+        assert_close(
+            (actual.mean_anomaly - expected.mean_anomaly).sin(),
+            0.0,
+            "mean anomaly difference",
+        );
+        assert_eq!(actual.clockwise, expected.clockwise);
+    }
+
+    #[test]
+    fn round_trip() {
+        for clockwise in [false, true] {
+            let orbit = elliptic_orbit(clockwise);
+            let elements = OrbitElements::of(&orbit);
+            let rebuilt = elements.to_orbit();
+
+            assert_eq!(elements.clockwise, clockwise);
+            assert_close(
+                elements.apoapsis().unwrap(),
+                orbit.get_apoapsis(),
+                "apoapsis",
+            );
+            assert_close(
+                elements.semi_major_axis(),
+                orbit.get_semi_major_axis(),
+                "semi-major axis",
+            );
+
+            for time in [0.0, 1234.5, 1e5] {
+                let expected = orbit.get_state_vectors_at_time(time);
+                let actual = rebuilt.get_state_vectors_at_time(time);
+                assert!(
+                    (actual.position - expected.position).length()
+                        < 1e-7 * expected.position.length()
+                );
+                assert!(
+                    (actual.velocity - expected.velocity).length()
+                        < 1e-7 * expected.velocity.length()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn periapsis_keeps_apoapsis() {
+        for clockwise in [false, true] {
+            let orbit = elliptic_orbit(clockwise);
+            let elements = OrbitElements::of(&orbit);
+
+            let edited = OrbitEdit::Periapsis(6.5e6).apply(&orbit).unwrap();
+            assert_close(edited.get_periapsis(), 6.5e6, "periapsis");
+            assert_close(edited.get_apoapsis(), orbit.get_apoapsis(), "apoapsis");
+            assert_elements(
+                &edited,
+                OrbitElements {
+                    periapsis: 6.5e6,
+                    eccentricity: edited.get_eccentricity(),
+                    ..elements
+                },
+            );
+        }
+
+        let orbit = elliptic_orbit(false);
+        assert_eq!(
+            OrbitEdit::Periapsis(2.0 * orbit.get_apoapsis()).apply(&orbit),
+            None
+        );
+        assert_eq!(OrbitEdit::Periapsis(-1.0).apply(&orbit), None);
+    }
+
+    #[test]
+    fn apoapsis_keeps_periapsis() {
+        let orbit = elliptic_orbit(false);
+        let elements = OrbitElements::of(&orbit);
+
+        let edited = OrbitEdit::Apoapsis(4e7).apply(&orbit).unwrap();
+        assert_close(edited.get_apoapsis(), 4e7, "apoapsis");
+        assert_elements(
+            &edited,
+            OrbitElements {
+                eccentricity: edited.get_eccentricity(),
+                ..elements
+            },
+        );
+
+        // A longer orbit takes longer to go around
+        assert!(orbital_period(&edited).unwrap() > orbital_period(&orbit).unwrap());
+
+        // Setting it to the periapsis makes the orbit circular
+        let circular = OrbitEdit::Apoapsis(orbit.get_periapsis())
+            .apply(&orbit)
+            .unwrap();
+        assert!(circular.get_eccentricity() < 1e-9);
+
+        assert_eq!(OrbitEdit::Apoapsis(1e6).apply(&orbit), None);
+    }
+
+    #[test]
+    fn eccentricity_keeps_periapsis() {
+        let orbit = elliptic_orbit(false);
+        let elements = OrbitElements::of(&orbit);
+
+        for eccentricity in [0.3, 0.9, 1.5] {
+            let edited = OrbitEdit::Eccentricity(eccentricity).apply(&orbit).unwrap();
+            assert_close(edited.get_eccentricity(), eccentricity, "eccentricity");
+            assert_elements(
+                &edited,
+                OrbitElements {
+                    eccentricity,
+                    ..elements
+                },
+            );
+        }
+
+        // Circular orbits have no periapsis direction to keep
+        let circular = OrbitEdit::Eccentricity(0.0).apply(&orbit).unwrap();
+        assert!(circular.get_eccentricity() < 1e-9);
+        assert_close(circular.get_periapsis(), orbit.get_periapsis(), "radius");
+
+        assert_eq!(OrbitEdit::Eccentricity(1.0).apply(&orbit), None);
+        assert_eq!(OrbitEdit::Eccentricity(-0.1).apply(&orbit), None);
+    }
+
+    #[test]
+    fn arg_pe_keeps_shape() {
+        for clockwise in [false, true] {
+            let orbit = elliptic_orbit(clockwise);
+            let elements = OrbitElements::of(&orbit);
+
+            let edited = OrbitEdit::ArgPe(2.5).apply(&orbit).unwrap();
+            assert_close(edited.get_periapsis(), orbit.get_periapsis(), "periapsis");
+            assert_close(edited.get_apoapsis(), orbit.get_apoapsis(), "apoapsis");
+            assert_elements(
+                &edited,
+                OrbitElements {
+                    arg_pe: 2.5,
+                    ..elements
+                },
+            );
+
+            // Same orbit, just turned around the parent
+            let time = 321.0;
+            let before = orbit.get_state_vectors_at_time(time).position;
+            let after = edited.get_state_vectors_at_time(time).position;
+            let turned = DVec2::from_angle(2.5 - elements.arg_pe).rotate(before);
+            assert!((after - turned).length() < 1e-7 * before.length());
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Projecting an orbit onto the surface of its rotating parent body.
+
+use core::f64::consts::TAU;
+use keplerian_sim::{Orbit2D, OrbitTrait2D};
+
+use crate::orbit::orbital_period;
+
+/// A point of a ground track: where on the parent body's
+/// surface an orbiting object is directly above.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GroundTrackPoint {
+    /// The simulation time of this point, in seconds.
+    pub time: f64,
+    /// The angle on the body's surface the object is above, in radians.
+    ///
+    /// This is measured counterclockwise from the body's own +X axis
+    /// (i.e. the direction a [`RootSpaceAngle`] of zero points in),
+    /// and is in the range `[0, 2π)`.
+    ///
+    /// [`RootSpaceAngle`]: crate::components::main_game::frames::RootSpaceAngle
+    pub surface_angle: f64,
+}
+
+/// Samples the ground track of an orbit over one orbital period,
+/// starting from the simulation time `now`.
+///
+/// `body_angle` is the parent body's rotation at `now`, and
+/// `angular_velocity` is how fast it spins, in radians per second,
+/// as in [`CelestialSpin`]. A body that doesn't spin has an
+/// angular velocity of zero, and the track then matches the
+/// orbit's path in the inertial frame.
+///
+/// Returns [`None`] for open orbits, as they have no period to sample,
+/// or if `samples` is zero.
+///
+/// [`CelestialSpin`]: crate::components::main_game::celestial::CelestialSpin
+#[must_use]
+pub fn ground_track(
+    orbit: &Orbit2D,
+    now: f64,
+    body_angle: f64,
+    angular_velocity: f64,
+    samples: u32,
+) -> Option<Vec<GroundTrackPoint>> {
+    let period = orbital_period(orbit)?;
+    if samples == 0 {
+        return None;
+    }
+
+    let step = period / f64::from(samples);
+
+    let track = (0..samples)
+        .map(|i| {
+            let elapsed = step * f64::from(i);
+            let time = now + elapsed;
+
+            let inertial_angle = orbit.get_state_vectors_at_time(time).position.to_angle();
+            let body_angle = angular_velocity.mul_add(elapsed, body_angle);
+
+            GroundTrackPoint {
+                time,
+                surface_angle: (inertial_angle - body_angle).rem_euclid(TAU),
+            }
+        })
+        .collect();
+
+    Some(track)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orbit::{mean_motion, orbit_from_elements};
+    use core::f64::consts::{FRAC_PI_2, PI};
+
+    const MU: f64 = 3.986e14;
+
+    /// Gets the shortest angle between two angles, in radians.
+    fn angle_diff(a: f64, b: f64) -> f64 {
+        let diff = (a - b).rem_euclid(TAU);
+        diff.min(TAU - diff)
+    }
+
+    #[test]
+    fn still_body_follows_inertial_path() {
+        let orbit = orbit_from_elements(7e6, 0.0, 0.0, 0.0, MU);
+        let track = ground_track(&orbit, 0.0, FRAC_PI_2, 0.0, 8).unwrap();
+
+        assert_eq!(track.len(), 8);
+        for (i, point) in (0..).zip(&track) {
+            // Quarter turn behind, as the body itself is turned a quarter turn
+            let expected = TAU * f64::from(i) / 8.0 - FRAC_PI_2;
+            assert!(
+                angle_diff(point.surface_angle, expected) < 1e-9,
+                "sample {i} at {}, expected {expected}",
+                point.surface_angle
+            );
+            assert!((0.0..TAU).contains(&point.surface_angle));
+        }
+    }
+
+    #[test]
+    fn synchronous_orbit_stays_put() {
+        let orbit = orbit_from_elements(4.2e7, 0.0, 0.0, PI, MU);
+        let spin = mean_motion(&orbit);
+
+        let track = ground_track(&orbit, 1000.0, 0.3, spin, 64).unwrap();
+
+        let first = track[0];
+        assert!((first.time - 1000.0).abs() < 1e-9);
+        for point in &track {
+            assert!(angle_diff(point.surface_angle, first.surface_angle) < 1e-9);
+        }
+    }
+
+    #[test]
+    fn open_orbit_has_no_track() {
+        let orbit = orbit_from_elements(-2e7, 1.4, 0.0, 0.0, MU);
+
+        assert!(ground_track(&orbit, 0.0, 0.0, 1e-4, 64).is_none());
+    }
+}
@@ -0,0 +1,230 @@
+//! Hohmann transfers, i.e. the cheapest two-burn transfers
+//! between two circular orbits around the same parent.
+//!
+//! The first burn stretches the orbit out (or in) to touch the target
+//! radius on the opposite side of the parent, and the second burn,
+//! half an orbit later, makes it circular there.
+
+use core::f64::consts::PI;
+use keplerian_sim::{Orbit2D, OrbitTrait2D};
+
+use crate::orbit::{maneuver::ManeuverNode, orbit_from_elements};
+
+/// The most eccentric an orbit can be for [`plan_hohmann`]
+/// to still count it as circular.
+const MAX_CIRCULAR_ECCENTRICITY: f64 = 1e-3;
+
+/// A Hohmann transfer between two circular orbits.
+#[derive(Clone, Copy, Debug)]
+pub struct HohmannTransfer {
+    /// The prograde delta-v of the burn leaving the starting orbit, in m/s.
+    ///
+    /// Negative when going down to a lower orbit.
+    pub departure_delta_v: f64,
+    /// The prograde delta-v of the burn circularizing
+    /// at the target radius, in m/s.
+    ///
+    /// Negative when going down to a lower orbit.
+    pub arrival_delta_v: f64,
+    /// How long the transfer takes between the two burns, in seconds.
+    pub transfer_time: f64,
+    /// The orbit followed between the two burns.
+    ///
+    /// This leaves from the +X axis at a simulation time of zero,
+    /// going counterclockwise.
+    pub orbit: Orbit2D,
+}
+
+impl HohmannTransfer {
+    /// Gets the total delta-v of both burns, in m/s.
+    #[must_use]
+    pub fn total_delta_v(&self) -> f64 {
+        self.departure_delta_v.abs() + self.arrival_delta_v.abs()
+    }
+}
+
+/// The two burns taking a vessel on a Hohmann transfer.
+#[derive(Clone, Copy, Debug)]
+pub struct HohmannPlan {
+    /// The burn leaving the starting orbit.
+    pub departure: ManeuverNode,
+    /// The burn circularizing at the target radius.
+    pub arrival: ManeuverNode,
+    /// The orbit followed between the two burns.
+    pub orbit: Orbit2D,
+}
+
+/// Gets the Hohmann transfer from a circular orbit of radius
+/// `start_radius` to one of radius `target_radius`, around a parent
+/// with the given gravitational parameter.
+///
+/// Returns [`None`] if either radius isn't positive.
+#[must_use]
+pub fn hohmann_transfer(start_radius: f64, target_radius: f64, mu: f64) -> Option<HohmannTransfer> {
+    if start_radius.is_nan() || target_radius.is_nan() || start_radius.min(target_radius) <= 0.0 {
+        return None;
+    }
+
+    let semi_major_axis = f64::midpoint(start_radius, target_radius);
+    let eccentricity = (target_radius - start_radius).abs() / (start_radius + target_radius);
+
+    // Vis-viva, at both ends of the transfer orbit
+    let speed_at = |radius: f64| (mu * (2.0 / radius - 1.0 / semi_major_axis)).sqrt();
+    let circular_speed_at = |radius: f64| (mu / radius).sqrt();
+
+    // Going down leaves from the apoapsis rather than the periapsis
+    let (arg_pe, mean_anomaly) = if target_radius < start_radius {
+        (PI, PI)
+    } else {
+        (0.0, 0.0)
+    };
+
+    Some(HohmannTransfer {
+        departure_delta_v: speed_at(start_radius) - circular_speed_at(start_radius),
+        arrival_delta_v: circular_speed_at(target_radius) - speed_at(target_radius),
+        transfer_time: PI * (semi_major_axis.powi(3) / mu).sqrt(),
+        orbit: orbit_from_elements(semi_major_axis, eccentricity, arg_pe, mean_anomaly, mu),
+    })
+}
+
+/// Plans a Hohmann transfer from a circular `orbit` to one of radius
+/// `target_radius` around the same parent, leaving at `now`.
+///
+/// As the starting orbit is circular, any point on it is as good
+/// as any other to leave from. The transfer keeps going around the
+/// parent the same way as the starting orbit.
+///
+/// Returns [`None`] if the orbit isn't circular, or the target radius
+/// isn't positive.
+#[must_use]
+pub fn plan_hohmann(orbit: &Orbit2D, target_radius: f64, now: f64) -> Option<HohmannPlan> {
+    if orbit.get_eccentricity() > MAX_CIRCULAR_ECCENTRICITY {
+        return None;
+    }
+
+    let mu = orbit.get_gravitational_parameter();
+    let start_radius = orbit.get_state_vectors_at_time(now).position.length();
+    let transfer = hohmann_transfer(start_radius, target_radius, mu)?;
+
+    let departure = ManeuverNode {
+        time: now,
+        prograde: transfer.departure_delta_v,
+        radial: 0.0,
+    };
+    let arrival = ManeuverNode {
+        time: now + transfer.transfer_time,
+        prograde: transfer.arrival_delta_v,
+        radial: 0.0,
+    };
+
+    Some(HohmannPlan {
+        departure,
+        arrival,
+        orbit: departure.apply(orbit),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::DVec2;
+    use keplerian_sim::StateVectors2D;
+
+    const MU: f64 = 3.986e14;
+    const LOW_ORBIT: f64 = 6.678e6;
+    const GEOSTATIONARY: f64 = 4.2164e7;
+
+    #[test]
+    fn matches_vis_viva() {
+        let transfer = hohmann_transfer(LOW_ORBIT, GEOSTATIONARY, MU).unwrap();
+
+        let sum = LOW_ORBIT + GEOSTATIONARY;
+        let expected_departure =
+            (MU / LOW_ORBIT).sqrt() * ((2.0 * GEOSTATIONARY / sum).sqrt() - 1.0);
+        let expected_arrival = (MU / GEOSTATIONARY).sqrt() * (1.0 - (2.0 * LOW_ORBIT / sum).sqrt());
+        let expected_time = PI * ((sum / 2.0).powi(3) / MU).sqrt();
+
+        assert!((transfer.departure_delta_v - expected_departure).abs() < 1e-6);
+        assert!((transfer.arrival_delta_v - expected_arrival).abs() < 1e-6);
+        assert!((transfer.transfer_time - expected_time).abs() < 1e-6);
+
+        // The textbook figures for going up to a geostationary orbit
+        assert!((transfer.departure_delta_v - 2425.8).abs() < 0.1);
+        assert!((transfer.arrival_delta_v - 1466.8).abs() < 0.1);
+        assert!((transfer.transfer_time - 18_990.1).abs() < 0.1);
+
+        // Going back down is the same transfer the other way
+        let back = hohmann_transfer(GEOSTATIONARY, LOW_ORBIT, MU).unwrap();
+        assert!((back.departure_delta_v + transfer.arrival_delta_v).abs() < 1e-6);
+        assert!((back.arrival_delta_v + transfer.departure_delta_v).abs() < 1e-6);
+        assert!((back.total_delta_v() - transfer.total_delta_v()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn transfer_orbit_touches_both_radii() {
+        for (start, target) in [(LOW_ORBIT, GEOSTATIONARY), (GEOSTATIONARY, LOW_ORBIT)] {
+            let transfer = hohmann_transfer(start, target, MU).unwrap();
+
+            let departure = transfer.orbit.get_state_vectors_at_time(0.0);
+            assert!((departure.position - DVec2::new(start, 0.0)).length() < 1e-6 * start);
+
+            let arrival = transfer
+                .orbit
+                .get_state_vectors_at_time(transfer.transfer_time);
+            assert!((arrival.position - DVec2::new(-target, 0.0)).length() < 1e-3 * target);
+        }
+
+        let same = hohmann_transfer(LOW_ORBIT, LOW_ORBIT, MU).unwrap();
+        assert!(same.total_delta_v() < 1e-9);
+
+        assert!(hohmann_transfer(0.0, LOW_ORBIT, MU).is_none());
+        assert!(hohmann_transfer(LOW_ORBIT, -1.0, MU).is_none());
+    }
+
+    #[test]
+    fn plan_reaches_target_orbit() {
+        for (start, target, clockwise) in [
+            (LOW_ORBIT, GEOSTATIONARY, false),
+            (GEOSTATIONARY, LOW_ORBIT, false),
+            (LOW_ORBIT, 2.0 * LOW_ORBIT, true),
+        ] {
+            let speed = (MU / start).sqrt();
+            let direction = if clockwise { -1.0 } else { 1.0 };
+            let orbit = StateVectors2D {
+                position: DVec2::new(0.0, start),
+                velocity: DVec2::new(-speed * direction, 0.0),
+            }
+            .to_cached_orbit(MU, 0.0);
+
+            let plan = plan_hohmann(&orbit, target, 100.0).unwrap();
+            assert!((plan.departure.time - 100.0).abs() < 1e-9);
+            assert!(plan.arrival.time > plan.departure.time);
+
+            let final_orbit = plan.arrival.apply(&plan.orbit);
+            assert!(
+                final_orbit.get_eccentricity() < 1e-6,
+                "ended up with an eccentricity of {}",
+                final_orbit.get_eccentricity()
+            );
+            assert!((final_orbit.get_semi_major_axis() / target - 1.0).abs() < 1e-6);
+
+            let sv = final_orbit.get_state_vectors_at_time(plan.arrival.time);
+            assert_eq!(
+                sv.position.perp_dot(sv.velocity) < 0.0,
+                clockwise,
+                "the transfer should keep going around the same way"
+            );
+        }
+    }
+
+    #[test]
+    fn plan_needs_circular_orbit() {
+        let orbit = StateVectors2D {
+            position: DVec2::new(LOW_ORBIT, 0.0),
+            velocity: DVec2::new(0.0, 9000.0),
+        }
+        .to_cached_orbit(MU, 0.0);
+
+        assert!(plan_hohmann(&orbit, GEOSTATIONARY, 0.0).is_none());
+    }
+}
@@ -0,0 +1,265 @@
+//! Finding the orbit that connects two positions in a given time,
+//! for planning intercepts.
+//!
+//! This is Lambert's problem, solved with universal variables
+//! as described in Curtis' *Orbital Mechanics for Engineering Students*.
+//!
+//! # Limitations
+//! Only single-revolution, counterclockwise (i.e. prograde) transfers
+//! on elliptical orbits are solved for. Transfers that would need an
+//! open orbit, or that go exactly halfway (or all the way) around the
+//! parent, have no solution here.
+
+use bevy::math::DVec2;
+use core::f64::consts::TAU;
+use keplerian_sim::{Orbit2D, OrbitTrait2D, StateVectors2D};
+
+use crate::orbit::maneuver::ManeuverNode;
+
+/// How many bisection steps to narrow down the universal variable with.
+const BISECTION_STEPS: u32 = 200;
+
+/// The largest relative error in the time of flight
+/// for a solution to be accepted.
+const TIME_TOLERANCE: f64 = 1e-6;
+
+/// How close the transfer angle can get to a multiple of π, in radians,
+/// before the transfer's plane counts as undefined.
+const MIN_ANGLE_SINE: f64 = 1e-9;
+
+/// The velocities at both ends of a transfer between two positions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transfer {
+    /// The velocity needed at the start position, relative to the parent.
+    pub departure_velocity: DVec2,
+    /// The velocity the object arrives at the target position with,
+    /// relative to the parent.
+    pub arrival_velocity: DVec2,
+}
+
+/// A burn that puts an object on a transfer to a target position.
+#[derive(Clone, Copy, Debug)]
+pub struct Intercept {
+    /// The burn to do at the departure time.
+    pub node: ManeuverNode,
+    /// The change in velocity the burn makes, relative to the parent.
+    pub delta_v: DVec2,
+    /// The orbit the object ends up on after the burn.
+    pub orbit: Orbit2D,
+}
+
+/// Approximates the Stumpff functions S(z) and C(z) for `z ≥ 0`.
+fn stumpff(z: f64) -> (f64, f64) {
+    if z < 1e-6 {
+        // The direct formulas lose all precision around zero
+        return (
+            z.mul_add(-1.0 / 120.0, 1.0 / 6.0),
+            z.mul_add(-1.0 / 24.0, 0.5),
+        );
+    }
+
+    let sqrt_z = z.sqrt();
+    let s = (sqrt_z - sqrt_z.sin()) / (z * sqrt_z);
+    let c = (1.0 - sqrt_z.cos()) / z;
+
+    (s, c)
+}
+
+/// Solves for the elliptical transfer that leaves `start` and reaches
+/// `target` after `time_of_flight` seconds, going counterclockwise
+/// around a parent with the given gravitational parameter.
+///
+/// Both positions are relative to the parent.
+///
+/// Returns [`None`] if there's no such transfer, including when it
+/// would need to be on an open orbit. See the
+/// [module docs][self#limitations] for what isn't supported.
+#[must_use]
+pub fn solve_lambert(
+    start: DVec2,
+    target: DVec2,
+    time_of_flight: f64,
+    mu: f64,
+) -> Option<Transfer> {
+    if time_of_flight <= 0.0 {
+        return None;
+    }
+
+    let r1 = start.length();
+    let r2 = target.length();
+    let angle = start.angle_to(target).rem_euclid(TAU);
+
+    if angle.sin().abs() < MIN_ANGLE_SINE {
+        return None;
+    }
+
+    let a = angle.sin() * (r1 * r2 / (1.0 - angle.cos())).sqrt();
+    let y = |z: f64| {
+        let (s, c) = stumpff(z);
+        a.mul_add(z.mul_add(s, -1.0) / c.sqrt(), r1 + r2)
+    };
+    let time = |z: f64| {
+        let (s, c) = stumpff(z);
+        let y = y(z);
+        ((y / c).powf(1.5) * s + a * y.sqrt()) / mu.sqrt()
+    };
+
+    // Elliptical transfers have 0 < z < 4π², and the time of flight
+    // grows with z, reaching infinity at the upper end
+    let mut low = 0.0;
+    let mut high = TAU * TAU;
+
+    for _ in 0..BISECTION_STEPS {
+        let mid = f64::midpoint(low, high);
+
+        if y(mid) < 0.0 || time(mid) < time_of_flight {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    let z = f64::midpoint(low, high);
+    let y = y(z);
+    if y <= 0.0 || ((time(z) - time_of_flight) / time_of_flight).abs() > TIME_TOLERANCE {
+        return None;
+    }
+
+    // Lagrange coefficients
+    let f = 1.0 - y / r1;
+    let g = a * (y / mu).sqrt();
+    let g_dot = 1.0 - y / r2;
+
+    Some(Transfer {
+        departure_velocity: (target - f * start) / g,
+        arrival_velocity: (g_dot * target - start) / g,
+    })
+}
+
+/// Plans a burn at `departure_time` that takes an object on `orbit`
+/// to the `target` position, relative to the same parent,
+/// at `arrival_time`.
+///
+/// Returns [`None`] if there's no transfer between the two, as in
+/// [`solve_lambert`].
+#[must_use]
+pub fn plan_intercept(
+    orbit: &Orbit2D,
+    departure_time: f64,
+    target: DVec2,
+    arrival_time: f64,
+) -> Option<Intercept> {
+    let mu = orbit.get_gravitational_parameter();
+    let sv = orbit.get_state_vectors_at_time(departure_time);
+
+    let transfer = solve_lambert(sv.position, target, arrival_time - departure_time, mu)?;
+    let delta_v = transfer.departure_velocity - sv.velocity;
+
+    Some(Intercept {
+        node: ManeuverNode::from_delta_v(departure_time, sv, delta_v),
+        delta_v,
+        orbit: StateVectors2D {
+            position: sv.position,
+            velocity: transfer.departure_velocity,
+        }
+        .to_cached_orbit(mu, departure_time),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orbit::{orbit_from_elements, orbital_period};
+    use core::f64::consts::{FRAC_PI_2, PI};
+
+    const MU: f64 = 3.986e14;
+
+    /// Checks that the orbit leaving `start` with the transfer's departure
+    /// velocity is at `target` after `time_of_flight` seconds.
+    fn assert_reaches(start: DVec2, target: DVec2, time_of_flight: f64, transfer: Transfer) {
+        let orbit = StateVectors2D {
+            position: start,
+            velocity: transfer.departure_velocity,
+        }
+        .to_cached_orbit(MU, 0.0);
+
+        let arrival = orbit.get_state_vectors_at_time(time_of_flight);
+        assert!(
+            arrival.position.distance(target) < 1e-3 * target.length(),
+            "arrived at {}, expected {target}",
+            arrival.position
+        );
+        assert!(
+            arrival.velocity.distance(transfer.arrival_velocity)
+                < 1e-3 * transfer.arrival_velocity.length(),
+            "arrived with {}, expected {}",
+            arrival.velocity,
+            transfer.arrival_velocity
+        );
+    }
+
+    #[test]
+    fn recovers_circular_orbit() {
+        let orbit = orbit_from_elements(7e6, 0.0, 0.0, 0.0, MU);
+        let time_of_flight = orbital_period(&orbit).unwrap() / 4.0;
+
+        let start = orbit.get_state_vectors_at_time(0.0);
+        let target = orbit.get_state_vectors_at_time(time_of_flight).position;
+
+        let transfer = solve_lambert(start.position, target, time_of_flight, MU).unwrap();
+
+        assert!(transfer.departure_velocity.distance(start.velocity) < 1e-2);
+        assert_reaches(start.position, target, time_of_flight, transfer);
+    }
+
+    #[test]
+    fn reaches_target_on_elliptical_transfers() {
+        let start = DVec2::new(7e6, 0.0);
+
+        for (angle, radius, time_of_flight) in [
+            (FRAC_PI_2, 9e6, 2000.0),
+            (2.5, 1.5e7, 6000.0),
+            (4.0, 8e6, 7000.0),
+            (0.3, 7.5e6, 3000.0),
+        ] {
+            let target = DVec2::from_angle(angle) * radius;
+
+            let transfer = solve_lambert(start, target, time_of_flight, MU)
+                .unwrap_or_else(|| panic!("no transfer to {target} in {time_of_flight} s"));
+
+            assert_reaches(start, target, time_of_flight, transfer);
+        }
+    }
+
+    #[test]
+    fn unsupported_transfers_have_no_solution() {
+        let start = DVec2::new(7e6, 0.0);
+
+        // Exactly halfway around, so the plane is undefined
+        assert!(solve_lambert(start, DVec2::from_angle(PI) * 8e6, 3000.0, MU).is_none());
+        // Far too fast for anything but an open orbit
+        assert!(solve_lambert(start, DVec2::new(0.0, 4e7), 60.0, MU).is_none());
+        assert!(solve_lambert(start, DVec2::new(0.0, 8e6), -10.0, MU).is_none());
+    }
+
+    #[test]
+    fn intercept_burn_reaches_target() {
+        let orbit = orbit_from_elements(7e6, 0.1, 0.0, 0.0, MU);
+        let target = DVec2::new(-3e6, 1.1e7);
+
+        let intercept = plan_intercept(&orbit, 500.0, target, 4500.0).unwrap();
+
+        let before = orbit.get_state_vectors_at_time(500.0);
+        let after = intercept.node.apply(&orbit);
+        assert!(
+            after
+                .get_state_vectors_at_time(500.0)
+                .velocity
+                .distance(before.velocity + intercept.delta_v)
+                < 1e-2
+        );
+
+        let arrival = intercept.orbit.get_state_vectors_at_time(4500.0);
+        assert!(arrival.position.distance(target) < 1e-3 * target.length());
+    }
+}
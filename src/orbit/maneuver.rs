@@ -0,0 +1,354 @@
+//! Impulsive maneuvers and their planners.
+
+use bevy::{ecs::component::Component, math::DVec2};
+use core::f64::consts::TAU;
+use keplerian_sim::{Orbit2D, OrbitTrait2D, StateVectors2D};
+
+use crate::orbit::{ApsisTarget, mean_anomaly_at_time, mean_motion, time_to_apsis};
+
+/// A planned impulsive burn.
+///
+/// As a component, this is the next burn planned for a vessel.
+#[derive(Clone, Copy, Component, Debug, PartialEq)]
+pub struct ManeuverNode {
+    /// The simulation time at which the burn happens, in seconds.
+    pub time: f64,
+    /// The delta-v along the prograde direction, in m/s.
+    pub prograde: f64,
+    /// The delta-v along the radial-out direction, in m/s.
+    ///
+    /// This is perpendicular to the prograde direction.
+    pub radial: f64,
+}
+
+impl ManeuverNode {
+    /// Gets the delta-v vector of this burn, given the state vectors
+    /// at the time of the burn.
+    #[must_use]
+    pub fn delta_v(&self, sv: StateVectors2D) -> DVec2 {
+        let (prograde, radial) = burn_frame(sv);
+        prograde * self.prograde + radial * self.radial
+    }
+
+    /// Plans a burn at `time` that changes the velocity by `delta_v`,
+    /// given the state vectors at the time of the burn.
+    #[must_use]
+    pub fn from_delta_v(time: f64, sv: StateVectors2D, delta_v: DVec2) -> Self {
+        let (prograde, radial) = burn_frame(sv);
+
+        Self {
+            time,
+            prograde: delta_v.dot(prograde),
+            radial: delta_v.dot(radial),
+        }
+    }
+
+    /// Gets the orbit that results from doing this burn on the given orbit.
+    #[must_use]
+    pub fn apply(&self, orbit: &Orbit2D) -> Orbit2D {
+        let sv = orbit.get_state_vectors_at_time(self.time);
+
+        StateVectors2D {
+            position: sv.position,
+            velocity: sv.velocity + self.delta_v(sv),
+        }
+        .to_cached_orbit(orbit.get_gravitational_parameter(), self.time)
+    }
+}
+
+/// Gets the prograde and radial-out unit vectors of the given state vectors.
+///
+/// The radial-out vector is perpendicular to the prograde vector and
+/// points away from the parent body.
+#[must_use]
+fn burn_frame(sv: StateVectors2D) -> (DVec2, DVec2) {
+    let prograde = sv.velocity.normalize_or_zero();
+
+    let radial = if sv.position.perp_dot(sv.velocity) >= 0.0 {
+        -prograde.perp()
+    } else {
+        prograde.perp()
+    };
+
+    (prograde, radial)
+}
+
+/// Gets the speed at a given radius of an orbit with the given
+/// semi-major axis, using the vis-viva equation.
+#[must_use]
+fn vis_viva(mu: f64, radius: f64, semi_major_axis: f64) -> f64 {
+    (mu * (2.0 / radius - 1.0 / semi_major_axis)).sqrt()
+}
+
+/// Gets the signed prograde delta-v needed to turn the orbit into
+/// one with the other apsis at `target_radius`, burning at
+/// `burn_radius`.
+#[must_use]
+fn dv_to_set_opposite_apsis(orbit: &Orbit2D, burn_radius: f64, target_radius: f64, mu: f64) -> f64 {
+    let old_speed = vis_viva(mu, burn_radius, orbit.get_semi_major_axis());
+    let new_speed = vis_viva(mu, burn_radius, f64::midpoint(burn_radius, target_radius));
+
+    new_speed - old_speed
+}
+
+/// Gets the signed prograde delta-v needed to change the periapsis
+/// of the orbit to `target_periapsis`, burning at the apoapsis.
+///
+/// Returns NaN for open orbits, as they have no apoapsis to burn at.
+#[must_use]
+pub fn dv_to_set_periapsis(orbit: &Orbit2D, target_periapsis: f64, mu: f64) -> f64 {
+    if orbit.get_eccentricity() >= 1.0 {
+        return f64::NAN;
+    }
+
+    dv_to_set_opposite_apsis(orbit, orbit.get_apoapsis(), target_periapsis, mu)
+}
+
+/// Gets the signed prograde delta-v needed to change the apoapsis
+/// of the orbit to `target_apoapsis`, burning at the periapsis.
+#[must_use]
+pub fn dv_to_set_apoapsis(orbit: &Orbit2D, target_apoapsis: f64, mu: f64) -> f64 {
+    dv_to_set_opposite_apsis(orbit, orbit.get_periapsis(), target_apoapsis, mu)
+}
+
+/// Plans a burn at the next apoapsis after `now` that changes the
+/// periapsis of the orbit to `target_periapsis`.
+///
+/// Returns [`None`] for open orbits.
+#[must_use]
+pub fn set_periapsis_node(
+    orbit: &Orbit2D,
+    target_periapsis: f64,
+    mu: f64,
+    now: f64,
+) -> Option<ManeuverNode> {
+    let time = now + time_to_apsis(orbit, now, ApsisTarget::Apoapsis)?;
+
+    Some(ManeuverNode {
+        time,
+        prograde: dv_to_set_periapsis(orbit, target_periapsis, mu),
+        radial: 0.0,
+    })
+}
+
+/// Plans a burn at the next periapsis after `now` that changes the
+/// apoapsis of the orbit to `target_apoapsis`.
+///
+/// Returns [`None`] if the orbit won't pass through its periapsis again.
+#[must_use]
+pub fn set_apoapsis_node(
+    orbit: &Orbit2D,
+    target_apoapsis: f64,
+    mu: f64,
+    now: f64,
+) -> Option<ManeuverNode> {
+    let time = now + time_to_apsis(orbit, now, ApsisTarget::Periapsis)?;
+
+    Some(ManeuverNode {
+        time,
+        prograde: dv_to_set_apoapsis(orbit, target_apoapsis, mu),
+        radial: 0.0,
+    })
+}
+
+/// Plans a burn at the next apoapsis after `now` that makes the orbit circular.
+///
+/// Returns [`None`] for open orbits.
+#[must_use]
+pub fn circularize_node(orbit: &Orbit2D, mu: f64, now: f64) -> Option<ManeuverNode> {
+    set_periapsis_node(orbit, orbit.get_apoapsis(), mu, now)
+}
+
+/// Gets the mean anomaly at the given true anomaly of an orbit
+/// with the given eccentricity.
+#[must_use]
+fn mean_anomaly_at_true_anomaly(eccentricity: f64, true_anomaly: f64) -> f64 {
+    let half = true_anomaly / 2.0;
+
+    if eccentricity < 1.0 {
+        let eccentric_anomaly = 2.0
+            * ((1.0 - eccentricity).sqrt() * half.sin())
+                .atan2((1.0 + eccentricity).sqrt() * half.cos());
+        eccentricity.mul_add(-eccentric_anomaly.sin(), eccentric_anomaly)
+    } else {
+        let ratio = ((eccentricity - 1.0) / (eccentricity + 1.0)).sqrt();
+        let hyperbolic_anomaly = 2.0 * (ratio * half.tan()).atanh();
+        eccentricity.mul_add(hyperbolic_anomaly.sinh(), -hyperbolic_anomaly)
+    }
+}
+
+/// Plans a burn at the next time after `now` that the orbit passes
+/// through `radius`, which makes the orbit circular there.
+///
+/// Unlike burns planned by [`circularize_node`], this may need some
+/// radial delta-v as well, as the vessel isn't necessarily moving
+/// sideways at that point.
+///
+/// Returns [`None`] if the orbit never reaches `radius` after `now`.
+#[must_use]
+pub fn circularize_at_radius_node(
+    orbit: &Orbit2D,
+    radius: f64,
+    mu: f64,
+    now: f64,
+) -> Option<ManeuverNode> {
+    let eccentricity = orbit.get_eccentricity();
+    let periapsis = orbit.get_periapsis();
+    let is_open = eccentricity >= 1.0;
+
+    let tolerance = 1e-9 * radius;
+    if radius < periapsis - tolerance || (!is_open && radius > orbit.get_apoapsis() + tolerance) {
+        return None;
+    }
+
+    // r = p / (1 + e cos ν), where p is the semi-latus rectum
+    let semi_latus_rectum = periapsis * (1.0 + eccentricity);
+    let cos_anomaly = if eccentricity > 0.0 {
+        ((semi_latus_rectum / radius - 1.0) / eccentricity).clamp(-1.0, 1.0)
+    } else {
+        1.0
+    };
+    let true_anomaly = cos_anomaly.acos();
+
+    let mean_now = mean_anomaly_at_time(orbit, now);
+    let mean_motion = mean_motion(orbit);
+
+    // The orbit passes through the radius on both sides of the periapsis
+    let wait = [true_anomaly, -true_anomaly]
+        .into_iter()
+        .map(|anomaly| mean_anomaly_at_true_anomaly(eccentricity, anomaly) - mean_now)
+        .filter_map(|mean_diff| {
+            if is_open {
+                (mean_diff >= 0.0).then_some(mean_diff / mean_motion)
+            } else {
+                Some(mean_diff.rem_euclid(TAU) / mean_motion)
+            }
+        })
+        .min_by(f64::total_cmp)?;
+
+    let time = now + wait;
+    let sv = orbit.get_state_vectors_at_time(time);
+
+    // Keep going around the parent the same way
+    let direction = sv.position.perp_dot(sv.velocity).signum();
+    let circular_speed = (mu / sv.position.length()).sqrt();
+    let circular_velocity = sv.position.normalize_or_zero().perp() * direction * circular_speed;
+
+    let delta_v = circular_velocity - sv.velocity;
+    let (prograde, radial) = burn_frame(sv);
+
+    Some(ManeuverNode {
+        time,
+        prograde: delta_v.dot(prograde),
+        radial: delta_v.dot(radial),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MU: f64 = 3.986e14;
+
+    fn elliptic_orbit() -> Orbit2D {
+        StateVectors2D {
+            position: DVec2::new(-3e6, 6e6),
+            velocity: DVec2::new(-7000.0, -2000.0),
+        }
+        .to_cached_orbit(MU, 0.0)
+    }
+
+    #[test]
+    fn set_periapsis() {
+        let orbit = elliptic_orbit();
+        let apoapsis = orbit.get_apoapsis();
+
+        for target in [6.5e6, orbit.get_periapsis(), 0.9 * apoapsis] {
+            let node = set_periapsis_node(&orbit, target, MU, 100.0).unwrap();
+            let new_orbit = node.apply(&orbit);
+
+            assert!(
+                (new_orbit.get_periapsis() - target).abs() < 1e-6 * target,
+                "expected periapsis {target}, got {}",
+                new_orbit.get_periapsis()
+            );
+            assert!((new_orbit.get_apoapsis() - apoapsis).abs() < 1e-6 * apoapsis);
+        }
+    }
+
+    #[test]
+    fn set_apoapsis() {
+        let orbit = elliptic_orbit();
+        let periapsis = orbit.get_periapsis();
+
+        for target in [1.2 * periapsis, orbit.get_apoapsis(), 4e7] {
+            let node = set_apoapsis_node(&orbit, target, MU, 100.0).unwrap();
+            let new_orbit = node.apply(&orbit);
+
+            assert!(
+                (new_orbit.get_apoapsis() - target).abs() < 1e-6 * target,
+                "expected apoapsis {target}, got {}",
+                new_orbit.get_apoapsis()
+            );
+            assert!((new_orbit.get_periapsis() - periapsis).abs() < 1e-6 * periapsis);
+        }
+    }
+
+    #[test]
+    fn no_dv_needed() {
+        let orbit = elliptic_orbit();
+
+        assert!(dv_to_set_periapsis(&orbit, orbit.get_periapsis(), MU).abs() < 1e-6);
+        assert!(dv_to_set_apoapsis(&orbit, orbit.get_apoapsis(), MU).abs() < 1e-6);
+    }
+
+    #[test]
+    fn circularize() {
+        let orbit = elliptic_orbit();
+
+        let node = circularize_node(&orbit, MU, 100.0).unwrap();
+        let new_orbit = node.apply(&orbit);
+        assert!(new_orbit.get_eccentricity() < 1e-6);
+        assert!((new_orbit.get_semi_major_axis() / orbit.get_apoapsis() - 1.0).abs() < 1e-6);
+
+        let radius = f64::midpoint(orbit.get_periapsis(), orbit.get_apoapsis());
+        let node = circularize_at_radius_node(&orbit, radius, MU, 100.0).unwrap();
+        assert!(node.time >= 100.0);
+        assert!(
+            node.radial.abs() > 1.0,
+            "burning mid-orbit should need radial delta-v"
+        );
+
+        let new_orbit = node.apply(&orbit);
+        assert!(new_orbit.get_eccentricity() < 1e-6);
+        assert!((new_orbit.get_semi_major_axis() / radius - 1.0).abs() < 1e-6);
+
+        assert_eq!(
+            circularize_at_radius_node(&orbit, 2.0 * orbit.get_apoapsis(), MU, 100.0),
+            None
+        );
+    }
+
+    #[test]
+    fn circularize_open_orbit() {
+        let orbit = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(1000.0, 11000.0),
+        }
+        .to_cached_orbit(MU, 0.0);
+
+        assert_eq!(circularize_node(&orbit, MU, 0.0), None);
+
+        // Already on the way out, so only the outbound crossing is left
+        let node = circularize_at_radius_node(&orbit, 2e7, MU, 0.0).unwrap();
+        let sv = orbit.get_state_vectors_at_time(node.time);
+        assert!(node.time > 0.0);
+        assert!((sv.position.length() / 2e7 - 1.0).abs() < 1e-6);
+
+        let new_orbit = node.apply(&orbit);
+        assert!(new_orbit.get_eccentricity() < 1e-6);
+
+        // Further in than the periapsis
+        assert_eq!(circularize_at_radius_node(&orbit, 1e6, MU, 0.0), None);
+    }
+}
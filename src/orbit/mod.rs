@@ -0,0 +1,476 @@
+//! Orbital mechanics helpers built on top of [`keplerian_sim`].
+
+use bevy::math::DVec2;
+use core::f64::consts::{PI, TAU};
+use keplerian_sim::{Orbit2D, OrbitTrait2D, StateVectors2D};
+
+use crate::components::main_game::{
+    celestial::CelestialBody,
+    frames::{RootSpaceLinearVelocity, RootSpacePosition},
+};
+
+pub mod approach;
+pub mod elements;
+pub mod ground_track;
+pub mod hohmann;
+pub mod lambert;
+pub mod maneuver;
+pub mod patched_conics;
+pub mod projection;
+
+/// The least sideways speed [`current_orbit`] gives trajectories,
+/// relative to the speed of a circular orbit at the same radius.
+const RADIAL_NUDGE: f64 = 1e-4;
+
+/// One of the two apsides of an orbit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ApsisTarget {
+    /// The closest point of the orbit to the parent body.
+    Periapsis,
+    /// The furthest point of the orbit from the parent body.
+    Apoapsis,
+}
+
+/// What following an orbit leads to, at a glance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OrbitClass {
+    /// A closed orbit that stays clear of the parent's surface.
+    Stable,
+    /// A closed orbit that dips below the parent's surface,
+    /// so following it ends in a crash.
+    Suborbital,
+    /// An open orbit, which leaves the parent for good.
+    Escape,
+}
+
+impl OrbitClass {
+    /// Classifies an orbit around the given parent body.
+    ///
+    /// The periapsis gets compared against the parent's base radius, so
+    /// terrain above sea level isn't accounted for. Open orbits count as
+    /// escaping even if their periapsis is below the surface, as they
+    /// might already be past it.
+    #[must_use]
+    #[cfg_attr(all(not(feature = "not-headless"), not(test)), expect(dead_code))]
+    pub(crate) fn of(orbit: &Orbit2D, parent: CelestialBody) -> Self {
+        if orbit.get_eccentricity() >= 1.0 {
+            Self::Escape
+        } else if orbit.get_periapsis() < f64::from(parent.base_radius) {
+            Self::Suborbital
+        } else {
+            Self::Stable
+        }
+    }
+}
+
+/// Gets the mean motion of the orbit, in radians per second.
+#[must_use]
+pub fn mean_motion(orbit: &Orbit2D) -> f64 {
+    let semi_major_axis = orbit.get_semi_major_axis().abs();
+    (orbit.get_gravitational_parameter() / semi_major_axis.powi(3)).sqrt()
+}
+
+/// Gets the mean anomaly of the orbit at the given simulation time.
+///
+/// For closed orbits, this isn't wrapped to a single revolution.
+#[must_use]
+pub fn mean_anomaly_at_time(orbit: &Orbit2D, time: f64) -> f64 {
+    mean_motion(orbit).mul_add(time, orbit.get_mean_anomaly_at_epoch())
+}
+
+/// Gets the time the orbit takes to go around once, in seconds,
+/// i.e. 2π√(a³/μ).
+///
+/// Returns [`None`] for open orbits, as they never come back around.
+#[must_use]
+pub fn orbital_period(orbit: &Orbit2D) -> Option<f64> {
+    (orbit.get_eccentricity() < 1.0).then(|| TAU / mean_motion(orbit))
+}
+
+/// Builds an orbit from its classical elements.
+///
+/// - `semi_major_axis` is in meters, and negative for open orbits.
+/// - `arg_pe` is the angle of the periapsis from the +X axis, in radians.
+/// - `mean_anomaly` is the mean anomaly at a simulation time of zero.
+///
+/// Parabolic orbits (an eccentricity of exactly 1) aren't supported,
+/// as they have no finite semi-major axis.
+#[must_use]
+pub fn orbit_from_elements(
+    semi_major_axis: f64,
+    eccentricity: f64,
+    arg_pe: f64,
+    mean_anomaly: f64,
+    mu: f64,
+) -> Orbit2D {
+    let periapsis = semi_major_axis * (1.0 - eccentricity);
+    let speed = (mu * (1.0 + eccentricity) / periapsis).sqrt();
+    let direction = DVec2::from_angle(arg_pe);
+
+    // Passing through the periapsis at the time that makes
+    // the mean anomaly at a time of zero come out right
+    let mean_motion = (mu / semi_major_axis.abs().powi(3)).sqrt();
+
+    StateVectors2D {
+        position: direction * periapsis,
+        velocity: direction.perp() * speed,
+    }
+    .to_cached_orbit(mu, -mean_anomaly / mean_motion)
+}
+
+/// Gets the osculating orbit of something at `pos` moving at `vel`,
+/// i.e. the orbit it would follow from now on if it coasted, around a
+/// parent with the given state vectors and gravitational parameter.
+///
+/// This works whatever the [`RailMode`] of the object is, so e.g. a
+/// landed vessel still gets the orbit it would be on if the ground
+/// vanished, even though most of that orbit is below the surface.
+///
+/// Radial trajectories, like that of a vessel launching straight up,
+/// have no orbital plane for [`Orbit2D`] to work with. They get nudged
+/// sideways by a tiny fraction of the circular orbit speed, which turns
+/// them into very thin ellipses (or hyperbolas) with the same apoapsis.
+///
+/// Returns [`None`] if there's no meaningful orbit, i.e. if the parent
+/// doesn't pull on anything, or the object is at the parent's center.
+///
+/// [`RailMode`]: crate::components::main_game::relations::RailMode
+#[must_use]
+pub fn current_orbit(
+    pos: RootSpacePosition,
+    vel: RootSpaceLinearVelocity,
+    parent: (RootSpacePosition, RootSpaceLinearVelocity),
+    mu: f64,
+    now: f64,
+) -> Option<Orbit2D> {
+    let (parent_pos, parent_vel) = parent;
+    let mut sv = pos.relative_to(vel, parent_pos, parent_vel);
+
+    if !(mu.is_finite() && mu > 0.0) || sv.position == DVec2::ZERO {
+        return None;
+    }
+
+    let radius_squared = sv.position.length_squared();
+    let min_momentum = RADIAL_NUDGE * (mu * radius_squared.sqrt()).sqrt();
+    let momentum = sv.position.perp_dot(sv.velocity);
+    if momentum.abs() < min_momentum {
+        let missing = min_momentum.copysign(momentum) - momentum;
+        sv.velocity += sv.position.perp() * (missing / radius_squared);
+    }
+
+    let orbit = sv.to_cached_orbit(mu, now);
+
+    (orbit.get_eccentricity().is_finite() && orbit.get_semi_major_axis().is_finite())
+        .then_some(orbit)
+}
+
+/// Gets the radius of the sphere of influence of a body, using
+/// the Laplace approximation.
+///
+/// `semi_major_axis` is that of the body's orbit around its parent.
+#[must_use]
+pub fn sphere_of_influence(semi_major_axis: f64, mass: f64, parent_mass: f64) -> f64 {
+    semi_major_axis * (mass / parent_mass).powf(0.4)
+}
+
+/// Gets the gravitational parameter a body of mass `mass` orbits
+/// its barycenter with, when paired up with a body of mass `other_mass`.
+///
+/// Each body's orbit around the barycenter is its orbit around the other
+/// one, scaled down by `other_mass / (mass + other_mass)` while keeping
+/// the same period, which works out to this gravitational parameter.
+#[must_use]
+pub fn barycentric_gravitational_parameter(
+    mass: f64,
+    other_mass: f64,
+    gravitational_constant: f64,
+) -> f64 {
+    gravitational_constant * other_mass.powi(3) / (mass + other_mass).powi(2)
+}
+
+/// Gets the time until the orbit next passes through the given apsis,
+/// starting from the simulation time `now`.
+///
+/// Returns [`None`] if the orbit will never pass through that apsis again,
+/// i.e. for the apoapsis of an open orbit, or for the periapsis of an open
+/// orbit that has already been passed.
+#[must_use]
+pub fn time_to_apsis(orbit: &Orbit2D, now: f64, apsis: ApsisTarget) -> Option<f64> {
+    let mean_anomaly = mean_anomaly_at_time(orbit, now);
+    let mean_motion = mean_motion(orbit);
+    let is_open = orbit.get_eccentricity() >= 1.0;
+
+    match (apsis, is_open) {
+        (ApsisTarget::Apoapsis, true) => None,
+        (ApsisTarget::Apoapsis, false) => Some((PI - mean_anomaly).rem_euclid(TAU) / mean_motion),
+        (ApsisTarget::Periapsis, false) => Some((-mean_anomaly).rem_euclid(TAU) / mean_motion),
+        (ApsisTarget::Periapsis, true) => {
+            (mean_anomaly <= 0.0).then(|| -mean_anomaly / mean_motion)
+        }
+    }
+}
+
+/// Gets the time until the orbit next crosses a sphere of influence
+/// of radius `soi` on its way out, starting from the simulation time `now`.
+///
+/// Returns zero if the orbit is already past the boundary and still
+/// heading outwards, and [`None`] if the orbit never crosses it, i.e. if
+/// it stays entirely inside or entirely outside of it.
+#[must_use]
+pub fn time_to_soi_exit(orbit: &Orbit2D, soi: f64, now: f64) -> Option<f64> {
+    let eccentricity = orbit.get_eccentricity();
+    let semi_major_axis = orbit.get_semi_major_axis();
+    let is_open = eccentricity >= 1.0;
+
+    if orbit.get_periapsis() >= soi || (!is_open && orbit.get_apoapsis() <= soi) {
+        return None;
+    }
+
+    let mean_anomaly = mean_anomaly_at_time(orbit, now);
+    let mean_motion = mean_motion(orbit);
+
+    // Both cases solve r = a(1 - e cos E) for the outbound anomaly,
+    // with the hyperbolic anomaly in place of E for open orbits
+    let cos_exit = (1.0 - soi / semi_major_axis) / eccentricity;
+
+    if is_open {
+        let exit = cos_exit.acosh();
+        let exit = eccentricity.mul_add(exit.sinh(), -exit);
+        Some(((exit - mean_anomaly) / mean_motion).max(0.0))
+    } else {
+        let exit = cos_exit.clamp(-1.0, 1.0).acos();
+        let exit = eccentricity.mul_add(-exit.sin(), exit);
+        let mean_anomaly = mean_anomaly.rem_euclid(TAU);
+
+        if (exit..=PI).contains(&mean_anomaly) {
+            Some(0.0)
+        } else {
+            Some((exit - mean_anomaly).rem_euclid(TAU) / mean_motion)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MU: f64 = 3.986e14;
+
+    #[test]
+    fn orbit_classes() {
+        let earth = CelestialBody {
+            base_radius: 6.371e6,
+            mass: 5.972e24,
+        };
+
+        for (semi_major_axis, eccentricity, expected) in [
+            (7e6, 0.01, OrbitClass::Stable),
+            (4.2e7, 0.8, OrbitClass::Stable),
+            (4e6, 0.6, OrbitClass::Suborbital),
+            (6e6, 0.0, OrbitClass::Suborbital),
+            (-1e7, 2.0, OrbitClass::Escape),
+            (-7e6, 1.5, OrbitClass::Escape),
+        ] {
+            let orbit = orbit_from_elements(semi_major_axis, eccentricity, 0.3, 0.0, MU);
+            assert_eq!(
+                OrbitClass::of(&orbit, earth),
+                expected,
+                "a = {semi_major_axis}, e = {eccentricity}"
+            );
+        }
+    }
+
+    #[test]
+    fn earth_sphere_of_influence() {
+        let soi = sphere_of_influence(1.496e11, 5.972e24, 1.989e30);
+        assert!((soi / 9.24e8 - 1.0).abs() < 0.01, "got {soi}");
+    }
+
+    #[test]
+    fn orbit_from_classical_elements() {
+        let (a, e, arg_pe, mean_anomaly) = (1e7, 0.3, 1.0, 2.0);
+        let orbit = orbit_from_elements(a, e, arg_pe, mean_anomaly, MU);
+
+        assert!((orbit.get_semi_major_axis() / a - 1.0).abs() < 1e-9);
+        assert!((orbit.get_eccentricity() - e).abs() < 1e-9);
+        assert!((mean_anomaly_at_time(&orbit, 0.0) - mean_anomaly).abs() < 1e-9);
+
+        let periapsis = orbit.get_state_vectors_at_true_anomaly(0.0).position;
+        assert!((periapsis.to_angle() - arg_pe).abs() < 1e-9);
+
+        let open = orbit_from_elements(-1e7, 1.5, 0.0, -0.5, MU);
+        assert!((open.get_eccentricity() - 1.5).abs() < 1e-9);
+        assert!((mean_anomaly_at_time(&open, 0.0) + 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn circular_orbital_period() {
+        let radius = 7e6;
+        let orbit = StateVectors2D {
+            position: DVec2::new(radius, 0.0),
+            velocity: DVec2::new(0.0, (MU / radius).sqrt()),
+        }
+        .to_cached_orbit(MU, 0.0);
+
+        let expected = TAU * (radius.powi(3) / MU).sqrt();
+        let period = orbital_period(&orbit).unwrap();
+        assert!((period - expected).abs() < 1e-6 * expected, "got {period}");
+
+        // Going around once ends up back at the start
+        let sv = orbit.get_state_vectors_at_time(period);
+        assert!((sv.position - DVec2::new(radius, 0.0)).length() < 1e-3);
+    }
+
+    #[test]
+    fn hyperbolic_orbital_period() {
+        let orbit = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 15000.0),
+        }
+        .to_cached_orbit(MU, 0.0);
+
+        assert_eq!(orbital_period(&orbit), None);
+    }
+
+    #[test]
+    fn apsis_timing() {
+        let orbit = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 8500.0),
+        }
+        .to_cached_orbit(MU, 0.0);
+
+        let to_apo = time_to_apsis(&orbit, 0.0, ApsisTarget::Apoapsis).unwrap();
+        let to_peri = time_to_apsis(&orbit, 0.0, ApsisTarget::Periapsis).unwrap();
+        let period = TAU / mean_motion(&orbit);
+
+        assert!((to_apo - period / 2.0).abs() < 1e-6 * period);
+        assert!(to_peri < 1e-6 * period || (to_peri - period).abs() < 1e-6 * period);
+
+        let apo_radius = orbit.get_state_vectors_at_time(to_apo).position.length();
+        assert!((apo_radius - orbit.get_apoapsis()).abs() < 1e-6 * apo_radius);
+
+        let escape = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(1000.0, 15000.0),
+        }
+        .to_cached_orbit(MU, 0.0);
+
+        assert_eq!(time_to_apsis(&escape, 0.0, ApsisTarget::Apoapsis), None);
+        assert_eq!(time_to_apsis(&escape, 0.0, ApsisTarget::Periapsis), None);
+        assert!(time_to_apsis(&escape, -1000.0, ApsisTarget::Periapsis).is_some());
+    }
+
+    #[test]
+    fn landed_vessel_has_sub_surface_orbit() {
+        // Resting on the equator of an Earth-sized planet,
+        // being carried around by its spin
+        let radius = 6.371e6;
+        let surface_speed = 465.0;
+        let parent = (
+            RootSpacePosition(DVec2::new(3e8, -1e8)),
+            RootSpaceLinearVelocity(DVec2::new(-800.0, 1200.0)),
+        );
+        let pos = RootSpacePosition(parent.0.0 + DVec2::new(0.0, radius));
+        let vel = RootSpaceLinearVelocity(parent.1.0 + DVec2::new(-surface_speed, 0.0));
+
+        let orbit = current_orbit(pos, vel, parent, MU, 100.0).expect("landed vessel has no orbit");
+
+        assert!((orbit.get_apoapsis() / radius - 1.0).abs() < 1e-6);
+        assert!(
+            orbit.get_periapsis() < 0.1 * radius,
+            "mostly below the surface"
+        );
+
+        let sv = orbit.get_state_vectors_at_time(100.0);
+        assert!(sv.position.distance(pos.0 - parent.0.0) < 1.0);
+        assert!(sv.velocity.distance(vel.0 - parent.1.0) < 1e-3);
+
+        assert!(current_orbit(parent.0, vel, parent, MU, 100.0).is_none());
+        assert!(current_orbit(pos, vel, parent, 0.0, 100.0).is_none());
+    }
+
+    #[test]
+    fn radial_trajectory_has_orbit() {
+        let radius = 7e6;
+        let parent = (
+            RootSpacePosition(DVec2::new(-2e8, 5e7)),
+            RootSpaceLinearVelocity(DVec2::new(300.0, -900.0)),
+        );
+        let radial = |speed: f64| {
+            current_orbit(
+                RootSpacePosition(parent.0.0 + DVec2::new(0.0, radius)),
+                RootSpaceLinearVelocity(parent.1.0 + DVec2::new(0.0, speed)),
+                parent,
+                MU,
+                0.0,
+            )
+            .expect("radial trajectory has no orbit")
+        };
+
+        // Falling straight down from rest, the apoapsis is where it is now
+        let at_rest = radial(0.0);
+        assert!((at_rest.get_apoapsis() / radius - 1.0).abs() < 1e-6);
+        assert!(at_rest.get_periapsis() < 1e-6 * radius);
+
+        // Rising at 1 km/s gets about 62 km higher
+        let rising = radial(1000.0);
+        let energy = 0.5f64.mul_add(1000.0f64.powi(2), -MU / radius);
+        let expected_apoapsis = -MU / energy;
+        assert!((rising.get_apoapsis() / expected_apoapsis - 1.0).abs() < 1e-6);
+
+        let sv = rising.get_state_vectors_at_time(0.0);
+        assert!(sv.position.distance(DVec2::new(0.0, radius)) < 1e-3);
+        assert!(sv.velocity.distance(DVec2::new(0.0, 1000.0)) < 1.0);
+
+        let escaping = radial(20_000.0);
+        assert!(escaping.get_eccentricity() > 1.0);
+    }
+
+    #[test]
+    fn circular_orbit_stays_in_soi() {
+        let radius = 7e6;
+        let orbit = StateVectors2D {
+            position: DVec2::new(radius, 0.0),
+            velocity: DVec2::new(0.0, (MU / radius).sqrt()),
+        }
+        .to_cached_orbit(MU, 0.0);
+
+        assert_eq!(time_to_soi_exit(&orbit, 9.24e8, 0.0), None);
+        assert_eq!(time_to_soi_exit(&orbit, 1e6, 0.0), None);
+    }
+
+    #[test]
+    fn soi_exit_timing() {
+        let soi = 9.24e8;
+
+        let escape = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 15000.0),
+        }
+        .to_cached_orbit(MU, 0.0);
+
+        let to_exit = time_to_soi_exit(&escape, soi, 0.0).unwrap();
+        assert!(to_exit.is_finite() && to_exit > 0.0);
+        let exit_radius = escape.get_state_vectors_at_time(to_exit).position.length();
+        assert!((exit_radius / soi - 1.0).abs() < 1e-6, "got {exit_radius}");
+
+        // Later on, the crossing gets closer, and then it's been passed
+        let later = time_to_soi_exit(&escape, soi, 1000.0).unwrap();
+        assert!((later - (to_exit - 1000.0)).abs() < 1e-6 * to_exit);
+        assert_eq!(time_to_soi_exit(&escape, soi, 2.0 * to_exit), Some(0.0));
+
+        // Elliptical orbits reaching past the SOI cross it on the way up
+        let elliptic = orbit_from_elements(6e8, 0.9, 0.0, 0.5, MU);
+        let to_exit = time_to_soi_exit(&elliptic, soi, 0.0).unwrap();
+        let exit = elliptic.get_state_vectors_at_time(to_exit);
+        assert!((exit.position.length() / soi - 1.0).abs() < 1e-6);
+        assert!(exit.position.dot(exit.velocity) > 0.0, "should be outbound");
+
+        // On the way down, the next crossing only comes after the periapsis
+        let inbound = orbit_from_elements(6e8, 0.9, 0.0, -0.5, MU);
+        let to_exit = time_to_soi_exit(&inbound, soi, 0.0).unwrap();
+        let to_peri = time_to_apsis(&inbound, 0.0, ApsisTarget::Periapsis).unwrap();
+        assert!(to_exit > to_peri);
+    }
+}
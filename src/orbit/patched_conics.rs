@@ -0,0 +1,304 @@
+//! Predicting sphere of influence changes without running the simulation.
+//!
+//! This follows the same rules as the live simulation, so the two
+//! shouldn't disagree on when and where something changes parents,
+//! apart from the live simulation only checking once per fixed tick.
+
+use bevy::{ecs::entity::Entity, math::DVec2};
+use keplerian_sim::{Orbit2D, OrbitTrait2D, StateVectors2D};
+
+use crate::orbit::sphere_of_influence;
+
+/// A celestial body, as far as a prediction is concerned.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PredictedBody {
+    /// The entity the body stands in for.
+    pub entity: Entity,
+    /// The mass, in kilograms.
+    pub mass: f64,
+    /// The gravitational parameter, in m^3 s^-2.
+    pub mu: f64,
+    /// The body's parent and its orbit around it,
+    /// or [`None`] for a top-level body.
+    pub parent: Option<(Entity, Orbit2D)>,
+}
+
+/// A stretch of a predicted trajectory spent around a single parent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitSegment {
+    /// The simulation time at which this segment starts, in seconds.
+    pub time: f64,
+    /// The body being orbited.
+    pub parent: Entity,
+    /// The orbit around that body.
+    pub orbit: Orbit2D,
+}
+
+/// Gets the radius of a body's sphere of influence, given its orbit
+/// around its parent.
+#[must_use]
+pub(crate) fn body_soi(orbit: &Orbit2D, mass: f64, parent_mass: f64) -> f64 {
+    sphere_of_influence(orbit.get_semi_major_axis(), mass, parent_mass)
+}
+
+/// Decides whether something has entered the sphere of influence
+/// of one of its parent's children.
+///
+/// - `rel_pos` is its position relative to its current parent.
+/// - `children` are the bodies orbiting the parent, along with their
+///   positions relative to it and their spheres of influence.
+///
+/// # Output
+/// The child it entered, if any.
+pub(crate) fn soi_entry(
+    rel_pos: DVec2,
+    children: impl IntoIterator<Item = (Entity, DVec2, f64)>,
+) -> Option<Entity> {
+    children
+        .into_iter()
+        .find(|&(_, child_pos, soi)| rel_pos.distance(child_pos) < soi)
+        .map(|(child, ..)| child)
+}
+
+/// Decides whether something has left its parent's sphere of influence.
+///
+/// - `rel_pos` is its position relative to its current parent.
+/// - `escape` is the parent's own parent, along with the radius of
+///   the parent's sphere of influence, if it has one to leave.
+///
+/// Only open orbits can leave, as closed ones are bound to come back.
+///
+/// # Output
+/// The parent's parent, if it left.
+pub(crate) fn soi_escape(
+    rel_pos: DVec2,
+    orbit: &Orbit2D,
+    escape: Option<(Entity, f64)>,
+) -> Option<Entity> {
+    escape
+        .filter(|&(_, soi)| orbit.get_eccentricity() >= 1.0 && rel_pos.length() > soi)
+        .map(|(grandparent, _)| grandparent)
+}
+
+/// Decides whether something has moved into another sphere of influence,
+/// see [`soi_entry`] and [`soi_escape`].
+///
+/// Entering a child's sphere of influence takes priority.
+///
+/// # Output
+/// The new parent, if it changed.
+fn soi_transition(
+    rel_pos: DVec2,
+    orbit: &Orbit2D,
+    escape: Option<(Entity, f64)>,
+    children: impl IntoIterator<Item = (Entity, DVec2, f64)>,
+) -> Option<Entity> {
+    soi_entry(rel_pos, children).or_else(|| soi_escape(rel_pos, orbit, escape))
+}
+
+/// Follows a trajectory forward by `duration` seconds, checking for
+/// sphere of influence changes every `step` seconds.
+///
+/// Bodies missing from `bodies` can't be entered or escaped to, and
+/// the trajectory stops being followed if its parent is missing.
+///
+/// # Output
+/// Every segment of the trajectory in order, starting with `start`.
+///
+/// # Panics
+/// Panics if `step` isn't positive.
+#[must_use]
+pub fn predict_soi_transitions(
+    bodies: &[PredictedBody],
+    start: OrbitSegment,
+    duration: f64,
+    step: f64,
+) -> Vec<OrbitSegment> {
+    assert!(step > 0.0, "steps of {step} s would never get anywhere");
+
+    let find = |entity: Entity| bodies.iter().find(|body| body.entity == entity);
+
+    let mut segments = vec![start];
+    let mut current = start;
+    let mut time = start.time;
+
+    while time < start.time + duration {
+        time = (time + step).min(start.time + duration);
+
+        let Some(parent) = find(current.parent) else {
+            break;
+        };
+
+        let sv = current.orbit.get_state_vectors_at_time(time);
+
+        let escape = parent.parent.and_then(|(grandparent, orbit)| {
+            let grandparent_body = find(grandparent)?;
+            Some((
+                grandparent,
+                body_soi(&orbit, parent.mass, grandparent_body.mass),
+            ))
+        });
+        let children = bodies.iter().filter_map(|body| match body.parent {
+            Some((body_parent, orbit)) if body_parent == parent.entity => Some((
+                body.entity,
+                orbit.get_state_vectors_at_time(time).position,
+                body_soi(&orbit, body.mass, parent.mass),
+            )),
+            _ => None,
+        });
+
+        let Some(new_parent) = soi_transition(sv.position, &current.orbit, escape, children) else {
+            continue;
+        };
+        let Some(new_parent_body) = find(new_parent) else {
+            break;
+        };
+
+        // Whichever way it went, one of the two parents orbits the other
+        let parent_sv = match (parent.parent, new_parent_body.parent) {
+            (Some((grandparent, orbit)), _) if grandparent == new_parent => {
+                let parent_sv = orbit.get_state_vectors_at_time(time);
+                StateVectors2D {
+                    position: -parent_sv.position,
+                    velocity: -parent_sv.velocity,
+                }
+            }
+            (_, Some((_, orbit))) => orbit.get_state_vectors_at_time(time),
+            _ => break,
+        };
+
+        current = OrbitSegment {
+            time,
+            parent: new_parent,
+            orbit: StateVectors2D {
+                position: sv.position - parent_sv.position,
+                velocity: sv.velocity - parent_sv.velocity,
+            }
+            .to_cached_orbit(new_parent_body.mu, time),
+        };
+        segments.push(current);
+    }
+
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{consts::GRAVITATIONAL_CONSTANT, orbit::orbit_from_elements};
+
+    const ALPHA_MU: f64 = 4e14;
+    const BETA_MU: f64 = 4.9e12;
+    const STEP: f64 = 600.0;
+
+    /// A planet with a moon on a circular orbit, starting along +X.
+    fn alpha_beta() -> [PredictedBody; 2] {
+        let alpha = Entity::from_raw_u32(1).unwrap();
+        let beta = Entity::from_raw_u32(2).unwrap();
+
+        [
+            PredictedBody {
+                entity: alpha,
+                mass: ALPHA_MU / GRAVITATIONAL_CONSTANT,
+                mu: ALPHA_MU,
+                parent: None,
+            },
+            PredictedBody {
+                entity: beta,
+                mass: BETA_MU / GRAVITATIONAL_CONSTANT,
+                mu: BETA_MU,
+                parent: Some((alpha, orbit_from_elements(3.844e8, 0.0, 0.0, 0.0, ALPHA_MU))),
+            },
+        ]
+    }
+
+    /// Checks that switching from `before` to `after` doesn't make
+    /// the trajectory jump, given the new parent's state relative to the old one.
+    fn assert_continuous(before: &Orbit2D, after: &Orbit2D, parent_sv: StateVectors2D, time: f64) {
+        let before = before.get_state_vectors_at_time(time);
+        let after = after.get_state_vectors_at_time(time);
+        assert!((parent_sv.position + after.position - before.position).length() < 1e-2);
+        assert!((parent_sv.velocity + after.velocity - before.velocity).length() < 1e-5);
+    }
+
+    #[test]
+    fn co_orbital_flyby_gets_captured() {
+        let bodies = alpha_beta();
+        let [alpha, beta] = bodies.map(|body| body.entity);
+        let (_, beta_orbit) = bodies[1].parent.unwrap();
+        let beta_soi = body_soi(&beta_orbit, bodies[1].mass, bodies[0].mass);
+
+        // Slightly lower than Beta and half a radian behind it, so it
+        // slowly catches up and drifts in at a low relative speed
+        let start = OrbitSegment {
+            time: 0.0,
+            parent: alpha,
+            orbit: orbit_from_elements(3.6e8, 0.0, 0.0, -0.5, ALPHA_MU),
+        };
+
+        let segments = predict_soi_transitions(&bodies, start, 2e6, STEP);
+
+        let [first, captured] = segments[..] else {
+            panic!("expected to enter Beta's SOI and stay, got {segments:#?}");
+        };
+        assert_eq!(first, start);
+        assert_eq!(captured.parent, beta);
+
+        // Catching up at ~2.8e-7 rad/s until ~0.17 rad behind
+        assert!(
+            (1.1e6..1.3e6).contains(&captured.time),
+            "entered Beta's SOI at {} s",
+            captured.time
+        );
+
+        let distance_at = |time| {
+            let vessel = start.orbit.get_state_vectors_at_time(time).position;
+            vessel.distance(beta_orbit.get_state_vectors_at_time(time).position)
+        };
+        assert!(distance_at(captured.time) < beta_soi);
+        assert!(distance_at(captured.time - STEP) >= beta_soi);
+
+        assert_continuous(
+            &start.orbit,
+            &captured.orbit,
+            beta_orbit.get_state_vectors_at_time(captured.time),
+            captured.time,
+        );
+        assert!(
+            captured.orbit.get_eccentricity() < 1.0,
+            "should've been captured, but is on its way out"
+        );
+        assert!((captured.orbit.get_gravitational_parameter() - BETA_MU).abs() < 1e-3);
+    }
+
+    #[test]
+    fn escapes_back_to_grandparent() {
+        let bodies = alpha_beta();
+        let [alpha, beta] = bodies.map(|body| body.entity);
+        let (_, beta_orbit) = bodies[1].parent.unwrap();
+
+        let start = OrbitSegment {
+            time: 0.0,
+            parent: beta,
+            orbit: orbit_from_elements(-1e7, 1.5, 0.0, 0.0, BETA_MU),
+        };
+
+        let segments = predict_soi_transitions(&bodies, start, 2e5, STEP);
+
+        let [_, escaped] = segments[..] else {
+            panic!("expected to leave Beta's SOI for good, got {segments:#?}");
+        };
+        assert_eq!(escaped.parent, alpha);
+
+        let beta_sv = beta_orbit.get_state_vectors_at_time(escaped.time);
+        assert_continuous(
+            &start.orbit,
+            &escaped.orbit,
+            StateVectors2D {
+                position: -beta_sv.position,
+                velocity: -beta_sv.velocity,
+            },
+            escaped.time,
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Projection of orbits onto the screen, for drawing them in map view.
+
+use bevy::math::DVec2;
+use keplerian_sim::{Orbit2D, OrbitTrait2D};
+
+use crate::components::main_game::{camera::SimCameraZoom, frames::RootSpacePosition};
+
+/// An orbit's path as an ellipse in camera space, ready to be drawn
+/// with a single ellipse primitive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScreenEllipse {
+    /// The center of the ellipse, in camera space.
+    ///
+    /// This is not the parent body's position, which is at one of the foci.
+    pub center: DVec2,
+    /// Half the length of the ellipse's major axis, in camera space.
+    pub semi_major_axis: f64,
+    /// Half the length of the ellipse's minor axis, in camera space.
+    pub semi_minor_axis: f64,
+    /// The counterclockwise angle, in radians, from the +X axis
+    /// to the major axis, pointing towards the periapsis.
+    pub rotation: f64,
+}
+
+/// Projects an orbit's path into camera space, given the position of
+/// the parent body and the simulation camera's offset and zoom.
+///
+/// Returns [`None`] for open orbits (eccentricity ≥ 1), as those aren't
+/// ellipses. Callers should sample points along the orbit instead.
+#[must_use]
+pub fn project_orbit(
+    orbit: &Orbit2D,
+    parent_pos: RootSpacePosition,
+    camera_offset: RootSpacePosition,
+    zoom: SimCameraZoom,
+) -> Option<ScreenEllipse> {
+    let eccentricity = orbit.get_eccentricity();
+    if eccentricity >= 1.0 {
+        return None;
+    }
+
+    let semi_major_axis = orbit.get_semi_major_axis();
+    let semi_minor_axis = semi_major_axis * eccentricity.mul_add(-eccentricity, 1.0).sqrt();
+
+    // Taking the direction from the periapsis' position covers both the
+    // argument of periapsis and the orbit's direction of travel
+    let periapsis_dir = orbit
+        .get_state_vectors_at_eccentric_anomaly(0.0)
+        .position
+        .normalize_or(DVec2::X);
+
+    // The parent sits at the focus, which is a distance of `a·e`
+    // away from the center towards the periapsis
+    let center = parent_pos.0 - periapsis_dir * (semi_major_axis * eccentricity);
+
+    Some(ScreenEllipse {
+        center: (center - camera_offset.0) * zoom.0,
+        semi_major_axis: semi_major_axis * zoom.0,
+        semi_minor_axis: semi_minor_axis * zoom.0,
+        rotation: periapsis_dir.to_angle(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keplerian_sim::StateVectors2D;
+
+    const MU: f64 = 3.986e14;
+
+    /// Gets how far off a camera-space point is from lying on the ellipse,
+    /// as the left-hand side of the ellipse equation minus one.
+    fn ellipse_error(ellipse: ScreenEllipse, point: DVec2) -> f64 {
+        let local = DVec2::from_angle(-ellipse.rotation).rotate(point - ellipse.center);
+
+        let axes = DVec2::new(ellipse.semi_major_axis, ellipse.semi_minor_axis);
+
+        (local / axes).length_squared() - 1.0
+    }
+
+    #[test]
+    fn circular_orbit() {
+        let orbit = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, (MU / 7e6).sqrt()),
+        }
+        .to_cached_orbit(MU, 0.0);
+
+        let parent_pos = RootSpacePosition(DVec2::new(1e6, -2e6));
+        let camera_offset = RootSpacePosition(DVec2::new(3e5, 4e5));
+        let zoom = SimCameraZoom(1e-4);
+
+        let ellipse = project_orbit(&orbit, parent_pos, camera_offset, zoom).unwrap();
+
+        let expected_center = (parent_pos.0 - camera_offset.0) * zoom.0;
+        assert!((ellipse.center - expected_center).length() < 1e-6);
+        assert!((ellipse.semi_major_axis - 700.0).abs() < 1e-6);
+        assert!((ellipse.semi_minor_axis - 700.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn elliptic_orbit_passes_through_path() {
+        for sv in [
+            StateVectors2D {
+                position: DVec2::new(-3e6, 6e6),
+                velocity: DVec2::new(-7000.0, -2000.0),
+            },
+            StateVectors2D {
+                position: DVec2::new(-3e6, 6e6),
+                velocity: DVec2::new(7000.0, 2000.0),
+            },
+            StateVectors2D {
+                position: DVec2::new(5e6, 5e6),
+                velocity: DVec2::new(1000.0, -8000.0),
+            },
+        ] {
+            let orbit = sv.to_cached_orbit(MU, 0.0);
+            let parent_pos = RootSpacePosition(DVec2::new(-4e6, 9e5));
+            let camera_offset = RootSpacePosition(DVec2::new(2e6, -1e6));
+            let zoom = SimCameraZoom(3e-5);
+
+            let ellipse = project_orbit(&orbit, parent_pos, camera_offset, zoom).unwrap();
+
+            for i in 0..16 {
+                let anomaly = f64::from(i) * core::f64::consts::TAU / 16.0;
+                let rel_pos = orbit
+                    .get_state_vectors_at_eccentric_anomaly(anomaly)
+                    .position;
+                let point = (parent_pos.0 + rel_pos - camera_offset.0) * zoom.0;
+
+                let error = ellipse_error(ellipse, point);
+                assert!(
+                    error.abs() < 1e-9,
+                    "point {i} is off the ellipse by {error}"
+                );
+            }
+
+            let periapsis = orbit.get_state_vectors_at_eccentric_anomaly(0.0).position;
+            let periapsis_angle = DVec2::from_angle(ellipse.rotation);
+            assert!((periapsis.normalize() - periapsis_angle).length() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn open_orbit_is_not_an_ellipse() {
+        let orbit = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 2.0 * (MU / 7e6).sqrt()),
+        }
+        .to_cached_orbit(MU, 0.0);
+
+        let projection = project_orbit(
+            &orbit,
+            RootSpacePosition(DVec2::ZERO),
+            RootSpacePosition(DVec2::ZERO),
+            SimCameraZoom(1.0),
+        );
+        assert_eq!(projection, None);
+    }
+}
@@ -1,5 +1,8 @@
 use bevy::{
-    ecs::{lifecycle::HookContext, world::DeferredWorld},
+    ecs::{
+        lifecycle::HookContext, schedule::ScheduleConfigs, system::ScheduleSystem,
+        world::DeferredWorld,
+    },
     prelude::*,
 };
 
@@ -8,15 +11,27 @@ use crate::{
         camera::Focusable, celestial::CelestialBody, relations::CelestialParent,
     },
     resources::{
-        controls::{FocusableData, FocusableEntry, GameControlMode},
+        controls::{
+            EdgeIndicators, FocusableData, FocusableEntry, GameControlMode, InputSmoothing,
+            OrbitLineDetail, ViewMode,
+        },
         scene::GameScene,
     },
     systems::main_game::{
         controls::{
-            camera::control_camera, cleanup_controls, control_switching, init_controls,
+            camera::{control_camera, focus_clicked_entity, pan_camera_with_mouse},
+            cleanup_controls, control_switching, init_controls,
             menu::control_menu,
+            vessel::control_vessel,
         },
+        indicators::{clear_edge_indicators, draw_edge_indicators},
+        map::{
+            apply_view_mode, draw_map_view, remesh_orbits, toggle_view_mode, update_orbit_meshes,
+        },
+        markers::draw_orbital_markers,
+        pause::toggle_pause,
         ui::controls::update_controls_text,
+        warp::change_warp_rate,
     },
 };
 
@@ -25,15 +40,30 @@ pub(crate) struct GameControlPlugin;
 impl Plugin for GameControlPlugin {
     fn build(&self, app: &mut App) {
         app.add_sub_state::<GameControlMode>();
+        app.add_sub_state::<ViewMode>();
+        app.init_resource::<InputSmoothing>();
+        app.init_resource::<OrbitLineDetail>();
+        app.init_resource::<EdgeIndicators>();
         app.add_systems(OnEnter(GameScene::InGame), init_controls);
+        app.add_systems(OnExit(ViewMode::Flight), clear_edge_indicators);
         app.add_systems(OnExit(GameScene::InGame), cleanup_controls);
         app.add_systems(
             Update,
             (
                 control_switching,
                 update_controls_text.run_if(state_changed::<GameControlMode>),
-                control_camera.run_if(in_state(GameControlMode::CameraControl)),
-                control_menu.run_if(in_state(GameControlMode::Menu)),
+                input_systems(),
+                toggle_view_mode,
+                (toggle_pause, change_warp_rate).run_if(not(in_state(GameControlMode::Menu))),
+                apply_view_mode.run_if(state_changed::<ViewMode>),
+                (
+                    remesh_orbits.run_if(resource_changed::<OrbitLineDetail>),
+                    update_orbit_meshes,
+                    draw_map_view,
+                )
+                    .chain()
+                    .run_if(in_state(ViewMode::Map)),
+                (draw_orbital_markers, draw_edge_indicators).run_if(in_state(ViewMode::Flight)),
             )
                 .run_if(in_state(GameScene::InGame)),
         );
@@ -44,6 +74,21 @@ impl Plugin for GameControlPlugin {
     }
 }
 
+/// The systems that respond to input, each gated to
+/// the [`GameControlMode`] it belongs to.
+///
+/// Mode switching itself isn't included here,
+/// as it needs to work in every mode.
+fn input_systems() -> ScheduleConfigs<ScheduleSystem> {
+    (
+        (control_camera, pan_camera_with_mouse, focus_clicked_entity)
+            .run_if(in_state(GameControlMode::CameraControl)),
+        control_menu.run_if(in_state(GameControlMode::Menu)),
+        control_vessel.run_if(in_state(GameControlMode::VesselControl)),
+    )
+        .into_configs()
+}
+
 fn on_focusable_added(mut world: DeferredWorld, ctx: HookContext) {
     let entity = ctx.entity;
 
@@ -86,3 +131,285 @@ fn on_focusable_removed(mut world: DeferredWorld, ctx: HookContext) {
 
     focusable_data.remove(index);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::main_game::{
+            camera::{CameraEasing, CameraOrientationMode, SimCamera, SimCameraZoom},
+            frames::{RootSpaceLinearVelocity, RootSpacePosition},
+            vessel::{Vessel, VesselInput},
+        },
+        math::quat_to_rot,
+        resources::{simulation::ActiveVessel, ui::AltimeterMode},
+        systems::main_game::camera::orient_camera,
+    };
+    use bevy::{math::DVec2, state::app::StatesPlugin, time::TimeUpdateStrategy};
+    use core::time::Duration;
+
+    fn set_mode(app: &mut App, mode: GameControlMode) {
+        app.world_mut()
+            .resource_mut::<NextState<GameControlMode>>()
+            .set(mode);
+        app.update();
+    }
+
+    #[test]
+    fn camera_zoom_is_mode_gated() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )));
+        app.insert_state(GameScene::InGame);
+        app.add_sub_state::<GameControlMode>();
+        app.add_sub_state::<AltimeterMode>();
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<ButtonInput<MouseButton>>();
+        app.init_resource::<FocusableData>();
+        app.add_systems(Update, input_systems());
+
+        let camera = app
+            .world_mut()
+            .spawn((Camera::default(), SimCamera, Transform::default()))
+            .id();
+        let zoom = |app: &App| app.world().get::<SimCameraZoom>(camera).unwrap().0;
+
+        app.update();
+
+        set_mode(&mut app, GameControlMode::Menu);
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Equal);
+
+        (0..4).for_each(|_| app.update());
+
+        assert!(
+            (zoom(&app) - 1.0).abs() < f64::EPSILON,
+            "zoom input should be ignored in menu mode"
+        );
+
+        set_mode(&mut app, GameControlMode::CameraControl);
+        app.update();
+
+        assert!(
+            zoom(&app) > 1.0,
+            "zoom input should be honored in camera control mode"
+        );
+    }
+
+    #[test]
+    fn manual_zoom_cancels_reset() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin));
+        app.insert_state(GameScene::InGame);
+        app.add_sub_state::<GameControlMode>();
+        app.add_sub_state::<AltimeterMode>();
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<ButtonInput<MouseButton>>();
+        app.init_resource::<FocusableData>();
+        app.add_systems(Update, input_systems());
+
+        let camera = app
+            .world_mut()
+            .spawn((Camera::default(), SimCamera, Transform::default()))
+            .id();
+        let easing = |app: &App| *app.world().get::<CameraEasing>(camera).unwrap();
+
+        app.update();
+        set_mode(&mut app, GameControlMode::CameraControl);
+
+        let press = |app: &mut App, key: KeyCode| {
+            let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+            input.reset_all();
+            input.press(key);
+            app.update();
+        };
+
+        press(&mut app, KeyCode::Digit0);
+        press(&mut app, KeyCode::KeyR);
+        assert!(easing(&app).zoom.is_some());
+        assert!(easing(&app).rotation.is_some());
+
+        press(&mut app, KeyCode::Equal);
+        assert!(
+            easing(&app).zoom.is_none(),
+            "zooming should cancel the reset"
+        );
+        assert!(easing(&app).rotation.is_some());
+
+        press(&mut app, KeyCode::KeyQ);
+        assert!(
+            easing(&app).rotation.is_none(),
+            "rotating should cancel the reset"
+        );
+    }
+
+    #[test]
+    fn prograde_mode_follows_velocity() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin));
+        app.insert_state(GameScene::InGame);
+        app.add_sub_state::<GameControlMode>();
+        app.add_sub_state::<AltimeterMode>();
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<ButtonInput<MouseButton>>();
+        app.init_resource::<FocusableData>();
+        app.init_resource::<InputSmoothing>();
+        // Long enough for the camera to finish turning every update
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs(1)));
+        app.add_systems(Update, (input_systems(), orient_camera).chain());
+
+        let body = app
+            .world_mut()
+            .spawn((
+                RootSpacePosition(DVec2::new(1e6, 1e6)),
+                RootSpaceLinearVelocity(DVec2::new(100.0, 0.0)),
+            ))
+            .id();
+        let vessel = app
+            .world_mut()
+            .spawn((
+                Vessel,
+                RootSpacePosition(DVec2::new(1e6 + 7e6, 1e6)),
+                RootSpaceLinearVelocity(DVec2::new(100.0, 7500.0)),
+                CelestialParent { entity: body },
+            ))
+            .id();
+        app.insert_resource(ActiveVessel {
+            entity: vessel,
+            prev_tick_position: RootSpacePosition(DVec2::new(1e6 + 7e6, 1e6)),
+            prev_tick_velocity: RootSpaceLinearVelocity(DVec2::new(100.0, 7500.0)),
+            prev_tick_parent: body,
+        });
+
+        let camera = app
+            .world_mut()
+            .spawn((
+                Camera::default(),
+                SimCamera,
+                CameraOrientationMode::Prograde,
+                Transform::default(),
+            ))
+            .id();
+        let rotation =
+            |app: &App| quat_to_rot(app.world().get::<Transform>(camera).unwrap().rotation);
+
+        app.update();
+        set_mode(&mut app, GameControlMode::CameraControl);
+
+        // Going straight up relative to the body, so no rotation needed
+        assert!(rotation(&app).abs() < 1e-6, "{}", rotation(&app));
+
+        let velocity = DVec2::new(100.0 - 3000.0, -3000.0);
+        app.world_mut()
+            .get_mut::<RootSpaceLinearVelocity>(vessel)
+            .unwrap()
+            .0 = velocity;
+        app.update();
+
+        // The velocity points up on screen
+        let relative = velocity - DVec2::new(100.0, 0.0);
+        let screen_up = DVec2::from_angle(rotation(&app)).rotate(DVec2::Y);
+        assert!(screen_up.angle_to(relative).abs() < 1e-6);
+
+        // Flying the vessel doesn't stop the camera from following it
+        set_mode(&mut app, GameControlMode::VesselControl);
+        let velocity = DVec2::new(100.0 + 5000.0, 1000.0);
+        app.world_mut()
+            .get_mut::<RootSpaceLinearVelocity>(vessel)
+            .unwrap()
+            .0 = velocity;
+        app.update();
+
+        let relative = velocity - DVec2::new(100.0, 0.0);
+        let screen_up = DVec2::from_angle(rotation(&app)).rotate(DVec2::Y);
+        assert!(screen_up.angle_to(relative).abs() < 1e-6);
+
+        // Rotating manually switches back to free rotation
+        set_mode(&mut app, GameControlMode::CameraControl);
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyQ);
+        app.update();
+        assert_eq!(
+            *app.world().get::<CameraOrientationMode>(camera).unwrap(),
+            CameraOrientationMode::Free
+        );
+    }
+
+    #[test]
+    fn vessel_input_ramps() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            50,
+        )));
+        app.insert_state(GameScene::InGame);
+        app.add_sub_state::<GameControlMode>();
+        app.add_sub_state::<AltimeterMode>();
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<ButtonInput<MouseButton>>();
+        app.init_resource::<FocusableData>();
+        app.init_resource::<InputSmoothing>();
+        app.init_resource::<OrbitLineDetail>();
+        app.add_systems(Update, input_systems());
+
+        let vessel = app.world_mut().spawn(Vessel).id();
+        app.insert_resource(ActiveVessel {
+            entity: vessel,
+            prev_tick_position: RootSpacePosition(DVec2::ZERO),
+            prev_tick_velocity: RootSpaceLinearVelocity(DVec2::ZERO),
+            prev_tick_parent: vessel,
+        });
+        let input = |app: &App| *app.world().get::<VesselInput>(vessel).unwrap();
+
+        app.update();
+        set_mode(&mut app, GameControlMode::VesselControl);
+
+        let hold = |app: &mut App, keys: &[KeyCode]| {
+            let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+            input.reset_all();
+            keys.iter().for_each(|&key| input.press(key));
+        };
+
+        // 2/s with 50ms frames takes 10 frames to go from 0 to 1
+        hold(&mut app, &[KeyCode::ShiftLeft, KeyCode::KeyA]);
+        app.update();
+        let first = input(&app);
+        assert!((first.throttle.current - 0.1).abs() < 1e-9, "{first:?}");
+        assert!((first.rotation.current - 0.1).abs() < 1e-9, "{first:?}");
+
+        (0..4).for_each(|_| app.update());
+        let halfway = input(&app);
+        assert!((halfway.throttle.current - 0.5).abs() < 1e-9, "{halfway:?}");
+
+        // Letting go keeps the throttle, but recenters rotation
+        hold(&mut app, &[]);
+        (0..20).for_each(|_| app.update());
+        let released = input(&app);
+        assert!(
+            (released.throttle.current - 0.5).abs() < 1e-9,
+            "{released:?}"
+        );
+        assert!(released.rotation.current.abs() < 1e-9, "{released:?}");
+
+        // Precision mode ramps slower and turns less hard
+        hold(&mut app, &[KeyCode::CapsLock]);
+        app.update();
+        assert!(input(&app).precision);
+
+        hold(&mut app, &[KeyCode::KeyD]);
+        app.update();
+        let precise = input(&app);
+        assert!(
+            (precise.rotation.current + 0.025).abs() < 1e-9,
+            "{precise:?}"
+        );
+
+        (0..40).for_each(|_| app.update());
+        assert!((input(&app).rotation.current + 0.25).abs() < 1e-9);
+    }
+}
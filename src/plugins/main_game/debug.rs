@@ -1,15 +1,33 @@
-use bevy::prelude::*;
-use bevy_rapier2d::render::RapierDebugRenderPlugin;
+use bevy::{
+    math::DVec2,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+use bevy_rapier2d::render::{DebugRenderContext, RapierDebugRenderPlugin};
 
 use crate::{
     components::main_game::{
+        camera::{SimCamera, SimCameraOffset, SimCameraZoom},
+        celestial::{CelestialBody, GravitationalParameter},
         frames::{RigidSpaceVelocity, RootSpaceLinearVelocity, RootSpacePosition},
-        relations::RailMode,
+        relations::{CelestialParent, RailMode},
         vessel::Vessel,
     },
-    resources::scene::GameScene,
+    consts::{
+        FilterLoadedVessels, FilterUnloadedVessels, colors::DEBUG_VELOCITY,
+        controls::KB_DEBUG_CYCLE_OVERLAY,
+    },
+    resources::{
+        debug::DebugDisplay,
+        scene::GameScene,
+        simulation::{GravityConstants, InvariantTolerance},
+    },
+    systems::main_game::{gravity::gravitational_parameter, rail::write_rail_to_sv},
 };
 
+/// How far ahead of a vessel its velocity vector reaches, in seconds.
+const VELOCITY_VECTOR_SECONDS: f64 = 1.0;
+
 pub(crate) struct GameDebugPlugin;
 
 impl Plugin for GameDebugPlugin {
@@ -19,6 +37,34 @@ impl Plugin for GameDebugPlugin {
             enabled: true,
             ..Default::default()
         });
+
+        app.init_resource::<DebugDisplay>();
+        app.add_systems(
+            Update,
+            (
+                cycle_debug_display,
+                apply_collider_display.run_if(resource_changed::<DebugDisplay>),
+            )
+                .chain(),
+        );
+        app.add_systems(
+            Update,
+            draw_velocity_vectors.run_if(in_state(GameScene::InGame)),
+        );
+
+        app.init_resource::<InvariantTolerance>();
+
+        if cfg!(debug_assertions) {
+            app.add_systems(
+                FixedPreUpdate,
+                (
+                    check_finite_state_vectors,
+                    check_orbital_invariants.map(drop),
+                )
+                    .after(write_rail_to_sv)
+                    .run_if(in_state(GameScene::InGame)),
+            );
+        }
     }
 }
 
@@ -35,6 +81,55 @@ fn switch_game_scenes(
     }
 }
 
+fn cycle_debug_display(input: Res<ButtonInput<KeyCode>>, mut display: ResMut<DebugDisplay>) {
+    if input.any_just_pressed(KB_DEBUG_CYCLE_OVERLAY) {
+        display.cycle();
+    }
+}
+
+/// Shows or hides the collider wireframes according to the [`DebugDisplay`].
+///
+/// This only turns off Rapier's debug rendering,
+/// not the physics simulation itself.
+fn apply_collider_display(display: Res<DebugDisplay>, mut context: ResMut<DebugRenderContext>) {
+    context.enabled = display.colliders;
+}
+
+/// Draws an arrow from every loaded vessel along its velocity relative
+/// to its parent, reaching where it'll be in [`VELOCITY_VECTOR_SECONDS`].
+fn draw_velocity_vectors(
+    mut gizmos: Gizmos,
+    display: Res<DebugDisplay>,
+    camera: Single<(&SimCameraOffset, &SimCameraZoom), With<SimCamera>>,
+    vessels: Query<
+        (
+            &RootSpacePosition,
+            &RootSpaceLinearVelocity,
+            &CelestialParent,
+        ),
+        FilterLoadedVessels,
+    >,
+    velocities: Query<&RootSpaceLinearVelocity>,
+    positions: Query<&RootSpacePosition>,
+) {
+    if !display.velocity_vectors {
+        return;
+    }
+
+    let (offset, zoom) = *camera;
+    let cam_pos = offset.immutably().get_root_position(positions);
+
+    for (pos, vel, parent) in &vessels {
+        let Ok(parent_vel) = velocities.get(parent.entity) else {
+            continue;
+        };
+
+        let tail = (pos.0 - cam_pos.0) * zoom.0;
+        let tip = tail + (vel.0 - parent_vel.0) * VELOCITY_VECTOR_SECONDS * zoom.0;
+        gizmos.arrow_2d(tail.as_vec2(), tip.as_vec2(), DEBUG_VELOCITY);
+    }
+}
+
 fn _print_vessel_sv(
     vessels: Query<
         (
@@ -54,3 +149,253 @@ fn _print_vessel_sv(
         );
     });
 }
+
+/// Logs an error for every loaded vessel whose state vectors aren't finite.
+///
+/// Placeholder NaNs should all be filled in by the time rails have been
+/// written to state vectors, so anything left over is a leak. Each vessel
+/// only gets reported once until its state vectors become finite again.
+fn check_finite_state_vectors(
+    vessels: Query<
+        (
+            Entity,
+            NameOrEntity,
+            &RootSpacePosition,
+            &RootSpaceLinearVelocity,
+        ),
+        FilterLoadedVessels,
+    >,
+    mut reported: Local<HashSet<Entity>>,
+) {
+    for (entity, name, pos, vel) in &vessels {
+        if pos.is_finite() && vel.is_finite() {
+            reported.remove(&entity);
+            continue;
+        }
+
+        if reported.insert(entity) {
+            error!("{name} has non-finite state vectors: {pos} | {vel}");
+        }
+    }
+
+    reported.retain(|&entity| vessels.contains(entity));
+}
+
+/// Quantities that stay exactly the same along a Keplerian orbit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct OrbitalInvariants {
+    /// The specific orbital energy, v²/2 − μ/r, in J/kg.
+    energy: f64,
+    /// The specific angular momentum, r × v, in m²/s.
+    angular_momentum: f64,
+}
+
+impl OrbitalInvariants {
+    fn new(rel_pos: DVec2, rel_vel: DVec2, mu: f64) -> Self {
+        Self {
+            energy: rel_vel.length_squared() / 2.0 - mu / rel_pos.length(),
+            angular_momentum: rel_pos.perp_dot(rel_vel),
+        }
+    }
+
+    /// Gets how far these have drifted from `baseline`, as the larger
+    /// of the two relative differences.
+    fn drift(self, baseline: Self) -> f64 {
+        let relative = |value: f64, baseline: f64| {
+            (value - baseline).abs() / baseline.abs().max(f64::MIN_POSITIVE)
+        };
+
+        relative(self.energy, baseline.energy)
+            .max(relative(self.angular_momentum, baseline.angular_momentum))
+    }
+}
+
+/// The invariants of a vessel when it got put on its current rail.
+struct InvariantBaseline {
+    rail_mode: RailMode,
+    invariants: OrbitalInvariants,
+    /// Whether a warning has already been logged for this rail.
+    reported: bool,
+}
+
+/// Logs a warning for every on-rails vessel whose specific orbital energy
+/// or angular momentum drifts past [`InvariantTolerance`] from what it was
+/// when the vessel got put on its current orbit.
+///
+/// Both are exactly conserved along an orbit, so any drift means the state
+/// vectors written from the rail don't match it. Each vessel only gets
+/// reported once until it gets put on a different rail.
+///
+/// # Output
+/// The worst drift of any vessel this tick, for tests to check.
+fn check_orbital_invariants(
+    vessels: Query<
+        (
+            Entity,
+            NameOrEntity,
+            &RootSpacePosition,
+            &RootSpaceLinearVelocity,
+            &CelestialParent,
+            &RailMode,
+        ),
+        FilterUnloadedVessels,
+    >,
+    parents: Query<(
+        &RootSpacePosition,
+        &RootSpaceLinearVelocity,
+        &CelestialBody,
+        Option<&GravitationalParameter>,
+    )>,
+    tolerance: Res<InvariantTolerance>,
+    constants: Res<GravityConstants>,
+    mut baselines: Local<HashMap<Entity, InvariantBaseline>>,
+) -> f64 {
+    let mut worst_drift: f64 = 0.0;
+
+    for (entity, name, pos, vel, parent, &rail_mode) in &vessels {
+        if !rail_mode.is_orbit() {
+            baselines.remove(&entity);
+            continue;
+        }
+
+        let Ok((parent_pos, parent_vel, body, mu)) = parents.get(parent.entity) else {
+            continue;
+        };
+
+        let sv = pos.relative_to(*vel, *parent_pos, *parent_vel);
+        let mu = gravitational_parameter(body, mu, &constants);
+        let invariants = OrbitalInvariants::new(sv.position, sv.velocity, mu);
+
+        let baseline = baselines.entry(entity).or_insert(InvariantBaseline {
+            rail_mode,
+            invariants,
+            reported: false,
+        });
+
+        if baseline.rail_mode != rail_mode {
+            *baseline = InvariantBaseline {
+                rail_mode,
+                invariants,
+                reported: false,
+            };
+            continue;
+        }
+
+        let drift = invariants.drift(baseline.invariants);
+        worst_drift = worst_drift.max(drift);
+
+        if drift > tolerance.0 && !baseline.reported {
+            baseline.reported = true;
+            warn!(
+                "{name}'s orbital invariants drifted by {drift:e} on rails: \
+                {invariants:?}, expected {expected:?}",
+                expected = baseline.invariants
+            );
+        }
+    }
+
+    baselines.retain(|&entity, _| vessels.contains(entity));
+
+    worst_drift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builders::vessel::VesselBuilder,
+        plugins::main_game::logic::GameLogicPlugin,
+        resources::simulation::ActiveVessel,
+        test_util::{body_builder, vessel_builder},
+    };
+    use bevy::{state::app::StatesPlugin, time::TimeUpdateStrategy};
+    use keplerian_sim::{OrbitTrait2D, StateVectors2D};
+
+    #[derive(Default, Resource)]
+    struct WorstDrift(f64);
+
+    #[test]
+    fn railed_vessel_has_no_drift() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, GameLogicPlugin::default()));
+        app.insert_state(GameScene::InGame);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            Time::<Fixed>::default().timestep(),
+        ));
+        app.init_resource::<InvariantTolerance>();
+        app.init_resource::<WorstDrift>();
+        app.add_systems(
+            FixedPreUpdate,
+            check_orbital_invariants
+                .pipe(|In(drift): In<f64>, mut worst: ResMut<WorstDrift>| {
+                    worst.0 = worst.0.max(drift);
+                })
+                .after(write_rail_to_sv),
+        );
+
+        let body = app
+            .world_mut()
+            .spawn(body_builder("Body", 1e6, 1e22).build_without_terrain())
+            .id();
+
+        let mu = 1e22 * GravityConstants::default().gravitational_constant;
+        let orbit = StateVectors2D {
+            position: DVec2::new(3e6, 0.0),
+            velocity: DVec2::new(0.0, 1.2 * (mu / 3e6).sqrt()),
+        }
+        .to_cached_orbit(mu, 0.0);
+
+        let railed = VesselBuilder {
+            rail_mode: RailMode::Orbit(orbit),
+            ..vessel_builder(
+                "Railed",
+                body,
+                RootSpacePosition(DVec2::NAN),
+                RootSpaceLinearVelocity(DVec2::NAN),
+            )
+        }
+        .build_on_rails();
+        app.world_mut().spawn(railed);
+
+        let active_pos = DVec2::new(0.0, -5e6);
+        let active = vessel_builder(
+            "Active",
+            body,
+            RootSpacePosition(active_pos),
+            RootSpaceLinearVelocity(DVec2::ZERO),
+        )
+        .build_rigid();
+        let active = app.world_mut().spawn(active).id();
+        app.insert_resource(ActiveVessel {
+            entity: active,
+            prev_tick_position: RootSpacePosition(active_pos),
+            prev_tick_velocity: RootSpaceLinearVelocity(DVec2::ZERO),
+            prev_tick_parent: body,
+        });
+
+        (0..1000).for_each(|_| app.update());
+
+        let ticks = app.world().resource::<Time<Fixed>>().elapsed_secs_f64()
+            / Time::<Fixed>::default().timestep().as_secs_f64();
+        assert!(ticks > 900.0, "only {ticks} fixed ticks ran");
+        assert!(orbit.get_eccentricity() > 0.1);
+
+        let drift = app.world().resource::<WorstDrift>().0;
+        assert!(drift < 1e-9, "railed vessel drifted by {drift:e}");
+    }
+
+    #[test]
+    fn drift_is_relative() {
+        let baseline = OrbitalInvariants {
+            energy: -2.0,
+            angular_momentum: 10.0,
+        };
+        let drifted = OrbitalInvariants {
+            energy: -2.2,
+            angular_momentum: 10.5,
+        };
+
+        assert!((drifted.drift(baseline) - 0.1).abs() < 1e-12);
+        assert!(baseline.drift(baseline).abs() < f64::EPSILON);
+    }
+}
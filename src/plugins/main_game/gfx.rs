@@ -1,14 +1,32 @@
 use bevy::prelude::*;
 
-use crate::{resources::scene::GameScene, systems::main_game::terrain::gfx::update_terrain_gfx};
+use crate::{
+    resources::{scene::GameScene, simulation::TerrainMeshConfig},
+    systems::main_game::{
+        camera::{
+            auto_zoom_camera, clamp_detached_camera, ease_camera, focus_camera, orient_camera,
+        },
+        terrain::gfx::update_terrain_gfx,
+    },
+};
 
 pub(crate) struct GameGfxPlugin;
 
 impl Plugin for GameGfxPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<TerrainMeshConfig>();
         app.add_systems(
             Update,
-            update_terrain_gfx.run_if(in_state(GameScene::InGame)),
+            (
+                clamp_detached_camera,
+                focus_camera,
+                auto_zoom_camera,
+                orient_camera,
+                ease_camera,
+                update_terrain_gfx,
+            )
+                .chain()
+                .run_if(in_state(GameScene::InGame)),
         );
     }
 }
@@ -1,10 +1,65 @@
-use crate::plugins::main_game::physics::GamePhysicsPlugin;
+use crate::{
+    autopilot::{ascent::GravityTurn, landing::KillHorizontalVelocity},
+    components::main_game::{
+        camera::{CameraOrientationMode, SimCameraOffset, SimCameraZoom},
+        celestial::{
+            Atmosphere, CelestialBody, CelestialSpin, GravitationalParameter, RotationPeriod,
+            Terrain,
+        },
+        frames::{
+            CameraSpaceTransform, RootSpaceAngle, RootSpaceAngularVelocity,
+            RootSpaceLinearVelocity, RootSpacePosition,
+        },
+        relations::{
+            CelestialChildren, CelestialParent, ChildObjects, ParentBody, RailMode, SoiMembers,
+        },
+        vessel::{
+            CrashTolerance, Debris, DragProfile, Engine, OrbitalVelocity, ReactionWheel,
+            SurfaceVelocity, VesselInput, WheelSaturation,
+        },
+    },
+    plugins::main_game::physics::GamePhysicsPlugin,
+    resources::simulation::{PhysicsConfig, SimulationRate},
+    systems::main_game::pause::sim_running,
+};
 use bevy::prelude::*;
 use bevy_rapier2d::{prelude::*, rapier::prelude::IntegrationParameters};
 
 /// The plugin for the game's inner logic that runs every
 /// frame, including physics.
-pub struct GameLogicPlugin;
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GameLogicPlugin {
+    config: Option<PhysicsConfig>,
+    rate: Option<SimulationRate>,
+}
+
+impl GameLogicPlugin {
+    /// Creates the plugin with custom physics tuning.
+    ///
+    /// Rapier's `dt` is still taken from the [`Time<Fixed>`] timestep
+    /// at plugin-build time, so set the timestep before adding this plugin,
+    /// or use [`with_rate`][Self::with_rate].
+    #[must_use]
+    pub const fn with_config(config: PhysicsConfig) -> Self {
+        Self {
+            config: Some(config),
+            rate: None,
+        }
+    }
+
+    /// Creates the plugin with a custom fixed tick rate, in Hz.
+    ///
+    /// This sets both the [`Time<Fixed>`] timestep and Rapier's `dt`,
+    /// so that they stay in sync. The physics tuning comes from
+    /// [`PhysicsConfig::for_rate`].
+    #[must_use]
+    pub const fn with_rate(hz: f64) -> Self {
+        Self {
+            config: None,
+            rate: Some(SimulationRate(hz)),
+        }
+    }
+}
 
 pub(crate) const RAPIER_CONFIGURATION: RapierConfiguration = RapierConfiguration {
     gravity: Vec2::ZERO,
@@ -13,29 +68,203 @@ pub(crate) const RAPIER_CONFIGURATION: RapierConfiguration = RapierConfiguration
     force_update_from_transform_changes: false,
 };
 
+/// Registers the game's components for reflection,
+/// so that they can be inspected and edited at runtime.
+fn register_types(app: &mut App) {
+    app.register_type::<RootSpacePosition>()
+        .register_type::<RootSpaceLinearVelocity>()
+        .register_type::<RootSpaceAngle>()
+        .register_type::<RootSpaceAngularVelocity>()
+        .register_type::<CameraSpaceTransform>()
+        .register_type::<CelestialParent>()
+        .register_type::<CelestialChildren>()
+        .register_type::<ParentBody>()
+        .register_type::<ChildObjects>()
+        .register_type::<SoiMembers>()
+        .register_type::<RailMode>()
+        .register_type::<Terrain>()
+        .register_type::<CelestialBody>()
+        .register_type::<GravitationalParameter>()
+        .register_type::<Atmosphere>()
+        .register_type::<CelestialSpin>()
+        .register_type::<RotationPeriod>()
+        .register_type::<SimCameraOffset>()
+        .register_type::<SimCameraZoom>()
+        .register_type::<CameraOrientationMode>()
+        .register_type::<VesselInput>()
+        .register_type::<SurfaceVelocity>()
+        .register_type::<OrbitalVelocity>()
+        .register_type::<CrashTolerance>()
+        .register_type::<Debris>()
+        .register_type::<DragProfile>()
+        .register_type::<Engine>()
+        .register_type::<ReactionWheel>()
+        .register_type::<WheelSaturation>()
+        .register_type::<GravityTurn>()
+        .register_type::<KillHorizontalVelocity>();
+}
+
 impl Plugin for GameLogicPlugin {
     fn build(&self, app: &mut App) {
-        let dt = app
-            .world()
-            .resource::<Time<Fixed>>()
-            .timestep()
-            .as_secs_f32();
+        if let Some(rate) = self.rate {
+            assert!(
+                rate.0.is_finite() && rate.0 > 0.0,
+                "simulation rate must be positive, got {} Hz",
+                rate.0
+            );
+
+            match app.world_mut().get_resource_mut::<Time<Fixed>>() {
+                Some(mut time) => time.set_timestep(rate.timestep()),
+                None => {
+                    app.insert_resource(Time::<Fixed>::from_duration(rate.timestep()));
+                }
+            }
+        }
+
+        let timestep = app.world().resource::<Time<Fixed>>().timestep();
+        let rate = SimulationRate(timestep.as_secs_f64().recip());
+        let config = self.config.unwrap_or_else(|| PhysicsConfig::for_rate(rate));
+
+        let dt = timestep.as_secs_f32();
         let physics = RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(10.0)
             .in_fixed_schedule()
             .with_custom_initialization(
                 RapierContextInitialization::InitializeDefaultRapierContext {
                     integration_parameters: IntegrationParameters {
                         dt,
-                        max_ccd_substeps: 4,
-                        num_solver_iterations: 32,
-                        normalized_max_corrective_velocity: 250.0,
+                        max_ccd_substeps: config.max_ccd_substeps,
+                        num_solver_iterations: config.num_solver_iterations,
+                        normalized_max_corrective_velocity: config
+                            .normalized_max_corrective_velocity,
                         ..Default::default()
                     },
                     rapier_configuration: RAPIER_CONFIGURATION,
                 },
             );
 
+        register_types(app);
+        app.insert_resource(config);
+        app.insert_resource(rate);
         app.insert_resource(StaticTransformOptimizations::from_threshold(0.3));
-        app.add_plugins((physics, GamePhysicsPlugin));
+        app.add_plugins((physics, GamePhysicsPlugin { config }));
+        app.configure_sets(
+            FixedPostUpdate,
+            PhysicsSet::StepSimulation.run_if(sim_running),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::state::app::StatesPlugin;
+    use bevy_rapier2d::plugin::RapierContextSimulation;
+    use core::any::TypeId;
+
+    #[test]
+    fn rate_sets_both_timesteps() {
+        for hz in [30.0, 64.0, 120.0] {
+            let mut app = App::new();
+            app.add_plugins((MinimalPlugins, StatesPlugin, GameLogicPlugin::with_rate(hz)));
+            app.update();
+
+            let timestep = app.world().resource::<Time<Fixed>>().timestep();
+            assert!((timestep.as_secs_f64() - hz.recip()).abs() < 1e-9);
+
+            let rate = app.world().resource::<SimulationRate>();
+            assert!((rate.0 - hz).abs() < 1e-6);
+
+            let dt = app
+                .world_mut()
+                .query::<&RapierContextSimulation>()
+                .single(app.world())
+                .expect("there should be a single Rapier context")
+                .integration_parameters
+                .dt;
+            assert!((f64::from(dt) - timestep.as_secs_f64()).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn rate_scales_physics_config() {
+        let config_at = |hz: f64| {
+            let mut app = App::new();
+            app.add_plugins((MinimalPlugins, StatesPlugin, GameLogicPlugin::with_rate(hz)));
+            app.update();
+
+            let config = *app.world().resource::<PhysicsConfig>();
+            let params = app
+                .world_mut()
+                .query::<&RapierContextSimulation>()
+                .single(app.world())
+                .expect("there should be a single Rapier context")
+                .integration_parameters;
+            assert_eq!(params.num_solver_iterations, config.num_solver_iterations);
+            assert_eq!(params.max_ccd_substeps, config.max_ccd_substeps);
+
+            config
+        };
+
+        assert_eq!(config_at(SimulationRate::DEFAULT.0), PhysicsConfig::DEFAULT);
+
+        // Twice the rate makes for half as long steps to solve
+        let fast = config_at(128.0);
+        assert_eq!(fast.num_solver_iterations, 16);
+        assert_eq!(fast.max_ccd_substeps, 2);
+        assert_eq!(
+            PhysicsConfig {
+                num_solver_iterations: PhysicsConfig::DEFAULT.num_solver_iterations,
+                max_ccd_substeps: PhysicsConfig::DEFAULT.max_ccd_substeps,
+                ..fast
+            },
+            PhysicsConfig::DEFAULT,
+            "only the solver tuning should change"
+        );
+
+        let slow = config_at(32.0);
+        assert_eq!(slow.num_solver_iterations, 64);
+        assert_eq!(slow.max_ccd_substeps, 8);
+
+        // Explicit configs are kept as-is
+        let mut app = App::new();
+        app.insert_resource(Time::<Fixed>::from_hz(128.0));
+        app.add_plugins((
+            MinimalPlugins,
+            StatesPlugin,
+            GameLogicPlugin::with_config(PhysicsConfig::DEFAULT),
+        ));
+        assert_eq!(
+            *app.world().resource::<PhysicsConfig>(),
+            PhysicsConfig::DEFAULT
+        );
+    }
+
+    #[test]
+    fn core_components_are_reflected() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, GameLogicPlugin::default()));
+
+        let registry = app.world().resource::<AppTypeRegistry>().read();
+        let components = [
+            TypeId::of::<RootSpacePosition>(),
+            TypeId::of::<CelestialParent>(),
+            TypeId::of::<CelestialChildren>(),
+            TypeId::of::<RailMode>(),
+            TypeId::of::<Terrain>(),
+            TypeId::of::<SimCameraOffset>(),
+            TypeId::of::<CameraOrientationMode>(),
+            TypeId::of::<VesselInput>(),
+        ];
+
+        for type_id in components {
+            let registration = registry
+                .get(type_id)
+                .expect("component should be registered");
+            assert!(
+                registration.data::<ReflectComponent>().is_some(),
+                "{} should reflect as a component",
+                registration.type_info().type_path()
+            );
+        }
     }
 }
@@ -1,41 +1,171 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, transform::TransformSystems};
 
 use crate::{
-    resources::scene::GameScene,
+    messages::{
+        crash::{VesselDestroyed, VesselImpact},
+        parts::{Stage, Undock},
+        relations::{Reparent, SoiChanged},
+        telemetry::TelemetryFrame,
+        warp::WarpTo,
+    },
+    resources::{
+        scene::GameScene,
+        simulation::{
+            ActiveVessel, DebrisLimit, FixedTickCounter, FloatingOrigin, GravityConstants,
+            PhysicsConfig, SignificantBodies, SimPaused, TelemetryEnabled, TerrainColliderConfig,
+            TimeWarp,
+        },
+    },
     systems::main_game::{
+        autopilot::{fly_gravity_turn, fly_kill_horizontal_velocity},
+        camera::enforce_min_vessel_size,
+        crash::{detect_impacts, handle_crashes, record_pre_step_velocities},
+        docking::{dock_vessels, handle_undocking},
+        drag::apply_atmospheric_drag,
+        engine::apply_engine_thrust,
         frame_sync::{
             post_rapier_frame_switch, pre_rapier_frame_switch, update_active_vessel_resource,
             write_rigid_pos_to_root, write_rigid_vel_to_root,
         },
-        gravity::apply_gravity_and_velocity,
-        rail::{write_rail_to_sv, write_sv_to_rail},
-        terrain::collider::update_terrain_colliders,
+        gravity::{
+            apply_gravity_and_velocity, update_gravitational_parameters, update_significant_bodies,
+        },
+        instruments::{update_orbital_velocity, update_rotation_period, update_surface_velocity},
+        loading::{enforce_debris_limit, update_vessel_loading},
+        origin::rebase_floating_origin,
+        parts::{
+            handle_staging, sync_part_transforms, update_part_colliders,
+            update_vessel_collision_groups,
+        },
+        pause::{apply_pause, sim_running},
+        rail::{spin_on_rails_vessels, write_rail_to_sv, write_sv_to_rail},
+        reaction_wheel::apply_reaction_wheels,
+        soi::{
+            detect_soi_entries, detect_soi_escapes, emit_soi_changes, handle_reparenting,
+            update_soi_members,
+        },
+        telemetry::emit_telemetry,
+        terrain::{
+            collider::{shift_terrain_colliders, update_terrain_colliders},
+            update_terrain_samplers,
+        },
+        ticks::{count_fixed_ticks, every_n_ticks},
+        warp::{apply_time_warp, handle_warp_to, limit_warp_rate, stop_warp_at_target},
     },
 };
 
-pub(crate) struct GamePhysicsPlugin;
+pub(crate) struct GamePhysicsPlugin {
+    pub(crate) config: PhysicsConfig,
+}
 
 impl Plugin for GamePhysicsPlugin {
     fn build(&self, app: &mut App) {
+        assert!(
+            self.config.vessel_unload_distance >= self.config.vessel_load_distance,
+            "vessels must not unload closer than they load"
+        );
+        assert!(
+            self.config.debris_unload_distance >= self.config.debris_load_distance,
+            "debris must not unload closer than it loads"
+        );
+
+        app.add_message::<SoiChanged>();
+        app.add_message::<Reparent>();
+        app.add_message::<TelemetryFrame>();
+        app.add_message::<WarpTo>();
+        app.add_message::<Stage>();
+        app.add_message::<Undock>();
+        app.add_message::<VesselImpact>();
+        app.add_message::<VesselDestroyed>();
+        app.init_resource::<TimeWarp>();
+        app.init_resource::<FixedTickCounter>();
+        app.init_resource::<GravityConstants>();
+        app.init_resource::<SignificantBodies>();
+        app.init_resource::<TerrainColliderConfig>();
+        app.init_resource::<SimPaused>();
+        app.init_resource::<DebrisLimit>();
+        app.add_systems(
+            Update,
+            (
+                handle_warp_to,
+                limit_warp_rate.run_if(resource_exists::<ActiveVessel>),
+                apply_time_warp,
+                apply_pause,
+            )
+                .chain()
+                .run_if(in_state(GameScene::InGame)),
+        );
         app.add_systems(
             FixedPreUpdate,
             (
-                write_rail_to_sv,
+                rebase_floating_origin.run_if(resource_exists::<FloatingOrigin>),
+                stop_warp_at_target,
+                handle_staging,
+                handle_undocking,
+                dock_vessels,
+                (update_gravitational_parameters, update_terrain_samplers),
+                enforce_debris_limit,
+                update_vessel_loading,
+                (detect_soi_entries, detect_soi_escapes),
+                handle_reparenting,
+                (write_rail_to_sv, spin_on_rails_vessels),
+                (apply_atmospheric_drag, update_significant_bodies),
                 apply_gravity_and_velocity,
                 update_active_vessel_resource,
-                (pre_rapier_frame_switch, update_terrain_colliders),
+                (fly_gravity_turn, fly_kill_horizontal_velocity)
+                    .run_if(resource_exists::<ActiveVessel>),
+                (apply_reaction_wheels, apply_engine_thrust),
+                (
+                    pre_rapier_frame_switch,
+                    update_part_colliders,
+                    update_vessel_collision_groups,
+                    update_terrain_colliders
+                        .run_if(every_n_ticks(self.config.terrain_collider_interval)),
+                ),
+                shift_terrain_colliders,
+                record_pre_step_velocities,
             )
                 .chain()
-                .run_if(in_state(GameScene::InGame)),
+                .run_if(in_state(GameScene::InGame))
+                .run_if(sim_running),
         );
         app.add_systems(
             FixedPostUpdate,
             (
                 (write_rigid_vel_to_root, write_rigid_pos_to_root),
+                detect_impacts,
+                handle_crashes,
                 (post_rapier_frame_switch, write_sv_to_rail),
+                enforce_min_vessel_size,
+                sync_part_transforms,
+                (
+                    emit_soi_changes,
+                    update_soi_members,
+                    update_orbital_velocity,
+                    update_surface_velocity,
+                    update_rotation_period,
+                ),
+                emit_telemetry.run_if(
+                    resource_exists::<TelemetryEnabled>.and(resource_exists::<ActiveVessel>),
+                ),
             )
                 .chain()
-                .run_if(in_state(GameScene::InGame)),
+                .run_if(in_state(GameScene::InGame))
+                .run_if(sim_running),
+        );
+        app.add_systems(FixedLast, count_fixed_ticks.run_if(sim_running));
+        // No fixed ticks run while paused, but the camera can still move
+        app.add_systems(
+            PostUpdate,
+            (
+                post_rapier_frame_switch,
+                enforce_min_vessel_size,
+                sync_part_transforms,
+            )
+                .chain()
+                .before(TransformSystems::Propagate)
+                .run_if(in_state(GameScene::InGame))
+                .run_if(not(sim_running)),
         );
     }
 }
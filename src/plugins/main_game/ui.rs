@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 
 use crate::{
-    resources::{scene::GameScene, ui::AltimeterMode},
+    resources::{scene::GameScene, simulation::ActiveVessel, ui::AltimeterMode},
     systems::main_game::ui::{
         altimeter::{
             self, apply_altimeter_format, calculate_altitude_format, init_altimeter,
@@ -25,9 +25,13 @@ impl Plugin for GameUiPlugin {
         app.add_systems(
             Update,
             (
-                calculate_oribar_state.pipe(apply_oribar_state),
-                calculate_altitude_format.pipe(apply_altimeter_format),
-                calculate_speedometer_format.pipe(apply_speedometer_format),
+                // There's no active vessel once the last one gets destroyed
+                (
+                    calculate_oribar_state.pipe(apply_oribar_state),
+                    calculate_altitude_format.pipe(apply_altimeter_format),
+                    calculate_speedometer_format.pipe(apply_speedometer_format),
+                )
+                    .run_if(resource_exists::<ActiveVessel>),
                 update_altimeter_ref_disp.run_if(state_changed::<AltimeterMode>),
                 oribar::handle_resize,
                 altimeter::handle_resize,
@@ -36,3 +40,74 @@ impl Plugin for GameUiPlugin {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::main_game::{
+            frames::{RootSpaceLinearVelocity, RootSpacePosition},
+            vessel::CrashTolerance,
+        },
+        messages::crash::VesselImpact,
+        plugins::main_game::logic::GameLogicPlugin,
+        test_util::{body_builder, vessel_builder},
+    };
+    use bevy::{
+        math::DVec2, state::app::StatesPlugin, time::TimeUpdateStrategy, window::WindowResized,
+    };
+
+    #[test]
+    fn ui_survives_last_vessel_crashing() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            StatesPlugin,
+            GameLogicPlugin::default(),
+        ));
+        app.init_asset::<Font>();
+        app.add_message::<WindowResized>();
+        app.add_plugins(GameUiPlugin);
+        app.insert_state(GameScene::InGame);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            Time::<Fixed>::default().timestep(),
+        ));
+
+        let body = app
+            .world_mut()
+            .spawn(body_builder("Body", 1e6, 1e22).build_without_terrain())
+            .id();
+
+        let position = RootSpacePosition(DVec2::new(0.0, 2e6));
+        let velocity = RootSpaceLinearVelocity(DVec2::ZERO);
+        let vessel = app
+            .world_mut()
+            .spawn((
+                vessel_builder("Last vessel", body, position, velocity).build_rigid(),
+                CrashTolerance {
+                    max_impact_speed: 10.0,
+                },
+            ))
+            .id();
+        app.insert_resource(ActiveVessel {
+            entity: vessel,
+            prev_tick_parent: body,
+            prev_tick_position: position,
+            prev_tick_velocity: velocity,
+        });
+
+        app.update();
+
+        app.world_mut().write_message(VesselImpact {
+            vessel,
+            speed: 100.0,
+        });
+        for _ in 0..4 {
+            app.update();
+        }
+
+        assert!(app.world().get_entity(vessel).is_err());
+        assert!(!app.world().contains_resource::<ActiveVessel>());
+    }
+}
@@ -66,7 +66,7 @@ impl Plugin for GameSetupPlugin {
             ),
             I18nPlugin,
             MyUiPlugin,
-            GameLogicPlugin,
+            GameLogicPlugin::default(),
             GameTransitionPlugin,
             GameDebugPlugin,
             GameGfxPlugin,
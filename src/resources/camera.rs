@@ -0,0 +1,58 @@
+use bevy::{math::DVec2, prelude::*};
+
+/// Keeps the simulation camera near the active vessel while it's detached.
+///
+/// This is opt-in; without this resource, a detached camera
+/// can wander arbitrarily far away.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct CameraBounds {
+    /// How far, in meters, a detached camera may get from the active vessel.
+    ///
+    /// Pushing the camera past this keeps it on the boundary, still
+    /// moving along it, rather than stopping it outright.
+    pub max_distance_from_active: f64,
+}
+
+impl CameraBounds {
+    /// Gets the closest position to `pos` that's within bounds,
+    /// given the active vessel's position.
+    #[must_use]
+    pub fn clamp(self, pos: DVec2, active_pos: DVec2) -> DVec2 {
+        let offset = pos - active_pos;
+
+        if offset.length() <= self.max_distance_from_active {
+            return pos;
+        }
+
+        active_pos + offset.normalize_or_zero() * self.max_distance_from_active
+    }
+}
+
+/// Keeps vessels visible when zoomed far out, by drawing them
+/// at a minimum size on screen.
+///
+/// This is opt-in; without this resource, vessels shrink with the
+/// zoom like everything else, and vanish once they're sub-pixel.
+/// Celestial bodies always scale with the zoom.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct MinVesselScreenSize {
+    /// The smallest a vessel gets drawn, across its larger side,
+    /// in logical pixels.
+    pub pixels: f32,
+}
+
+impl MinVesselScreenSize {
+    /// Gets the scale to draw an object with the given radius, in meters,
+    /// at. `zoomed_scale` is the scale the camera's zoom alone
+    /// would give it.
+    #[must_use]
+    pub fn scale(self, radius: f32, zoomed_scale: f32) -> f32 {
+        let min_scale = self.pixels / (2.0 * radius);
+
+        if radius > 0.0 && zoomed_scale < min_scale {
+            min_scale
+        } else {
+            zoomed_scale
+        }
+    }
+}
@@ -3,7 +3,7 @@ use core::{fmt::Display, ops::Range};
 use bevy::{platform::collections::HashMap, prelude::*};
 use derive_more::with_trait::IsVariant;
 
-use crate::{fl, resources::scene::GameScene};
+use crate::{components::main_game::map::ORBIT_MESH_POINTS, fl, resources::scene::GameScene};
 
 /// An enum determining how to interpret inputs, akin to Vim's different modes.
 ///
@@ -35,6 +35,75 @@ impl Display for GameControlMode {
     }
 }
 
+/// How quickly vessel inputs ease towards what the player wants.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct InputSmoothing {
+    /// How much each input can change by per second.
+    pub rate: f64,
+    /// What to scale [`rate`][Self::rate] and the maximum
+    /// rotation input by while precision mode is on.
+    pub precision_factor: f64,
+}
+
+impl Default for InputSmoothing {
+    fn default() -> Self {
+        Self {
+            rate: 2.0,
+            precision_factor: 0.25,
+        }
+    }
+}
+
+/// Whether the camera shows the vessel up close or the orbital map.
+///
+/// Only affects in-game.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, SubStates, IsVariant)]
+#[source(GameScene = GameScene::InGame)]
+pub(crate) enum ViewMode {
+    /// The camera follows the active vessel like usual.
+    #[default]
+    Flight,
+    /// The camera frames the active vessel's orbit around its parent,
+    /// with orbits and spheres of influence drawn on top.
+    Map,
+}
+
+/// How detailed orbit lines in the map view are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Resource)]
+pub struct OrbitLineDetail {
+    /// How many points get sampled along each orbit.
+    ///
+    /// Zoomed out, only some of them get drawn.
+    pub points: u32,
+}
+
+impl Default for OrbitLineDetail {
+    fn default() -> Self {
+        Self {
+            points: ORBIT_MESH_POINTS,
+        }
+    }
+}
+
+/// Which off-screen entities get an arrow at the edge of the
+/// flight view pointing towards them.
+#[derive(Clone, Debug, PartialEq, Eq, Resource)]
+pub struct EdgeIndicators {
+    /// Whether to point towards the active vessel's parent body.
+    pub parent_body: bool,
+    /// Other entities to point towards, e.g. a rendezvous target.
+    pub entities: Vec<Entity>,
+}
+
+impl Default for EdgeIndicators {
+    fn default() -> Self {
+        Self {
+            parent_body: true,
+            entities: Vec::new(),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub(crate) struct FocusableEntry {
     pub(crate) entity: Entity,
@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+
+/// Which debug overlays get drawn.
+///
+/// Each overlay can be turned on and off on its own, or all of them can
+/// be cycled through a few presets with [`cycle`][Self::cycle].
+/// Hiding the collider wireframes only stops them from being drawn,
+/// the colliders themselves keep working as usual.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Resource)]
+pub struct DebugDisplay {
+    /// Whether to draw the wireframes of every collider.
+    pub colliders: bool,
+    /// Whether to draw orbit lines in the map view.
+    pub orbit_lines: bool,
+    /// Whether to draw the spheres of influence of orbiting
+    /// bodies in the map view.
+    pub soi_circles: bool,
+    /// Whether to draw an arrow along the velocity of every loaded
+    /// vessel, relative to its parent.
+    pub velocity_vectors: bool,
+}
+
+impl DebugDisplay {
+    /// The presets [`cycle`][Self::cycle] goes through, in order.
+    pub const PRESETS: [Self; 4] = [
+        Self::DEFAULT,
+        // Once terrain meshes render, the colliders mostly get in the way
+        Self {
+            colliders: false,
+            ..Self::DEFAULT
+        },
+        Self {
+            colliders: true,
+            orbit_lines: true,
+            soi_circles: true,
+            velocity_vectors: true,
+        },
+        Self {
+            colliders: false,
+            orbit_lines: false,
+            soi_circles: false,
+            velocity_vectors: false,
+        },
+    ];
+
+    const DEFAULT: Self = Self {
+        colliders: true,
+        orbit_lines: true,
+        soi_circles: true,
+        velocity_vectors: false,
+    };
+
+    /// Switches to the next one of the [`PRESETS`][Self::PRESETS].
+    ///
+    /// Overlays that have been toggled by hand start over
+    /// from the first preset.
+    pub fn cycle(&mut self) {
+        let next = Self::PRESETS
+            .iter()
+            .position(|preset| preset == self)
+            .map_or(0, |index| (index + 1) % Self::PRESETS.len());
+
+        *self = Self::PRESETS[next];
+    }
+}
+
+impl Default for DebugDisplay {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_through_presets() {
+        let mut display = DebugDisplay::default();
+        assert_eq!(display, DebugDisplay::PRESETS[0]);
+
+        for preset in DebugDisplay::PRESETS.iter().skip(1) {
+            display.cycle();
+            assert_eq!(display, *preset);
+        }
+
+        display.cycle();
+        assert_eq!(display, DebugDisplay::default());
+
+        let mut custom = DebugDisplay {
+            orbit_lines: false,
+            ..DebugDisplay::default()
+        };
+        custom.cycle();
+        assert_eq!(custom, DebugDisplay::PRESETS[0]);
+    }
+}
@@ -1,4 +1,6 @@
+pub mod camera;
 pub(crate) mod controls;
+pub mod debug;
 pub mod scene;
 pub mod simulation;
 pub(crate) mod ui;
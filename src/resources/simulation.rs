@@ -1,5 +1,10 @@
-use crate::components::main_game::frames::{RootSpaceLinearVelocity, RootSpacePosition};
-use bevy::prelude::*;
+use crate::{
+    components::main_game::frames::{RootSpaceLinearVelocity, RootSpacePosition},
+    consts::{GRAVITATIONAL_CONSTANT, WARP_RATES},
+};
+use bevy::{math::DVec2, prelude::*};
+use bevy_rapier2d::prelude::VHACDParameters;
+use core::time::Duration;
 
 #[derive(Resource)]
 pub struct ActiveVessel {
@@ -8,3 +13,414 @@ pub struct ActiveVessel {
     pub prev_tick_velocity: RootSpaceLinearVelocity,
     pub prev_tick_parent: Entity,
 }
+
+/// Enables sending a
+/// [`TelemetryFrame`][crate::messages::telemetry::TelemetryFrame]
+/// every fixed tick while it exists.
+#[derive(Clone, Copy, Debug, Default, Resource)]
+pub struct TelemetryEnabled;
+
+/// The amount of fixed ticks that have fully run so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource)]
+pub struct FixedTickCounter(pub u64);
+
+/// Whether the simulation is paused.
+///
+/// While paused, no physics, gravity or rails get updated, and
+/// simulation time stops passing, but the camera and rendering
+/// keep working.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource)]
+pub struct SimPaused(pub bool);
+
+/// Keeps root-space positions small by moving the origin of root space
+/// to the active vessel whenever it strays too far.
+///
+/// Far from the origin, positions lose precision, which gets noticeable
+/// at interplanetary distances. Every position in root space gets moved
+/// along with the origin, so nothing relative to anything else changes.
+///
+/// This is opt-in; without this resource, the origin stays put.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct FloatingOrigin {
+    /// How far, in meters, the active vessel may get from the origin
+    /// before the origin gets moved to it.
+    pub threshold: f64,
+    /// Where the current origin is, relative to the original one.
+    ///
+    /// Adding this to a root-space position gets it relative to the
+    /// original origin instead.
+    pub offset: DVec2,
+}
+
+impl FloatingOrigin {
+    /// Starts off at the original origin.
+    #[must_use]
+    pub const fn new(threshold: f64) -> Self {
+        Self {
+            threshold,
+            offset: DVec2::ZERO,
+        }
+    }
+}
+
+/// How many fixed ticks, and so physics steps, run per second
+/// of simulation time.
+///
+/// Higher rates make collisions more accurate, at a linear cost
+/// in performance. Rapier reads the rate once when
+/// [`GameLogicPlugin`][crate::plugins::main_game::logic::GameLogicPlugin]
+/// gets built, so use
+/// [`GameLogicPlugin::with_rate`][crate::plugins::main_game::logic::GameLogicPlugin::with_rate]
+/// to change it instead of changing this resource.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct SimulationRate(pub f64);
+
+impl SimulationRate {
+    /// Bevy's default fixed timestep of 64 Hz.
+    pub const DEFAULT: Self = Self(64.0);
+
+    /// Gets the time between fixed ticks.
+    #[must_use]
+    pub fn timestep(self) -> Duration {
+        Duration::from_secs_f64(self.0.recip())
+    }
+}
+
+impl Default for SimulationRate {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The celestial bodies pulling hard enough on the active vessel
+/// to matter, as of the current fixed tick.
+///
+/// See [`PhysicsConfig::significant_gravity_threshold`].
+#[derive(Clone, Debug, Default, PartialEq, Resource)]
+pub struct SignificantBodies(pub Vec<Entity>);
+
+/// How far the specific orbital energy and angular momentum of
+/// on-rails vessels may drift, relative to when they were put on
+/// their current rail, before a warning gets logged.
+///
+/// Only used by the debug check in debug builds.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct InvariantTolerance(pub f64);
+
+impl Default for InvariantTolerance {
+    fn default() -> Self {
+        Self(1e-6)
+    }
+}
+
+/// The physical constants used for gravity.
+///
+/// These default to their real-world values. Tests may
+/// override them to work with cleaner numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct GravityConstants {
+    /// The gravitational constant, in m^3 kg^-1 s^-2.
+    pub gravitational_constant: f64,
+}
+
+impl Default for GravityConstants {
+    fn default() -> Self {
+        Self {
+            gravitational_constant: GRAVITATIONAL_CONSTANT,
+        }
+    }
+}
+
+/// Tuning knobs for the convex decomposition of terrain colliders.
+///
+/// Terrain colliders get split into convex pieces using V-HACD.
+/// Coarser settings make that faster and produce fewer pieces,
+/// at the cost of the collider following the terrain less closely.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct TerrainColliderConfig {
+    /// The maximum concavity allowed in each convex piece.
+    ///
+    /// Higher values allow more concavity to get filled in,
+    /// which gives fewer pieces.
+    pub concavity: f32,
+    /// The bias towards splitting along symmetry planes.
+    pub alpha: f32,
+    /// The resolution of the voxel grid the terrain gets rasterized onto.
+    ///
+    /// Lower values are faster but lose smaller terrain details.
+    pub resolution: u32,
+    /// The fewest outline points a terrain collider gets built out of.
+    ///
+    /// With fewer points than this, bodies fall back to a ball of their
+    /// base radius, which the terrain never goes below. Anything under 3
+    /// gets treated as 3, as that's the least a closed outline needs.
+    pub min_vertices: usize,
+}
+
+impl TerrainColliderConfig {
+    /// Gets the V-HACD parameters for these settings.
+    #[must_use]
+    pub fn vhacd_parameters(&self) -> VHACDParameters {
+        VHACDParameters {
+            concavity: self.concavity,
+            alpha: self.alpha,
+            resolution: self.resolution,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for TerrainColliderConfig {
+    fn default() -> Self {
+        Self {
+            concavity: 0.015,
+            alpha: 0.0,
+            resolution: VHACDParameters::default().resolution,
+            min_vertices: 3,
+        }
+    }
+}
+
+/// Tuning knobs for the terrain meshes.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct TerrainMeshConfig {
+    /// How many vertices at each end of a LoD level get blended into
+    /// the coarser level around it.
+    ///
+    /// The finer level's vertices get pulled onto the coarser level's
+    /// outline where the two meet, easing back to their own shape
+    /// over this many vertices. Zero turns blending off.
+    pub stitch_blend: u32,
+    /// How many frames pass between terrain mesh rebuilds.
+    ///
+    /// Like [`PhysicsConfig::terrain_collider_interval`], raising this
+    /// trades accuracy for performance, with the last mesh being kept
+    /// in between rebuilds. Values of 0 are treated as 1.
+    pub rebuild_interval: u32,
+}
+
+impl Default for TerrainMeshConfig {
+    fn default() -> Self {
+        Self {
+            stitch_blend: 8,
+            rebuild_interval: 1,
+        }
+    }
+}
+
+/// Tuning knobs for the physics engine.
+///
+/// Rapier reads these once when
+/// [`GameLogicPlugin`][crate::plugins::main_game::logic::GameLogicPlugin]
+/// gets built, so changing this resource afterwards has no effect.
+/// Use [`GameLogicPlugin::with_config`][crate::plugins::main_game::logic::GameLogicPlugin::with_config]
+/// to change them instead.
+///
+/// Rapier steps once per fixed tick, with its `dt` taken from the
+/// [`Time<Fixed>`] timestep at plugin-build time (see [`SimulationRate`]). Lowering the timestep
+/// makes every step cheaper to solve accurately, so fewer iterations and
+/// substeps are needed for the same stability, and vice versa.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct PhysicsConfig {
+    /// The amount of solver iterations per physics step.
+    ///
+    /// Higher values make contacts (e.g. landed vessels) stiffer
+    /// and more stable, at a linear cost in performance.
+    pub num_solver_iterations: usize,
+    /// The maximum amount of continuous collision detection
+    /// substeps per physics step.
+    ///
+    /// Higher values stop fast vessels from tunneling
+    /// through terrain more reliably.
+    pub max_ccd_substeps: usize,
+    /// The maximum velocity, relative to the contact size, that
+    /// the solver may use to push penetrating bodies apart.
+    pub normalized_max_corrective_velocity: f32,
+    /// How many fixed ticks pass between terrain collider rebuilds.
+    ///
+    /// Terrain colliders are expensive to generate, so raising this
+    /// trades collider accuracy for performance. In between rebuilds,
+    /// the last collider gets reused. Values of 0 are treated as 1.
+    pub terrain_collider_interval: u64,
+    /// The extra angle, in radians, that terrain colliders get padded
+    /// by on both sides of each nearby vessel.
+    ///
+    /// On top of this, the padding grows with the vessel's tangential
+    /// speed, so that the collider still reaches ahead of the vessel
+    /// by the time it gets rebuilt.
+    pub collider_margin_angle: f64,
+    /// How close, in meters, an unloaded vessel needs to get to the
+    /// active vessel to get loaded, i.e. taken off-rails.
+    pub vessel_load_distance: f64,
+    /// How far, in meters, a loaded vessel needs to get from the
+    /// active vessel to get unloaded, i.e. put on-rails.
+    ///
+    /// This should be larger than [`vessel_load_distance`][Self::vessel_load_distance],
+    /// so that vessels near the boundary don't get loaded and unloaded
+    /// every tick.
+    pub vessel_unload_distance: f64,
+    /// Like [`vessel_load_distance`][Self::vessel_load_distance],
+    /// but for [`Debris`], which is kept loaded in a smaller radius.
+    ///
+    /// [`Debris`]: crate::components::main_game::vessel::Debris
+    pub debris_load_distance: f64,
+    /// Like [`vessel_unload_distance`][Self::vessel_unload_distance],
+    /// but for [`Debris`].
+    ///
+    /// [`Debris`]: crate::components::main_game::vessel::Debris
+    pub debris_unload_distance: f64,
+    /// The gravitational acceleration, in m/s², that a celestial body
+    /// needs to exert at the active vessel's position for loaded vessels
+    /// to get pulled by it on top of their parent body.
+    ///
+    /// Lower values make loaded vessels follow more accurate trajectories,
+    /// but each extra body costs one more acceleration per loaded vessel
+    /// per tick. Loaded vessels may drift away from the orbits they'd
+    /// follow on rails, which only consider the parent body.
+    ///
+    /// Defaults to infinity, which leaves only the parent body.
+    pub significant_gravity_threshold: f64,
+    /// Whether loaded vessels pull on each other, on top of
+    /// getting pulled by celestial bodies.
+    ///
+    /// Defaults to [`None`], in which case vessels have no gravity.
+    pub vessel_self_gravity: Option<VesselSelfGravity>,
+    /// Whether loaded vessels collide with each other, on top of
+    /// colliding with celestial bodies.
+    ///
+    /// Defaults to `false`, so that vessels pass through each other,
+    /// e.g. while staging. Docking works either way.
+    pub vessel_collisions: bool,
+}
+
+/// Settings for loaded vessels pulling on each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VesselSelfGravity {
+    /// The most loaded vessels to sum the pulls between.
+    ///
+    /// Every pair of vessels gets considered, so the cost grows with
+    /// the square of this. Past this many loaded vessels, none of them
+    /// pull on each other.
+    pub max_vessels: usize,
+}
+
+impl PhysicsConfig {
+    pub const DEFAULT: Self = Self {
+        num_solver_iterations: 32,
+        max_ccd_substeps: 4,
+        normalized_max_corrective_velocity: 250.0,
+        terrain_collider_interval: 1,
+        collider_margin_angle: 0.0,
+        vessel_load_distance: 2250.0,
+        vessel_unload_distance: 2500.0,
+        debris_load_distance: 500.0,
+        debris_unload_distance: 750.0,
+        significant_gravity_threshold: f64::INFINITY,
+        vessel_self_gravity: None,
+        vessel_collisions: false,
+    };
+
+    /// Gets the default tuning for the given simulation rate.
+    ///
+    /// [`DEFAULT`][Self::DEFAULT] is tuned for [`SimulationRate::DEFAULT`].
+    /// The solver iterations and CCD substeps get scaled by how much longer
+    /// each step is than at that rate, so that stability stays about the same.
+    #[must_use]
+    pub fn for_rate(rate: SimulationRate) -> Self {
+        let scale = SimulationRate::DEFAULT.0 / rate.0;
+        #[expect(
+            clippy::cast_possible_truncation,
+            clippy::cast_precision_loss,
+            clippy::cast_sign_loss
+        )]
+        let scaled = |value: usize| ((value as f64 * scale).ceil() as usize).max(1);
+
+        Self {
+            num_solver_iterations: scaled(Self::DEFAULT.num_solver_iterations),
+            max_ccd_substeps: scaled(Self::DEFAULT.max_ccd_substeps),
+            ..Self::DEFAULT
+        }
+    }
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The most [`Debris`] that may exist at once.
+///
+/// Past this, the oldest debris gets despawned to make room,
+/// unless it's the active vessel.
+///
+/// [`Debris`]: crate::components::main_game::vessel::Debris
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Resource)]
+pub struct DebrisLimit {
+    pub max_count: usize,
+}
+
+impl Default for DebrisLimit {
+    fn default() -> Self {
+        Self { max_count: 64 }
+    }
+}
+
+/// The simulation's time warp state.
+///
+/// This gets applied onto [`Time<Virtual>`], which in turn makes
+/// more fixed ticks run every frame.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct TimeWarp {
+    /// How many times faster than real time the simulation should run.
+    pub rate: f64,
+    /// The simulation time, in seconds, at which warping should stop.
+    ///
+    /// When this is set, the rate gets lowered as that time approaches
+    /// to avoid overshooting it.
+    pub until: Option<f64>,
+}
+
+impl Default for TimeWarp {
+    fn default() -> Self {
+        Self {
+            rate: 1.0,
+            until: None,
+        }
+    }
+}
+
+impl TimeWarp {
+    /// Gets the fastest of the [`WARP_RATES`] that isn't faster than `rate`.
+    #[must_use]
+    pub fn step_at_most(rate: f64) -> f64 {
+        WARP_RATES
+            .into_iter()
+            .rfind(|&step| step <= rate)
+            .unwrap_or(WARP_RATES[0])
+    }
+
+    /// Steps up to the next of the [`WARP_RATES`], stopping
+    /// any warp towards a target time.
+    pub fn step_up(&mut self) {
+        *self = Self {
+            rate: WARP_RATES
+                .into_iter()
+                .find(|&step| step > self.rate)
+                .unwrap_or(self.rate),
+            until: None,
+        };
+    }
+
+    /// Steps down to the previous of the [`WARP_RATES`], stopping
+    /// any warp towards a target time.
+    pub fn step_down(&mut self) {
+        *self = Self {
+            rate: WARP_RATES
+                .into_iter()
+                .rfind(|&step| step < self.rate)
+                .unwrap_or(self.rate),
+            until: None,
+        };
+    }
+}
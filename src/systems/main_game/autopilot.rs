@@ -0,0 +1,509 @@
+//! Flying the active vessel while an autopilot is engaged.
+
+use bevy::{ecs::query::QueryData, math::DVec2, prelude::*};
+use bevy_rapier2d::prelude::ReadMassProperties;
+use keplerian_sim::OrbitTrait2D;
+
+use crate::{
+    autopilot::{
+        AutopilotStatus, ThrustCommand,
+        ascent::GravityTurn,
+        landing::{KillHorizontalVelocity, SurfaceState},
+    },
+    components::main_game::{
+        celestial::{CelestialBody, GravitationalParameter},
+        frames::{
+            RootSpaceAngle, RootSpaceAngularVelocity, RootSpaceLinearVelocity, RootSpacePosition,
+        },
+        relations::CelestialParent,
+        vessel::{ControlPoint, Engine, SurfaceVelocity, Vessel, VesselInput},
+    },
+    orbit::current_orbit,
+    resources::simulation::ActiveVessel,
+};
+
+/// How hard to turn, per radian between where the vessel
+/// is facing and where the autopilot wants it to face.
+const STEER_GAIN: f64 = 2.0;
+
+/// How hard to turn against the vessel's spin, per rad/s.
+const STEER_DAMPING: f64 = 1.0;
+
+/// Gets the rotation input that turns the vessel's [`ControlPoint`]
+/// towards `direction` and holds it there, given the vessel's raw
+/// angle and angular velocity.
+///
+/// A zero `direction` lets the vessel turn freely.
+fn hold_attitude(control_point: ControlPoint, angle: f64, angvel: f64, direction: DVec2) -> f64 {
+    if direction == DVec2::ZERO {
+        return 0.0;
+    }
+
+    let facing = control_point.forward(angle);
+    STEER_GAIN
+        .mul_add(facing.angle_to(direction), -STEER_DAMPING * angvel)
+        .clamp(-1.0, 1.0)
+}
+
+/// Flies a vessel according to an autopilot's command, skipping
+/// the easing that the player's inputs go through.
+fn follow_command(
+    input: &mut VesselInput,
+    control_point: Option<&ControlPoint>,
+    angle: RootSpaceAngle,
+    angvel: RootSpaceAngularVelocity,
+    command: ThrustCommand,
+) {
+    let rotation = hold_attitude(
+        control_point.copied().unwrap_or_default(),
+        angle.0,
+        angvel.0,
+        command.direction,
+    );
+
+    for (axis, value) in [
+        (&mut input.throttle, command.throttle),
+        (&mut input.rotation, rotation),
+    ] {
+        axis.target = value;
+        axis.current = value;
+    }
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub(crate) struct VesselData {
+    entity: Entity,
+    turn: &'static GravityTurn,
+    input: &'static mut VesselInput,
+    pos: &'static RootSpacePosition,
+    vel: &'static RootSpaceLinearVelocity,
+    angle: &'static RootSpaceAngle,
+    angvel: &'static RootSpaceAngularVelocity,
+    parent: &'static CelestialParent,
+    control_point: Option<&'static ControlPoint>,
+    has_engine: Has<Engine>,
+}
+
+#[derive(QueryData)]
+pub(crate) struct ParentData {
+    pos: &'static RootSpacePosition,
+    vel: &'static RootSpaceLinearVelocity,
+    body: &'static CelestialBody,
+    mu: &'static GravitationalParameter,
+}
+
+/// Steers and throttles the active vessel according to its
+/// [`GravityTurn`], removing it once it's done.
+///
+/// The autopilot drives the vessel's [`VesselInput`] directly,
+/// skipping the easing that the player's inputs go through.
+/// Vessels without an [`Engine`] could never raise their apoapsis,
+/// so their [`GravityTurn`] gets removed straight away.
+pub(crate) fn fly_gravity_turn(
+    mut commands: Commands,
+    active_vessel: Res<ActiveVessel>,
+    mut vessels: Query<VesselData>,
+    parents: Query<ParentData, Without<Vessel>>,
+) {
+    let Ok(mut vessel) = vessels.get_mut(active_vessel.entity) else {
+        return;
+    };
+    let Ok(parent) = parents.get(vessel.parent.entity) else {
+        error!("Vessel {} is missing a parent!", vessel.entity);
+        return;
+    };
+
+    if !vessel.has_engine {
+        warn!(
+            "Vessel {} has no engine to fly its gravity turn with, disengaging",
+            vessel.entity
+        );
+        commands.entity(vessel.entity).remove::<GravityTurn>();
+        return;
+    }
+
+    let rel_pos = vessel.pos.0 - parent.pos.0;
+    let radius = f64::from(parent.body.base_radius);
+    let altitude = rel_pos.length() - radius;
+    let apoapsis = current_orbit(
+        *vessel.pos,
+        *vessel.vel,
+        (*parent.pos, *parent.vel),
+        parent.mu.0,
+        0.0,
+    )
+    .filter(|orbit| orbit.get_eccentricity() < 1.0)
+    .map_or(f64::INFINITY, |orbit| orbit.get_apoapsis())
+        - radius;
+
+    let (command, status) = vessel
+        .turn
+        .step(altitude, apoapsis, rel_pos.normalize_or_zero());
+
+    if status != AutopilotStatus::Active {
+        commands.entity(vessel.entity).remove::<GravityTurn>();
+    }
+
+    follow_command(
+        &mut vessel.input,
+        vessel.control_point,
+        *vessel.angle,
+        *vessel.angvel,
+        command,
+    );
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub(crate) struct LandingVesselData {
+    entity: Entity,
+    autopilot: &'static KillHorizontalVelocity,
+    input: &'static mut VesselInput,
+    pos: &'static RootSpacePosition,
+    surface_vel: &'static SurfaceVelocity,
+    angle: &'static RootSpaceAngle,
+    angvel: &'static RootSpaceAngularVelocity,
+    mass: &'static ReadMassProperties,
+    parent: &'static CelestialParent,
+    control_point: Option<&'static ControlPoint>,
+    engine: Option<&'static Engine>,
+}
+
+/// Steers and throttles the active vessel according to its
+/// [`KillHorizontalVelocity`], removing it once it's done
+/// or has given up.
+///
+/// The horizontal velocity comes from the vessel's [`SurfaceVelocity`],
+/// so it's nulled relative to the spinning surface below.
+pub(crate) fn fly_kill_horizontal_velocity(
+    mut commands: Commands,
+    active_vessel: Res<ActiveVessel>,
+    mut vessels: Query<LandingVesselData>,
+    parents: Query<(&RootSpacePosition, &GravitationalParameter), Without<Vessel>>,
+) {
+    let Ok(mut vessel) = vessels.get_mut(active_vessel.entity) else {
+        return;
+    };
+    let Ok((parent_pos, mu)) = parents.get(vessel.parent.entity) else {
+        error!("Vessel {} is missing a parent!", vessel.entity);
+        return;
+    };
+
+    let mass = f64::from(vessel.mass.get().mass);
+    if mass <= 0.0 && vessel.engine.is_some() {
+        // Rapier hasn't worked out the mass yet
+        return;
+    }
+
+    let rel_pos = vessel.pos.0 - parent_pos.0;
+    let up = rel_pos.normalize_or_zero();
+    let surface = SurfaceState {
+        up,
+        velocity: up * vessel.surface_vel.vertical + up.perp() * vessel.surface_vel.horizontal,
+        gravity: mu.0 / rel_pos.length_squared(),
+    };
+    let max_accel = vessel
+        .engine
+        .map_or(0.0, |engine| engine.commanded_thrust(1.0) / mass);
+
+    let (command, status) = vessel.autopilot.step(
+        surface,
+        max_accel,
+        vessel.engine.copied().is_some_and(Engine::has_fuel),
+    );
+
+    if status != AutopilotStatus::Active {
+        commands
+            .entity(vessel.entity)
+            .remove::<KillHorizontalVelocity>();
+    }
+
+    follow_command(
+        &mut vessel.input,
+        vessel.control_point,
+        *vessel.angle,
+        *vessel.angvel,
+        command,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::main_game::vessel::ReactionWheel,
+        plugins::main_game::logic::GameLogicPlugin,
+        resources::{scene::GameScene, simulation::GravityConstants},
+        test_util::{body_builder, vessel_builder},
+    };
+    use bevy::{state::app::StatesPlugin, time::TimeUpdateStrategy};
+    use core::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn attitude_hold_about_control_point() {
+        // Holding +X with the control point 90° counterclockwise of the
+        // nose leaves the nose pointing 90° clockwise of +X
+        let control_point = ControlPoint {
+            forward_offset_angle: FRAC_PI_2,
+        };
+        let target = DVec2::X;
+
+        let (mut angle, mut angvel) = (0.0, 0.0);
+        for _ in 0..60 * 64 {
+            let rotation = hold_attitude(control_point, angle, angvel, target);
+            angvel += rotation / 64.0;
+            angle += angvel / 64.0;
+        }
+
+        assert!(control_point.forward(angle).distance(target) < 1e-3);
+        assert!(angvel.abs() < 1e-3);
+        assert!(
+            ControlPoint::default()
+                .forward(angle)
+                .distance(DVec2::NEG_Y)
+                < 1e-3
+        );
+
+        // Without an offset, the nose itself gets held on the target
+        let (mut angle, mut angvel) = (0.0, 0.0);
+        for _ in 0..60 * 64 {
+            let rotation = hold_attitude(ControlPoint::default(), angle, angvel, target);
+            angvel += rotation / 64.0;
+            angle += angvel / 64.0;
+        }
+        assert!(ControlPoint::default().forward(angle).distance(target) < 1e-3);
+    }
+
+    #[test]
+    fn gravity_turn_raises_apoapsis() {
+        const BODY_RADIUS: f32 = 1000.0;
+        const BODY_MASS: f64 = 1e16;
+        const TARGET_APOAPSIS: f64 = 200.0;
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, GameLogicPlugin::default()));
+        app.insert_state(GameScene::InGame);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            Time::<Fixed>::default().timestep(),
+        ));
+
+        let body = app
+            .world_mut()
+            .spawn(body_builder("Body", BODY_RADIUS, BODY_MASS).build_without_terrain())
+            .id();
+
+        let position = RootSpacePosition(DVec2::new(0.0, f64::from(BODY_RADIUS) + 10.0));
+        let velocity = RootSpaceLinearVelocity(DVec2::ZERO);
+        let vessel = app
+            .world_mut()
+            .spawn((
+                vessel_builder("Launcher", body, position, velocity).build_rigid(),
+                Engine {
+                    max_thrust: 20.0,
+                    fuel: f64::INFINITY,
+                },
+                ReactionWheel { max_torque: 10.0 },
+                GravityTurn {
+                    start_altitude: 50.0,
+                    end_altitude: 150.0,
+                    target_angle: 0.3,
+                    target_apoapsis: TARGET_APOAPSIS,
+                },
+            ))
+            .id();
+        app.insert_resource(ActiveVessel {
+            entity: vessel,
+            prev_tick_parent: body,
+            prev_tick_position: position,
+            prev_tick_velocity: velocity,
+        });
+
+        let mu = BODY_MASS * GravityConstants::default().gravitational_constant;
+        let apoapsis = |app: &App| {
+            let world = app.world();
+            let sv = |entity| {
+                (
+                    *world.get::<RootSpacePosition>(entity).unwrap(),
+                    *world.get::<RootSpaceLinearVelocity>(entity).unwrap(),
+                )
+            };
+            let (pos, vel) = sv(vessel);
+            let orbit = current_orbit(pos, vel, sv(body), mu, 0.0).unwrap();
+            assert!(orbit.get_eccentricity() < 1.0, "escaping");
+            orbit.get_apoapsis() - f64::from(BODY_RADIUS)
+        };
+
+        let start_apoapsis = apoapsis(&app);
+        assert!(start_apoapsis < TARGET_APOAPSIS);
+
+        // Should take about 4 seconds
+        for _ in 0..20 * 64 {
+            app.update();
+
+            if app.world().get::<GravityTurn>(vessel).is_none() {
+                break;
+            }
+        }
+
+        assert!(
+            app.world().get::<GravityTurn>(vessel).is_none(),
+            "autopilot should have finished, apoapsis is at {} m",
+            apoapsis(&app)
+        );
+        assert!(apoapsis(&app) >= TARGET_APOAPSIS * 0.99);
+        assert!(apoapsis(&app) > start_apoapsis);
+
+        let input = app.world().get::<VesselInput>(vessel).unwrap();
+        assert!(
+            input.throttle.current.abs() < 1e-12,
+            "engines should be cut"
+        );
+    }
+
+    #[test]
+    fn gravity_turn_needs_engine() {
+        let mut app = App::new();
+        app.add_systems(Update, fly_gravity_turn);
+
+        let body = app
+            .world_mut()
+            .spawn(body_builder("Body", 1000.0, 1e16).build_without_terrain())
+            .id();
+        let vessel = app
+            .world_mut()
+            .spawn((
+                Vessel,
+                CelestialParent { entity: body },
+                RootSpacePosition(DVec2::new(0.0, 1010.0)),
+                RootSpaceLinearVelocity(DVec2::ZERO),
+                RootSpaceAngle(0.0),
+                RootSpaceAngularVelocity(0.0),
+                GravityTurn {
+                    start_altitude: 50.0,
+                    end_altitude: 150.0,
+                    target_angle: 0.3,
+                    target_apoapsis: 200.0,
+                },
+            ))
+            .id();
+        app.insert_resource(ActiveVessel {
+            entity: vessel,
+            prev_tick_parent: body,
+            prev_tick_position: RootSpacePosition(DVec2::new(0.0, 1010.0)),
+            prev_tick_velocity: RootSpaceLinearVelocity(DVec2::ZERO),
+        });
+
+        app.update();
+
+        assert!(
+            app.world().get::<GravityTurn>(vessel).is_none(),
+            "vessels without engines shouldn't stay locked into the autopilot"
+        );
+    }
+
+    #[test]
+    fn kills_horizontal_velocity_in_flight() {
+        const BODY_RADIUS: f32 = 1000.0;
+        const BODY_MASS: f64 = 1e16;
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, GameLogicPlugin::default()));
+        app.insert_state(GameScene::InGame);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            Time::<Fixed>::default().timestep(),
+        ));
+
+        let body = app
+            .world_mut()
+            .spawn(body_builder("Body", BODY_RADIUS, BODY_MASS).build_without_terrain())
+            .id();
+
+        let position = RootSpacePosition(DVec2::new(0.0, f64::from(BODY_RADIUS) + 100.0));
+        let velocity = RootSpaceLinearVelocity(DVec2::new(5.0, 0.0));
+        let vessel = app
+            .world_mut()
+            .spawn((
+                vessel_builder("Lander", body, position, velocity).build_rigid(),
+                Engine {
+                    max_thrust: 20.0,
+                    fuel: f64::INFINITY,
+                },
+                ReactionWheel { max_torque: 10.0 },
+                KillHorizontalVelocity::default(),
+            ))
+            .id();
+        app.insert_resource(ActiveVessel {
+            entity: vessel,
+            prev_tick_parent: body,
+            prev_tick_position: position,
+            prev_tick_velocity: velocity,
+        });
+
+        for _ in 0..30 * 64 {
+            app.update();
+
+            if app.world().get::<KillHorizontalVelocity>(vessel).is_none() {
+                break;
+            }
+        }
+
+        let surface_vel = *app.world().get::<SurfaceVelocity>(vessel).unwrap();
+        assert!(
+            app.world().get::<KillHorizontalVelocity>(vessel).is_none(),
+            "autopilot should have finished, still moving at {} m/s",
+            surface_vel.horizontal
+        );
+        assert!(surface_vel.horizontal.abs() < 0.2);
+        assert!(surface_vel.vertical.abs() < 1.0);
+
+        let altitude = app
+            .world()
+            .get::<RootSpacePosition>(vessel)
+            .unwrap()
+            .0
+            .length()
+            - f64::from(BODY_RADIUS);
+        assert!(altitude > 50.0, "sank to {altitude} m");
+    }
+
+    #[test]
+    fn kill_horizontal_velocity_needs_engine() {
+        let mut app = App::new();
+        app.add_systems(Update, fly_kill_horizontal_velocity);
+
+        let body = app
+            .world_mut()
+            .spawn(body_builder("Body", 1000.0, 1e16).build_without_terrain())
+            .id();
+        let vessel = app
+            .world_mut()
+            .spawn((
+                Vessel,
+                CelestialParent { entity: body },
+                RootSpacePosition(DVec2::new(0.0, 1010.0)),
+                SurfaceVelocity {
+                    vertical: 0.0,
+                    horizontal: 5.0,
+                },
+                RootSpaceAngle(0.0),
+                RootSpaceAngularVelocity(0.0),
+                ReadMassProperties::default(),
+                KillHorizontalVelocity::default(),
+            ))
+            .id();
+        app.insert_resource(ActiveVessel {
+            entity: vessel,
+            prev_tick_parent: body,
+            prev_tick_position: RootSpacePosition(DVec2::new(0.0, 1010.0)),
+            prev_tick_velocity: RootSpaceLinearVelocity(DVec2::ZERO),
+        });
+
+        app.update();
+
+        assert!(
+            app.world().get::<KillHorizontalVelocity>(vessel).is_none(),
+            "vessels without engines shouldn't stay locked into the autopilot"
+        );
+    }
+}
@@ -0,0 +1,718 @@
+//! Automatic zooming and bounding of the simulation camera
+
+use bevy::{math::DVec2, prelude::*};
+use bevy_rapier2d::prelude::Collider;
+
+use crate::{
+    components::main_game::{
+        camera::{
+            AutoZoom, CameraEasing, CameraOrientationMode, FocusProgress, FocusTransition,
+            SimCamera, SimCameraOffset, SimCameraZoom,
+        },
+        celestial::CelestialBody,
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::CelestialParent,
+        vessel::Vessel,
+    },
+    math::{quat_to_rot, rot_to_quat, rotate_toward},
+    resources::{
+        camera::{CameraBounds, MinVesselScreenSize},
+        simulation::ActiveVessel,
+    },
+};
+
+/// The viewport size to frame the focus in when the camera
+/// doesn't render to anything, e.g. when running headless.
+pub(crate) const FALLBACK_VIEWPORT_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+
+/// How fast the camera turns to follow its [`CameraOrientationMode`],
+/// in radians per second.
+const ORIENT_TURN_RATE: f64 = core::f64::consts::TAU;
+
+/// How close the zoom's logarithm needs to get to the target's
+/// for the animation to finish.
+const AUTO_ZOOM_EPSILON: f64 = 1e-3;
+
+pub(crate) type FocusSizeQuery<'w, 's> =
+    Query<'w, 's, (Option<&'static CelestialBody>, Option<&'static Collider>)>;
+
+/// Gets the radius of the thing the camera is focused on.
+///
+/// Celestial bodies use their base radius, while anything else
+/// falls back to half of the larger side of its collider's AABB.
+pub(crate) fn focus_radius(entity: Entity, query: FocusSizeQuery) -> Option<f64> {
+    let (body, collider) = query.get(entity).ok()?;
+
+    if let Some(body) = body {
+        return Some(f64::from(body.base_radius));
+    }
+
+    let aabb = collider?.raw.compute_local_aabb();
+    let width = aabb.maxs.x - aabb.mins.x;
+    let height = aabb.maxs.y - aabb.mins.y;
+
+    Some(f64::from(width.max(height)) / 2.0)
+}
+
+/// Keeps a detached simulation camera within [`CameraBounds`]
+/// of the active vessel, if that resource exists.
+pub(crate) fn clamp_detached_camera(
+    cameras: Query<&mut SimCameraOffset, With<SimCamera>>,
+    bounds: Option<Res<CameraBounds>>,
+    active_vessel: Option<Res<ActiveVessel>>,
+    positions: Query<&RootSpacePosition>,
+) {
+    let (Some(bounds), Some(active_vessel)) = (bounds, active_vessel) else {
+        return;
+    };
+
+    let Ok(active_pos) = positions.get(active_vessel.entity) else {
+        return;
+    };
+
+    for mut offset in cameras {
+        let SimCameraOffset::Detached(pos) = *offset else {
+            continue;
+        };
+
+        let clamped = bounds.clamp(pos.0, active_pos.0);
+        if clamped != pos.0 {
+            *offset = SimCameraOffset::Detached(RootSpacePosition(clamped));
+        }
+    }
+}
+
+pub(crate) fn auto_zoom_camera(
+    cameras: Query<
+        (
+            &Camera,
+            &mut SimCameraOffset,
+            &mut SimCameraZoom,
+            &mut AutoZoom,
+        ),
+        With<SimCamera>,
+    >,
+    focus_sizes: FocusSizeQuery,
+    time: Res<Time<Real>>,
+) {
+    for (camera, mut offset, mut zoom, mut auto_zoom) in cameras {
+        let focus = match *offset {
+            SimCameraOffset::Attached { entity, .. } => Some(entity),
+            SimCameraOffset::Detached(_) => None,
+        };
+
+        if focus != auto_zoom.last_focus {
+            auto_zoom.last_focus = focus;
+
+            if let Some(radius) = focus.and_then(|entity| focus_radius(entity, focus_sizes)) {
+                let viewport = camera
+                    .logical_viewport_size()
+                    .unwrap_or(FALLBACK_VIEWPORT_SIZE);
+                auto_zoom.target = Some(SimCameraZoom::fitting(radius, viewport, auto_zoom.fill).0);
+            }
+        }
+
+        let Some(target) = auto_zoom.target else {
+            continue;
+        };
+
+        // Animate in log-space so that zooming by orders
+        // of magnitude doesn't look like a sudden jump
+        let remaining = target.ln() - zoom.0.ln();
+
+        if remaining.abs() < AUTO_ZOOM_EPSILON {
+            zoom.0 = target;
+            auto_zoom.target = None;
+
+            if auto_zoom.recenter {
+                auto_zoom.recenter = false;
+                if let SimCameraOffset::Attached { offset, .. } = offset.as_mut() {
+                    *offset = DVec2::ZERO;
+                }
+            }
+            continue;
+        }
+
+        let progress = 1.0 - (-auto_zoom.rate * time.delta_secs_f64()).exp();
+        zoom.0 = (zoom.0.ln() + remaining * progress).exp();
+
+        if auto_zoom.recenter
+            && let SimCameraOffset::Attached { offset, .. } = offset.as_mut()
+        {
+            *offset *= 1.0 - progress;
+        }
+    }
+}
+
+type StateQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static RootSpacePosition,
+        &'static RootSpaceLinearVelocity,
+        Option<&'static CelestialParent>,
+    ),
+>;
+
+/// Gets the position and velocity of the active vessel relative to its parent.
+fn active_vessel_relative_sv(
+    active_vessel: Option<&ActiveVessel>,
+    states: &StateQuery,
+) -> Option<(DVec2, DVec2)> {
+    let (pos, vel, parent) = states.get(active_vessel?.entity).ok()?;
+    let (parent_pos, parent_vel, _) = states.get(parent?.entity).ok()?;
+
+    Some((pos.0 - parent_pos.0, vel.0 - parent_vel.0))
+}
+
+/// Turns the simulation camera to follow its [`CameraOrientationMode`].
+///
+/// This runs whatever the control mode, so that the camera keeps
+/// following the active vessel while it's being flown. Following it
+/// takes over from any rotation easing in progress.
+///
+/// The camera turns the shorter way around, at no more than
+/// [`ORIENT_TURN_RATE`], so switching modes doesn't snap it around.
+/// Like the other camera systems, this goes by real time.
+pub(crate) fn orient_camera(
+    cameras: Query<(&mut Transform, &mut CameraEasing, &CameraOrientationMode), With<SimCamera>>,
+    active_vessel: Option<Res<ActiveVessel>>,
+    states: StateQuery,
+    time: Res<Time<Real>>,
+) {
+    let Some((rel_pos, rel_vel)) = active_vessel_relative_sv(active_vessel.as_deref(), &states)
+    else {
+        return;
+    };
+
+    let max_turn = ORIENT_TURN_RATE * time.delta_secs_f64();
+
+    for (mut transform, mut easing, orientation) in cameras {
+        if let Some(target) = orientation.rotation(rel_pos, rel_vel) {
+            easing.rotation = None;
+
+            let rotation = rotate_toward(quat_to_rot(transform.rotation), target, max_turn);
+            transform.rotation = rot_to_quat(rotation);
+        }
+    }
+}
+
+/// Advances any [`CameraEasing`] in progress.
+///
+/// Like the other camera systems, this goes by real time, so that
+/// the camera keeps working while paused or time warping.
+pub(crate) fn ease_camera(
+    cameras: Query<(&mut Transform, &mut SimCameraZoom, &mut CameraEasing), With<SimCamera>>,
+    time: Res<Time<Real>>,
+) {
+    let delta = time.delta_secs_f64();
+
+    for (mut transform, mut zoom, mut easing) in cameras {
+        let duration = easing.duration;
+
+        if let Some(ease) = &mut easing.zoom {
+            let (log_zoom, finished) = ease.advance(delta, duration);
+            zoom.0 = log_zoom.exp();
+            if finished {
+                easing.zoom = None;
+            }
+        }
+
+        if let Some(ease) = &mut easing.rotation {
+            let (rotation, finished) = ease.advance(delta, duration);
+            transform.rotation = rot_to_quat(rotation);
+            if finished {
+                easing.rotation = None;
+            }
+        }
+    }
+}
+
+/// Advances any [`FocusTransition`] in progress.
+///
+/// The camera stays detached on the way there, and only gets
+/// attached, if at all, once it arrives.
+pub(crate) fn focus_camera(
+    mut commands: Commands,
+    cameras: Query<
+        (
+            Entity,
+            &Camera,
+            &mut SimCameraOffset,
+            &mut SimCameraZoom,
+            &mut FocusTransition,
+            Option<&mut AutoZoom>,
+        ),
+        With<SimCamera>,
+    >,
+    positions: Query<&RootSpacePosition>,
+    focus_sizes: FocusSizeQuery,
+    time: Res<Time<Real>>,
+) {
+    for (entity, camera, mut offset, mut zoom, mut transition, auto_zoom) in cameras {
+        let target = transition.target;
+        let Ok(&target_pos) = positions.get(target) else {
+            warn!("Cannot focus on {target}, as it has no position");
+            commands.entity(entity).remove::<FocusTransition>();
+            continue;
+        };
+
+        let starting = transition.progress.is_none();
+        let (duration, fill, attach) = (transition.duration, transition.fill, transition.attach);
+
+        let progress = transition.progress.get_or_insert_with(|| {
+            let viewport = camera
+                .logical_viewport_size()
+                .unwrap_or(FALLBACK_VIEWPORT_SIZE);
+            let target_zoom = focus_radius(target, focus_sizes).map_or(zoom.0, |radius| {
+                SimCameraZoom::fitting(radius, viewport, fill).0
+            });
+
+            FocusProgress::new(
+                offset.immutably().get_root_position(positions),
+                zoom.0,
+                target_zoom,
+            )
+        });
+
+        let delta = time.delta_secs_f64();
+        let (log_zoom, _) = progress.zoom.advance(delta, duration);
+        let (travel, arrived) = progress.travel.advance(delta, duration);
+        zoom.0 = log_zoom.exp();
+
+        if !arrived {
+            let pos = progress.from.0.lerp(target_pos.0, travel);
+            *offset = SimCameraOffset::Detached(RootSpacePosition(pos));
+        } else if attach {
+            *offset = SimCameraOffset::Attached {
+                entity: target,
+                last_known_pos: target_pos,
+                offset: DVec2::ZERO,
+            };
+        } else {
+            *offset = SimCameraOffset::Detached(target_pos);
+        }
+
+        // Don't let auto-zooming fight over the zoom, nor start
+        // zooming again once the camera has arrived
+        if (starting || arrived)
+            && let Some(mut auto_zoom) = auto_zoom
+        {
+            auto_zoom.target = None;
+            auto_zoom.recenter = false;
+            auto_zoom.last_focus = (arrived && attach).then_some(target);
+        }
+
+        if arrived {
+            commands.entity(entity).remove::<FocusTransition>();
+        }
+    }
+}
+
+/// Scales up vessels that would be drawn smaller than
+/// the [`MinVesselScreenSize`], if that resource exists.
+///
+/// This needs to run after the vessels' transforms have been converted
+/// into camera space. Only the rendered scale changes, as the scale
+/// gets reset before Rapier steps.
+#[expect(clippy::cast_possible_truncation)]
+pub(crate) fn enforce_min_vessel_size(
+    min_size: Option<Res<MinVesselScreenSize>>,
+    vessels: Query<(Entity, &mut Transform), With<Vessel>>,
+    sizes: FocusSizeQuery,
+) {
+    let Some(min_size) = min_size else {
+        return;
+    };
+
+    for (entity, mut transform) in vessels {
+        let Some(radius) = focus_radius(entity, sizes) else {
+            continue;
+        };
+
+        let scale = min_size.scale(radius as f32, transform.scale.x);
+        #[expect(clippy::float_cmp)]
+        if scale != transform.scale.x {
+            transform.scale = Vec3::splat(scale);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builders::camera::SimCameraBuilder, commands::FocusOn,
+        components::main_game::frames::RootSpaceLinearVelocity,
+    };
+    use bevy::time::TimeUpdateStrategy;
+    use core::time::Duration;
+
+    fn spawn_body(app: &mut App, radius: f32) -> Entity {
+        app.world_mut()
+            .spawn((
+                CelestialBody {
+                    base_radius: radius,
+                    mass: 1.0,
+                },
+                RootSpacePosition(DVec2::ZERO),
+            ))
+            .id()
+    }
+
+    fn focus_on(app: &mut App, camera: Entity, entity: Entity) {
+        *app.world_mut()
+            .get_mut::<SimCameraOffset>(camera)
+            .expect("camera should have an offset") = SimCameraOffset::Attached {
+            entity,
+            last_known_pos: RootSpacePosition(DVec2::ZERO),
+            offset: DVec2::ZERO,
+        };
+    }
+
+    fn zoom(app: &App, camera: Entity) -> f64 {
+        app.world()
+            .get::<SimCameraZoom>(camera)
+            .expect("camera should have a zoom")
+            .0
+    }
+
+    #[test]
+    fn zooms_to_fit_new_focus() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            50,
+        )));
+        app.add_systems(Update, auto_zoom_camera);
+
+        let rover = spawn_body(&mut app, 2.0);
+        let giant = spawn_body(&mut app, 7e7);
+
+        let camera = app
+            .world_mut()
+            .spawn((
+                SimCameraBuilder {
+                    offset: SimCameraOffset::Attached {
+                        entity: rover,
+                        last_known_pos: RootSpacePosition(DVec2::ZERO),
+                        offset: DVec2::ZERO,
+                    },
+                    zoom: SimCameraZoom(1.0),
+                    transform: Transform::IDENTITY,
+                }
+                .build(true),
+                AutoZoom::default(),
+            ))
+            .id();
+
+        let fill = AutoZoom::default().fill;
+        let rover_zoom = SimCameraZoom::fitting(2.0, FALLBACK_VIEWPORT_SIZE, fill).0;
+        let giant_zoom = SimCameraZoom::fitting(7e7, FALLBACK_VIEWPORT_SIZE, fill).0;
+
+        (0..100).for_each(|_| app.update());
+        assert!((zoom(&app, camera) / rover_zoom - 1.0).abs() < 1e-9);
+
+        focus_on(&mut app, camera, giant);
+        app.update();
+        app.update();
+        let midway = zoom(&app, camera);
+        assert!(
+            giant_zoom < midway && midway < rover_zoom,
+            "zoom should be animated, not jump straight to the target"
+        );
+
+        (0..100).for_each(|_| app.update());
+        assert!((zoom(&app, camera) / giant_zoom - 1.0).abs() < 1e-9);
+
+        focus_on(&mut app, camera, rover);
+        (0..100).for_each(|_| app.update());
+        assert!((zoom(&app, camera) / rover_zoom - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reset_eases_regardless_of_frame_rate() {
+        let run = |step_millis: u64| {
+            let mut app = App::new();
+            app.add_plugins(MinimalPlugins);
+            app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                step_millis,
+            )));
+            app.add_systems(Update, ease_camera);
+
+            let mut easing = CameraEasing::new(0.2);
+            easing.reset_zoom(100.0);
+            easing.reset_rotation(3.0 * core::f64::consts::FRAC_PI_2);
+
+            let camera = app
+                .world_mut()
+                .spawn((
+                    SimCamera,
+                    SimCameraZoom(100.0),
+                    Transform::from_rotation(rot_to_quat(3.0 * core::f64::consts::FRAC_PI_2)),
+                    easing,
+                ))
+                .id();
+
+            // The first update has no delta
+            app.update();
+            let mut samples = Vec::new();
+            for _ in 0..(300 / step_millis) {
+                app.update();
+                samples.push(zoom(&app, camera));
+            }
+
+            let rotation = app.world().get::<Transform>(camera).unwrap().rotation;
+            (
+                samples,
+                rotation,
+                *app.world().get::<CameraEasing>(camera).unwrap(),
+            )
+        };
+
+        let (fast, fast_rotation, fast_easing) = run(10);
+        let (slow, slow_rotation, _) = run(50);
+
+        // Halfway through, both should be at the same zoom
+        assert!(fast[9] < 100.0 && fast[9] > 1.0, "zoom should be eased");
+        assert!((fast[9] / slow[1] - 1.0).abs() < 1e-6);
+
+        assert!((fast.last().unwrap() - 1.0).abs() < 1e-9);
+        assert!((slow.last().unwrap() - 1.0).abs() < 1e-9);
+        assert!(fast_rotation.abs_diff_eq(Quat::IDENTITY, 1e-6));
+        assert!(slow_rotation.abs_diff_eq(Quat::IDENTITY, 1e-6));
+        assert_eq!(fast_easing, CameraEasing::new(0.2));
+    }
+
+    #[test]
+    fn orientation_turns_the_shorter_way() {
+        use core::f64::consts::{FRAC_PI_2, PI, TAU};
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )));
+        app.add_systems(Update, orient_camera);
+
+        let body = app
+            .world_mut()
+            .spawn((
+                RootSpacePosition(DVec2::ZERO),
+                RootSpaceLinearVelocity(DVec2::ZERO),
+            ))
+            .id();
+
+        // Surface up is just past -π, while the camera is just short of +π
+        let target = -PI + 0.5;
+        let pos = RootSpacePosition(DVec2::from_angle(target + FRAC_PI_2) * 7e6);
+        let vessel = app
+            .world_mut()
+            .spawn((
+                pos,
+                RootSpaceLinearVelocity(DVec2::ZERO),
+                CelestialParent { entity: body },
+            ))
+            .id();
+        app.insert_resource(ActiveVessel {
+            entity: vessel,
+            prev_tick_position: pos,
+            prev_tick_velocity: RootSpaceLinearVelocity(DVec2::ZERO),
+            prev_tick_parent: body,
+        });
+
+        let start = PI - 0.5;
+        let camera = app
+            .world_mut()
+            .spawn((
+                SimCamera,
+                CameraOrientationMode::SurfaceUp,
+                Transform::from_rotation(rot_to_quat(start)),
+            ))
+            .id();
+        let offset_from = |app: &App, angle: f64| {
+            let rotation = quat_to_rot(app.world().get::<Transform>(camera).unwrap().rotation);
+            (rotation - angle + PI).rem_euclid(TAU) - PI
+        };
+
+        // The first update has no delta
+        app.update();
+        assert!(offset_from(&app, start).abs() < 1e-6);
+
+        // Counterclockwise through ±π, at the capped rate
+        app.update();
+        let turned = offset_from(&app, start);
+        assert!(
+            (turned - ORIENT_TURN_RATE * 0.1).abs() < 1e-5,
+            "turned by {turned}"
+        );
+
+        app.update();
+        assert!(offset_from(&app, target).abs() < 1e-5);
+    }
+
+    #[test]
+    fn detached_camera_slides_along_bounds() {
+        let mut app = App::new();
+        app.add_systems(Update, clamp_detached_camera);
+
+        let vessel = app
+            .world_mut()
+            .spawn(RootSpacePosition(DVec2::new(1e9, 0.0)))
+            .id();
+        app.insert_resource(ActiveVessel {
+            entity: vessel,
+            prev_tick_position: RootSpacePosition(DVec2::new(1e9, 0.0)),
+            prev_tick_velocity: RootSpaceLinearVelocity(DVec2::ZERO),
+            prev_tick_parent: vessel,
+        });
+
+        let camera = app
+            .world_mut()
+            .spawn((
+                SimCamera,
+                SimCameraOffset::Detached(RootSpacePosition(DVec2::new(1e9, 5e4))),
+            ))
+            .id();
+
+        let move_camera = |app: &mut App, pos: DVec2| {
+            *app.world_mut().get_mut::<SimCameraOffset>(camera).unwrap() =
+                SimCameraOffset::Detached(RootSpacePosition(pos));
+            app.update();
+
+            match *app.world().get::<SimCameraOffset>(camera).unwrap() {
+                SimCameraOffset::Detached(pos) => pos.0,
+                SimCameraOffset::Attached { .. } => panic!("camera got attached"),
+            }
+        };
+
+        // Without bounds, the camera goes wherever
+        let pos = move_camera(&mut app, DVec2::new(1e9, 5e4));
+        assert_eq!(pos, DVec2::new(1e9, 5e4));
+
+        app.insert_resource(CameraBounds {
+            max_distance_from_active: 1e4,
+        });
+
+        let pos = move_camera(&mut app, DVec2::new(1e9, 5e4));
+        assert!((pos - DVec2::new(1e9, 1e4)).length() < 1e-6);
+
+        // Pushing outwards while also moving sideways
+        // keeps the camera moving sideways
+        let pos = move_camera(&mut app, pos + DVec2::new(1e3, 1e3));
+        assert!((pos.distance(DVec2::new(1e9, 0.0)) - 1e4).abs() < 1e-6);
+        assert!(pos.x - 1e9 > 900.0);
+
+        // Inside the bounds, nothing changes
+        let pos = move_camera(&mut app, DVec2::new(1e9 + 10.0, -20.0));
+        assert_eq!(pos, DVec2::new(1e9 + 10.0, -20.0));
+    }
+
+    #[test]
+    fn tiny_vessels_stay_visible() {
+        let mut app = App::new();
+        app.add_systems(Update, enforce_min_vessel_size);
+
+        let zoomed = |zoom: f32| Transform::from_scale(Vec3::splat(zoom));
+
+        // 2 m across, so 2 px across at a zoom of 1
+        let vessel = app
+            .world_mut()
+            .spawn((Vessel, Collider::ball(1.0), zoomed(1e-3)))
+            .id();
+        let body = spawn_body(&mut app, 1.0);
+        app.world_mut().entity_mut(body).insert(zoomed(1e-3));
+
+        let scale = |app: &App, entity: Entity| app.world().get::<Transform>(entity).unwrap().scale;
+
+        // Opt-in, so nothing changes without the resource
+        app.update();
+        assert_eq!(scale(&app, vessel), Vec3::splat(1e-3));
+
+        app.insert_resource(MinVesselScreenSize { pixels: 8.0 });
+        app.update();
+        assert_eq!(scale(&app, vessel), Vec3::splat(4.0));
+        assert_eq!(
+            scale(&app, body),
+            Vec3::splat(1e-3),
+            "bodies shouldn't be scaled"
+        );
+
+        // Already big enough on screen
+        app.world_mut().entity_mut(vessel).insert(zoomed(10.0));
+        app.update();
+        assert_eq!(scale(&app, vessel), Vec3::splat(10.0));
+    }
+
+    #[test]
+    fn focus_on_frames_target() {
+        for attach in [false, true] {
+            let mut app = App::new();
+            app.add_plugins(MinimalPlugins);
+            app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                50,
+            )));
+            app.add_systems(Update, focus_camera);
+
+            let planet = spawn_body(&mut app, 6e6);
+            app.world_mut()
+                .entity_mut(planet)
+                .insert(RootSpacePosition(DVec2::new(4e8, -1e8)));
+
+            let camera = app
+                .world_mut()
+                .spawn(
+                    SimCameraBuilder {
+                        offset: SimCameraOffset::Detached(RootSpacePosition(DVec2::ZERO)),
+                        zoom: SimCameraZoom(1.0),
+                        transform: Transform::IDENTITY,
+                    }
+                    .build(true),
+                )
+                .id();
+
+            app.world_mut().commands().queue(FocusOn {
+                entity: planet,
+                frame_radius: 0.4,
+                attach,
+            });
+            app.world_mut().flush();
+
+            app.update();
+            app.update();
+            let pos = match *app.world().get::<SimCameraOffset>(camera).unwrap() {
+                SimCameraOffset::Detached(pos) => pos.0,
+                SimCameraOffset::Attached { .. } => panic!("camera attached too early"),
+            };
+            assert!(pos.length() > 0.0 && pos.length() < DVec2::new(4e8, -1e8).length());
+
+            for _ in 0..20 {
+                app.update();
+            }
+
+            let world = app.world();
+            assert!(world.get::<FocusTransition>(camera).is_none());
+
+            let offset = *world.get::<SimCameraOffset>(camera).unwrap();
+            match offset {
+                SimCameraOffset::Attached { entity, offset, .. } => {
+                    assert!(attach, "camera shouldn't have been attached");
+                    assert_eq!(entity, planet);
+                    assert_eq!(offset, DVec2::ZERO);
+                }
+                SimCameraOffset::Detached(pos) => {
+                    assert!(!attach, "camera should have been attached");
+                    assert_eq!(pos.0, DVec2::new(4e8, -1e8));
+                }
+            }
+
+            // The planet's radius takes up 40% of half the viewport's height
+            let projected_radius = 6e6 * zoom(&app, camera);
+            let expected = 0.4 * f64::from(FALLBACK_VIEWPORT_SIZE.min_element()) / 2.0;
+            assert!(
+                (projected_radius / expected - 1.0).abs() < 1e-9,
+                "projected radius is {projected_radius}, expected {expected}"
+            );
+        }
+    }
+}
@@ -2,22 +2,27 @@
 
 use crate::{
     components::main_game::{
-        camera::{Focusable, SimCamera, SimCameraOffset, SimCameraZoom},
+        camera::{
+            CameraEasing, CameraOrientationMode, Focusable, SimCamera, SimCameraOffset,
+            SimCameraZoom,
+        },
         celestial::CelestialBody,
         frames::RootSpacePosition,
         vessel::Vessel,
     },
     consts::controls::{
-        FAST_SPEED_MODIFIER, KB_CAM_FAST_MOD, KB_CAM_MOV_DOWN, KB_CAM_MOV_LEFT, KB_CAM_MOV_RESET,
-        KB_CAM_MOV_RIGHT, KB_CAM_MOV_UP, KB_CAM_ROT_LEFT, KB_CAM_ROT_RESET, KB_CAM_ROT_RIGHT,
-        KB_CAM_SLOW_MOD, KB_CAM_SWITCH_NEXT, KB_CAM_SWITCH_PREV, KB_CAM_ZOOM_IN, KB_CAM_ZOOM_OUT,
-        KB_CAM_ZOOM_RESET, MAX_ZOOM, MIN_ZOOM, MOVE_SPEED_MULT, NORMAL_SPEED_MODIFIER,
-        SLOW_SPEED_MODIFIER, ZOOM_SPEED_MULT,
+        CLICK_MAX_DRAG, FAST_SPEED_MODIFIER, KB_CAM_CYCLE_ORIENTATION, KB_CAM_FAST_MOD,
+        KB_CAM_MOV_DOWN, KB_CAM_MOV_LEFT, KB_CAM_MOV_RESET, KB_CAM_MOV_RIGHT, KB_CAM_MOV_UP,
+        KB_CAM_ROT_LEFT, KB_CAM_ROT_RESET, KB_CAM_ROT_RIGHT, KB_CAM_SLOW_MOD, KB_CAM_SWITCH_NEXT,
+        KB_CAM_SWITCH_PREV, KB_CAM_TOGGLE_ATTACH, KB_CAM_ZOOM_IN, KB_CAM_ZOOM_OUT,
+        KB_CAM_ZOOM_RESET, MAX_ZOOM, MB_CAM_FOCUS, MB_CAM_PAN, MIN_ZOOM, MOVE_SPEED_MULT,
+        NORMAL_SPEED_MODIFIER, SLOW_SPEED_MODIFIER, ZOOM_SPEED_MULT,
     },
     math::quat_to_rot,
     resources::controls::FocusableData,
+    systems::main_game::camera::{FocusSizeQuery, focus_radius},
 };
-use bevy::{ecs::query::QueryData, math::DVec2, prelude::*};
+use bevy::{ecs::query::QueryData, math::DVec2, prelude::*, window::PrimaryWindow};
 use core::{cmp::Ordering, f64::consts::TAU};
 
 #[derive(QueryData)]
@@ -26,6 +31,8 @@ pub(crate) struct SimCameraInfo {
     transform: &'static mut Transform,
     offset: &'static mut SimCameraOffset,
     zoom: &'static mut SimCameraZoom,
+    easing: &'static mut CameraEasing,
+    orientation: &'static mut CameraOrientationMode,
 }
 
 type FilterSimCamera = (
@@ -38,28 +45,43 @@ type FilterSimCamera = (
 
 type FocusableQuery<'w, 's> = Query<'w, 's, (Entity, &'static RootSpacePosition), With<Focusable>>;
 
+pub(crate) type PickableQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static RootSpacePosition, Has<Vessel>),
+    (With<Focusable>, Or<(With<CelestialBody>, With<Vessel>)>),
+>;
+
 #[derive(Clone, Copy)]
 enum SwitchDirection {
     Prev,
     Next,
 }
 
+fn find_closest(
+    current_pos: RootSpacePosition,
+    focusables: FocusableQuery,
+) -> Option<(Entity, RootSpacePosition)> {
+    focusables
+        .into_iter()
+        .min_by(|(_, pos_a), (_, pos_b)| {
+            pos_a
+                .distance_squared(current_pos.0)
+                .partial_cmp(&pos_b.distance_squared(current_pos.0))
+                .unwrap_or(Ordering::Equal)
+        })
+        .map(|(entity, &position)| (entity, position))
+}
+
 fn focus_closest(
     mut offset: Mut<SimCameraOffset>,
     current_pos: RootSpacePosition,
     focusables: FocusableQuery,
 ) {
-    let closest = focusables.into_iter().min_by(|(_, pos_a), (_, pos_b)| {
-        pos_a
-            .distance_squared(current_pos.0)
-            .partial_cmp(&pos_b.distance_squared(current_pos.0))
-            .unwrap_or(Ordering::Equal)
-    });
-
-    if let Some((entity, position)) = closest {
+    if let Some((entity, position)) = find_closest(current_pos, focusables) {
         *offset = SimCameraOffset::Attached {
             entity,
-            last_known_pos: *position,
+            last_known_pos: position,
             offset: DVec2::ZERO,
         };
     }
@@ -121,7 +143,7 @@ fn switch_focus(
 pub(crate) fn control_camera(
     mut camera: Single<SimCameraInfo, FilterSimCamera>,
     key: Res<ButtonInput<KeyCode>>,
-    time: Res<Time>,
+    time: Res<Time<Real>>,
     mut queries: ParamSet<(Query<&RootSpacePosition>, FocusableQuery)>,
     focusable_data: Res<FocusableData>,
 ) {
@@ -136,27 +158,40 @@ pub(crate) fn control_camera(
     let delta_amount = time.delta_secs_f64() * speed_mult;
 
     // Camera: 40s/rev | 4s/rev | 1s/rev
+    if key.any_pressed(KB_CAM_ROT_LEFT) || key.any_pressed(KB_CAM_ROT_RIGHT) {
+        camera.easing.rotation = None;
+        *camera.orientation = CameraOrientationMode::Free;
+    }
     if key.any_pressed(KB_CAM_ROT_LEFT) {
         camera.transform.rotate_z((delta_amount * TAU) as f32);
     }
     if key.any_pressed(KB_CAM_ROT_RIGHT) {
         camera.transform.rotate_z((-delta_amount * TAU) as f32);
     }
-    if key.any_pressed(KB_CAM_ROT_RESET) {
-        camera.transform.rotation = Quat::IDENTITY;
+    if key.any_just_pressed(KB_CAM_ROT_RESET) {
+        let rotation = quat_to_rot(camera.transform.rotation);
+        camera.easing.reset_rotation(rotation);
+        *camera.orientation = CameraOrientationMode::Free;
+    }
+    if key.any_just_pressed(KB_CAM_CYCLE_ORIENTATION) {
+        *camera.orientation = camera.orientation.next();
     }
 
     let cam_rotation = quat_to_rot(camera.transform.rotation);
 
     // Zoom: 5s/double | 0.5s/double | 0.125s/double
+    if key.any_pressed(KB_CAM_ZOOM_OUT) || key.any_pressed(KB_CAM_ZOOM_IN) {
+        camera.easing.zoom = None;
+    }
     if key.any_pressed(KB_CAM_ZOOM_OUT) {
         camera.zoom.0 = (camera.zoom.0 / (ZOOM_SPEED_MULT * delta_amount).exp()).max(MIN_ZOOM);
     }
     if key.any_pressed(KB_CAM_ZOOM_IN) {
         camera.zoom.0 = (camera.zoom.0 * (ZOOM_SPEED_MULT * delta_amount).exp()).min(MAX_ZOOM);
     }
-    if key.any_pressed(KB_CAM_ZOOM_RESET) {
-        camera.zoom.0 = 1.0;
+    if key.any_just_pressed(KB_CAM_ZOOM_RESET) {
+        let zoom = camera.zoom.0;
+        camera.easing.reset_zoom(zoom);
     }
 
     // Movement
@@ -193,6 +228,20 @@ pub(crate) fn control_camera(
     // Focus switching
     let current_pos = camera.offset.mutably().get_root_position(queries.p0());
 
+    if key.any_just_pressed(KB_CAM_TOGGLE_ATTACH) {
+        let is_attached = matches!(*camera.offset, SimCameraOffset::Attached { .. });
+
+        if is_attached {
+            camera.offset.detach(queries.p0());
+        } else if let Some((entity, _)) = find_closest(current_pos, queries.p1())
+            && let Err(err) = camera.offset.attach(entity, queries.p0())
+        {
+            error!("Couldn't attach camera to {entity}: {err}");
+        }
+
+        return;
+    }
+
     if key.any_just_pressed(KB_CAM_SWITCH_PREV) {
         switch_focus(
             camera.offset.reborrow(),
@@ -212,3 +261,309 @@ pub(crate) fn control_camera(
         );
     }
 }
+
+/// Converts a drag across the screen, in logical pixels, into the
+/// root-space delta that moves the camera such that the map follows
+/// the cursor 1:1.
+///
+/// This undoes the scaling and rotation that gets applied when
+/// converting root-space positions into camera space.
+#[must_use]
+pub(crate) fn drag_to_pan_delta(screen_delta: Vec2, zoom: SimCameraZoom, rotation: Quat) -> DVec2 {
+    // Screen-space +Y points down, unlike in camera space
+    let camera_delta = DVec2::new(f64::from(screen_delta.x), -f64::from(screen_delta.y));
+
+    // The map moves with the cursor, so the camera moves against it
+    -DVec2::from_angle(quat_to_rot(rotation)).rotate(camera_delta) / zoom.0
+}
+
+/// Gets the root-space position under a point on the screen.
+///
+/// `cursor` is in logical pixels from the top-left corner of the
+/// viewport, which is `viewport_size` logical pixels large.
+#[must_use]
+pub(crate) fn screen_to_root(
+    cursor: Vec2,
+    viewport_size: Vec2,
+    camera_pos: RootSpacePosition,
+    zoom: SimCameraZoom,
+    rotation: Quat,
+) -> RootSpacePosition {
+    // The viewport's center is where the camera is
+    RootSpacePosition(
+        camera_pos.0 - drag_to_pan_delta(cursor - viewport_size / 2.0, zoom, rotation),
+    )
+}
+
+/// Gets the focusable celestial body or vessel under the cursor, if any.
+///
+/// Use [`screen_to_root`] to get `cursor_pos` from a point on the screen.
+/// Celestial bodies count as circles of their base radius, and vessels
+/// as circles around their collider's AABB. When several of them are
+/// under the cursor, vessels win over bodies, then the closest one wins.
+#[must_use]
+pub(crate) fn pick_entity(
+    cursor_pos: RootSpacePosition,
+    pickables: PickableQuery,
+    sizes: FocusSizeQuery,
+) -> Option<Entity> {
+    pickables
+        .iter()
+        .filter_map(|(entity, pos, is_vessel)| {
+            let distance = pos.0.distance(cursor_pos.0);
+            let radius = focus_radius(entity, sizes)?;
+            (distance <= radius).then_some((entity, distance, is_vessel))
+        })
+        .min_by(|(_, dist_a, vessel_a), (_, dist_b, vessel_b)| {
+            vessel_b
+                .cmp(vessel_a)
+                .then_with(|| dist_a.total_cmp(dist_b))
+        })
+        .map(|(entity, ..)| entity)
+}
+
+pub(crate) fn pan_camera_with_mouse(
+    mut camera: Single<SimCameraInfo, FilterSimCamera>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut prev_cursor: Local<Option<Vec2>>,
+) {
+    let cursor = window
+        .cursor_position()
+        .filter(|_| mouse.any_pressed(MB_CAM_PAN));
+
+    if let (Some(prev), Some(cursor)) = (*prev_cursor, cursor) {
+        let delta = drag_to_pan_delta(cursor - prev, *camera.zoom, camera.transform.rotation);
+
+        if let SimCameraOffset::Detached(pos) = &mut *camera.offset {
+            pos.0 += delta;
+        }
+    }
+
+    *prev_cursor = cursor;
+}
+
+/// Focuses the camera on the celestial body or vessel that gets
+/// clicked on, see [`pick_entity`].
+///
+/// Clicks that move the cursor further than [`CLICK_MAX_DRAG`] count as
+/// drags instead, so panning the map around doesn't change the focus.
+pub(crate) fn focus_clicked_entity(
+    mut camera: Single<SimCameraInfo, FilterSimCamera>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    positions: Query<&RootSpacePosition>,
+    pickables: PickableQuery,
+    sizes: FocusSizeQuery,
+    mut pressed_at: Local<Option<Vec2>>,
+) {
+    let cursor = window.cursor_position();
+
+    if mouse.any_just_pressed(MB_CAM_FOCUS) {
+        *pressed_at = cursor;
+    }
+    if !mouse.any_just_released(MB_CAM_FOCUS) {
+        return;
+    }
+
+    let Some((pressed_at, cursor)) = pressed_at.take().zip(cursor) else {
+        return;
+    };
+    if pressed_at.distance(cursor) > CLICK_MAX_DRAG {
+        return;
+    }
+
+    let camera_pos = camera.offset.mutably().get_root_position(positions);
+    let cursor_pos = screen_to_root(
+        cursor,
+        window.size(),
+        camera_pos,
+        *camera.zoom,
+        camera.transform.rotation,
+    );
+
+    let Some(entity) = pick_entity(cursor_pos, pickables, sizes) else {
+        return;
+    };
+    let Ok(&last_known_pos) = positions.get(entity) else {
+        return;
+    };
+
+    *camera.offset = SimCameraOffset::Attached {
+        entity,
+        last_known_pos,
+        offset: DVec2::ZERO,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy_rapier2d::prelude::Collider;
+    use core::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn drag_pans_against_cursor() {
+        let delta = drag_to_pan_delta(Vec2::new(10.0, 4.0), SimCameraZoom(2.0), Quat::IDENTITY);
+        assert!((delta - DVec2::new(-5.0, 2.0)).length() < 1e-9);
+
+        // Camera rotated counterclockwise by 90°, so screen-right is root-space +Y
+        let rotated = drag_to_pan_delta(
+            Vec2::new(10.0, 4.0),
+            SimCameraZoom(2.0),
+            Quat::from_rotation_z(FRAC_PI_2),
+        );
+        assert!((rotated - DVec2::new(-2.0, -5.0)).length() < 1e-6);
+    }
+
+    #[test]
+    fn zooming_out_pans_further() {
+        let drag = Vec2::new(-30.0, 12.0);
+        let rotation = Quat::from_rotation_z(0.7);
+
+        let near = drag_to_pan_delta(drag, SimCameraZoom(1.0), rotation);
+        let far = drag_to_pan_delta(drag, SimCameraZoom(1e-3), rotation);
+
+        assert!((far - near * 1e3).length() < 1e-6 * far.length());
+        assert!((near.length() - f64::from(drag.length())).abs() < 1e-5);
+    }
+
+    #[test]
+    fn screen_center_is_camera() {
+        let viewport = Vec2::new(800.0, 600.0);
+        let camera_pos = RootSpacePosition(DVec2::new(1e6, -2e6));
+        let zoom = SimCameraZoom(0.5);
+
+        let center = screen_to_root(viewport / 2.0, viewport, camera_pos, zoom, Quat::IDENTITY);
+        assert!((center.0 - camera_pos.0).length() < 1e-9);
+
+        // Screen-space +Y points down
+        let corner = screen_to_root(Vec2::ZERO, viewport, camera_pos, zoom, Quat::IDENTITY);
+        assert!((corner.0 - camera_pos.0 - DVec2::new(-800.0, 600.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn picks_vessels_over_bodies() {
+        let mut world = World::new();
+
+        let planet = world
+            .spawn((
+                CelestialBody {
+                    base_radius: 1000.0,
+                    mass: 1.0,
+                },
+                RootSpacePosition(DVec2::ZERO),
+                Focusable,
+            ))
+            .id();
+        let near_vessel = world
+            .spawn((
+                Vessel,
+                Focusable,
+                Collider::cuboid(5.0, 2.0),
+                RootSpacePosition(DVec2::new(1000.0, 0.0)),
+            ))
+            .id();
+        let far_vessel = world
+            .spawn((
+                Vessel,
+                Focusable,
+                Collider::cuboid(5.0, 2.0),
+                RootSpacePosition(DVec2::new(1008.0, 0.0)),
+            ))
+            .id();
+        // Not a body or a vessel, so never picked
+        world.spawn((
+            Focusable,
+            Collider::ball(1e6),
+            RootSpacePosition(DVec2::ZERO),
+        ));
+        // Not focusable, so never picked either
+        world.spawn((
+            Vessel,
+            Collider::cuboid(1e6, 1e6),
+            RootSpacePosition(DVec2::ZERO),
+        ));
+
+        let mut pick = |x: f64| {
+            world
+                .run_system_once(move |pickables: PickableQuery, sizes: FocusSizeQuery| {
+                    pick_entity(RootSpacePosition(DVec2::new(x, 0.0)), pickables, sizes)
+                })
+                .unwrap()
+        };
+
+        assert_eq!(pick(500.0), Some(planet));
+        // Inside the planet as well as both vessels
+        assert_eq!(pick(999.0), Some(near_vessel));
+        assert_eq!(pick(1005.0), Some(far_vessel));
+        assert_eq!(pick(1020.0), None);
+    }
+
+    #[test]
+    fn click_focuses_picked_entity() {
+        let mut world = World::new();
+        world.init_resource::<ButtonInput<MouseButton>>();
+
+        let body = world
+            .spawn((
+                CelestialBody {
+                    base_radius: 100.0,
+                    mass: 1.0,
+                },
+                RootSpacePosition(DVec2::new(500.0, 0.0)),
+                Focusable,
+            ))
+            .id();
+        let window = world.spawn((Window::default(), PrimaryWindow)).id();
+        let camera = world
+            .spawn((
+                Camera::default(),
+                SimCamera,
+                SimCameraOffset::Detached(RootSpacePosition(DVec2::ZERO)),
+                SimCameraZoom(1.0),
+                CameraEasing::default(),
+                CameraOrientationMode::default(),
+            ))
+            .id();
+
+        // Registered rather than run once, so the press position persists
+        let system = world.register_system(focus_clicked_entity);
+        let center = world.get::<Window>(window).unwrap().size() / 2.0;
+        let click = |world: &mut World, from: Vec2, to: Vec2| {
+            for (pos, pressed) in [(from, true), (to, false)] {
+                world
+                    .get_mut::<Window>(window)
+                    .unwrap()
+                    .set_cursor_position(Some(pos));
+
+                let mut mouse = world.resource_mut::<ButtonInput<MouseButton>>();
+                mouse.clear();
+                if pressed {
+                    mouse.press(MouseButton::Left);
+                } else {
+                    mouse.release(MouseButton::Left);
+                }
+
+                world.run_system(system).unwrap();
+            }
+        };
+        let focused = |world: &World| match world.get::<SimCameraOffset>(camera).unwrap() {
+            SimCameraOffset::Attached { entity, .. } => Some(*entity),
+            SimCameraOffset::Detached(_) => None,
+        };
+
+        // Dragging over the body isn't a click
+        let on_body = center + Vec2::new(500.0, 0.0);
+        click(&mut world, center, on_body);
+        assert_eq!(focused(&world), None);
+
+        // Clicking on empty space doesn't change anything
+        click(&mut world, center, center);
+        assert_eq!(focused(&world), None);
+
+        click(&mut world, on_body, on_body + Vec2::new(1.0, 1.0));
+        assert_eq!(focused(&world), Some(body));
+    }
+}
@@ -11,6 +11,7 @@ use bevy::prelude::*;
 
 pub(crate) mod camera;
 pub(crate) mod menu;
+pub(crate) mod vessel;
 
 pub(crate) fn init_controls(mut commands: Commands) {
     commands.init_resource::<FocusableData>();
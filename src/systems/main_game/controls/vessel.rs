@@ -0,0 +1,67 @@
+#![cfg_attr(not(feature = "not-headless"), expect(dead_code))]
+
+use bevy::prelude::*;
+
+use crate::{
+    autopilot::{ascent::GravityTurn, landing::KillHorizontalVelocity},
+    components::main_game::vessel::VesselInput,
+    consts::controls::{
+        KB_VESSEL_PRECISION, KB_VESSEL_ROT_LEFT, KB_VESSEL_ROT_RIGHT, KB_VESSEL_THROTTLE_DOWN,
+        KB_VESSEL_THROTTLE_UP,
+    },
+    resources::{controls::InputSmoothing, simulation::ActiveVessel},
+};
+
+/// Sets the active vessel's input targets from the keyboard,
+/// and eases its inputs towards them.
+///
+/// Letting go of the throttle keys keeps the throttle wherever it got
+/// to, while letting go of the rotation keys eases rotation back to 0.
+///
+/// Vessels flown by a [`GravityTurn`] or [`KillHorizontalVelocity`]
+/// ignore the keyboard until it's done.
+pub(crate) fn control_vessel(
+    key: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    smoothing: Res<InputSmoothing>,
+    active_vessel: Option<Res<ActiveVessel>>,
+    mut inputs: Query<&mut VesselInput, (Without<GravityTurn>, Without<KillHorizontalVelocity>)>,
+) {
+    let Some(active_vessel) = active_vessel else {
+        return;
+    };
+    let Ok(mut input) = inputs.get_mut(active_vessel.entity) else {
+        return;
+    };
+
+    if key.any_just_pressed(KB_VESSEL_PRECISION) {
+        input.precision = !input.precision;
+    }
+
+    let factor = if input.precision {
+        smoothing.precision_factor
+    } else {
+        1.0
+    };
+
+    input.throttle.target = if key.any_pressed(KB_VESSEL_THROTTLE_UP) {
+        1.0
+    } else if key.any_pressed(KB_VESSEL_THROTTLE_DOWN) {
+        0.0
+    } else {
+        input.throttle.current
+    };
+
+    let mut rotation = 0.0;
+    if key.any_pressed(KB_VESSEL_ROT_LEFT) {
+        rotation += 1.0;
+    }
+    if key.any_pressed(KB_VESSEL_ROT_RIGHT) {
+        rotation -= 1.0;
+    }
+    input.rotation.target = rotation * factor;
+
+    let max_delta = smoothing.rate * factor * time.delta_secs_f64();
+    input.throttle.approach(max_delta);
+    input.rotation.approach(max_delta);
+}
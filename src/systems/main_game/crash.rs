@@ -0,0 +1,225 @@
+//! Vessels getting destroyed by crashing into things
+
+use bevy::{math::DVec2, platform::collections::HashSet, prelude::*};
+
+use crate::{
+    components::main_game::{
+        camera::{SimCamera, SimCameraOffset},
+        frames::{RigidSpaceVelocity, RootSpacePosition},
+        vessel::{CrashTolerance, ImpactSensor, Vessel},
+    },
+    consts::{FilterLoadedVessels, MIN_IMPACT_SPEED},
+    messages::crash::{VesselDestroyed, VesselImpact},
+    resources::simulation::ActiveVessel,
+};
+
+/// Remembers the velocity of every vessel with an [`ImpactSensor`],
+/// right before Rapier steps.
+pub(crate) fn record_pre_step_velocities(
+    sensors: Query<(&RigidSpaceVelocity, &mut ImpactSensor), FilterLoadedVessels>,
+) {
+    for (velocity, mut sensor) in sensors {
+        sensor.pre_step_linvel = Some(velocity.linvel);
+    }
+}
+
+/// Sends a [`VesselImpact`] for every vessel whose velocity changed by
+/// at least [`MIN_IMPACT_SPEED`] during the physics step.
+///
+/// Gravity and drag get applied before the step, so any change
+/// in velocity during the step comes from collisions.
+pub(crate) fn detect_impacts(
+    sensors: Query<(Entity, &RigidSpaceVelocity, &mut ImpactSensor), FilterLoadedVessels>,
+    mut writer: MessageWriter<VesselImpact>,
+) {
+    for (entity, velocity, mut sensor) in sensors {
+        let Some(pre_step_linvel) = sensor.pre_step_linvel.take() else {
+            continue;
+        };
+
+        let speed = f64::from(velocity.linvel.distance(pre_step_linvel));
+
+        if speed >= MIN_IMPACT_SPEED {
+            writer.write(VesselImpact {
+                vessel: entity,
+                speed,
+            });
+        }
+    }
+}
+
+/// Destroys vessels that hit something harder than their [`CrashTolerance`].
+///
+/// If the active vessel gets destroyed, the nearest remaining vessel
+/// becomes the active one, and the camera follows it. If there's no
+/// vessel left, the [`ActiveVessel`] resource gets removed and the
+/// camera stays where the crash happened.
+pub(crate) fn handle_crashes(
+    mut commands: Commands,
+    mut impacts: MessageReader<VesselImpact>,
+    tolerances: Query<&CrashTolerance>,
+    vessels: Query<(Entity, &RootSpacePosition), With<Vessel>>,
+    cameras: Query<&mut SimCameraOffset, With<SimCamera>>,
+    positions: Query<&RootSpacePosition>,
+    active_vessel: Option<ResMut<ActiveVessel>>,
+    mut writer: MessageWriter<VesselDestroyed>,
+) {
+    let mut destroyed = HashSet::new();
+
+    for &VesselImpact { vessel, speed } in impacts.read() {
+        let Ok(tolerance) = tolerances.get(vessel) else {
+            continue;
+        };
+
+        if speed <= tolerance.max_impact_speed || !destroyed.insert(vessel) {
+            continue;
+        }
+
+        info!("Vessel {vessel} crashed at {speed:.1} m/s");
+
+        commands.entity(vessel).despawn();
+        writer.write(VesselDestroyed {
+            vessel,
+            impact_speed: speed,
+        });
+    }
+
+    if destroyed.is_empty() {
+        return;
+    }
+
+    let mut replacement = None;
+
+    if let Some(mut active_vessel) = active_vessel
+        && destroyed.contains(&active_vessel.entity)
+    {
+        let crash_pos = positions
+            .get(active_vessel.entity)
+            .copied()
+            .unwrap_or(RootSpacePosition(DVec2::ZERO));
+
+        replacement = vessels
+            .iter()
+            .filter(|(entity, _)| !destroyed.contains(entity))
+            .min_by(|(_, a), (_, b)| {
+                a.distance_squared_to(crash_pos)
+                    .total_cmp(&b.distance_squared_to(crash_pos))
+            })
+            .map(|(entity, &pos)| (entity, pos));
+
+        match replacement {
+            // The previous tick's state gets updated along with
+            // the rest of the active vessel's state next tick
+            Some((entity, _)) => active_vessel.entity = entity,
+            None => commands.remove_resource::<ActiveVessel>(),
+        }
+    }
+
+    for mut offset in cameras {
+        let SimCameraOffset::Attached { entity, .. } = *offset else {
+            continue;
+        };
+
+        if !destroyed.contains(&entity) {
+            continue;
+        }
+
+        match replacement {
+            Some((entity, pos)) => {
+                *offset = SimCameraOffset::Attached {
+                    entity,
+                    last_known_pos: pos,
+                    offset: DVec2::ZERO,
+                };
+            }
+            None => offset.detach(positions.as_readonly()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::main_game::frames::RootSpaceLinearVelocity;
+
+    fn spawn_vessel(app: &mut App, pos: DVec2) -> Entity {
+        app.world_mut()
+            .spawn((
+                Vessel,
+                CrashTolerance {
+                    max_impact_speed: 10.0,
+                },
+                RootSpacePosition(pos),
+            ))
+            .id()
+    }
+
+    fn impact(app: &mut App, vessel: Entity, speed: f64) {
+        app.world_mut()
+            .write_message(VesselImpact { vessel, speed });
+        app.update();
+    }
+
+    #[test]
+    fn crash_switches_active_vessel() {
+        let mut app = App::new();
+        app.add_message::<VesselImpact>();
+        app.add_message::<VesselDestroyed>();
+        app.add_systems(Update, handle_crashes);
+
+        let active = spawn_vessel(&mut app, DVec2::new(1e6, 0.0));
+        let near = spawn_vessel(&mut app, DVec2::new(1e6, 100.0));
+        let far = spawn_vessel(&mut app, DVec2::new(-1e6, 0.0));
+
+        app.insert_resource(ActiveVessel {
+            entity: active,
+            prev_tick_position: RootSpacePosition(DVec2::new(1e6, 0.0)),
+            prev_tick_velocity: RootSpaceLinearVelocity(DVec2::ZERO),
+            prev_tick_parent: active,
+        });
+
+        let camera = app
+            .world_mut()
+            .spawn((
+                SimCamera,
+                SimCameraOffset::Attached {
+                    entity: active,
+                    last_known_pos: RootSpacePosition(DVec2::new(1e6, 0.0)),
+                    offset: DVec2::new(5.0, 0.0),
+                },
+            ))
+            .id();
+
+        let camera_target = |app: &App| match *app.world().get::<SimCameraOffset>(camera).unwrap() {
+            SimCameraOffset::Attached { entity, .. } => Some(entity),
+            SimCameraOffset::Detached(_) => None,
+        };
+
+        // Within tolerance
+        impact(&mut app, active, 9.0);
+        assert!(app.world().get_entity(active).is_ok());
+        assert_eq!(camera_target(&app), Some(active));
+
+        impact(&mut app, active, 30.0);
+        assert!(app.world().get_entity(active).is_err());
+        assert_eq!(app.world().resource::<ActiveVessel>().entity, near);
+        assert_eq!(camera_target(&app), Some(near));
+
+        let destroyed = app.world().resource::<Messages<VesselDestroyed>>();
+        let destroyed: Vec<_> = destroyed.iter_current_update_messages().copied().collect();
+        assert_eq!(
+            destroyed,
+            [VesselDestroyed {
+                vessel: active,
+                impact_speed: 30.0,
+            }]
+        );
+
+        impact(&mut app, near, 30.0);
+        assert_eq!(app.world().resource::<ActiveVessel>().entity, far);
+
+        impact(&mut app, far, 30.0);
+        assert!(!app.world().contains_resource::<ActiveVessel>());
+        assert_eq!(camera_target(&app), None);
+    }
+}
@@ -0,0 +1,543 @@
+//! Docking vessels together and undocking them
+
+use bevy::{math::DVec2, platform::collections::HashSet, prelude::*};
+use bevy_rapier2d::prelude::{
+    AdditionalMassProperties, Ccd, Collider, Friction, ReadMassProperties, Restitution, RigidBody,
+};
+use core::f64::consts::{PI, TAU};
+
+use crate::{
+    builders::vessel::VesselBuilder,
+    components::main_game::{
+        camera::Focusable,
+        frames::{
+            RigidSpaceVelocity, RootSpaceAngle, RootSpaceAngularVelocity, RootSpaceLinearVelocity,
+            RootSpacePosition,
+        },
+        relations::{CelestialParent, ChildObjects, ParentBody, RailMode},
+        vessel::{DockedVessel, DockingPort, DragProfile, Vessel, VesselPart},
+    },
+    consts::FilterLoadedVessels,
+    math::{body_mass, quat_to_rot, rot_to_quat},
+    messages::parts::Undock,
+    resources::simulation::ActiveVessel,
+    systems::main_game::parts::StagingRootQuery,
+};
+
+/// How close two docking ports need to be to dock, in meters.
+const DOCKING_DISTANCE_TOLERANCE: f64 = 0.5;
+
+/// How far from facing each other two docking ports
+/// can be while still docking, in radians.
+const DOCKING_ANGLE_TOLERANCE: f64 = 0.1;
+
+/// How fast two vessels can be moving relative
+/// to each other while still docking, in m/s.
+const DOCKING_SPEED_TOLERANCE: f64 = 1.0;
+
+type DockingRootQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static RootSpacePosition,
+        &'static mut RootSpaceLinearVelocity,
+        &'static Transform,
+        &'static AdditionalMassProperties,
+        &'static Collider,
+        Option<&'static VesselPart>,
+        Option<&'static ChildObjects>,
+    ),
+    FilterLoadedVessels,
+>;
+
+/// Where a docking port is, both relative to its vessel's root part
+/// and in root space.
+#[derive(Clone, Copy)]
+struct PortPose {
+    port: Entity,
+    root: Entity,
+    local_pos: Vec2,
+    local_facing: f32,
+    pos: DVec2,
+    facing: f64,
+    vel: DVec2,
+}
+
+impl PortPose {
+    fn can_dock_with(&self, other: &Self) -> bool {
+        let misalignment = ((self.facing - other.facing).rem_euclid(TAU) - PI).abs();
+
+        self.root != other.root
+            && self.pos.distance(other.pos) < DOCKING_DISTANCE_TOLERANCE
+            && misalignment < DOCKING_ANGLE_TOLERANCE
+            && self.vel.distance(other.vel) < DOCKING_SPEED_TOLERANCE
+    }
+}
+
+/// Gets the position and facing of a docking port relative to
+/// its vessel's root part.
+///
+/// `part` is the part the port is on, or [`None`] if
+/// it's on the root part itself.
+#[must_use]
+fn port_local_pose(port: &DockingPort, part: Option<&VesselPart>) -> (Vec2, f32) {
+    match part {
+        Some(part) => (
+            part.offset + Vec2::from_angle(part.angle).rotate(port.offset),
+            part.angle + port.facing,
+        ),
+        None => (port.offset, port.facing),
+    }
+}
+
+/// Gets the pose of a part relative to `new_root`, given its
+/// pose relative to a root that sits at `offset` and `angle`
+/// relative to `new_root`.
+#[must_use]
+fn reparent_part(part: &VesselPart, offset: Vec2, angle: f32) -> VesselPart {
+    VesselPart {
+        offset: offset + Vec2::from_angle(angle).rotate(part.offset),
+        angle: angle + part.angle,
+        ..part.clone()
+    }
+}
+
+/// The reverse of [`reparent_part`].
+#[must_use]
+fn unparent_part(part: &VesselPart, offset: Vec2, angle: f32) -> VesselPart {
+    VesselPart {
+        offset: Vec2::from_angle(-angle).rotate(part.offset - offset),
+        angle: part.angle - angle,
+        ..part.clone()
+    }
+}
+
+/// Docks vessels whose docking ports line up.
+///
+/// The lighter vessel gets snapped into place such that the ports
+/// line up exactly, then becomes part of the heavier vessel. Momentum
+/// is conserved, so the merged vessel moves at the vessels' combined
+/// velocity. Spin is taken from the heavier vessel as-is.
+pub(crate) fn dock_vessels(
+    mut commands: Commands,
+    ports: Query<(
+        Entity,
+        &DockingPort,
+        Option<&VesselPart>,
+        Option<&ParentBody>,
+        Has<Vessel>,
+    )>,
+    docked: Query<&DockedVessel>,
+    mut roots: DockingRootQuery,
+    parts: Query<&VesselPart, Without<Vessel>>,
+    mut active_vessel: Option<ResMut<ActiveVessel>>,
+) {
+    let busy_ports: HashSet<Entity> = docked
+        .iter()
+        .flat_map(|docked| [docked.port, docked.other_port])
+        .collect();
+
+    let poses: Vec<PortPose> = ports
+        .iter()
+        .filter(|(entity, ..)| !busy_ports.contains(entity))
+        .filter_map(|(entity, port, part, parent_body, is_root)| {
+            let root = if is_root { entity } else { parent_body?.entity };
+            let (root_pos, root_vel, root_transform, ..) = roots.get(root).ok()?;
+
+            let (local_pos, local_facing) = port_local_pose(port, part.filter(|_| !is_root));
+            let root_angle = quat_to_rot(root_transform.rotation);
+
+            Some(PortPose {
+                port: entity,
+                root,
+                local_pos,
+                local_facing,
+                pos: root_pos.0 + DVec2::from_angle(root_angle).rotate(local_pos.as_dvec2()),
+                facing: root_angle + f64::from(local_facing),
+                vel: root_vel.0,
+            })
+        })
+        .collect();
+
+    let mut merged_roots = HashSet::new();
+
+    for (i, a) in poses.iter().enumerate() {
+        for b in &poses[i + 1..] {
+            if merged_roots.contains(&a.root) || merged_roots.contains(&b.root) {
+                continue;
+            }
+
+            if !a.can_dock_with(b) {
+                continue;
+            }
+
+            let Ok([a_root, b_root]) = roots.get_many([a.root, b.root]) else {
+                continue;
+            };
+            // Part masses are single precision, like Rapier's
+            #[expect(clippy::cast_possible_truncation)]
+            let [a_mass, b_mass] = [a_root.3, b_root.3].map(|mass| body_mass(mass) as f32);
+
+            let (keep, absorb, keep_mass, absorb_mass) = if a_mass >= b_mass {
+                (a, b, a_mass, b_mass)
+            } else {
+                (b, a, b_mass, a_mass)
+            };
+
+            merged_roots.insert(a.root);
+            merged_roots.insert(b.root);
+
+            dock(
+                &mut commands,
+                keep,
+                absorb,
+                (keep_mass, absorb_mass),
+                &mut roots,
+                parts,
+            );
+
+            if let Some(active_vessel) = active_vessel.as_mut()
+                && active_vessel.entity == absorb.root
+            {
+                active_vessel.entity = keep.root;
+            }
+        }
+    }
+}
+
+/// Makes the vessel of `absorb` a part of the vessel of `keep`.
+fn dock(
+    commands: &mut Commands,
+    keep: &PortPose,
+    absorb: &PortPose,
+    (keep_mass, absorb_mass): (f32, f32),
+    roots: &mut DockingRootQuery,
+    parts: Query<&VesselPart, Without<Vessel>>,
+) {
+    // Snap the ports onto each other, facing opposite directions
+    let angle = keep.local_facing + core::f32::consts::PI - absorb.local_facing;
+    let offset = keep.local_pos - Vec2::from_angle(angle).rotate(absorb.local_pos);
+
+    let Ok((_, _, _, _, absorb_collider, absorb_part, absorb_children)) = roots.get(absorb.root)
+    else {
+        return;
+    };
+
+    let (shape, mass) = match absorb_part {
+        Some(part) => (part.shape.clone(), part.mass),
+        None => (absorb_collider.clone(), absorb_mass),
+    };
+    let children: Vec<Entity> = absorb_children
+        .map(|children| children.iter().collect())
+        .unwrap_or_default();
+
+    for &child in &children {
+        let Ok(part) = parts.get(child) else {
+            continue;
+        };
+
+        commands.entity(child).insert((
+            ParentBody { entity: keep.root },
+            reparent_part(part, offset, angle),
+        ));
+    }
+
+    commands
+        .entity(absorb.root)
+        .remove::<(
+            (Vessel, RigidBody, Friction, Restitution, Ccd, Focusable),
+            (Collider, AdditionalMassProperties, ReadMassProperties),
+            (DragProfile, CelestialParent, RailMode),
+            (
+                RootSpacePosition,
+                RootSpaceLinearVelocity,
+                RootSpaceAngle,
+                RootSpaceAngularVelocity,
+                RigidSpaceVelocity,
+            ),
+        )>()
+        .insert((
+            ParentBody { entity: keep.root },
+            VesselPart {
+                offset,
+                angle,
+                shape,
+                mass,
+            },
+            DockedVessel {
+                port: absorb.port,
+                other_port: keep.port,
+                parts: children,
+            },
+        ));
+
+    let Ok((_, mut keep_vel, _, _, keep_collider, keep_part, _)) = roots.get_mut(keep.root) else {
+        return;
+    };
+
+    let momentum = keep.vel * f64::from(keep_mass) + absorb.vel * f64::from(absorb_mass);
+    keep_vel.0 = momentum / f64::from(keep_mass + absorb_mass);
+
+    if keep_part.is_none() {
+        commands.entity(keep.root).insert(VesselPart {
+            offset: Vec2::ZERO,
+            angle: 0.0,
+            shape: keep_collider.clone(),
+            mass: keep_mass,
+        });
+    }
+}
+
+/// Splits docked vessels back into independent vessels.
+///
+/// Works like staging, but with the docked vessel's parts coming
+/// along with it, and with the impulse along the ports' facing.
+pub(crate) fn handle_undocking(
+    mut commands: Commands,
+    mut reader: MessageReader<Undock>,
+    docked: Query<(Entity, &DockedVessel, &VesselPart, &ParentBody), Without<Vessel>>,
+    ports: Query<&DockingPort>,
+    parts: Query<&VesselPart, Without<Vessel>>,
+    mut roots: StagingRootQuery,
+) {
+    for &Undock { port, impulse } in reader.read() {
+        let Some((entity, docked_vessel, part, parent_body)) = docked
+            .iter()
+            .find(|(_, docked, ..)| docked.port == port || docked.other_port == port)
+        else {
+            warn!("Attempted to undock at {port}, which isn't a docked docking port");
+            continue;
+        };
+
+        let Ok((
+            root_pos,
+            mut root_vel,
+            root_rigid_vel,
+            root_transform,
+            parent,
+            root_mass,
+            mut root_part,
+        )) = roots.get_mut(parent_body.entity)
+        else {
+            warn!("Docking port {port} isn't on a loaded vessel; not undocking");
+            continue;
+        };
+
+        let Ok(own_port) = ports.get(docked_vessel.port) else {
+            continue;
+        };
+        let (_, port_facing) = port_local_pose(own_port, parts.get(docked_vessel.port).ok());
+
+        let root_angle = quat_to_rot(root_transform.rotation);
+        let offset = DVec2::from_angle(root_angle).rotate(part.offset.as_dvec2());
+        let spin_vel = offset.perp() * f64::from(root_rigid_vel.angvel);
+
+        // This vessel's port faces the other vessel, so
+        // this vessel gets pushed the opposite way
+        let push = -DVec2::from_angle(root_angle + f64::from(port_facing)) * impulse;
+
+        let child_parts: Vec<(Entity, &VesselPart)> = docked_vessel
+            .parts
+            .iter()
+            .filter_map(|&child| Some((child, parts.get(child).ok()?)))
+            .collect();
+
+        let undocked_mass = f64::from(part.mass)
+            + child_parts
+                .iter()
+                .map(|(_, part)| f64::from(part.mass))
+                .sum::<f64>();
+        let remaining_mass = body_mass(root_mass) - undocked_mass;
+
+        let undocked_vel = root_vel.0
+            + spin_vel
+            + if undocked_mass > 0.0 {
+                push / undocked_mass
+            } else {
+                DVec2::ZERO
+            };
+
+        if remaining_mass > 0.0 {
+            root_vel.0 -= push / remaining_mass;
+        }
+
+        for (child, child_part) in child_parts {
+            commands.entity(child).insert((
+                ParentBody { entity },
+                unparent_part(child_part, part.offset, part.angle),
+            ));
+        }
+
+        let drag = DragProfile::from_aabb(
+            part.shape.raw.compute_local_aabb(),
+            DragProfile::DEFAULT_DRAG_COEFFICIENT,
+        );
+
+        commands
+            .entity(entity)
+            .remove::<(ParentBody, DockedVessel)>()
+            .insert((
+                VesselBuilder::<ColorMaterial>::base_bundle(),
+                VesselPart {
+                    offset: Vec2::ZERO,
+                    angle: 0.0,
+                    ..part.clone()
+                },
+                part.shape.clone(),
+                AdditionalMassProperties::Mass(part.mass),
+                ReadMassProperties::default(),
+                drag,
+                *parent,
+                RailMode::None,
+                RootSpacePosition(root_pos.0 + offset),
+                RootSpaceLinearVelocity(undocked_vel),
+                RigidSpaceVelocity {
+                    angvel: root_rigid_vel.angvel,
+                    linvel: Vec2::NAN,
+                },
+                RootSpaceAngle(root_angle + f64::from(part.angle)),
+                RootSpaceAngularVelocity(f64::from(root_rigid_vel.angvel)),
+                Transform::from_rotation(rot_to_quat(root_angle + f64::from(part.angle))),
+            ));
+
+        // Makes the remaining vessel's collider and mass
+        // get rebuilt without the undocked parts
+        root_part.set_changed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        systems::main_game::parts::update_part_colliders,
+        test_util::{attached_part, root_part},
+    };
+
+    fn velocity(app: &App, entity: Entity) -> DVec2 {
+        app.world()
+            .get::<RootSpaceLinearVelocity>(entity)
+            .expect("vessel should have a velocity")
+            .0
+    }
+
+    fn mass(app: &App, entity: Entity) -> f64 {
+        body_mass(
+            app.world()
+                .get::<AdditionalMassProperties>(entity)
+                .expect("vessel should have a mass"),
+        )
+    }
+
+    #[test]
+    fn dock_and_undock() {
+        let mut app = App::new();
+        app.add_message::<Undock>();
+        app.add_systems(
+            Update,
+            (dock_vessels, handle_undocking, update_part_colliders).chain(),
+        );
+
+        let body = app.world_mut().spawn_empty().id();
+
+        let station = app
+            .world_mut()
+            .spawn((
+                Vessel,
+                DockingPort {
+                    offset: Vec2::new(1.0, 0.0),
+                    facing: 0.0,
+                },
+                Collider::cuboid(1.0, 1.0),
+                AdditionalMassProperties::Mass(10.0),
+                RootSpacePosition(DVec2::ZERO),
+                RootSpaceLinearVelocity(DVec2::new(1.0, 0.0)),
+                RigidSpaceVelocity::zero(),
+                Transform::default(),
+                CelestialParent { entity: body },
+            ))
+            .id();
+
+        // Rotated by 180°, so its port faces the station's port
+        let ship = app
+            .world_mut()
+            .spawn((
+                root_part(Collider::cuboid(1.0, 1.0), 2.0),
+                DockingPort {
+                    offset: Vec2::new(1.0, 0.0),
+                    facing: 0.0,
+                },
+                RootSpacePosition(DVec2::new(2.2, 0.0)),
+                RootSpaceLinearVelocity(DVec2::new(1.5, 0.0)),
+                RigidSpaceVelocity::zero(),
+                Transform::from_rotation(Quat::from_rotation_z(core::f32::consts::PI)),
+                CelestialParent { entity: body },
+            ))
+            .id();
+
+        let ship_part = app
+            .world_mut()
+            .spawn(attached_part(
+                ship,
+                VesselPart {
+                    offset: Vec2::new(0.0, 2.0),
+                    angle: 0.0,
+                    shape: Collider::ball(0.5),
+                    mass: 1.0,
+                },
+            ))
+            .id();
+
+        app.update();
+
+        let ship_ref = app.world().entity(ship);
+        assert!(!ship_ref.contains::<Vessel>());
+        assert_eq!(
+            ship_ref.get::<ParentBody>().map(|parent| parent.entity),
+            Some(station)
+        );
+        let ship_vessel_part = ship_ref.get::<VesselPart>().unwrap();
+        assert!((ship_vessel_part.offset - Vec2::new(2.0, 0.0)).length() < 1e-5);
+
+        let part = app.world().get::<VesselPart>(ship_part).unwrap();
+        assert!((part.offset - Vec2::new(2.0, -2.0)).length() < 1e-5);
+        assert_eq!(
+            app.world()
+                .get::<ParentBody>(ship_part)
+                .map(|parent| parent.entity),
+            Some(station)
+        );
+
+        let merged_vel = (10.0 + 3.0 * 1.5) / 13.0;
+        assert!((velocity(&app, station) - DVec2::new(merged_vel, 0.0)).length() < 1e-9);
+        assert!((mass(&app, station) - 13.0).abs() < 1e-5);
+
+        app.world_mut().write_message(Undock {
+            port: station,
+            impulse: 3.0,
+        });
+        app.update();
+
+        let ship_ref = app.world().entity(ship);
+        assert!(ship_ref.contains::<Vessel>());
+        assert!(!ship_ref.contains::<ParentBody>());
+        assert!(!ship_ref.contains::<DockedVessel>());
+
+        let ship_pos = ship_ref.get::<RootSpacePosition>().unwrap();
+        assert!((ship_pos.0 - DVec2::new(2.0, 0.0)).length() < 1e-5);
+
+        let part = app.world().get::<VesselPart>(ship_part).unwrap();
+        assert!((part.offset - Vec2::new(0.0, 2.0)).length() < 1e-5);
+        assert_eq!(
+            app.world()
+                .get::<ParentBody>(ship_part)
+                .map(|parent| parent.entity),
+            Some(ship)
+        );
+
+        assert!((velocity(&app, ship) - DVec2::new(merged_vel + 1.0, 0.0)).length() < 1e-6);
+        assert!((velocity(&app, station) - DVec2::new(merged_vel - 0.3, 0.0)).length() < 1e-6);
+        assert!((mass(&app, station) - 10.0).abs() < 1e-5);
+        assert!((mass(&app, ship) - 3.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,89 @@
+//! Atmospheric drag for loaded vessels
+
+use bevy::{ecs::query::QueryData, math::DVec2, prelude::*};
+use bevy_rapier2d::prelude::{ExternalForce, ReadMassProperties};
+
+use crate::{
+    components::main_game::{
+        celestial::{Atmosphere, CelestialBody},
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::CelestialParent,
+        vessel::{DragProfile, Vessel},
+    },
+    consts::FilterLoadedVessels,
+};
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub(crate) struct VesselData {
+    name: NameOrEntity,
+    pos: &'static RootSpacePosition,
+    vel: &'static RootSpaceLinearVelocity,
+    parent: &'static CelestialParent,
+    drag: &'static DragProfile,
+    mass: &'static ReadMassProperties,
+    force: &'static mut ExternalForce,
+}
+
+#[derive(QueryData)]
+pub(crate) struct ParentData {
+    pos: &'static RootSpacePosition,
+    vel: &'static RootSpaceLinearVelocity,
+    body_data: &'static CelestialBody,
+    atmosphere: Option<&'static Atmosphere>,
+}
+
+/// Gets the drag force on a vessel, in newtons.
+fn drag_force(
+    vessel: &VesselDataItem,
+    celestials: Query<ParentData, Without<Vessel>>,
+    dt: f64,
+) -> DVec2 {
+    let Ok(parent) = celestials.get(vessel.parent.entity) else {
+        error!("Vessel {} is missing a parent!", vessel.name);
+        return DVec2::ZERO;
+    };
+
+    let Some(atmosphere) = parent.atmosphere else {
+        return DVec2::ZERO;
+    };
+
+    let mass = f64::from(vessel.mass.get().mass);
+    if mass <= 0.0 {
+        return DVec2::ZERO;
+    }
+
+    let rel_pos = vessel.pos.0 - parent.pos.0;
+    let altitude = rel_pos.length() - f64::from(parent.body_data.base_radius);
+    let density = atmosphere.density_at(altitude);
+    if density <= 0.0 {
+        return DVec2::ZERO;
+    }
+
+    // TODO: Consider celestial rotation
+    let rel_vel = vessel.vel.0 - parent.vel.0;
+    let force = vessel.drag.drag_force(density, rel_vel);
+
+    // Drag can only slow a vessel down relative to the air,
+    // never push it backwards
+    force.clamp_length_max(rel_vel.length() * mass / dt)
+}
+
+/// Sets the [`ExternalForce`] of loaded vessels to the drag
+/// they feel from their parent's [`Atmosphere`], if any.
+///
+/// Nothing else pushes vessels through [`ExternalForce`], so this
+/// overwrites the force instead of adding to it, which also clears
+/// out the last tick's drag once a vessel leaves the atmosphere.
+pub(crate) fn apply_atmospheric_drag(
+    mut vessels: Query<VesselData, FilterLoadedVessels>,
+    celestials: Query<ParentData, Without<Vessel>>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs_f64();
+
+    vessels.iter_mut().for_each(|mut vessel| {
+        let force = drag_force(&vessel, celestials, dt);
+        vessel.force.force = force.as_vec2();
+    });
+}
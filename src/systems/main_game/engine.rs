@@ -0,0 +1,50 @@
+//! Pushing loaded vessels with their engines
+
+use bevy::{ecs::query::QueryData, math::DVec2, prelude::*};
+use bevy_rapier2d::prelude::ReadMassProperties;
+
+use crate::{
+    components::main_game::{
+        frames::{RootSpaceAngle, RootSpaceLinearVelocity},
+        vessel::{Engine, VesselInput},
+    },
+    consts::FilterLoadedVessels,
+};
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub(crate) struct EngineData {
+    engine: &'static mut Engine,
+    input: &'static VesselInput,
+    angle: &'static RootSpaceAngle,
+    vel: &'static mut RootSpaceLinearVelocity,
+    mass: &'static ReadMassProperties,
+}
+
+/// Speeds loaded vessels up along their forward direction
+/// according to their throttle input, with as much thrust
+/// as their [`Engine`] can give, using up its fuel.
+pub(crate) fn apply_engine_thrust(
+    mut vessels: Query<EngineData, FilterLoadedVessels>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs_f64();
+
+    vessels.iter_mut().for_each(|mut vessel| {
+        let mass = f64::from(vessel.mass.get().mass);
+        if mass <= 0.0 {
+            return;
+        }
+
+        let throttle = vessel.input.throttle.current;
+        let thrust = vessel.engine.commanded_thrust(throttle);
+        if thrust <= 0.0 {
+            return;
+        }
+        vessel.engine.burn(throttle, dt);
+
+        let forward = DVec2::from_angle(vessel.angle.0).perp();
+
+        vessel.vel.0 += forward * (thrust / mass * dt);
+    });
+}
@@ -5,8 +5,8 @@ use crate::{
         camera::{SimCamera, SimCameraOffset, SimCameraZoom},
         celestial::Terrain,
         frames::{
-            RigidSpaceTransform, RigidSpaceVelocity, RigidSpaceVelocityImpl,
-            RootSpaceLinearVelocity, RootSpacePosition,
+            RigidSpaceTransform, RigidSpaceVelocity, RigidSpaceVelocityImpl, RootSpaceAngle,
+            RootSpaceAngularVelocity, RootSpaceLinearVelocity, RootSpacePosition,
         },
         relations::CelestialParent,
     },
@@ -24,7 +24,12 @@ pub(crate) fn write_rigid_pos_to_root(
         (Without<RootSpacePosition>, FilterLoadedVessels),
     >,
     mut with_root_pos: Query<
-        (&Transform, &mut RootSpacePosition, &CelestialParent),
+        (
+            &Transform,
+            &mut RootSpacePosition,
+            Option<&mut RootSpaceAngle>,
+            &CelestialParent,
+        ),
         FilterLoadedVessels,
     >,
     active_vessel: Option<Res<ActiveVessel>>,
@@ -45,11 +50,15 @@ pub(crate) fn write_rigid_pos_to_root(
 
         commands.entity(entity).insert(new_root_position);
     }
-    for (transform, mut root_space_pos, parent) in &mut with_root_pos {
+    for (transform, mut root_space_pos, root_space_angle, parent) in &mut with_root_pos {
         if parent.entity != active_vessel.prev_tick_parent {
             continue;
         }
 
+        if let Some(mut root_space_angle) = root_space_angle {
+            *root_space_angle = RootSpaceAngle::from_rigid_space_rotation(transform.rotation);
+        }
+
         let transform = RigidSpaceTransform(*transform);
         let new_pos = transform
             .position()
@@ -70,6 +79,7 @@ pub(crate) fn write_rigid_vel_to_root(
         (
             &RigidSpaceVelocity,
             &mut RootSpaceLinearVelocity,
+            Option<&mut RootSpaceAngularVelocity>,
             &CelestialParent,
         ),
         FilterLoadedVessels,
@@ -89,16 +99,28 @@ pub(crate) fn write_rigid_vel_to_root(
 
         commands.entity(entity).insert(new_root_velocity);
     }
-    for (rigid_vel, mut root_space_vel, parent) in &mut with_root_vel {
+    for (rigid_vel, mut root_space_vel, root_space_angvel, parent) in &mut with_root_vel {
         if parent.entity != active_vessel.prev_tick_parent {
             continue;
         }
 
+        if let Some(mut root_space_angvel) = root_space_angvel {
+            *root_space_angvel = rigid_vel.to_root_space_angular_velocity();
+        }
+
         *root_space_vel = rigid_vel.to_root_space_linear_velocity(active_vessel.prev_tick_velocity);
     }
 }
 
-/// Updates the last tick position and last parent body of the active vessel.
+/// Updates the last tick position and last parent body of the active
+/// vessel, which rigid space gets centered on.
+///
+/// This works the same whether the active vessel is loaded or on rails,
+/// as on-rails vessels get their state vectors written from their rails
+/// beforehand. If the active vessel has no usable state vectors, e.g.
+/// because it's been despawned, rigid space stays centered on its last
+/// known spot rather than jumping somewhere arbitrary, and a warning gets
+/// logged once until it has usable state vectors again.
 pub(crate) fn update_active_vessel_resource(
     query: Query<(
         &RootSpacePosition,
@@ -106,13 +128,27 @@ pub(crate) fn update_active_vessel_resource(
         &CelestialParent,
     )>,
     active_vessel: Option<ResMut<ActiveVessel>>,
+    mut warned: Local<bool>,
 ) {
     let Some(mut active_vessel) = active_vessel else {
         return;
     };
-    let Ok((position, velocity, parent)) = query.get(active_vessel.entity) else {
+
+    let state = query
+        .get(active_vessel.entity)
+        .ok()
+        .filter(|(position, velocity, _)| position.is_finite() && velocity.is_finite());
+    let Some((position, velocity, parent)) = state else {
+        if !*warned {
+            *warned = true;
+            warn!(
+                "Not recentering rigid space, as the active vessel {} has no usable state vectors",
+                active_vessel.entity
+            );
+        }
         return;
     };
+    *warned = false;
 
     active_vessel.prev_tick_parent = parent.entity;
     active_vessel.prev_tick_position = *position;
@@ -122,6 +158,8 @@ pub(crate) fn update_active_vessel_resource(
 fn pre_rapier_frame_switch_inner(
     root_pos: RootSpacePosition,
     root_vel: RootSpaceLinearVelocity,
+    root_angle: Option<RootSpaceAngle>,
+    root_angvel: Option<RootSpaceAngularVelocity>,
     mut transform: Mut<'_, Transform>,
     mut rigid_vel: Mut<'_, RigidSpaceVelocity>,
     active_vessel: &ActiveVessel,
@@ -132,6 +170,13 @@ fn pre_rapier_frame_switch_inner(
         .extend(0.0);
     transform.scale = Vec3::ONE;
     rigid_vel.linvel = *root_vel.to_rigid_space_linear_velocity(active_vessel.prev_tick_velocity);
+
+    if let Some(root_angle) = root_angle {
+        transform.rotation = root_angle.to_rigid_space_rotation();
+    }
+    if let Some(root_angvel) = root_angvel {
+        rigid_vel.angvel = root_angvel.to_rigid_space_angular_velocity();
+    }
 }
 
 /// Sets transform into the rigid transform so that Rapier can process it
@@ -140,6 +185,8 @@ pub(crate) fn pre_rapier_frame_switch(
         (
             &RootSpacePosition,
             &RootSpaceLinearVelocity,
+            Option<&RootSpaceAngle>,
+            Option<&RootSpaceAngularVelocity>,
             &mut Transform,
             &mut RigidSpaceVelocity,
         ),
@@ -153,11 +200,19 @@ pub(crate) fn pre_rapier_frame_switch(
         return;
     };
 
-    query
-        .into_iter()
-        .for_each(|(&root_pos, &root_vel, transform, rigid_vel)| {
-            pre_rapier_frame_switch_inner(root_pos, root_vel, transform, rigid_vel, &active_vessel);
-        });
+    query.into_iter().for_each(
+        |(&root_pos, &root_vel, root_angle, root_angvel, transform, rigid_vel)| {
+            pre_rapier_frame_switch_inner(
+                root_pos,
+                root_vel,
+                root_angle.copied(),
+                root_angvel.copied(),
+                transform,
+                rigid_vel,
+                &active_vessel,
+            );
+        },
+    );
 
     terrestrial_cels.into_iter().for_each(|mut transform| {
         // Translation is done at the collider level
@@ -167,7 +222,7 @@ pub(crate) fn pre_rapier_frame_switch(
 
 /// Sets transform into the camera transform so Bevy can render it
 pub(crate) fn post_rapier_frame_switch(
-    query: Query<(&mut Transform, &RootSpacePosition), Without<Terrain>>,
+    query: Query<(&mut Transform, &RootSpacePosition, Option<&RootSpaceAngle>), Without<Terrain>>,
     terrestrial_cels: Option<Query<&mut Transform, With<Terrain>>>,
     sim_camera: Query<(&mut SimCameraOffset, &SimCameraZoom, &Camera), With<SimCamera>>,
     camera_offset_query: Query<&RootSpacePosition>,
@@ -180,12 +235,17 @@ pub(crate) fn post_rapier_frame_switch(
 
     let cam_offset = cam_offset.mutably().get_root_position(camera_offset_query);
 
-    query.into_iter().for_each(|(mut transform, &root_pos)| {
-        let rotation = transform.rotation;
-        *transform = root_pos
-            .to_camera_space_transform(rotation, cam_offset, cam_zoom)
-            .0;
-    });
+    query
+        .into_iter()
+        .for_each(|(mut transform, &root_pos, root_angle)| {
+            // On-rails vessels don't get rotated by Rapier, so their
+            // rotation only gets updated in root space
+            let rotation =
+                root_angle.map_or(transform.rotation, |angle| angle.to_rigid_space_rotation());
+            *transform = root_pos
+                .to_camera_space_transform(rotation, cam_offset, cam_zoom)
+                .0;
+        });
 
     if let Some(terrestrial_cels) = terrestrial_cels {
         terrestrial_cels.into_iter().for_each(|mut transform| {
@@ -195,3 +255,51 @@ pub(crate) fn post_rapier_frame_switch(
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{ecs::system::RunSystemOnce, math::DVec2};
+
+    #[test]
+    fn active_vessel_without_state_keeps_center() {
+        let mut world = World::new();
+
+        let parent = world.spawn_empty().id();
+        let vessel = world
+            .spawn((
+                RootSpacePosition(DVec2::new(10.0, 20.0)),
+                RootSpaceLinearVelocity(DVec2::new(1.0, 2.0)),
+                CelestialParent { entity: parent },
+            ))
+            .id();
+        world.insert_resource(ActiveVessel {
+            entity: vessel,
+            prev_tick_parent: parent,
+            prev_tick_position: RootSpacePosition(DVec2::ZERO),
+            prev_tick_velocity: RootSpaceLinearVelocity(DVec2::ZERO),
+        });
+
+        world
+            .run_system_once(update_active_vessel_resource)
+            .unwrap();
+        let active = world.resource::<ActiveVessel>();
+        assert_eq!(active.prev_tick_position.0, DVec2::new(10.0, 20.0));
+        assert_eq!(active.prev_tick_velocity.0, DVec2::new(1.0, 2.0));
+
+        // Neither unusable nor missing state vectors move rigid space
+        world.get_mut::<RootSpacePosition>(vessel).unwrap().0 = DVec2::NAN;
+        world
+            .run_system_once(update_active_vessel_resource)
+            .unwrap();
+        world.despawn(vessel);
+        world
+            .run_system_once(update_active_vessel_resource)
+            .unwrap();
+
+        let active = world.resource::<ActiveVessel>();
+        assert_eq!(active.prev_tick_position.0, DVec2::new(10.0, 20.0));
+        assert_eq!(active.prev_tick_velocity.0, DVec2::new(1.0, 2.0));
+        assert_eq!(active.prev_tick_parent, parent);
+    }
+}
@@ -1,15 +1,18 @@
 //! Newtonian gravity application for loaded vessels
 
-use bevy::{ecs::query::QueryData, prelude::*};
+use bevy::{ecs::query::QueryData, math::DVec2, prelude::*};
+use bevy_rapier2d::prelude::AdditionalMassProperties;
 
 use crate::{
     components::main_game::{
-        celestial::CelestialBody,
+        celestial::{CelestialBody, GravitationalParameter},
         frames::{RootSpaceLinearVelocity, RootSpacePosition},
         relations::CelestialParent,
         vessel::Vessel,
     },
-    consts::{FilterLoadedVessels, GRAVITATIONAL_CONSTANT, GRAVITY_MIN_RADIUS},
+    consts::{FilterLoadedVessels, GRAVITY_MIN_RADIUS},
+    math::body_mass,
+    resources::simulation::{ActiveVessel, GravityConstants, PhysicsConfig, SignificantBodies},
 };
 
 #[derive(QueryData)]
@@ -19,6 +22,16 @@ pub(crate) struct VesselData {
     pos: &'static mut RootSpacePosition,
     vel: &'static mut RootSpaceLinearVelocity,
     parent: &'static CelestialParent,
+    mass: Option<&'static AdditionalMassProperties>,
+}
+
+/// A loaded vessel pulling on the other ones, as of the start of the tick.
+#[derive(Clone, Copy)]
+struct Attractor {
+    entity: Entity,
+    pos: RootSpacePosition,
+    vel: RootSpaceLinearVelocity,
+    mu: f64,
 }
 
 #[derive(QueryData)]
@@ -26,22 +39,111 @@ pub(crate) struct ParentData {
     pos: &'static RootSpacePosition,
     vel: &'static RootSpaceLinearVelocity,
     body_data: &'static CelestialBody,
+    mu: Option<&'static GravitationalParameter>,
+}
+
+/// Gets the gravitational parameter of a celestial body,
+/// recalculating it from the mass if the body lacks a
+/// [`GravitationalParameter`].
+#[must_use]
+pub(crate) fn gravitational_parameter(
+    body: &CelestialBody,
+    mu: Option<&GravitationalParameter>,
+    constants: &GravityConstants,
+) -> f64 {
+    mu.map_or_else(|| body.gravitational_parameter(constants), |mu| mu.0)
+}
+
+pub(crate) fn update_gravitational_parameters(
+    mut bodies: Query<(Ref<CelestialBody>, &mut GravitationalParameter)>,
+    constants: Res<GravityConstants>,
+) {
+    for (body, mut mu) in &mut bodies {
+        if body.is_changed() || constants.is_changed() {
+            mu.0 = body.gravitational_parameter(&constants);
+        }
+    }
+}
+
+/// Gets the gravitational acceleration at `pos` towards a body at `body_pos`.
+fn pull(mu: f64, pos: RootSpacePosition, body_pos: RootSpacePosition) -> DVec2 {
+    let r_sq = pos.distance_squared_to(body_pos).max(GRAVITY_MIN_RADIUS);
+    mu * (body_pos - pos) / (r_sq.sqrt() * r_sq)
+}
+
+/// Finds the celestial bodies pulling on the active vessel harder
+/// than [`PhysicsConfig::significant_gravity_threshold`], strongest first.
+///
+/// The pulls only get compared at the active vessel's position, as
+/// every other loaded vessel is close enough to it to feel the same ones.
+pub(crate) fn update_significant_bodies(
+    mut significant: ResMut<SignificantBodies>,
+    active_vessel: Option<Res<ActiveVessel>>,
+    vessels: Query<&RootSpacePosition, With<Vessel>>,
+    celestials: Query<(Entity, ParentData), Without<Vessel>>,
+    config: Res<PhysicsConfig>,
+    constants: Res<GravityConstants>,
+) {
+    significant.0.clear();
+
+    let Some(active_pos) = active_vessel.and_then(|active| vessels.get(active.entity).ok()) else {
+        return;
+    };
+
+    let mut pulls: Vec<_> = celestials
+        .iter()
+        .map(|(entity, body)| {
+            let mu = gravitational_parameter(body.body_data, body.mu, &constants);
+            (entity, pull(mu, *active_pos, *body.pos).length())
+        })
+        .filter(|&(_, pull)| pull >= config.significant_gravity_threshold)
+        .collect();
+
+    pulls.sort_unstable_by(|(_, a), (_, b)| b.total_cmp(a));
+    significant
+        .0
+        .extend(pulls.into_iter().map(|(entity, _)| entity));
 }
 
 fn apply_gravity_inner(
     mut vessel: VesselDataItem,
     celestials: Query<ParentData, Without<Vessel>>,
+    significant: &[Entity],
+    attractors: &[Attractor],
     time: &Time,
+    constants: &GravityConstants,
 ) {
-    let Ok(parent) = celestials.get(vessel.parent.entity) else {
+    let entity = vessel.name.entity;
+    let parent = vessel.parent.entity;
+    if !celestials.contains(parent) {
         error!("Vessel {} is missing a parent!", vessel.name);
         return;
-    };
+    }
 
-    let parent_mass = parent.body_data.mass;
-    let parent_mu = parent_mass * GRAVITATIONAL_CONSTANT;
+    // We assume the bodies' orbits, if any, have negligible local curvature,
+    // and likewise for the other vessels' trajectories
+    let accel_at = |pos: RootSpacePosition, time_offset: f64| -> DVec2 {
+        let from_bodies: DVec2 = core::iter::once(parent)
+            .chain(significant.iter().copied().filter(|&body| body != parent))
+            .filter_map(|body| celestials.get(body).ok())
+            .map(|body| {
+                let mu = gravitational_parameter(body.body_data, body.mu, constants);
+                let body_pos = *body.pos + body.vel.0 * time_offset;
+                pull(mu, pos, body_pos)
+            })
+            .sum();
 
-    let rel_pos = vessel.pos.0 - parent.pos.0;
+        let from_vessels: DVec2 = attractors
+            .iter()
+            .filter(|attractor| attractor.entity != entity)
+            .map(|attractor| {
+                let attractor_pos = attractor.pos + attractor.vel.0 * time_offset;
+                pull(attractor.mu, pos, attractor_pos)
+            })
+            .sum();
+
+        from_bodies + from_vessels
+    };
 
     let delta_secs = time.delta_secs_f64();
 
@@ -49,24 +151,263 @@ fn apply_gravity_inner(
     // p(t + Δt) = p(t) + v(t) * Δt + 0.5a(t) * Δt^2;
     // v(t + Δt) = v(t) + 0.5 * (a(t) + a(t + Δt)) * Δt;
 
-    let r_sq = rel_pos.length_squared().max(GRAVITY_MIN_RADIUS);
-    let accel = -parent_mu * rel_pos / (r_sq.sqrt() * r_sq);
-    vessel.pos.0 += vessel.vel.0 * delta_secs + 0.5 * accel * delta_secs.powi(2);
+    let accel = accel_at(*vessel.pos, 0.0);
+    *vessel.pos += vessel.vel.0 * delta_secs + 0.5 * accel * delta_secs.powi(2);
 
-    // We assume the parent's orbit, if any, has negligible local curvature
-    let new_parent_pos = RootSpacePosition(parent.pos.0 + parent.vel.0 * delta_secs);
-    let new_rel_pos = vessel.pos.0 - new_parent_pos.0;
-    let new_r_sq = new_rel_pos.length_squared().max(GRAVITY_MIN_RADIUS);
-    let new_accel = -parent_mu * new_rel_pos / (new_r_sq.sqrt() * new_r_sq);
-    vessel.vel.0 += 0.5 * (accel + new_accel) * delta_secs;
+    let new_accel = accel_at(*vessel.pos, delta_secs);
+    *vessel.vel += 0.5 * (accel + new_accel) * delta_secs;
 }
 
+/// Integrates the state vectors of loaded vessels.
+///
+/// Vessels get pulled by their parent body, as well as every other
+/// body in [`SignificantBodies`]. With
+/// [`PhysicsConfig::vessel_self_gravity`] set, they also get pulled by
+/// each other, as long as there aren't too many of them.
+///
+/// This uses velocity Verlet, which is symplectic, so a coasting
+/// vessel's orbital energy oscillates rather than drifting away over
+/// long coasts. Rapier never sees gravity as a force.
+///
+/// On-rails vessels are left out, as their state vectors get
+/// written from their [`RailMode`][crate::components::main_game::relations::RailMode]
+/// every tick instead.
 pub(crate) fn apply_gravity_and_velocity(
     mut vessels: Query<VesselData, FilterLoadedVessels>,
     celestials: Query<ParentData, Without<Vessel>>,
+    significant: Res<SignificantBodies>,
+    config: Res<PhysicsConfig>,
     time: Res<Time>,
+    constants: Res<GravityConstants>,
 ) {
+    let attractors: Vec<_> = match config.vessel_self_gravity {
+        Some(self_gravity) if vessels.iter().count() <= self_gravity.max_vessels => vessels
+            .iter()
+            .filter_map(|vessel| {
+                // Vessels pull as point masses at their position,
+                // whatever their center of mass offset
+                let mu = body_mass(vessel.mass?) * constants.gravitational_constant;
+                (mu > 0.0).then_some(Attractor {
+                    entity: vessel.name.entity,
+                    pos: *vessel.pos,
+                    vel: *vessel.vel,
+                    mu,
+                })
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
     vessels.iter_mut().for_each(|vessel| {
-        apply_gravity_inner(vessel, celestials, &time);
+        apply_gravity_inner(
+            vessel,
+            celestials,
+            &significant.0,
+            &attractors,
+            &time,
+            &constants,
+        );
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::simulation::VesselSelfGravity;
+    use core::time::Duration;
+
+    #[test]
+    fn significant_bodies_culled_and_sorted() {
+        let mut app = App::new();
+        app.insert_resource(PhysicsConfig {
+            significant_gravity_threshold: 1e-3,
+            ..PhysicsConfig::DEFAULT
+        });
+        app.init_resource::<GravityConstants>();
+        app.init_resource::<SignificantBodies>();
+        app.add_systems(Update, update_significant_bodies);
+
+        let mut spawn_body = |pos: DVec2, mu: f64| {
+            app.world_mut()
+                .spawn((
+                    CelestialBody::default(),
+                    GravitationalParameter(mu),
+                    RootSpacePosition(pos),
+                    RootSpaceLinearVelocity(DVec2::ZERO),
+                ))
+                .id()
+        };
+
+        // ~9.8 m/s², ~2e-3 m/s² and ~4e-6 m/s² at the vessel respectively
+        let planet = spawn_body(DVec2::ZERO, 4e14);
+        let moon = spawn_body(DVec2::new(5.64e7, 0.0), 5e12);
+        let far_moon = spawn_body(DVec2::new(-4e9, 0.0), 5e13);
+
+        let vessel = app
+            .world_mut()
+            .spawn((Vessel, RootSpacePosition(DVec2::new(6.4e6, 0.0))))
+            .id();
+        app.insert_resource(ActiveVessel {
+            entity: vessel,
+            prev_tick_position: RootSpacePosition(DVec2::new(6.4e6, 0.0)),
+            prev_tick_velocity: RootSpaceLinearVelocity(DVec2::ZERO),
+            prev_tick_parent: planet,
+        });
+
+        app.update();
+        let significant = &app.world().resource::<SignificantBodies>().0;
+        assert_eq!(significant, &[planet, moon]);
+        assert!(!significant.contains(&far_moon));
+
+        app.world_mut()
+            .resource_mut::<PhysicsConfig>()
+            .significant_gravity_threshold = f64::INFINITY;
+        app.update();
+        assert!(app.world().resource::<SignificantBodies>().0.is_empty());
+    }
+
+    #[test]
+    fn vessels_fall_toward_each_other() {
+        const MASS: f32 = 1e12;
+
+        let mut app = App::new();
+        app.init_resource::<GravityConstants>();
+        app.init_resource::<SignificantBodies>();
+        app.init_resource::<Time>();
+        app.add_systems(Update, apply_gravity_and_velocity);
+
+        // Massless, so that only the vessels pull on each other
+        let body = app
+            .world_mut()
+            .spawn((
+                CelestialBody::default(),
+                GravitationalParameter(0.0),
+                RootSpacePosition(DVec2::ZERO),
+                RootSpaceLinearVelocity(DVec2::ZERO),
+            ))
+            .id();
+
+        let mut spawn_vessel = |x: f64| {
+            app.world_mut()
+                .spawn((
+                    Vessel,
+                    RootSpacePosition(DVec2::new(x, 1e6)),
+                    RootSpaceLinearVelocity(DVec2::ZERO),
+                    CelestialParent { entity: body },
+                    AdditionalMassProperties::Mass(MASS),
+                ))
+                .id()
+        };
+
+        let left = spawn_vessel(-50.0);
+        let right = spawn_vessel(50.0);
+
+        let step = |app: &mut App, self_gravity| {
+            app.insert_resource(PhysicsConfig {
+                vessel_self_gravity: self_gravity,
+                ..PhysicsConfig::DEFAULT
+            });
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs(1));
+            app.update();
+
+            let vel = |entity| {
+                app.world()
+                    .get::<RootSpaceLinearVelocity>(entity)
+                    .unwrap()
+                    .0
+            };
+            (vel(left), vel(right))
+        };
+
+        // Disabled by default, and too many vessels to count
+        let (left_vel, right_vel) = step(&mut app, None);
+        assert_eq!((left_vel, right_vel), (DVec2::ZERO, DVec2::ZERO));
+
+        let (left_vel, right_vel) = step(&mut app, Some(VesselSelfGravity { max_vessels: 1 }));
+        assert_eq!((left_vel, right_vel), (DVec2::ZERO, DVec2::ZERO));
+
+        let (left_vel, right_vel) = step(&mut app, Some(VesselSelfGravity { max_vessels: 2 }));
+
+        let expected = GravityConstants::default().gravitational_constant * f64::from(MASS) / 1e4;
+        assert!((left_vel.x - expected).abs() / expected < 1e-3);
+        assert!(left_vel.y.abs() < 1e-12);
+        assert!((left_vel + right_vel).length() < 1e-12);
+
+        let left_pos = app.world().get::<RootSpacePosition>(left).unwrap().0;
+        let right_pos = app.world().get::<RootSpacePosition>(right).unwrap().0;
+        assert!(right_pos.x - left_pos.x < 100.0);
+        assert!((left_pos.x + right_pos.x).abs() < 1e-9);
+    }
+
+    #[test]
+    fn coasting_energy_does_not_drift() {
+        const MU: f64 = 3.986e14;
+        const TICKS: u32 = 10_000;
+
+        let mut app = App::new();
+        app.insert_resource(PhysicsConfig::DEFAULT);
+        app.init_resource::<GravityConstants>();
+        app.init_resource::<SignificantBodies>();
+        app.init_resource::<Time>();
+        app.add_systems(Update, apply_gravity_and_velocity);
+
+        let body = app
+            .world_mut()
+            .spawn((
+                CelestialBody::default(),
+                GravitationalParameter(MU),
+                RootSpacePosition(DVec2::ZERO),
+                RootSpaceLinearVelocity(DVec2::ZERO),
+            ))
+            .id();
+
+        // Slightly elliptical, so that the speed and radius both vary
+        let start_pos = DVec2::new(7e6, 0.0);
+        let start_vel = DVec2::new(0.0, 1.05 * (MU / start_pos.length()).sqrt());
+        let vessel = app
+            .world_mut()
+            .spawn((
+                Vessel,
+                RootSpacePosition(start_pos),
+                RootSpaceLinearVelocity(start_vel),
+                CelestialParent { entity: body },
+            ))
+            .id();
+
+        let energy = |pos: DVec2, vel: DVec2| 0.5 * vel.length_squared() - MU / pos.length();
+        let start_energy = energy(start_pos, start_vel);
+
+        // The force-based alternative, i.e. explicit Euler
+        let (mut euler_pos, mut euler_vel) = (start_pos, start_vel);
+        let mut max_drift: f64 = 0.0;
+
+        for _ in 0..TICKS {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs(1));
+            app.update();
+
+            let pos = app.world().get::<RootSpacePosition>(vessel).unwrap().0;
+            let vel = app
+                .world()
+                .get::<RootSpaceLinearVelocity>(vessel)
+                .unwrap()
+                .0;
+            max_drift = max_drift.max(((energy(pos, vel) - start_energy) / start_energy).abs());
+
+            let accel = -MU * euler_pos / euler_pos.length().powi(3);
+            euler_pos += euler_vel;
+            euler_vel += accel;
+        }
+
+        let euler_drift = ((energy(euler_pos, euler_vel) - start_energy) / start_energy).abs();
+
+        assert!(max_drift < 1e-5, "energy drifted by {max_drift}");
+        assert!(
+            euler_drift > 100.0 * max_drift,
+            "Verlet drifted by {max_drift}, Euler by {euler_drift}"
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! Arrows at the edge of the flight view pointing towards off-screen entities.
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use strum::VariantArray;
+
+use crate::{
+    assets::fonts::URI_FONT_JETBRAINS_MONO,
+    components::main_game::{
+        camera::{SimCamera, SimCameraOffset, SimCameraZoom},
+        frames::RootSpacePosition,
+        ui::indicators::EdgeIndicatorLabel,
+    },
+    consts::{colors::EDGE_INDICATOR, si::SIPrefix},
+    resources::{controls::EdgeIndicators, simulation::ActiveVessel},
+    systems::main_game::camera::FALLBACK_VIEWPORT_SIZE,
+};
+
+/// How far from the edge of the screen the arrows' tips are,
+/// in logical pixels.
+const EDGE_MARGIN: f32 = 16.0;
+
+/// How long each arrow is, in logical pixels.
+const ARROW_LENGTH: f32 = 24.0;
+
+/// How far from the arrows' tails the distance text is,
+/// in logical pixels.
+const LABEL_GAP: f32 = 4.0;
+
+const LABEL_FONT_SIZE: f32 = 14.0;
+
+/// Gets where to put the tip of the arrow pointing towards something at
+/// `screen_pos`, given relative to the center of a viewport of size
+/// `viewport`, with +Y pointing up.
+///
+/// The tip goes where the line from the center of the viewport to
+/// `screen_pos` crosses the viewport's edges, brought `margin` closer
+/// to the center on both axes.
+///
+/// Returns [`None`] if `screen_pos` is within the viewport.
+#[must_use]
+pub(crate) fn edge_position(screen_pos: Vec2, viewport: Vec2, margin: f32) -> Option<Vec2> {
+    let half = viewport / 2.0;
+    if screen_pos.x.abs() <= half.x && screen_pos.y.abs() <= half.y {
+        return None;
+    }
+
+    // Axes the position isn't off on give an infinite scale, which
+    // the other axis then wins over
+    let bounds = (half - margin).max(Vec2::ZERO);
+    let scale = (bounds / screen_pos.abs()).min_element();
+
+    Some(screen_pos * scale)
+}
+
+/// Formats a distance with an SI prefix, to one decimal place.
+#[must_use]
+fn format_distance(meters: f64) -> String {
+    // Rounding to one decimal place shouldn't give "1000.0"
+    let prefix = SIPrefix::VARIANTS
+        .iter()
+        .copied()
+        .find(|prefix| meters < 999.95 * prefix.multiplier())
+        .unwrap_or(SIPrefix::Quetta);
+    let scaled = meters / prefix.multiplier();
+
+    match prefix.to_char() {
+        Some(prefix) => format!("{scaled:.1} {prefix}m"),
+        None => format!("{scaled:.1} m"),
+    }
+}
+
+/// Positions a label so that it grows away from the nearest edges
+/// of the screen, given the position of its anchor relative to the
+/// center of the screen.
+fn place_label(node: &mut Node, anchor: Vec2, half_viewport: Vec2) {
+    node.position_type = PositionType::Absolute;
+
+    (node.left, node.right) = if anchor.x < 0.0 {
+        (Val::Px(half_viewport.x + anchor.x), Val::Auto)
+    } else {
+        (Val::Auto, Val::Px(half_viewport.x - anchor.x))
+    };
+    (node.top, node.bottom) = if anchor.y < 0.0 {
+        (Val::Auto, Val::Px(half_viewport.y + anchor.y))
+    } else {
+        (Val::Px(half_viewport.y - anchor.y), Val::Auto)
+    };
+}
+
+/// Draws an arrow at the edge of the flight view for every off-screen
+/// entity in [`EdgeIndicators`], labelled with its distance from the
+/// active vessel.
+///
+/// The active vessel's parent body is the one it had as of the last
+/// fixed tick.
+///
+/// Like the orbital markers, the arrows get drawn relative to the
+/// simulation camera, which only has a rotation of its own.
+pub(crate) fn draw_edge_indicators(
+    mut commands: Commands,
+    mut gizmos: Gizmos,
+    indicators: Res<EdgeIndicators>,
+    active_vessel: Option<Res<ActiveVessel>>,
+    cameras: Query<(&Camera, &Transform, &SimCameraOffset, &SimCameraZoom), With<SimCamera>>,
+    positions: Query<&RootSpacePosition>,
+    mut labels: Query<(Entity, &EdgeIndicatorLabel, &mut Node, &mut Text)>,
+    server: Res<AssetServer>,
+) {
+    let mut existing: HashMap<Entity, Entity> = labels
+        .iter()
+        .map(|(label, indicator, ..)| (indicator.target, label))
+        .collect();
+
+    let camera = cameras.iter().find(|(camera, ..)| camera.is_active);
+
+    if let Some(active_vessel) = active_vessel
+        && let Some((camera, cam_transform, cam_offset, cam_zoom)) = camera
+        && let Ok(&vessel_pos) = positions.get(active_vessel.entity)
+    {
+        let viewport = camera
+            .logical_viewport_size()
+            .unwrap_or(FALLBACK_VIEWPORT_SIZE);
+        let half_viewport = viewport / 2.0;
+        let cam_pos = cam_offset.immutably().get_root_position(positions);
+        let rotation = cam_transform.rotation;
+
+        let parent = indicators
+            .parent_body
+            .then_some(active_vessel.prev_tick_parent);
+
+        for target in parent
+            .into_iter()
+            .chain(indicators.entities.iter().copied())
+        {
+            let Ok(&target_pos) = positions.get(target) else {
+                continue;
+            };
+
+            let camera_space = ((target_pos.0 - cam_pos.0) * cam_zoom.0).as_vec2();
+            let screen_pos = (rotation.inverse() * camera_space.extend(0.0)).truncate();
+            let Some(tip) = edge_position(screen_pos, viewport, EDGE_MARGIN) else {
+                continue;
+            };
+
+            let dir = tip.normalize_or_zero();
+            let tail = tip - dir * ARROW_LENGTH;
+            let to_world = |screen: Vec2| (rotation * screen.extend(0.0)).truncate();
+            gizmos.arrow_2d(to_world(tail), to_world(tip), EDGE_INDICATOR);
+
+            let text = format_distance(vessel_pos.distance_to(target_pos));
+            let anchor = tail - dir * LABEL_GAP;
+
+            match existing.remove(&target) {
+                Some(label) => {
+                    let Ok((.., mut node, mut label_text)) = labels.get_mut(label) else {
+                        continue;
+                    };
+                    place_label(&mut node, anchor, half_viewport);
+                    label_text.0 = text;
+                }
+                None => {
+                    let mut node = Node::default();
+                    place_label(&mut node, anchor, half_viewport);
+                    commands.spawn((
+                        EdgeIndicatorLabel { target },
+                        node,
+                        Text(text),
+                        TextFont::from(server.load::<Font>(URI_FONT_JETBRAINS_MONO))
+                            .with_font_size(LABEL_FONT_SIZE),
+                        TextColor(EDGE_INDICATOR),
+                    ));
+                }
+            }
+        }
+    }
+
+    // Whatever's left is back on-screen, or no longer tracked
+    for label in existing.into_values() {
+        commands.entity(label).despawn();
+    }
+}
+
+/// Removes every edge indicator label, e.g. when leaving the flight view.
+pub(crate) fn clear_edge_indicators(
+    mut commands: Commands,
+    labels: Query<Entity, With<EdgeIndicatorLabel>>,
+) {
+    for label in &labels {
+        commands.entity(label).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEWPORT: Vec2 = Vec2::new(1280.0, 720.0);
+
+    #[test]
+    fn on_screen_has_no_indicator() {
+        assert_eq!(edge_position(Vec2::ZERO, VIEWPORT, 16.0), None);
+        assert_eq!(
+            edge_position(Vec2::new(640.0, -360.0), VIEWPORT, 16.0),
+            None
+        );
+        assert_eq!(
+            edge_position(Vec2::new(-100.0, 300.0), VIEWPORT, 16.0),
+            None
+        );
+    }
+
+    #[test]
+    fn off_screen_clamps_to_edge() {
+        // Straight off the right edge
+        let tip = edge_position(Vec2::new(5000.0, 0.0), VIEWPORT, 16.0).unwrap();
+        assert!((tip - Vec2::new(624.0, 0.0)).length() < 1e-3, "{tip}");
+
+        // Off the top edge, which gets reached first along the way
+        let tip = edge_position(Vec2::new(-1000.0, 2000.0), VIEWPORT, 16.0).unwrap();
+        assert!((tip - Vec2::new(-172.0, 344.0)).length() < 1e-3, "{tip}");
+
+        // Off both edges, but past the left one by more
+        let tip = edge_position(Vec2::new(-6400.0, -720.0), VIEWPORT, 0.0).unwrap();
+        assert!((tip - Vec2::new(-640.0, -72.0)).length() < 1e-3, "{tip}");
+
+        // Every tip stays inside the viewport, along the same direction
+        for angle in (0..64u8).map(|i| f32::from(i) / 64.0 * core::f32::consts::TAU) {
+            let pos = Vec2::from_angle(angle) * 1e6;
+            let tip = edge_position(pos, VIEWPORT, 16.0).unwrap();
+
+            assert!(tip.x.abs() <= 624.0 + 1e-3 && tip.y.abs() <= 344.0 + 1e-3);
+            assert!(tip.x.abs() > 624.0 - 1e-3 || tip.y.abs() > 344.0 - 1e-3);
+            assert!(tip.angle_to(pos).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn distance_formatting() {
+        assert_eq!(format_distance(0.0), "0.0 m");
+        assert_eq!(format_distance(512.34), "512.3 m");
+        assert_eq!(format_distance(999.97), "1.0 km");
+        assert_eq!(format_distance(3.844e8), "384.4 Mm");
+    }
+}
@@ -0,0 +1,75 @@
+//! Readouts for flight instruments
+
+use bevy::prelude::*;
+
+use crate::{
+    components::main_game::{
+        celestial::{CelestialSpin, RotationPeriod},
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::CelestialParent,
+        vessel::{OrbitalVelocity, SurfaceVelocity},
+    },
+    consts::FilterLoadedVessels,
+};
+
+pub(crate) fn update_orbital_velocity(
+    mut vessels: Query<
+        (
+            NameOrEntity,
+            &RootSpacePosition,
+            &RootSpaceLinearVelocity,
+            &CelestialParent,
+            &mut OrbitalVelocity,
+        ),
+        FilterLoadedVessels,
+    >,
+    parents: Query<(&RootSpacePosition, &RootSpaceLinearVelocity)>,
+) {
+    for (name, pos, vel, parent, mut orbital_vel) in &mut vessels {
+        let Ok((parent_pos, parent_vel)) = parents.get(parent.entity) else {
+            error!("Vessel {name} is missing a parent!");
+            continue;
+        };
+
+        *orbital_vel = OrbitalVelocity::from_relative(pos.0 - parent_pos.0, vel.0 - parent_vel.0);
+    }
+}
+
+pub(crate) fn update_surface_velocity(
+    mut vessels: Query<
+        (
+            NameOrEntity,
+            &RootSpacePosition,
+            &RootSpaceLinearVelocity,
+            &CelestialParent,
+            &mut SurfaceVelocity,
+        ),
+        FilterLoadedVessels,
+    >,
+    parents: Query<(
+        &RootSpacePosition,
+        &RootSpaceLinearVelocity,
+        Option<&CelestialSpin>,
+    )>,
+) {
+    for (name, pos, vel, parent, mut surface_vel) in &mut vessels {
+        let Ok((parent_pos, parent_vel, spin)) = parents.get(parent.entity) else {
+            error!("Vessel {name} is missing a parent!");
+            continue;
+        };
+
+        *surface_vel = SurfaceVelocity::from_relative(
+            *pos - *parent_pos,
+            *vel - *parent_vel,
+            spin.map_or(0.0, |spin| spin.angular_velocity),
+        );
+    }
+}
+
+pub(crate) fn update_rotation_period(
+    mut bodies: Query<(&CelestialSpin, &mut RotationPeriod), Changed<CelestialSpin>>,
+) {
+    for (spin, mut period) in &mut bodies {
+        period.0 = spin.rotation_period();
+    }
+}
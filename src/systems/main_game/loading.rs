@@ -0,0 +1,268 @@
+//! Loading and unloading vessels based on their distance to the active vessel
+
+use bevy::{ecs::system::SystemChangeTick, prelude::*};
+use bevy_rapier2d::prelude::RigidBodyDisabled;
+use core::cmp::Reverse;
+
+use crate::{
+    components::main_game::{
+        celestial::{CelestialBody, CelestialSpin, GravitationalParameter},
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::{CelestialParent, RailMode},
+        vessel::{Debris, Vessel},
+    },
+    consts::{FilterLoadedVessels, FilterUnloadedVessels},
+    resources::simulation::{ActiveVessel, DebrisLimit, GravityConstants, PhysicsConfig},
+    systems::main_game::{gravity::gravitational_parameter, rail::rail_to_relative_sv},
+};
+
+type LoadedQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static RootSpacePosition,
+        &'static RootSpaceLinearVelocity,
+        &'static CelestialParent,
+        &'static mut RailMode,
+        Has<Debris>,
+    ),
+    FilterLoadedVessels,
+>;
+
+type UnloadedQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut RootSpacePosition,
+        &'static mut RootSpaceLinearVelocity,
+        &'static CelestialParent,
+        &'static RailMode,
+        Has<Debris>,
+    ),
+    FilterUnloadedVessels,
+>;
+
+type ParentQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static RootSpacePosition,
+        &'static RootSpaceLinearVelocity,
+        &'static CelestialBody,
+        Option<&'static GravitationalParameter>,
+        Option<&'static CelestialSpin>,
+    ),
+    (With<CelestialBody>, Without<Vessel>),
+>;
+
+/// Puts vessels far away from the active vessel on-rails, and takes
+/// vessels close to it off-rails.
+///
+/// Vessels get unloaded past [`PhysicsConfig::vessel_unload_distance`]
+/// and loaded within [`PhysicsConfig::vessel_load_distance`], or the
+/// shorter debris distances for [`Debris`]. The active vessel itself
+/// always stays loaded.
+///
+/// Unloaded vessels get their current state vectors turned into an orbit,
+/// unless they're landed, in which case they stay attached to the surface.
+/// Loaded vessels get their state vectors seeded from their rail, which
+/// the rigid-space velocity then gets derived from before Rapier steps.
+pub(crate) fn update_vessel_loading(
+    mut commands: Commands,
+    mut loaded: LoadedQuery,
+    mut unloaded: UnloadedQuery,
+    parents: ParentQuery,
+    active_vessel: Option<Res<ActiveVessel>>,
+    config: Res<PhysicsConfig>,
+    constants: Res<GravityConstants>,
+    time: Res<Time>,
+) {
+    let Some(active_vessel) = active_vessel else {
+        return;
+    };
+
+    let Some(active_pos) = loaded
+        .get(active_vessel.entity)
+        .map(|(_, pos, ..)| *pos)
+        .or_else(|_| unloaded.get(active_vessel.entity).map(|(_, pos, ..)| *pos))
+        .ok()
+    else {
+        return;
+    };
+
+    // The state vectors were last written at the end of the previous tick
+    let now = time.elapsed().saturating_sub(time.delta());
+
+    for (entity, pos, vel, parent, mut rail_mode, is_debris) in &mut loaded {
+        let unload_distance = if is_debris {
+            config.debris_unload_distance
+        } else {
+            config.vessel_unload_distance
+        };
+
+        let distance = pos.distance_to(active_pos);
+        if entity == active_vessel.entity || distance <= unload_distance {
+            continue;
+        }
+
+        let Ok((parent_pos, parent_vel, body, mu, _)) = parents.get(parent.entity) else {
+            continue;
+        };
+
+        if !rail_mode.is_surface() {
+            let orbit = pos
+                .relative_to(*vel, *parent_pos, *parent_vel)
+                .to_cached_orbit(
+                    gravitational_parameter(body, mu, &constants),
+                    now.as_secs_f64(),
+                );
+
+            *rail_mode = RailMode::Orbit(orbit);
+        }
+
+        commands.entity(entity).insert(RigidBodyDisabled);
+    }
+
+    for (entity, mut pos, mut vel, parent, &rail_mode, is_debris) in &mut unloaded {
+        let load_distance = if is_debris {
+            config.debris_load_distance
+        } else {
+            config.vessel_load_distance
+        };
+
+        let distance = pos.distance_to(active_pos);
+        if entity != active_vessel.entity && distance >= load_distance {
+            continue;
+        }
+
+        let Ok((parent_pos, parent_vel, .., spin)) = parents.get(parent.entity) else {
+            continue;
+        };
+
+        let body_rotation = spin.map_or(0.0, |spin| spin.angular_velocity);
+        if let Some(sv) = rail_to_relative_sv(rail_mode, now, body_rotation) {
+            *pos = *parent_pos + sv.position;
+            *vel = *parent_vel + sv.velocity;
+        }
+
+        commands.entity(entity).remove::<RigidBodyDisabled>();
+    }
+}
+
+/// Despawns the oldest [`Debris`] while there's more of it
+/// than the [`DebrisLimit`] allows.
+///
+/// The active vessel never gets despawned, even if it's debris.
+pub(crate) fn enforce_debris_limit(
+    mut commands: Commands,
+    debris: Query<(Entity, Ref<Debris>)>,
+    limit: Res<DebrisLimit>,
+    active_vessel: Option<Res<ActiveVessel>>,
+    ticks: SystemChangeTick,
+) {
+    let Some(excess) = debris.iter().count().checked_sub(limit.max_count) else {
+        return;
+    };
+    if excess == 0 {
+        return;
+    }
+
+    let active = active_vessel.map(|active| active.entity);
+    let mut candidates: Vec<_> = debris
+        .iter()
+        .filter(|&(entity, _)| Some(entity) != active)
+        .map(|(entity, debris)| {
+            let age = ticks.this_run().get().wrapping_sub(debris.added().get());
+            (age, entity)
+        })
+        .collect();
+
+    candidates.sort_by_key(|&(age, _)| Reverse(age));
+
+    for (_, entity) in candidates.into_iter().take(excess) {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::DVec2;
+
+    fn set_position(app: &mut App, entity: Entity, pos: DVec2) {
+        app.world_mut()
+            .get_mut::<RootSpacePosition>(entity)
+            .unwrap()
+            .0 = pos;
+    }
+
+    fn is_loaded(app: &App, entity: Entity) -> bool {
+        !app.world().entity(entity).contains::<RigidBodyDisabled>()
+    }
+
+    #[test]
+    fn load_with_hysteresis() {
+        let mut app = App::new();
+        app.insert_resource(PhysicsConfig::DEFAULT);
+        app.init_resource::<GravityConstants>();
+        app.init_resource::<Time>();
+        app.add_systems(Update, update_vessel_loading);
+
+        let body = app
+            .world_mut()
+            .spawn((
+                CelestialBody::default(),
+                GravitationalParameter(4e14),
+                RootSpacePosition(DVec2::ZERO),
+                RootSpaceLinearVelocity(DVec2::ZERO),
+            ))
+            .id();
+
+        let mut spawn_vessel = |pos: DVec2| {
+            app.world_mut()
+                .spawn((
+                    Vessel,
+                    RootSpacePosition(pos),
+                    RootSpaceLinearVelocity(DVec2::new(0.0, 6000.0)),
+                    CelestialParent { entity: body },
+                ))
+                .id()
+        };
+
+        let active = spawn_vessel(DVec2::new(1e7, 0.0));
+        let far = spawn_vessel(DVec2::new(1e7 + 3000.0, 0.0));
+        let between = spawn_vessel(DVec2::new(1e7 + 2400.0, 0.0));
+
+        app.insert_resource(ActiveVessel {
+            entity: active,
+            prev_tick_position: RootSpacePosition(DVec2::new(1e7, 0.0)),
+            prev_tick_velocity: RootSpaceLinearVelocity(DVec2::new(0.0, 6000.0)),
+            prev_tick_parent: body,
+        });
+
+        app.update();
+
+        assert!(is_loaded(&app, active));
+        assert!(!is_loaded(&app, far));
+        assert!(is_loaded(&app, between));
+        assert!(app.world().get::<RailMode>(far).unwrap().is_orbit());
+
+        // Closer than the unload distance, but not yet close enough to load
+        set_position(&mut app, active, DVec2::new(1e7 + 500.0, 0.0));
+        app.update();
+        assert!(!is_loaded(&app, far));
+
+        set_position(&mut app, active, DVec2::new(1e7 + 2000.0, 0.0));
+        app.update();
+        assert!(is_loaded(&app, far));
+        assert!(is_loaded(&app, between));
+
+        let far_ref = app.world().entity(far);
+        let far_pos = far_ref.get::<RootSpacePosition>().unwrap().0;
+        let far_vel = far_ref.get::<RootSpaceLinearVelocity>().unwrap().0;
+        assert!((far_pos - DVec2::new(1e7 + 3000.0, 0.0)).length() < 1e-3);
+        assert!((far_vel - DVec2::new(0.0, 6000.0)).length() < 1e-6);
+    }
+}
@@ -0,0 +1,460 @@
+//! The orbital map view.
+
+use bevy::{
+    math::{DVec2, Isometry2d},
+    prelude::*,
+};
+use keplerian_sim::{Orbit2D, OrbitTrait2D};
+
+use crate::{
+    components::main_game::{
+        camera::{AutoZoom, SimCamera, SimCameraOffset, SimCameraZoom},
+        celestial::{CelestialBody, GravitationalParameter},
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        map::OrbitMesh,
+        relations::{CelestialParent, RailMode},
+    },
+    consts::{
+        colors::{MAP_ORBIT, MAP_ORBIT_ESCAPE, MAP_ORBIT_SUBORBITAL, MAP_SOI},
+        controls::KB_TOGGLE_VIEW_MODE,
+    },
+    orbit::{OrbitClass, current_orbit, projection::project_orbit, sphere_of_influence},
+    resources::{
+        controls::{OrbitLineDetail, ViewMode},
+        debug::DebugDisplay,
+        simulation::ActiveVessel,
+    },
+    systems::main_game::camera::FALLBACK_VIEWPORT_SIZE,
+};
+
+type StateQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static RootSpacePosition,
+        &'static RootSpaceLinearVelocity,
+        Option<&'static CelestialParent>,
+        Option<&'static GravitationalParameter>,
+    ),
+>;
+
+/// An entity's orbit around its parent, derived from their state vectors.
+struct ParentOrbit {
+    parent: Entity,
+    orbit: Orbit2D,
+    distance: f64,
+}
+
+fn orbit_around_parent(entity: Entity, states: &StateQuery) -> Option<ParentOrbit> {
+    let (&pos, &vel, parent, _) = states.get(entity).ok()?;
+    let parent = parent?.entity;
+    let (&parent_pos, &parent_vel, _, mu) = states.get(parent).ok()?;
+
+    Some(ParentOrbit {
+        parent,
+        orbit: current_orbit(pos, vel, (parent_pos, parent_vel), mu?.0, 0.0)?,
+        distance: pos.distance_to(parent_pos),
+    })
+}
+
+pub(crate) fn toggle_view_mode(
+    mode: Res<State<ViewMode>>,
+    mut next_mode: ResMut<NextState<ViewMode>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+) {
+    if !keyboard.any_just_pressed(KB_TOGGLE_VIEW_MODE) {
+        return;
+    }
+
+    next_mode.set(match mode.get() {
+        ViewMode::Flight => ViewMode::Map,
+        ViewMode::Map => ViewMode::Flight,
+    });
+}
+
+/// Moves the camera to frame whatever the current [`ViewMode`] shows.
+///
+/// The map view focuses the active vessel's parent and zooms out to its
+/// whole orbit, while the flight view goes back to the active vessel
+/// at the zoom it had before. Both get animated through [`AutoZoom`].
+pub(crate) fn apply_view_mode(
+    mode: Res<State<ViewMode>>,
+    camera: Single<
+        (
+            Entity,
+            &Camera,
+            &mut SimCameraOffset,
+            &SimCameraZoom,
+            Option<&mut AutoZoom>,
+        ),
+        With<SimCamera>,
+    >,
+    active_vessel: Option<Res<ActiveVessel>>,
+    states: StateQuery,
+    positions: Query<&RootSpacePosition>,
+    mut flight_zoom: Local<Option<f64>>,
+    mut commands: Commands,
+) {
+    let Some(active_vessel) = active_vessel else {
+        return;
+    };
+
+    let (camera_entity, camera, mut offset, zoom, auto_zoom) = camera.into_inner();
+    let fill = auto_zoom.as_deref().copied().unwrap_or_default().fill;
+
+    let (focus, target) = match mode.get() {
+        ViewMode::Flight => {
+            // Nothing to go back to if the map was never opened
+            let Some(target) = flight_zoom.take() else {
+                return;
+            };
+            (active_vessel.entity, Some(target))
+        }
+        ViewMode::Map => {
+            let Some(parent_orbit) = orbit_around_parent(active_vessel.entity, &states) else {
+                warn!("cannot frame the map view: active vessel has no orbit");
+                return;
+            };
+
+            let viewport = camera
+                .logical_viewport_size()
+                .unwrap_or(FALLBACK_VIEWPORT_SIZE);
+            *flight_zoom = Some(zoom.0);
+
+            let target = SimCameraZoom::framing_orbit(
+                &parent_orbit.orbit,
+                parent_orbit.distance,
+                viewport,
+                fill,
+            );
+            (parent_orbit.parent, Some(target.0))
+        }
+    };
+
+    if offset.attach(focus, positions).is_err() {
+        return;
+    }
+
+    match auto_zoom {
+        Some(mut auto_zoom) => {
+            auto_zoom.last_focus = Some(focus);
+            auto_zoom.target = target;
+            auto_zoom.recenter = true;
+        }
+        None => {
+            commands.entity(camera_entity).insert(AutoZoom {
+                last_focus: Some(focus),
+                target,
+                recenter: true,
+                ..Default::default()
+            });
+        }
+    }
+}
+
+/// Regenerates the [`OrbitMesh`] of every entity whose orbit changed shape,
+/// adding or removing it as the rail starts or stops being an orbit.
+///
+/// Rails that got rewritten with about the same orbit, like those of loaded
+/// vessels every tick, keep their mesh.
+pub(crate) fn update_orbit_meshes(
+    mut commands: Commands,
+    query: Query<(Entity, &RailMode, Option<&mut OrbitMesh>), Changed<RailMode>>,
+    detail: Res<OrbitLineDetail>,
+) {
+    for (entity, rail_mode, mesh) in query {
+        match (rail_mode.as_orbit(), mesh) {
+            (Some(orbit), Some(mut mesh)) => {
+                if !mesh.follows(&orbit) {
+                    *mesh = OrbitMesh::from_orbit(&orbit, detail.points);
+                }
+            }
+            (Some(orbit), None) => {
+                commands
+                    .entity(entity)
+                    .insert(OrbitMesh::from_orbit(&orbit, detail.points));
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<OrbitMesh>();
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// Regenerates every [`OrbitMesh`] with the new [`OrbitLineDetail`].
+pub(crate) fn remesh_orbits(
+    query: Query<(&RailMode, &mut OrbitMesh)>,
+    detail: Res<OrbitLineDetail>,
+) {
+    for (rail_mode, mut mesh) in query {
+        if let Some(orbit) = rail_mode.as_orbit() {
+            *mesh = OrbitMesh::from_orbit(&orbit, detail.points);
+        }
+    }
+}
+
+type OrbiterQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static OrbitMesh,
+        &'static RailMode,
+        &'static CelestialParent,
+        Option<&'static CelestialBody>,
+    ),
+>;
+
+/// Gets the on-screen line of a cached orbit, relative to the camera.
+///
+/// The orbit gets anchored at its parent's current position,
+/// so orbits around moving parents (e.g. a moon's orbit around its
+/// planet) follow the parent around.
+fn orbit_line(
+    mesh: &OrbitMesh,
+    parent_pos: RootSpacePosition,
+    cam_pos: RootSpacePosition,
+    zoom: SimCameraZoom,
+) -> impl Iterator<Item = Vec2> {
+    let parent_cam_pos = parent_pos.0 - cam_pos.0;
+
+    mesh.decimated(mesh.extent() * zoom.0)
+        .map(move |pos| ((parent_cam_pos + pos) * zoom.0).as_vec2())
+}
+
+/// Gets the color to draw an orbit line in, so that crashing and
+/// escaping stand out from the rest.
+///
+/// Orbits around something other than a celestial body
+/// get drawn like stable ones.
+const fn orbit_color(class: Option<OrbitClass>) -> Color {
+    match class {
+        Some(OrbitClass::Suborbital) => MAP_ORBIT_SUBORBITAL,
+        Some(OrbitClass::Escape) => MAP_ORBIT_ESCAPE,
+        Some(OrbitClass::Stable) | None => MAP_ORBIT,
+    }
+}
+
+/// Draws the orbit lines and spheres of influence of everything
+/// in orbit, as far as the [`DebugDisplay`] allows.
+///
+/// Closed orbits get drawn as ellipses through [`project_orbit`],
+/// while open ones fall back to their [`OrbitMesh`].
+#[expect(clippy::cast_possible_truncation)]
+pub(crate) fn draw_map_view(
+    mut gizmos: Gizmos,
+    camera: Single<(&SimCameraOffset, &SimCameraZoom), With<SimCamera>>,
+    orbiters: OrbiterQuery,
+    bodies: Query<&CelestialBody>,
+    positions: Query<&RootSpacePosition>,
+    display: Res<DebugDisplay>,
+    detail: Res<OrbitLineDetail>,
+) {
+    let (offset, &zoom) = *camera;
+    let cam_pos = offset.immutably().get_root_position(positions);
+
+    if display.orbit_lines {
+        for (_, mesh, rail_mode, parent, _) in &orbiters {
+            let Ok(&parent_pos) = positions.get(parent.entity) else {
+                continue;
+            };
+
+            let orbit = rail_mode.as_orbit();
+            let class = orbit
+                .zip(bodies.get(parent.entity).ok())
+                .map(|(orbit, &body)| OrbitClass::of(&orbit, body));
+            let color = orbit_color(class);
+
+            match orbit.and_then(|orbit| project_orbit(&orbit, parent_pos, cam_pos, zoom)) {
+                Some(ellipse) => {
+                    let isometry = Isometry2d::new(
+                        ellipse.center.as_vec2(),
+                        Rot2::radians(ellipse.rotation as f32),
+                    );
+                    let half_size = DVec2::new(ellipse.semi_major_axis, ellipse.semi_minor_axis);
+
+                    gizmos
+                        .ellipse_2d(isometry, half_size.as_vec2(), color)
+                        .resolution(detail.points);
+                }
+                None => {
+                    gizmos.linestrip_2d(orbit_line(mesh, parent_pos, cam_pos, zoom), color);
+                }
+            }
+        }
+    }
+
+    if !display.soi_circles {
+        return;
+    }
+
+    for (entity, _, rail_mode, parent, body) in &orbiters {
+        if let Some(body) = body
+            && let Ok(parent_body) = bodies.get(parent.entity)
+            && let Some(orbit) = rail_mode.as_orbit()
+            && orbit.get_eccentricity() < 1.0
+        {
+            let soi = sphere_of_influence(orbit.get_semi_major_axis(), body.mass, parent_body.mass);
+            let Ok(body_pos) = positions.get(entity) else {
+                continue;
+            };
+
+            gizmos.circle_2d(
+                Isometry2d::from_translation(((body_pos.0 - cam_pos.0) * zoom.0).as_vec2()),
+                (soi * zoom.0) as f32,
+                MAP_SOI,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{components::main_game::map::ORBIT_MESH_POINTS, orbit::orbit_from_elements};
+    use bevy::ecs::system::RunSystemOnce;
+    use keplerian_sim::StateVectors2D;
+
+    #[test]
+    fn nested_orbits_follow_parents() {
+        let mut world = World::new();
+        let mu = 1e20;
+
+        let mut spawn = |pos: DVec2, parent: Option<(Entity, f64)>| {
+            let mut entity = world.spawn(RootSpacePosition(pos));
+            if let Some((parent, radius)) = parent {
+                let orbit = orbit_from_elements(radius, 0.0, 0.0, 0.0, mu);
+                entity.insert((
+                    CelestialParent { entity: parent },
+                    RailMode::Orbit(orbit),
+                    OrbitMesh::from_orbit(&orbit, ORBIT_MESH_POINTS),
+                ));
+            }
+            entity.id()
+        };
+
+        let star = spawn(DVec2::new(-3e7, 2e7), None);
+        let planet = spawn(DVec2::new(7e7, 2e7), Some((star, 1e8)));
+        let moon = spawn(DVec2::new(7e7, 2.2e7), Some((planet, 2e6)));
+        let probe = spawn(DVec2::new(7.01e7, 2.2e7), Some((moon, 1e5)));
+
+        let cam_pos = RootSpacePosition(DVec2::new(5e7, 1e7));
+        let zoom = SimCameraZoom(1e-5);
+
+        let centers = world
+            .run_system_once(
+                move |orbiters: OrbiterQuery, positions: Query<&RootSpacePosition>| {
+                    orbiters
+                        .iter()
+                        .map(|(entity, mesh, _, parent, _)| {
+                            let parent_pos = *positions.get(parent.entity).unwrap();
+                            let line = orbit_line(mesh, parent_pos, cam_pos, zoom);
+
+                            let mut points: Vec<_> = line.collect();
+                            // Closed orbits repeat their first point
+                            points.pop();
+
+                            #[expect(clippy::cast_precision_loss)]
+                            let len = points.len() as f32;
+                            let center = points.iter().sum::<Vec2>() / len;
+                            let radius =
+                                points.iter().map(|p| p.distance(center)).sum::<f32>() / len;
+                            (entity, center, radius)
+                        })
+                        .collect::<Vec<_>>()
+                },
+            )
+            .unwrap();
+
+        let mut drawn: Vec<_> = centers.iter().map(|&(entity, ..)| entity).collect();
+        drawn.sort_unstable();
+        let mut orbiters = vec![planet, moon, probe];
+        orbiters.sort_unstable();
+        assert_eq!(drawn, orbiters, "the star has no orbit to draw");
+
+        for (entity, center, radius) in centers {
+            let parent = world.get::<CelestialParent>(entity).unwrap().entity;
+            let parent_pos = world.get::<RootSpacePosition>(parent).unwrap();
+            let expected = ((parent_pos.0 - cam_pos.0) * zoom.0).as_vec2();
+
+            // Coarser orbits don't always get sampled perfectly evenly
+            assert!(
+                (center - expected).length() < 1e-2 * radius,
+                "orbit of {entity} centered at {center}, expected {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn orbit_mesh_regenerates_on_change() {
+        let mut app = App::new();
+        app.init_resource::<OrbitLineDetail>();
+        app.add_systems(
+            Update,
+            (
+                remesh_orbits.run_if(resource_changed::<OrbitLineDetail>),
+                update_orbit_meshes,
+            )
+                .chain(),
+        );
+
+        let orbit = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 8000.0),
+        }
+        .to_cached_orbit(3.986e14, 0.0);
+
+        let entity = app.world_mut().spawn(RailMode::Orbit(orbit)).id();
+        app.update();
+
+        let mesh_changed = |app: &App| {
+            app.world()
+                .entity(entity)
+                .get_ref::<OrbitMesh>()
+                .expect("orbit should have a mesh")
+                .last_changed()
+        };
+
+        let first = mesh_changed(&app);
+        app.update();
+        assert_eq!(mesh_changed(&app), first, "unchanged orbit got remeshed");
+
+        // Loaded vessels get the same orbit written back every tick
+        let rewritten = orbit
+            .get_state_vectors_at_time(10.0)
+            .to_cached_orbit(3.986e14, 10.0);
+        *app.world_mut().get_mut::<RailMode>(entity).unwrap() = RailMode::Orbit(rewritten);
+        app.update();
+        assert_eq!(mesh_changed(&app), first, "rewritten orbit got remeshed");
+
+        let faster = StateVectors2D {
+            position: DVec2::new(7e6, 0.0),
+            velocity: DVec2::new(0.0, 9000.0),
+        }
+        .to_cached_orbit(3.986e14, 0.0);
+        *app.world_mut().get_mut::<RailMode>(entity).unwrap() = RailMode::Orbit(faster);
+        app.update();
+        assert_ne!(
+            mesh_changed(&app),
+            first,
+            "changed orbit didn't get remeshed"
+        );
+        assert_eq!(
+            *app.world().get::<OrbitMesh>(entity).unwrap(),
+            OrbitMesh::from_orbit(&faster, ORBIT_MESH_POINTS)
+        );
+
+        app.insert_resource(OrbitLineDetail { points: 64 });
+        app.update();
+        assert_eq!(
+            *app.world().get::<OrbitMesh>(entity).unwrap(),
+            OrbitMesh::from_orbit(&faster, 64),
+            "mesh didn't follow the new detail"
+        );
+
+        *app.world_mut().get_mut::<RailMode>(entity).unwrap() = RailMode::None;
+        app.update();
+        assert!(!app.world().entity(entity).contains::<OrbitMesh>());
+    }
+}
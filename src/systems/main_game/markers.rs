@@ -0,0 +1,70 @@
+//! Prograde, retrograde and radial markers for the flight view.
+
+use bevy::{
+    math::{DVec2, Isometry2d},
+    prelude::*,
+};
+
+use crate::{
+    components::main_game::{
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::CelestialParent,
+        vessel::OrbitalMarkers,
+    },
+    consts::colors::{MARKER_PROGRADE, MARKER_RADIAL},
+    resources::simulation::ActiveVessel,
+};
+
+/// How far from the center of the screen the markers get drawn,
+/// in logical pixels.
+const MARKER_SCREEN_RADIUS: f32 = 240.0;
+
+/// How big each marker is, in logical pixels.
+const MARKER_SIZE: f32 = 10.0;
+
+/// Draws the active vessel's [`OrbitalMarkers`] around the center
+/// of the screen, like the markers on a navball.
+///
+/// The simulation camera has no translation or scale of its own, so
+/// drawing a root-space direction at a fixed distance from the origin
+/// puts it at a fixed distance from the center of the screen, with the
+/// camera's rotation applied.
+pub(crate) fn draw_orbital_markers(
+    mut gizmos: Gizmos,
+    active_vessel: Option<Res<ActiveVessel>>,
+    vessels: Query<(
+        &RootSpacePosition,
+        &RootSpaceLinearVelocity,
+        &CelestialParent,
+    )>,
+    parents: Query<(&RootSpacePosition, &RootSpaceLinearVelocity)>,
+) {
+    let Some(active_vessel) = active_vessel else {
+        return;
+    };
+    let Ok((pos, vel, parent)) = vessels.get(active_vessel.entity) else {
+        return;
+    };
+    let Ok((parent_pos, parent_vel)) = parents.get(parent.entity) else {
+        return;
+    };
+
+    let sv = pos.relative_to(*vel, *parent_pos, *parent_vel);
+    let markers = OrbitalMarkers::from_relative(sv.position, sv.velocity);
+    let at = |dir: DVec2| Isometry2d::from_translation(dir.as_vec2() * MARKER_SCREEN_RADIUS);
+
+    if let Some(dir) = markers.prograde {
+        gizmos.circle_2d(at(dir), MARKER_SIZE, MARKER_PROGRADE);
+    }
+    if let Some(dir) = markers.retrograde {
+        gizmos.circle_2d(at(dir), MARKER_SIZE, MARKER_PROGRADE);
+        gizmos.cross_2d(at(dir), MARKER_SIZE, MARKER_PROGRADE);
+    }
+    if let Some(dir) = markers.radial_out {
+        gizmos.circle_2d(at(dir), MARKER_SIZE, MARKER_RADIAL);
+    }
+    if let Some(dir) = markers.radial_in {
+        gizmos.circle_2d(at(dir), MARKER_SIZE, MARKER_RADIAL);
+        gizmos.cross_2d(at(dir), MARKER_SIZE, MARKER_RADIAL);
+    }
+}
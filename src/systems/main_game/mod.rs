@@ -1,8 +1,30 @@
+pub(crate) mod autopilot;
+pub(crate) mod camera;
 pub(crate) mod controls;
+pub(crate) mod crash;
+pub(crate) mod docking;
+pub(crate) mod drag;
+pub(crate) mod engine;
 pub(crate) mod frame_sync;
 pub(crate) mod gravity;
+#[cfg(feature = "not-headless")]
+pub(crate) mod indicators;
+pub(crate) mod instruments;
+pub(crate) mod loading;
+#[cfg(feature = "not-headless")]
+pub(crate) mod map;
+#[cfg(feature = "not-headless")]
+pub(crate) mod markers;
+pub(crate) mod origin;
+pub(crate) mod parts;
+pub(crate) mod pause;
 pub(crate) mod rail;
+pub(crate) mod reaction_wheel;
+pub(crate) mod soi;
+pub(crate) mod telemetry;
 pub(crate) mod terrain;
+pub(crate) mod ticks;
 pub(crate) mod transition;
 #[cfg(feature = "not-headless")]
 pub(crate) mod ui;
+pub(crate) mod warp;
@@ -0,0 +1,51 @@
+//! Moving the origin of root space along with the active vessel.
+
+use bevy::prelude::*;
+
+use crate::{
+    components::main_game::{
+        camera::{FocusTransition, SimCameraOffset},
+        frames::RootSpacePosition,
+    },
+    resources::simulation::{ActiveVessel, FloatingOrigin},
+};
+
+/// Moves the origin of root space to the active vessel once it's further
+/// than [`FloatingOrigin::threshold`] from it.
+///
+/// Every root-space position gets moved along with it, including the
+/// ones kept in resources and cameras, so that nothing relative changes.
+/// Velocities are left as they are, as the origin doesn't keep moving.
+pub(crate) fn rebase_floating_origin(
+    mut origin: ResMut<FloatingOrigin>,
+    active_vessel: Option<ResMut<ActiveVessel>>,
+    mut positions: Query<&mut RootSpacePosition>,
+    mut cameras: Query<(&mut SimCameraOffset, Option<&mut FocusTransition>)>,
+) {
+    let Some(mut active_vessel) = active_vessel else {
+        return;
+    };
+    let Ok(&active_pos) = positions.get(active_vessel.entity) else {
+        return;
+    };
+
+    if active_pos.0.length() <= origin.threshold {
+        return;
+    }
+
+    let new_origin = active_pos.0;
+
+    for mut pos in &mut positions {
+        pos.0 -= new_origin;
+    }
+    active_vessel.prev_tick_position.0 -= new_origin;
+
+    for (mut offset, focus) in &mut cameras {
+        offset.rebase(new_origin);
+        if let Some(progress) = focus.and_then(|focus| focus.into_inner().progress.as_mut()) {
+            progress.from.0 -= new_origin;
+        }
+    }
+
+    origin.offset += new_origin;
+}
@@ -0,0 +1,486 @@
+//! Multi-part vessels
+
+use bevy::{math::DVec2, platform::collections::HashSet, prelude::*};
+use bevy_rapier2d::prelude::{
+    AdditionalMassProperties, Collider, CollisionGroups, MassProperties, ReadMassProperties,
+    SolverGroups,
+};
+
+use crate::{
+    builders::vessel::VesselBuilder,
+    components::main_game::{
+        frames::{
+            RigidSpaceVelocity, RootSpaceAngle, RootSpaceAngularVelocity, RootSpaceLinearVelocity,
+            RootSpacePosition,
+        },
+        relations::{CelestialParent, ChildObjects, ParentBody, RailMode},
+        vessel::{DragProfile, Vessel, VesselPart},
+    },
+    consts::{CELESTIAL_COLLISION_GROUP, FilterLoadedVessels, VESSEL_COLLISION_GROUP},
+    math::{body_mass, quat_to_rot, rot_to_quat},
+    messages::parts::Stage,
+    resources::simulation::PhysicsConfig,
+};
+
+type RootPartQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        Ref<'static, VesselPart>,
+        Option<Ref<'static, ChildObjects>>,
+        &'static mut Collider,
+        &'static mut AdditionalMassProperties,
+    ),
+    With<Vessel>,
+>;
+
+pub(crate) type StagingRootQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static RootSpacePosition,
+        &'static mut RootSpaceLinearVelocity,
+        &'static RigidSpaceVelocity,
+        &'static Transform,
+        &'static CelestialParent,
+        &'static AdditionalMassProperties,
+        &'static mut VesselPart,
+    ),
+    FilterLoadedVessels,
+>;
+
+/// How the mass of a single part is spread out, relative to the root part.
+struct PartMass {
+    mass: f32,
+    /// The center of mass of the part, relative to the root part.
+    center: Vec2,
+    /// The angular inertia of the part around its own center of mass.
+    inertia: f32,
+}
+
+impl PartMass {
+    /// Gets the mass distribution of a part, assuming its mass is
+    /// spread evenly over its shape.
+    fn new(part: &VesselPart, offset: Vec2, angle: f32) -> Self {
+        let unit = part.shape.raw.mass_properties(1.0);
+        let shape_mass = unit.mass();
+
+        let inertia = if shape_mass > 0.0 {
+            unit.principal_inertia() * part.mass / shape_mass
+        } else {
+            0.0
+        };
+        let local_com = Vec2::new(unit.local_com.x, unit.local_com.y);
+
+        Self {
+            mass: part.mass,
+            center: offset + Vec2::from_angle(angle).rotate(local_com),
+            inertia,
+        }
+    }
+}
+
+/// Combines the mass of every part of a vessel into the mass
+/// properties of its root part.
+#[must_use]
+fn combine_part_masses(parts: &[PartMass]) -> MassProperties {
+    let mass: f32 = parts.iter().map(|part| part.mass).sum();
+
+    if mass <= 0.0 {
+        return MassProperties::default();
+    }
+
+    let center = parts
+        .iter()
+        .map(|part| part.center * part.mass)
+        .sum::<Vec2>()
+        / mass;
+
+    // Parallel axis theorem
+    let inertia = parts
+        .iter()
+        .map(|part| {
+            part.mass
+                .mul_add(part.center.distance_squared(center), part.inertia)
+        })
+        .sum();
+
+    MassProperties {
+        local_center_of_mass: center,
+        mass,
+        principal_inertia: inertia,
+    }
+}
+
+/// Lets vessels collide with each other if, and only if,
+/// [`PhysicsConfig::vessel_collisions`] is set.
+///
+/// Vessels always collide with celestial bodies.
+pub(crate) fn update_vessel_collision_groups(
+    config: Res<PhysicsConfig>,
+    mut vessels: Query<(Ref<Vessel>, &mut CollisionGroups, &mut SolverGroups)>,
+) {
+    let filters = if config.vessel_collisions {
+        CELESTIAL_COLLISION_GROUP | VESSEL_COLLISION_GROUP
+    } else {
+        CELESTIAL_COLLISION_GROUP
+    };
+
+    for (vessel, mut collision_groups, mut solver_groups) in &mut vessels {
+        if vessel.is_added() || config.is_changed() {
+            collision_groups.filters = filters;
+            solver_groups.filters = filters;
+        }
+    }
+}
+
+/// Rebuilds the compound collider and mass properties of multi-part
+/// vessels whenever their parts change.
+///
+/// Parts getting attached or detached, through staging or docking,
+/// change or remove the root's [`ChildObjects`], which triggers a rebuild.
+/// The root's [`AdditionalMassProperties`] gets the total mass of
+/// every part, at their combined center of mass.
+pub(crate) fn update_part_colliders(
+    roots: RootPartQuery,
+    parts: Query<Ref<VesselPart>, Without<Vessel>>,
+    mut removed_children: RemovedComponents<ChildObjects>,
+) {
+    let lost_children: HashSet<Entity> = removed_children.read().collect();
+
+    for (entity, root_part, children, mut collider, mut mass) in roots {
+        let is_changed = root_part.is_changed()
+            || children.as_ref().is_some_and(Ref::is_changed)
+            || lost_children.contains(&entity);
+
+        let children = children
+            .as_deref()
+            .map_or(&[][..], |children| children.as_slice());
+
+        let is_changed = is_changed
+            || children
+                .iter()
+                .filter_map(|&entity| parts.get(entity).ok())
+                .any(|part| part.is_changed());
+
+        if !is_changed {
+            continue;
+        }
+
+        let root_mass = PartMass::new(&root_part, Vec2::ZERO, 0.0);
+
+        if children.is_empty() {
+            *collider = Collider::compound(vec![(Vec2::ZERO, 0.0, root_part.shape.clone())]);
+            *mass = AdditionalMassProperties::MassProperties(combine_part_masses(&[root_mass]));
+            continue;
+        }
+
+        let mut shapes = vec![(Vec2::ZERO, 0.0, root_part.shape.clone())];
+        let mut masses = vec![root_mass];
+
+        for part in children.iter().filter_map(|&entity| parts.get(entity).ok()) {
+            shapes.push((part.offset, part.angle, part.shape.clone()));
+            masses.push(PartMass::new(&part, part.offset, part.angle));
+        }
+
+        *collider = Collider::compound(shapes);
+        *mass = AdditionalMassProperties::MassProperties(combine_part_masses(&masses));
+    }
+}
+
+/// Detaches staged parts from their vessels, turning them into
+/// vessels of their own.
+///
+/// The new vessel starts off where the part was, moving with the old
+/// vessel (including its spin) plus the separation impulse. Its
+/// [`RailMode`] gets calculated at the end of the tick like
+/// any other loaded vessel.
+pub(crate) fn handle_staging(
+    mut commands: Commands,
+    mut reader: MessageReader<Stage>,
+    parts: Query<(NameOrEntity, &VesselPart, &ParentBody), Without<Vessel>>,
+    mut roots: StagingRootQuery,
+) {
+    for &Stage {
+        part: entity,
+        impulse,
+    } in reader.read()
+    {
+        let Ok((name, part, parent_body)) = parts.get(entity) else {
+            warn!("Attempted to stage {entity}, which isn't an attached vessel part");
+            continue;
+        };
+
+        let Ok((
+            root_pos,
+            mut root_vel,
+            root_rigid_vel,
+            root_transform,
+            parent,
+            root_mass,
+            mut root_part,
+        )) = roots.get_mut(parent_body.entity)
+        else {
+            warn!("Vessel part {name} isn't attached to a loaded vessel; not staging");
+            continue;
+        };
+
+        let root_angle = quat_to_rot(root_transform.rotation);
+        let offset = DVec2::from_angle(root_angle).rotate(part.offset.as_dvec2());
+        let spin_vel = offset.perp() * f64::from(root_rigid_vel.angvel);
+
+        let part_mass = f64::from(part.mass);
+        let remaining_mass = body_mass(root_mass) - part_mass;
+
+        let part_vel = root_vel.0
+            + spin_vel
+            + if part_mass > 0.0 {
+                impulse / part_mass
+            } else {
+                DVec2::ZERO
+            };
+
+        if remaining_mass > 0.0 {
+            root_vel.0 -= impulse / remaining_mass;
+        }
+
+        let drag = DragProfile::from_aabb(
+            part.shape.raw.compute_local_aabb(),
+            DragProfile::DEFAULT_DRAG_COEFFICIENT,
+        );
+
+        commands.entity(entity).remove::<ParentBody>().insert((
+            VesselBuilder::<ColorMaterial>::base_bundle(),
+            part.shape.clone(),
+            AdditionalMassProperties::Mass(part.mass),
+            ReadMassProperties::default(),
+            drag,
+            *parent,
+            RailMode::None,
+            RootSpacePosition(root_pos.0 + offset),
+            RootSpaceLinearVelocity(part_vel),
+            RigidSpaceVelocity {
+                angvel: root_rigid_vel.angvel,
+                linvel: Vec2::NAN,
+            },
+            RootSpaceAngle(root_angle + f64::from(part.angle)),
+            RootSpaceAngularVelocity(f64::from(root_rigid_vel.angvel)),
+            Transform::from_rotation(rot_to_quat(root_angle + f64::from(part.angle))),
+        ));
+
+        // Makes the remaining vessel's collider and mass
+        // get rebuilt without the detached part
+        root_part.set_changed();
+    }
+}
+
+/// Keeps the transforms of non-root parts fixed relative to their root part.
+///
+/// This needs to run after the root part's transform
+/// has been converted into camera space.
+pub(crate) fn sync_part_transforms(
+    roots: Query<(&Transform, &ChildObjects), With<Vessel>>,
+    mut parts: Query<(&VesselPart, &mut Transform), Without<Vessel>>,
+) {
+    for (root_transform, children) in roots {
+        for entity in children.iter() {
+            let Ok((part, mut transform)) = parts.get_mut(entity) else {
+                continue;
+            };
+
+            *transform = root_transform.mul_transform(part.local_transform());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{attached_part, root_part};
+    use core::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn parts_follow_root() {
+        let mut app = App::new();
+        app.add_systems(Update, (update_part_colliders, sync_part_transforms));
+
+        let root = app
+            .world_mut()
+            .spawn((
+                root_part(Collider::cuboid(1.0, 2.0), 10.0),
+                Transform::from_xyz(100.0, 50.0, 0.0)
+                    .with_rotation(Quat::from_rotation_z(FRAC_PI_2)),
+            ))
+            .id();
+
+        let part = app
+            .world_mut()
+            .spawn(attached_part(
+                root,
+                VesselPart {
+                    offset: Vec2::new(0.0, 3.0),
+                    angle: 0.0,
+                    shape: Collider::ball(1.0),
+                    mass: 2.5,
+                },
+            ))
+            .id();
+
+        app.update();
+
+        let collider = app.world().get::<Collider>(root).unwrap();
+        let compound = collider
+            .raw
+            .as_compound()
+            .expect("root collider should be a compound");
+        assert_eq!(compound.shapes().len(), 2);
+
+        let mass = body_mass(app.world().get::<AdditionalMassProperties>(root).unwrap());
+        assert!((mass - 12.5).abs() < 1e-6);
+
+        // The root is rotated 90° counterclockwise, so +Y becomes -X
+        let transform = app.world().get::<Transform>(part).unwrap();
+        assert!((transform.translation - Vec3::new(97.0, 50.0, 0.0)).length() < 1e-4);
+
+        app.world_mut()
+            .get_mut::<Transform>(root)
+            .unwrap()
+            .translation = Vec3::new(-20.0, 0.0, 0.0);
+        app.update();
+
+        let transform = app.world().get::<Transform>(part).unwrap();
+        assert!((transform.translation - Vec3::new(-23.0, 0.0, 0.0)).length() < 1e-4);
+    }
+
+    #[test]
+    fn center_of_mass_between_parts() {
+        let mut app = App::new();
+        app.add_systems(Update, update_part_colliders);
+
+        let root = app
+            .world_mut()
+            .spawn(root_part(Collider::ball(1.0), 5.0))
+            .id();
+
+        let mass_properties = |app: &App| {
+            let AdditionalMassProperties::MassProperties(props) =
+                *app.world().get::<AdditionalMassProperties>(root).unwrap()
+            else {
+                panic!("root mass should have mass properties");
+            };
+            props
+        };
+
+        app.update();
+
+        let single = mass_properties(&app);
+        assert!((single.mass - 5.0).abs() < 1e-6);
+        assert!(single.local_center_of_mass.length() < 1e-6);
+
+        let part = app
+            .world_mut()
+            .spawn(attached_part(
+                root,
+                VesselPart {
+                    offset: Vec2::new(4.0, -2.0),
+                    angle: FRAC_PI_2,
+                    shape: Collider::ball(1.0),
+                    mass: 5.0,
+                },
+            ))
+            .id();
+
+        app.update();
+
+        let double = mass_properties(&app);
+        assert!((double.mass - 10.0).abs() < 1e-6);
+        assert!((double.local_center_of_mass - Vec2::new(2.0, -1.0)).length() < 1e-5);
+        assert!(double.principal_inertia > 2.0 * single.principal_inertia);
+
+        app.world_mut().entity_mut(part).remove::<ParentBody>();
+        app.update();
+
+        let detached = mass_properties(&app);
+        assert!((detached.mass - 5.0).abs() < 1e-6);
+        assert!(detached.local_center_of_mass.length() < 1e-6);
+    }
+
+    #[test]
+    fn staging_detaches_part() {
+        let mut app = App::new();
+        app.add_message::<Stage>();
+        app.add_systems(Update, (handle_staging, update_part_colliders).chain());
+
+        let body = app.world_mut().spawn_empty().id();
+
+        let root_vel = DVec2::new(100.0, -20.0);
+
+        let root = app
+            .world_mut()
+            .spawn((
+                root_part(Collider::cuboid(1.0, 2.0), 10.0),
+                RootSpacePosition(DVec2::new(5e6, 0.0)),
+                RootSpaceLinearVelocity(root_vel),
+                RigidSpaceVelocity::zero(),
+                Transform::from_rotation(Quat::from_rotation_z(FRAC_PI_2)),
+                CelestialParent { entity: body },
+            ))
+            .id();
+
+        let part = app
+            .world_mut()
+            .spawn(attached_part(
+                root,
+                VesselPart {
+                    offset: Vec2::new(0.0, 3.0),
+                    angle: 0.0,
+                    shape: Collider::ball(1.0),
+                    mass: 2.0,
+                },
+            ))
+            .id();
+
+        app.update();
+
+        app.world_mut().write_message(Stage {
+            part,
+            impulse: DVec2::new(-10.0, 0.0),
+        });
+        app.update();
+
+        let part_ref = app.world().entity(part);
+        assert!(part_ref.contains::<Vessel>());
+        assert!(!part_ref.contains::<ParentBody>());
+        assert_eq!(
+            part_ref
+                .get::<CelestialParent>()
+                .map(|parent| parent.entity),
+            Some(body)
+        );
+
+        // The root is rotated 90° counterclockwise, so +Y becomes -X
+        let part_pos = part_ref.get::<RootSpacePosition>().unwrap();
+        assert!((part_pos.0 - DVec2::new(5e6 - 3.0, 0.0)).length() < 1e-4);
+
+        let part_vel = part_ref.get::<RootSpaceLinearVelocity>().unwrap();
+        assert!((part_vel.0 - (root_vel + DVec2::new(-5.0, 0.0))).length() < 1e-9);
+
+        let root_ref = app.world().entity(root);
+        assert!(!root_ref.contains::<ChildObjects>());
+
+        let new_root_vel = root_ref.get::<RootSpaceLinearVelocity>().unwrap();
+        assert!((new_root_vel.0 - (root_vel + DVec2::new(1.0, 0.0))).length() < 1e-9);
+
+        let mass = body_mass(root_ref.get::<AdditionalMassProperties>().unwrap());
+        assert!((mass - 10.0).abs() < 1e-6);
+
+        let compound = root_ref
+            .get::<Collider>()
+            .unwrap()
+            .raw
+            .as_compound()
+            .expect("root collider should be a compound");
+        assert_eq!(compound.shapes().len(), 1);
+    }
+}
@@ -0,0 +1,33 @@
+//! Pausing the simulation
+
+use bevy::prelude::*;
+
+#[cfg(feature = "not-headless")]
+use crate::consts::controls::KB_TOGGLE_PAUSE;
+use crate::resources::simulation::SimPaused;
+
+/// A run condition that passes while the simulation isn't paused.
+pub(crate) fn sim_running(paused: Res<SimPaused>) -> bool {
+    !paused.0
+}
+
+#[cfg(feature = "not-headless")]
+pub(crate) fn toggle_pause(mut paused: ResMut<SimPaused>, keyboard: Res<ButtonInput<KeyCode>>) {
+    if keyboard.any_just_pressed(KB_TOGGLE_PAUSE) {
+        paused.0 = !paused.0;
+    }
+}
+
+/// Applies [`SimPaused`] onto the virtual clock.
+///
+/// This stops simulation time from passing while paused, so that
+/// on-rails objects don't jump ahead once the simulation resumes.
+pub(crate) fn apply_pause(paused: Res<SimPaused>, mut virtual_time: ResMut<Time<Virtual>>) {
+    if paused.0 != virtual_time.is_paused() {
+        if paused.0 {
+            virtual_time.pause();
+        } else {
+            virtual_time.unpause();
+        }
+    }
+}
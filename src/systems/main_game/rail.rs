@@ -1,26 +1,33 @@
 use crate::{
     components::main_game::{
-        celestial::CelestialBody,
-        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        celestial::{
+            CelestialBody, CelestialSpin, GravitationalParameter, SurfaceData, SurfaceDataItem,
+        },
+        frames::{
+            RootSpaceAngle, RootSpaceAngularVelocity, RootSpaceLinearVelocity, RootSpacePosition,
+        },
         relations::{CelestialChildren, CelestialParent, RailMode, SurfaceAttachment},
-        vessel::Vessel,
+        vessel::{LandedState, Vessel},
     },
-    consts::{FilterLoadedVessels, FilterUnloadedVessels, GRAVITATIONAL_CONSTANT},
+    consts::{FilterLoadedVessels, FilterUnloadedVessels},
+    resources::simulation::GravityConstants,
+    systems::main_game::gravity::gravitational_parameter,
     trace,
 };
 use bevy::{ecs::query::QueryData, math::DVec2, prelude::*};
-use bevy_rapier2d::{
-    plugin::{RapierContext, ReadRapierContext},
-    prelude::*,
-};
-use core::{fmt::Debug, ops::Sub, time::Duration};
-use keplerian_sim::{OrbitTrait2D, StateVectors2D};
+use bevy_rapier2d::plugin::{RapierContext, ReadRapierContext};
+use core::{f64::consts::TAU, fmt::Debug, ops::Sub, time::Duration};
+use keplerian_sim::StateVectors2D;
 
 type FilterUnloadedVesselOrCelestialBody = Or<(FilterUnloadedVessels, With<CelestialBody>)>;
 
+/// Everything below the top-level bodies that follows its rail.
+type FilterRailNodes = (With<CelestialParent>, FilterUnloadedVesselOrCelestialBody);
+
 #[derive(QueryData)]
 #[query_data(mutable)]
 pub(crate) struct NodeData {
+    parent: &'static CelestialParent,
     rail_mode: &'static RailMode,
     pos: &'static mut RootSpacePosition,
     vel: &'static mut RootSpaceLinearVelocity,
@@ -28,8 +35,13 @@ pub(crate) struct NodeData {
 }
 
 #[derive(QueryData)]
+#[query_data(mutable)]
 pub(crate) struct RootData {
-    children: &'static CelestialChildren,
+    body: &'static CelestialBody,
+    rail_mode: Option<&'static RailMode>,
+    pos: &'static mut RootSpacePosition,
+    vel: &'static mut RootSpaceLinearVelocity,
+    children: Option<&'static CelestialChildren>,
 }
 
 /// State vector query data
@@ -46,6 +58,7 @@ pub(crate) struct ChildData {
     entity: Entity,
     parent: &'static CelestialParent,
     rail_mode: &'static mut RailMode,
+    landed: &'static mut LandedState,
     pos: &'static RootSpacePosition,
     vel: &'static RootSpaceLinearVelocity,
 }
@@ -55,7 +68,19 @@ pub(crate) struct ParentData {
     entity: Entity,
     pos: &'static RootSpacePosition,
     vel: &'static RootSpaceLinearVelocity,
-    mass: &'static AdditionalMassProperties,
+    body_data: &'static CelestialBody,
+    mu: Option<&'static GravitationalParameter>,
+}
+
+/// The surface of a body, along with how far the body is rotated.
+type SurfaceQuery<'w, 's> = Query<'w, 's, (SurfaceData, Option<&'static RootSpaceAngle>)>;
+
+/// What every node needs while moving things along their rails.
+#[derive(Clone, Copy)]
+struct RailContext<'a, 'w, 's> {
+    surfaces: &'a SurfaceQuery<'w, 's>,
+    spins: &'a Query<'w, 's, &'static CelestialSpin>,
+    time: Time,
 }
 
 const ZERO_SV: (RootSpacePosition, RootSpaceLinearVelocity) = (
@@ -63,60 +88,87 @@ const ZERO_SV: (RootSpacePosition, RootSpaceLinearVelocity) = (
     RootSpaceLinearVelocity(DVec2::ZERO),
 );
 
+/// Writes a loaded vessel's state vectors back into its rail.
+///
+/// The Rapier context gets passed in rather than looked up, so
+/// this works the same whichever context the vessel lives in.
 fn write_sv_to_rail_inner(
     rapier_context: &RapierContext<'_>,
     mut vessel: ChildDataItem,
     parent: ParentDataItem,
     time: &Time,
+    constants: &GravityConstants,
 ) {
-    let rel_pos = vessel.pos.0 - parent.pos.0;
-
     let touching = rapier_context
         .contact_pair(vessel.entity, parent.entity)
         .is_some_and(|c| c.has_any_active_contact());
 
     if touching {
+        vessel.landed.record((*vessel.vel - *parent.vel).length());
+
+        // Contacts jitter slightly even at rest, so keep the
+        // attachment still once the vessel has settled down
+        if vessel.landed.is_settled() && vessel.rail_mode.is_surface() {
+            return;
+        }
+
         // TODO: Consider celestial rotation
-        let radius = rel_pos.length();
-        let angle = rel_pos.to_angle();
+        let radius = vessel.pos.distance_to(*parent.pos);
+        let angle = parent.pos.direction_to(*vessel.pos).to_angle();
         let attachment = SurfaceAttachment { angle, radius };
         *vessel.rail_mode = RailMode::Surface(attachment);
         return;
     }
 
-    let rel_vel = vessel.vel.0 - parent.vel.0;
-
-    let parent_mass = match parent.mass {
-        AdditionalMassProperties::Mass(mass) => *mass,
-        AdditionalMassProperties::MassProperties(prop) => prop.mass,
-    };
+    vessel.landed.set_if_neq(LandedState::default());
 
-    let orbit = StateVectors2D {
-        position: rel_pos,
-        velocity: rel_vel,
-    }
-    .to_cached_orbit(
-        GRAVITATIONAL_CONSTANT * f64::from(parent_mass),
-        time.elapsed_secs_f64(),
-    );
+    let orbit = vessel
+        .pos
+        .relative_to(*vessel.vel, *parent.pos, *parent.vel)
+        .to_cached_orbit(
+            gravitational_parameter(parent.body_data, parent.mu, constants),
+            time.elapsed_secs_f64(),
+        );
 
     *vessel.rail_mode = RailMode::Orbit(orbit);
 }
 
+/// Writes the state vectors of loaded vessels back into their rails,
+/// using the default Rapier context to check for surface contact.
+///
+/// If there isn't exactly one default context, e.g. because a second
+/// one got marked as the default too, this logs a warning and skips
+/// the tick instead of guessing which one to use. To simulate ahead
+/// for predictions, run a separate [`App`] with its own
+/// [`GameLogicPlugin`][crate::plugins::main_game::logic::GameLogicPlugin]
+/// instead, so that it gets its own default context and leaves this
+/// one alone.
 pub(crate) fn write_sv_to_rail(
     rapier_context: ReadRapierContext,
     mut vessels: Query<ChildData, FilterLoadedVessels>,
     cel_query: Query<ParentData, (With<CelestialBody>, Without<Vessel>)>,
     time: Res<Time>,
+    constants: Res<GravityConstants>,
+    mut warned: Local<bool>,
 ) {
-    let rapier_context = rapier_context
-        .single()
-        .expect("there should be only one rapier context");
+    let rapier_context = match rapier_context.single() {
+        Ok(rapier_context) => {
+            *warned = false;
+            rapier_context
+        }
+        Err(err) => {
+            if !*warned {
+                *warned = true;
+                warn!("not writing state vectors to rails without a single default context: {err}");
+            }
+            return;
+        }
+    };
     vessels.iter_mut().for_each(|vessel| {
         let Ok(parent) = cel_query.get(vessel.parent.entity) else {
             return;
         };
-        write_sv_to_rail_inner(&rapier_context, vessel, parent, &time);
+        write_sv_to_rail_inner(&rapier_context, vessel, parent, &time, &constants);
     });
 }
 
@@ -147,24 +199,130 @@ impl Debug for RelativeStateVectors {
     }
 }
 
-fn convert_rail_to_relative_sv(rail: RailMode, time: Duration) -> RelativeStateVectors {
-    match rail {
-        RailMode::None => unreachable!("RailMode::None should have been skipped"),
-        RailMode::Orbit(o) => {
-            let sv = o.get_state_vectors_at_time(time.as_secs_f64());
-            RelativeStateVectors {
-                position: sv.position,
-                velocity: sv.velocity,
-            }
-        }
-        RailMode::Surface(a) => {
-            // TODO: Consider celestial rotation
-            let position = DVec2::from_angle(a.angle) * a.radius;
-            RelativeStateVectors {
-                position,
-                velocity: DVec2::ZERO,
-            }
+/// Gets the state vectors of a rail relative to its parent at the given time.
+///
+/// `body_rotation` is the parent's angular velocity, in rad/s,
+/// see [`RailMode::relative_state_at`].
+///
+/// Returns [`None`] for [`RailMode::None`].
+#[must_use]
+pub(crate) fn rail_to_relative_sv(
+    rail: RailMode,
+    time: Duration,
+    body_rotation: f64,
+) -> Option<StateVectors2D> {
+    if rail.is_none() {
+        return None;
+    }
+
+    let sv = convert_rail_to_relative_sv(rail, time, body_rotation);
+
+    Some(StateVectors2D {
+        position: sv.position,
+        velocity: sv.velocity,
+    })
+}
+
+fn convert_rail_to_relative_sv(
+    rail: RailMode,
+    time: Duration,
+    body_rotation: f64,
+) -> RelativeStateVectors {
+    let (position, velocity) = rail.relative_state_at(time.as_secs_f64(), body_rotation);
+
+    RelativeStateVectors { position, velocity }
+}
+
+/// Puts a surface attachment's vessel right on the surface.
+///
+/// The attachment's radius gets captured wherever the vessel was when it
+/// touched down, which can be partway into the terrain, e.g. after a fast
+/// landing at high warp. Using the terrain's radius instead stops the vessel
+/// from tunneling through the terrain once it gets loaded again.
+///
+/// The attachment's angle is in root space, so the body's own rotation,
+/// if it has any, gets taken out before sampling its terrain.
+fn clamp_to_surface(
+    rail_mode: RailMode,
+    surface: Option<(SurfaceDataItem, Option<&RootSpaceAngle>)>,
+) -> RailMode {
+    match (rail_mode, surface) {
+        (RailMode::Surface(attachment), Some((surface, body_angle))) => {
+            let body_angle = body_angle.map_or(0.0, |angle| angle.0);
+
+            RailMode::Surface(SurfaceAttachment {
+                radius: surface.surface_radius(attachment.angle - body_angle),
+                ..attachment
+            })
         }
+        _ => rail_mode,
+    }
+}
+
+/// Moves something along its rail from last tick to this one,
+/// relative to its parent's new state vectors.
+///
+/// `body_rotation` is the parent's angular velocity, in rad/s,
+/// which surface attachments get carried along at.
+///
+/// # Output
+/// The new state vectors, how much the velocity relative to the parent
+/// changed, and how much further it moved than its new velocity
+/// accounts for. The last two get passed on to the children.
+fn advance_rail(
+    rail_mode: RailMode,
+    parent_sv: (RootSpacePosition, RootSpaceLinearVelocity),
+    pos: &mut RootSpacePosition,
+    vel: &mut RootSpaceLinearVelocity,
+    time: &Time,
+    body_rotation: f64,
+) -> ((RootSpacePosition, RootSpaceLinearVelocity), DVec2, DVec2) {
+    let old_rel_sv = convert_rail_to_relative_sv(
+        rail_mode,
+        time.elapsed().checked_sub(time.delta()).unwrap(),
+        body_rotation,
+    );
+    let new_rel_sv = convert_rail_to_relative_sv(rail_mode, time.elapsed(), body_rotation);
+
+    trace!("      rel old: {old_rel_sv:?}");
+    trace!("      rel new: {new_rel_sv:?}");
+
+    let new_root_pos = RootSpacePosition(parent_sv.0.0 + new_rel_sv.position);
+    let new_root_vel = RootSpaceLinearVelocity(parent_sv.1.0 + new_rel_sv.velocity);
+
+    trace!("      pos: {} -> {new_root_pos}", *pos);
+    trace!("      vel: {} -> {new_root_vel}", *vel);
+
+    *pos = new_root_pos;
+    *vel = new_root_vel;
+
+    let vel_shift = new_rel_sv.velocity - old_rel_sv.velocity;
+    let pos_shift =
+        (new_rel_sv.position - old_rel_sv.position) - new_rel_sv.velocity * time.delta_secs_f64();
+
+    ((new_root_pos, new_root_vel), vel_shift, pos_shift)
+}
+
+/// Gets the mass-weighted average state vectors of the given bodies.
+///
+/// Falls back to the origin, at rest, if there's no mass at all.
+fn barycenter(
+    bodies: impl Iterator<Item = (f64, RootSpacePosition, RootSpaceLinearVelocity)>,
+) -> (RootSpacePosition, RootSpaceLinearVelocity) {
+    let (mass, weighted_pos, weighted_vel) = bodies.fold(
+        (0.0, DVec2::ZERO, DVec2::ZERO),
+        |(total, pos_sum, vel_sum), (mass, pos, vel)| {
+            (total + mass, pos_sum + pos.0 * mass, vel_sum + vel.0 * mass)
+        },
+    );
+
+    if mass > 0.0 {
+        (
+            RootSpacePosition(weighted_pos / mass),
+            RootSpaceLinearVelocity(weighted_vel / mass),
+        )
+    } else {
+        ZERO_SV
     }
 }
 
@@ -172,20 +330,28 @@ fn convert_rail_to_relative_sv(rail: RailMode, time: Duration) -> RelativeStateV
 /// - Try to find it using the `on_rails_query`
 ///   - Calculate new SV using `RailMode` and `parent_sv`
 ///   - Calculate SV difference
-///   - Recurse, changing the `parent_sv` and `accum_shift`
+///   - Recurse, changing the `parent_sv`, `accum_shift` and `accum_pos_shift`
 /// - Try to find it using the `off_rails` query
-///   - Shift SV using `accum_shift`
+///   - Shift SV using `accum_shift` and `accum_pos_shift`
+///
+/// Off-rails vessels get integrated with their new velocity later on in the
+/// tick, which would move them by `new_vel * dt`. Their parent, however,
+/// actually moved by `new_pos - old_pos`. `accum_pos_shift` makes up for
+/// the difference, so that a vessel at rest relative to its parent
+/// stays at rest.
 fn write_rail_to_sv_inner(
     node: Entity,
     parent_sv: (RootSpacePosition, RootSpaceLinearVelocity),
     accum_shift: RootSpaceLinearVelocity,
-    mut on_rails_query: Query<NodeData, FilterUnloadedVesselOrCelestialBody>,
+    accum_pos_shift: DVec2,
+    mut on_rails_query: Query<NodeData, FilterRailNodes>,
     mut off_rails_query: Query<SvData, (With<CelestialParent>, FilterLoadedVessels)>,
-    time: Time,
+    ctx: RailContext,
 ) {
     trace!("Rail: Processing {node:?}");
     trace!("  parent_sv {} {}", parent_sv.0, parent_sv.1);
     trace!("  accum_shift {} {}", accum_shift.0, accum_shift);
+    trace!("  accum_pos_shift {accum_pos_shift}");
 
     let Ok(mut node) = on_rails_query.get_mut(node) else {
         trace!("      couldn't find in on-rails query");
@@ -195,8 +361,10 @@ fn write_rail_to_sv_inner(
             return;
         };
 
+        trace!("      pos: {} += {}", *sv.pos, accum_pos_shift);
         trace!("      vel: {} += {}", *sv.vel, accum_shift);
 
+        sv.pos.0 += accum_pos_shift;
         sv.vel.0 += accum_shift.0;
 
         return;
@@ -207,23 +375,19 @@ fn write_rail_to_sv_inner(
         return;
     }
 
-    let old_rel_sv = convert_rail_to_relative_sv(
-        *node.rail_mode,
-        time.elapsed().checked_sub(time.delta()).unwrap(),
+    let rail_mode = clamp_to_surface(*node.rail_mode, ctx.surfaces.get(node.parent.entity).ok());
+    let body_rotation = ctx
+        .spins
+        .get(node.parent.entity)
+        .map_or(0.0, |spin| spin.angular_velocity);
+    let (new_sv, vel_shift, pos_shift) = advance_rail(
+        rail_mode,
+        parent_sv,
+        &mut node.pos,
+        &mut node.vel,
+        &ctx.time,
+        body_rotation,
     );
-    let new_rel_sv = convert_rail_to_relative_sv(*node.rail_mode, time.elapsed());
-
-    trace!("      rel old: {old_rel_sv:?}");
-    trace!("      rel new: {new_rel_sv:?}");
-
-    let new_root_pos = RootSpacePosition(parent_sv.0.0 + new_rel_sv.position);
-    let new_root_vel = RootSpaceLinearVelocity(parent_sv.1.0 + new_rel_sv.velocity);
-
-    trace!("      pos: {} -> {new_root_pos}", *node.pos);
-    trace!("      vel: {} -> {new_root_vel}", *node.vel);
-
-    *node.pos = new_root_pos;
-    *node.vel = new_root_vel;
 
     let Some(children) = node.children else {
         trace!("      ...no children found");
@@ -235,31 +399,148 @@ fn write_rail_to_sv_inner(
     children.into_iter().for_each(|child| {
         write_rail_to_sv_inner(
             child,
-            (new_root_pos, new_root_vel),
-            RootSpaceLinearVelocity(accum_shift.0 + (new_rel_sv.velocity - old_rel_sv.velocity)),
+            new_sv,
+            RootSpaceLinearVelocity(accum_shift.0 + vel_shift),
+            accum_pos_shift + pos_shift,
             on_rails_query.reborrow(),
             off_rails_query.reborrow(),
-            time,
+            ctx,
         );
     });
 }
 
+/// Moves everything on rails along, starting from the top-level bodies.
+///
+/// Top-level bodies without rails stay where they are. Ones with
+/// [`RailMode::Orbit`] orbit the barycenter of every top-level body
+/// instead, e.g. for binary stars. Their rails are assumed to agree
+/// on where that barycenter is, i.e. to keep it still, so build them with
+/// [`barycentric_gravitational_parameter`][crate::orbit::barycentric_gravitational_parameter].
+///
+/// Vessels attached to the surface rest right on it,
+/// see [`clamp_to_surface`].
 pub(crate) fn write_rail_to_sv(
-    roots: Query<RootData, Without<CelestialParent>>,
-    mut on_rails_query: Query<NodeData, FilterUnloadedVesselOrCelestialBody>,
+    mut roots: Query<RootData, Without<CelestialParent>>,
+    mut on_rails_query: Query<NodeData, FilterRailNodes>,
     mut off_rails_query: Query<SvData, (With<CelestialParent>, FilterLoadedVessels)>,
+    surfaces: SurfaceQuery,
+    spins: Query<&CelestialSpin>,
     time: Res<Time>,
 ) {
-    roots.iter().for_each(|root| {
-        root.children.iter().for_each(|node| {
+    let ctx = RailContext {
+        surfaces: &surfaces,
+        spins: &spins,
+        time: *time,
+    };
+
+    let barycenter = barycenter(
+        roots
+            .iter()
+            .map(|root| (root.body.mass, *root.pos, *root.vel)),
+    );
+
+    roots.iter_mut().for_each(|mut root| {
+        let (sv, vel_shift, pos_shift) = match root.rail_mode {
+            Some(&rail_mode) if !rail_mode.is_none() => advance_rail(
+                rail_mode,
+                barycenter,
+                &mut root.pos,
+                &mut root.vel,
+                &time,
+                0.0,
+            ),
+            _ => ((*root.pos, *root.vel), DVec2::ZERO, DVec2::ZERO),
+        };
+
+        let Some(children) = root.children else {
+            return;
+        };
+
+        children.iter().for_each(|node| {
             write_rail_to_sv_inner(
                 node,
-                ZERO_SV,
-                RootSpaceLinearVelocity(DVec2::ZERO),
+                sv,
+                RootSpaceLinearVelocity(vel_shift),
+                pos_shift,
                 on_rails_query.reborrow(),
                 off_rails_query.reborrow(),
-                *time,
+                ctx,
             );
         });
     });
 }
+
+/// Keeps on-rails vessels spinning at their root-space angular velocity.
+///
+/// Landed vessels stay still relative to the surface, so they turn along
+/// with their parent's [`CelestialSpin`] instead, if it has any. Their
+/// angular velocity gets set to match, so they keep turning the same way
+/// once they come off rails.
+pub(crate) fn spin_on_rails_vessels(
+    query: Query<
+        (
+            &mut RootSpaceAngle,
+            &mut RootSpaceAngularVelocity,
+            &RailMode,
+            Option<&CelestialParent>,
+        ),
+        FilterUnloadedVessels,
+    >,
+    spins: Query<&CelestialSpin>,
+    time: Res<Time>,
+) {
+    for (mut angle, mut angvel, rail_mode, parent) in query {
+        if rail_mode.is_surface() {
+            angvel.0 = parent
+                .and_then(|parent| spins.get(parent.entity).ok())
+                .map_or(0.0, |spin| spin.angular_velocity);
+        }
+
+        angle.0 = angvel
+            .0
+            .mul_add(time.delta_secs_f64(), angle.0)
+            .rem_euclid(TAU);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_context_skips_writing() {
+        let mut app = App::new();
+        app.init_resource::<Time>();
+        app.init_resource::<GravityConstants>();
+        app.add_systems(Update, write_sv_to_rail);
+
+        let body = app
+            .world_mut()
+            .spawn((
+                CelestialBody {
+                    base_radius: 1e6,
+                    mass: 1e20,
+                },
+                RootSpacePosition(DVec2::ZERO),
+                RootSpaceLinearVelocity(DVec2::ZERO),
+            ))
+            .id();
+
+        let vessel = app
+            .world_mut()
+            .spawn((
+                Vessel,
+                CelestialParent { entity: body },
+                RailMode::None,
+                RootSpacePosition(DVec2::new(0.0, 2e6)),
+                RootSpaceLinearVelocity(DVec2::new(1000.0, 0.0)),
+            ))
+            .id();
+
+        // No Rapier plugin, so there's no context to check contacts with
+        app.update();
+        app.update();
+
+        assert!(app.world().get::<RailMode>(vessel).unwrap().is_none());
+    }
+}
@@ -0,0 +1,48 @@
+//! Turning loaded vessels with their reaction wheels
+
+use bevy::{ecs::query::QueryData, prelude::*};
+use bevy_rapier2d::prelude::ReadMassProperties;
+
+use crate::{
+    components::main_game::{
+        frames::RootSpaceAngularVelocity,
+        vessel::{ReactionWheel, VesselInput, WheelSaturation},
+    },
+    consts::FilterLoadedVessels,
+};
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub(crate) struct WheelData {
+    wheel: &'static ReactionWheel,
+    saturation: Option<&'static mut WheelSaturation>,
+    input: &'static VesselInput,
+    angvel: &'static mut RootSpaceAngularVelocity,
+    mass: &'static ReadMassProperties,
+}
+
+/// Spins loaded vessels up according to their rotation input,
+/// with as much torque as their [`ReactionWheel`] can give.
+///
+/// There's no separate cap on how fast a vessel can spin,
+/// the limited torque is what keeps it from spinning up instantly.
+pub(crate) fn apply_reaction_wheels(
+    mut vessels: Query<WheelData, FilterLoadedVessels>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs_f64();
+
+    vessels.iter_mut().for_each(|mut vessel| {
+        let inertia = f64::from(vessel.mass.get().principal_inertia);
+        if inertia <= 0.0 {
+            return;
+        }
+
+        let mut torque = vessel.wheel.commanded_torque(vessel.input.rotation.current);
+        if let Some(saturation) = &mut vessel.saturation {
+            torque = saturation.apply(torque, dt);
+        }
+
+        vessel.angvel.0 += torque / inertia * dt;
+    });
+}
@@ -0,0 +1,459 @@
+use bevy::prelude::*;
+use keplerian_sim::{OrbitTrait2D, StateVectors2D};
+
+use crate::{
+    components::main_game::{
+        celestial::{CelestialBody, GravitationalParameter},
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::{
+            CelestialChildren, CelestialParent, PrevCelestialParent, RailMode, SoiMembers,
+        },
+        vessel::Vessel,
+    },
+    messages::relations::{Reparent, SoiChanged},
+    orbit::patched_conics::{body_soi, soi_entry, soi_escape},
+    resources::simulation::GravityConstants,
+    systems::main_game::gravity::gravitational_parameter,
+};
+
+/// Sends a [`SoiChanged`] message for every entity whose
+/// [`CelestialParent`] changed since the last check.
+pub(crate) fn emit_soi_changes(
+    mut commands: Commands,
+    query: Query<
+        (Entity, &CelestialParent, Option<&mut PrevCelestialParent>),
+        Changed<CelestialParent>,
+    >,
+    mut writer: MessageWriter<SoiChanged>,
+) {
+    for (entity, parent, prev_parent) in query {
+        let Some(mut prev_parent) = prev_parent else {
+            commands
+                .entity(entity)
+                .insert(PrevCelestialParent(parent.entity));
+            continue;
+        };
+
+        if prev_parent.0 == parent.entity {
+            continue;
+        }
+
+        writer.write(SoiChanged {
+            vessel: entity,
+            from: prev_parent.0,
+            to: parent.entity,
+        });
+
+        prev_parent.0 = parent.entity;
+    }
+}
+
+/// Brings every body's [`SoiMembers`] in line with the
+/// [`CelestialParent`]s of the vessels around it.
+///
+/// Lists that didn't change are left alone, so that
+/// [`Changed<SoiMembers>`] only picks up actual changes.
+pub(crate) fn update_soi_members(
+    bodies: Query<(&mut SoiMembers, Option<&CelestialChildren>)>,
+    vessels: Query<(), With<Vessel>>,
+) {
+    for (mut members, children) in bodies {
+        let current = children
+            .iter()
+            .flat_map(|children| children.iter())
+            .filter(|&child| vessels.contains(child));
+
+        if !members.iter().copied().eq(current.clone()) {
+            members.0 = current.collect();
+        }
+    }
+}
+
+type SoiBodyQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static RootSpacePosition,
+        &'static CelestialBody,
+        Option<&'static RailMode>,
+        Option<&'static CelestialParent>,
+        Option<&'static CelestialChildren>,
+    ),
+    Without<Vessel>,
+>;
+
+/// Sends a [`Reparent`] message for every vessel on an open orbit that
+/// has left its parent's sphere of influence, moving it to the parent's
+/// own parent.
+///
+/// Without this, a departing vessel would keep following its hyperbola
+/// outwards forever. Root bodies have no sphere of influence to leave.
+pub(crate) fn detect_soi_escapes(
+    vessels: Query<(Entity, &RootSpacePosition, &CelestialParent, &RailMode), With<Vessel>>,
+    bodies: SoiBodyQuery,
+    mut writer: MessageWriter<Reparent>,
+) {
+    for (vessel, pos, parent, vessel_rail) in vessels {
+        let Some(vessel_orbit) = vessel_rail.as_orbit() else {
+            continue;
+        };
+
+        let Ok((parent_pos, body, Some(rail_mode), Some(grandparent), _)) =
+            bodies.get(parent.entity)
+        else {
+            continue;
+        };
+
+        let Some(orbit) = rail_mode.as_orbit() else {
+            continue;
+        };
+
+        let Ok((_, grandparent_body, ..)) = bodies.get(grandparent.entity) else {
+            continue;
+        };
+
+        let escape = (
+            grandparent.entity,
+            body_soi(&orbit, body.mass, grandparent_body.mass),
+        );
+
+        if let Some(new_parent) = soi_escape(pos.0 - parent_pos.0, &vessel_orbit, Some(escape)) {
+            writer.write(Reparent { vessel, new_parent });
+        }
+    }
+}
+
+/// Sends a [`Reparent`] message for every on-rails vessel that has
+/// entered the sphere of influence of a body orbiting its parent,
+/// moving it to that body.
+///
+/// Together with [`detect_soi_escapes`], this follows the same rules as
+/// [`predict_soi_transitions`][crate::orbit::patched_conics::predict_soi_transitions].
+pub(crate) fn detect_soi_entries(
+    vessels: Query<(Entity, &RootSpacePosition, &CelestialParent, &RailMode), With<Vessel>>,
+    bodies: SoiBodyQuery,
+    mut writer: MessageWriter<Reparent>,
+) {
+    for (vessel, pos, parent, vessel_rail) in vessels {
+        if vessel_rail.as_orbit().is_none() {
+            continue;
+        }
+
+        let Ok((parent_pos, body, .., Some(children))) = bodies.get(parent.entity) else {
+            continue;
+        };
+
+        let children = children.iter().filter_map(|child| {
+            let (child_pos, child_body, child_rail, ..) = bodies.get(child).ok()?;
+            let orbit = child_rail?.as_orbit()?;
+            Some((
+                child,
+                child_pos.0 - parent_pos.0,
+                body_soi(&orbit, child_body.mass, body.mass),
+            ))
+        });
+
+        if let Some(new_parent) = soi_entry(pos.0 - parent_pos.0, children) {
+            writer.write(Reparent { vessel, new_parent });
+        }
+    }
+}
+
+/// Moves vessels to the parents requested through [`Reparent`] messages.
+///
+/// The new orbit is written right away, so an on-rails vessel
+/// follows it starting from this tick.
+pub(crate) fn handle_reparenting(
+    mut commands: Commands,
+    mut reader: MessageReader<Reparent>,
+    mut vessels: Query<
+        (
+            &RootSpacePosition,
+            &RootSpaceLinearVelocity,
+            &CelestialParent,
+            &mut RailMode,
+        ),
+        With<Vessel>,
+    >,
+    bodies: Query<
+        (
+            &RootSpacePosition,
+            &RootSpaceLinearVelocity,
+            &CelestialBody,
+            Option<&GravitationalParameter>,
+        ),
+        Without<Vessel>,
+    >,
+    constants: Res<GravityConstants>,
+    time: Res<Time>,
+) {
+    // The state vectors were last written at the end of the previous tick
+    let now = time.elapsed().saturating_sub(time.delta());
+
+    for &Reparent { vessel, new_parent } in reader.read() {
+        let Ok((pos, vel, parent, mut rail_mode)) = vessels.get_mut(vessel) else {
+            warn!("Attempted to reparent {vessel}, which isn't a vessel");
+            continue;
+        };
+
+        let Ok((parent_pos, parent_vel, body, mu)) = bodies.get(new_parent) else {
+            warn!("Attempted to reparent {vessel} to {new_parent}, which isn't a celestial body");
+            continue;
+        };
+
+        if parent.entity == new_parent {
+            continue;
+        }
+
+        let orbit = StateVectors2D {
+            position: pos.0 - parent_pos.0,
+            velocity: vel.0 - parent_vel.0,
+        }
+        .to_cached_orbit(
+            gravitational_parameter(body, mu, &constants),
+            now.as_secs_f64(),
+        );
+
+        *rail_mode = RailMode::Orbit(orbit);
+        commands
+            .entity(vessel)
+            .insert(CelestialParent { entity: new_parent });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::DVec2;
+
+    #[test]
+    fn reparent_keeps_state_vectors() {
+        let mut app = App::new();
+        app.add_message::<Reparent>();
+        app.init_resource::<GravityConstants>();
+        app.init_resource::<Time>();
+        app.add_systems(Update, handle_reparenting);
+
+        let planet = app
+            .world_mut()
+            .spawn((
+                CelestialBody::default(),
+                GravitationalParameter(4e14),
+                RootSpacePosition(DVec2::ZERO),
+                RootSpaceLinearVelocity(DVec2::ZERO),
+            ))
+            .id();
+
+        let moon_pos = DVec2::new(4e8, 0.0);
+        let moon_vel = DVec2::new(0.0, 1000.0);
+        let moon = app
+            .world_mut()
+            .spawn((
+                CelestialBody::default(),
+                GravitationalParameter(5e12),
+                RootSpacePosition(moon_pos),
+                RootSpaceLinearVelocity(moon_vel),
+                CelestialParent { entity: planet },
+            ))
+            .id();
+
+        let vessel_pos = DVec2::new(3.9e8, 2e7);
+        let vessel_vel = DVec2::new(-300.0, 1200.0);
+        let vessel = app
+            .world_mut()
+            .spawn((
+                Vessel,
+                RootSpacePosition(vessel_pos),
+                RootSpaceLinearVelocity(vessel_vel),
+                CelestialParent { entity: planet },
+            ))
+            .id();
+
+        app.world_mut().write_message(Reparent {
+            vessel,
+            new_parent: moon,
+        });
+        app.update();
+
+        let vessel_ref = app.world().entity(vessel);
+        assert_eq!(vessel_ref.get::<CelestialParent>().unwrap().entity, moon);
+
+        let orbit = vessel_ref
+            .get::<RailMode>()
+            .unwrap()
+            .as_orbit()
+            .expect("vessel should be on an orbit");
+        assert!((orbit.get_gravitational_parameter() - 5e12).abs() < 1.0);
+
+        let sv = orbit.get_state_vectors_at_time(0.0);
+        assert!((moon_pos + sv.position - vessel_pos).length() < 1e-3);
+        assert!((moon_vel + sv.velocity - vessel_vel).length() < 1e-6);
+
+        // The vessel's own state vectors stay as they were
+        assert_eq!(vessel_ref.get::<RootSpacePosition>().unwrap().0, vessel_pos);
+        assert_eq!(
+            vessel_ref.get::<RootSpaceLinearVelocity>().unwrap().0,
+            vessel_vel
+        );
+    }
+
+    const PLANET_MU: f64 = 4e14;
+    const MOON_MU: f64 = 5e12;
+
+    /// Spawns a planet with a moon on a circular orbit around it,
+    /// returning the planet, the moon, and the moon's state vectors.
+    ///
+    /// The moon's SOI is around 6.9e7 m.
+    fn spawn_planet_and_moon(app: &mut App) -> (Entity, Entity, DVec2, DVec2) {
+        let constants = GravityConstants::default();
+        let planet = app
+            .world_mut()
+            .spawn((
+                CelestialBody {
+                    base_radius: 6e6,
+                    mass: PLANET_MU / constants.gravitational_constant,
+                },
+                GravitationalParameter(PLANET_MU),
+                RootSpacePosition(DVec2::ZERO),
+                RootSpaceLinearVelocity(DVec2::ZERO),
+            ))
+            .id();
+
+        let moon_pos = DVec2::new(4e8, 0.0);
+        let moon_vel = DVec2::new(0.0, (PLANET_MU / 4e8).sqrt());
+        let moon_orbit = StateVectors2D {
+            position: moon_pos,
+            velocity: moon_vel,
+        }
+        .to_cached_orbit(PLANET_MU, 0.0);
+        let moon = app
+            .world_mut()
+            .spawn((
+                CelestialBody {
+                    base_radius: 1.7e6,
+                    mass: MOON_MU / constants.gravitational_constant,
+                },
+                GravitationalParameter(MOON_MU),
+                RootSpacePosition(moon_pos),
+                RootSpaceLinearVelocity(moon_vel),
+                RailMode::Orbit(moon_orbit),
+                CelestialParent { entity: planet },
+            ))
+            .id();
+
+        (planet, moon, moon_pos, moon_vel)
+    }
+
+    #[test]
+    fn escaping_vessel_moves_to_grandparent() {
+        let mut app = App::new();
+        app.add_message::<Reparent>();
+        app.init_resource::<GravityConstants>();
+        app.init_resource::<Time>();
+        app.add_systems(Update, (detect_soi_escapes, handle_reparenting).chain());
+
+        let (planet, moon, moon_pos, moon_vel) = spawn_planet_and_moon(&mut app);
+
+        let mut spawn_vessel = |rel_pos: DVec2, rel_vel: DVec2| {
+            let vessel_vel = moon_vel + rel_vel;
+            let orbit = StateVectors2D {
+                position: rel_pos,
+                velocity: vessel_vel - moon_vel,
+            }
+            .to_cached_orbit(MOON_MU, 0.0);
+
+            app.world_mut()
+                .spawn((
+                    Vessel,
+                    RootSpacePosition(moon_pos + rel_pos),
+                    RootSpaceLinearVelocity(vessel_vel),
+                    RailMode::Orbit(orbit),
+                    CelestialParent { entity: moon },
+                ))
+                .id()
+        };
+
+        let departing = DVec2::new(1500.0, 0.0);
+        let inside = spawn_vessel(DVec2::new(3e7, 0.0), departing);
+        let escaped = spawn_vessel(DVec2::new(9e7, 0.0), departing);
+        // Closed orbits can't be on their way out
+        let falling = spawn_vessel(DVec2::new(9e7, 0.0), DVec2::ZERO);
+
+        app.update();
+
+        let parent = |app: &App, vessel| app.world().get::<CelestialParent>(vessel).unwrap().entity;
+        assert_eq!(parent(&app, inside), moon);
+        assert_eq!(parent(&app, escaped), planet);
+        assert_eq!(parent(&app, falling), moon);
+
+        let vessel_ref = app.world().entity(escaped);
+        let pos = vessel_ref.get::<RootSpacePosition>().unwrap().0;
+        let vel = vessel_ref.get::<RootSpaceLinearVelocity>().unwrap().0;
+        let orbit = vessel_ref
+            .get::<RailMode>()
+            .unwrap()
+            .as_orbit()
+            .expect("escaped vessel should be on an orbit");
+
+        // The handoff doesn't make the vessel jump
+        let sv = orbit.get_state_vectors_at_time(0.0);
+        assert!((orbit.get_gravitational_parameter() - PLANET_MU).abs() < 1.0);
+        assert!((sv.position - pos).length() < 1e-3);
+        assert!((sv.velocity - vel).length() < 1e-6);
+
+        // Now that it's out, it stays out
+        app.update();
+        assert_eq!(parent(&app, escaped), planet);
+    }
+
+    #[test]
+    fn arriving_vessel_moves_to_child() {
+        let mut app = App::new();
+        app.add_message::<Reparent>();
+        app.init_resource::<GravityConstants>();
+        app.init_resource::<Time>();
+        app.add_systems(Update, (detect_soi_entries, handle_reparenting).chain());
+
+        let (planet, moon, moon_pos, moon_vel) = spawn_planet_and_moon(&mut app);
+
+        let mut spawn_vessel = |rel_pos: DVec2| {
+            let pos = moon_pos + rel_pos;
+            let orbit = StateVectors2D {
+                position: pos,
+                velocity: moon_vel,
+            }
+            .to_cached_orbit(PLANET_MU, 0.0);
+
+            app.world_mut()
+                .spawn((
+                    Vessel,
+                    RootSpacePosition(pos),
+                    RootSpaceLinearVelocity(moon_vel),
+                    RailMode::Orbit(orbit),
+                    CelestialParent { entity: planet },
+                ))
+                .id()
+        };
+
+        // Coming in from the planet's side, even on a closed orbit
+        let arriving = spawn_vessel(DVec2::new(3e7, 0.0));
+        let passing = spawn_vessel(DVec2::new(9e7, 0.0));
+
+        app.update();
+
+        let parent = |app: &App, vessel| app.world().get::<CelestialParent>(vessel).unwrap().entity;
+        assert_eq!(parent(&app, arriving), moon);
+        assert_eq!(parent(&app, passing), planet);
+
+        let orbit = app
+            .world()
+            .get::<RailMode>(arriving)
+            .unwrap()
+            .as_orbit()
+            .expect("arriving vessel should be on an orbit");
+        assert!((orbit.get_gravitational_parameter() - MOON_MU).abs() < 1.0);
+        assert!(
+            (orbit.get_state_vectors_at_time(0.0).position - DVec2::new(3e7, 0.0)).length() < 1e-3
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::main_game::{
+        celestial::CelestialBody,
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::{CelestialParent, RailMode},
+        vessel::{Engine, OrbitalVelocity, SurfaceVelocity},
+    },
+    messages::telemetry::TelemetryFrame,
+    resources::simulation::ActiveVessel,
+};
+
+/// Sends a [`TelemetryFrame`] describing the active vessel.
+pub(crate) fn emit_telemetry(
+    active_vessel: Res<ActiveVessel>,
+    vessels: Query<(
+        &RootSpacePosition,
+        &RootSpaceLinearVelocity,
+        &CelestialParent,
+        &RailMode,
+        &OrbitalVelocity,
+        &SurfaceVelocity,
+        Option<&Engine>,
+    )>,
+    parents: Query<(&RootSpacePosition, &RootSpaceLinearVelocity, &CelestialBody)>,
+    time: Res<Time>,
+    mut writer: MessageWriter<TelemetryFrame>,
+) {
+    let Ok((pos, vel, parent, rail_mode, orbital_velocity, surface_velocity, engine)) =
+        vessels.get(active_vessel.entity)
+    else {
+        return;
+    };
+
+    let Ok((parent_pos, parent_vel, parent_body)) = parents.get(parent.entity) else {
+        return;
+    };
+
+    let rel_position = pos.0 - parent_pos.0;
+
+    writer.write(TelemetryFrame {
+        time: time.elapsed_secs_f64(),
+        vessel: active_vessel.entity,
+        parent: parent.entity,
+        position: *pos,
+        velocity: *vel,
+        rel_position,
+        rel_velocity: vel.0 - parent_vel.0,
+        orbital_velocity: *orbital_velocity,
+        rail_mode: *rail_mode,
+        altitude: rel_position.length() - f64::from(parent_body.base_radius),
+        surface_velocity: *surface_velocity,
+        landed: rail_mode.is_surface(),
+        fuel: engine.map_or(0.0, |engine| engine.fuel),
+    });
+}
@@ -1,15 +1,15 @@
 use crate::{
     components::main_game::{
         celestial::{CelestialBody, Terrain},
-        frames::RootSpacePosition,
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
         relations::CelestialChildren,
-        terrain::collider::{PrevColliderPoints, PrevIndexRanges},
-        vessel::Vessel,
+        terrain::collider::{PrevColliderOrigin, PrevColliderPoints, PrevIndexRanges},
+        vessel::{OrbitalVelocity, Vessel},
     },
-    resources::simulation::ActiveVessel,
+    resources::simulation::{ActiveVessel, PhysicsConfig, TerrainColliderConfig},
     terrain::collider::{
-        create_index_buffer, gen_idx_ranges, gen_points, get_theta_range,
-        is_vessel_within_terrain_altitude, verts_at_lod_level,
+        create_index_buffer, gen_idx_ranges, gen_points, get_theta_padding, get_theta_range,
+        is_vessel_within_terrain_altitude, pad_theta_range, verts_at_lod_level,
     },
 };
 use bevy::{ecs::query::QueryData, prelude::*};
@@ -39,25 +39,39 @@ type VesselQuery<'w, 's> = Query<
 pub(crate) struct CelestialComponents {
     entity: Entity,
     position: &'static RootSpacePosition,
+    velocity: &'static RootSpaceLinearVelocity,
     collider: &'static mut Collider,
     children: &'static CelestialChildren,
     terrain: &'static Terrain,
     prev_ranges: Option<&'static mut PrevIndexRanges>,
     prev_pts: Option<&'static mut PrevColliderPoints>,
+    prev_origin: Option<&'static mut PrevColliderOrigin>,
 }
 
 #[derive(QueryData)]
 pub(crate) struct VesselData {
     position: &'static RootSpacePosition,
+    velocity: &'static RootSpaceLinearVelocity,
     collider: &'static Collider,
 }
 
+/// How far terrain colliders reach ahead of moving vessels.
+#[derive(Clone, Copy)]
+struct ColliderMargin {
+    /// The constant padding, in radians.
+    angle: f64,
+    /// The time until the next collider rebuild, in seconds.
+    lookahead: f64,
+}
+
 fn gen_theta_ranges(
-    celestial_position: RootSpacePosition,
-    terrain: &Terrain,
-    children: &CelestialChildren,
+    celestial: &CelestialComponentsItem,
     vessel_query: VesselQuery,
+    margin: ColliderMargin,
 ) -> Vec<RangeInclusive<f64>> {
+    let terrain = celestial.terrain;
+    let children = celestial.children;
+
     let iter = children
         .iter()
         .filter_map(|entity| vessel_query.get(entity).ok());
@@ -66,7 +80,8 @@ fn gen_theta_ranges(
     let mut vec = Vec::with_capacity(size);
 
     for vessel in iter {
-        let vessel_rel_pos = vessel.position.0 - celestial_position.0;
+        let vessel_rel_pos = vessel.position.0 - celestial.position.0;
+        let vessel_rel_vel = vessel.velocity.0 - celestial.velocity.0;
         let aabb = vessel.collider.raw.compute_local_aabb();
         if !is_vessel_within_terrain_altitude(aabb, vessel_rel_pos.length(), terrain) {
             continue;
@@ -74,25 +89,25 @@ fn gen_theta_ranges(
 
         // TODO: Consider celestial rotation
         let range = get_theta_range(aabb, vessel_rel_pos, 0.0, terrain);
-        vec.push(range);
+        let tangential_speed =
+            OrbitalVelocity::from_relative(vessel_rel_pos, vessel_rel_vel).prograde;
+        let padding = get_theta_padding(tangential_speed, margin.lookahead, margin.angle, terrain);
+        vec.push(pad_theta_range(range, padding));
     }
 
     vec
 }
 
-fn polyline_with_ball(
+/// Decomposes a closed terrain outline into convex pieces, along with
+/// a ball filling in the body's inside.
+pub(crate) fn polyline_with_ball(
     points: &[OPoint<f32, Const<2>>],
     // points: &[Vec2],
     indices: &[[u32; 2]],
     ball_offset: Vec2,
     ball_radius: f32,
+    params: &VHACDParameters,
 ) -> Collider {
-    let params = VHACDParameters {
-        concavity: 0.015,
-        alpha: 0.0,
-        ..Default::default()
-    };
-
     let mut parts = vec![];
 
     parts.push((
@@ -100,7 +115,7 @@ fn polyline_with_ball(
         SharedShape::ball(ball_radius),
     ));
 
-    let decomp = VHACD::decompose(&params, points, indices, true);
+    let decomp = VHACD::decompose(params, points, indices, true);
 
     for vertices in decomp.compute_exact_convex_hulls(points, indices) {
         if let Some(convex) = SharedShape::convex_polyline(vertices) {
@@ -113,20 +128,46 @@ fn polyline_with_ball(
     Collider::from(shape)
 }
 
+/// Builds a terrain collider out of its outline points.
+///
+/// If there are fewer points than [`TerrainColliderConfig::min_vertices`],
+/// this falls back to just the ball filling in the body's inside, so that
+/// there's still something to collide with.
+pub(crate) fn terrain_collider(
+    points: &[OPoint<f32, Const<2>>],
+    ball_offset: Vec2,
+    ball_radius: f32,
+    config: &TerrainColliderConfig,
+) -> Collider {
+    if points.len() < config.min_vertices.max(3) {
+        let ball = (
+            Isometry::translation(ball_offset.x, ball_offset.y),
+            SharedShape::ball(ball_radius),
+        );
+        return Collider::from(SharedShape::compound(vec![ball]));
+    }
+
+    #[expect(clippy::cast_possible_truncation)]
+    polyline_with_ball(
+        points,
+        &create_index_buffer(points.len() as u32),
+        ball_offset,
+        ball_radius,
+        &config.vhacd_parameters(),
+    )
+}
+
 fn update_collider(
     mut celestial: CelestialComponentsItem,
     vessel_query: VesselQuery,
     active_vessel: &ActiveVessel,
+    margin: ColliderMargin,
+    config: &TerrainColliderConfig,
     commands: &mut Commands,
 ) {
     let rigid_pos = celestial.position.0 - active_vessel.prev_tick_position.0;
 
-    let theta_ranges = gen_theta_ranges(
-        *celestial.position,
-        celestial.terrain,
-        celestial.children,
-        vessel_query,
-    );
+    let theta_ranges = gen_theta_ranges(&celestial, vessel_query, margin);
     let verts = verts_at_lod_level(celestial.terrain.subdivs);
     let idx_ranges = gen_idx_ranges(&theta_ranges, verts);
 
@@ -151,9 +192,6 @@ fn update_collider(
             .collect()
     } else {
         let terrain_pts = gen_points(*celestial.terrain, &idx_ranges);
-        if terrain_pts.len() < 3 {
-            return; // Not a valid mesh, ignore
-        }
         let collider_pts: Vec<_> = terrain_pts
             .iter()
             .map(|point| point.phys_downcast(rigid_pos))
@@ -174,13 +212,21 @@ fn update_collider(
     }
 
     #[expect(clippy::cast_possible_truncation)]
-    let decomp = polyline_with_ball(
+    let collider = terrain_collider(
         &collider_pts,
-        &create_index_buffer(collider_pts.len() as u32),
         rigid_pos.as_vec2(),
         (celestial.terrain.offset - celestial.terrain.multiplier) as f32,
+        config,
     );
-    *celestial.collider = decomp;
+    *celestial.collider = collider;
+
+    if let Some(ref mut origin) = celestial.prev_origin {
+        origin.0 = rigid_pos;
+    } else {
+        commands
+            .entity(celestial.entity)
+            .insert(PrevColliderOrigin(rigid_pos));
+    }
 }
 
 pub(crate) fn update_terrain_colliders(
@@ -188,13 +234,113 @@ pub(crate) fn update_terrain_colliders(
     vessel_query: VesselQuery,
     mut commands: Commands,
     active_vessel: Option<Res<ActiveVessel>>,
+    config: Res<PhysicsConfig>,
+    collider_config: Res<TerrainColliderConfig>,
+    time: Res<Time<Fixed>>,
 ) {
     let Some(active_vessel) = active_vessel else {
         error!("cannot update terrain colliders: active vessel doesn't exist");
         return;
     };
 
+    #[expect(clippy::cast_precision_loss)]
+    let margin = ColliderMargin {
+        angle: config.collider_margin_angle,
+        lookahead: config.terrain_collider_interval.max(1) as f64 * time.timestep().as_secs_f64(),
+    };
+
     for celestial in celestial_query {
-        update_collider(celestial, vessel_query, &active_vessel, &mut commands);
+        update_collider(
+            celestial,
+            vessel_query,
+            &active_vessel,
+            margin,
+            &collider_config,
+            &mut commands,
+        );
+    }
+}
+
+/// Lines up terrain colliders with the current rigid-space frame.
+///
+/// Terrain colliders are built relative to the active vessel, so on ticks
+/// where they don't get rebuilt, the reused collider has to be moved by
+/// however much the body moved relative to the active vessel since then.
+pub(crate) fn shift_terrain_colliders(
+    celestial_query: Query<
+        (&RootSpacePosition, &PrevColliderOrigin, &mut Transform),
+        With<Terrain>,
+    >,
+    active_vessel: Option<Res<ActiveVessel>>,
+) {
+    let Some(active_vessel) = active_vessel else {
+        return;
+    };
+
+    for (position, origin, mut transform) in celestial_query {
+        let rigid_pos = position.0 - active_vessel.prev_tick_position.0;
+        transform.translation = (rigid_pos - origin.0).as_vec2().extend(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::DVec2;
+    use core::ops::Range;
+
+    #[test]
+    fn too_few_points_fall_back_to_ball() {
+        let terrain = Terrain {
+            seed: 2401,
+            octaves: 6,
+            frequency: 400.0,
+            gain: 0.4,
+            lacunarity: 0.6,
+            offset: 1000.0,
+            multiplier: 10.0,
+            subdivs: 0,
+        };
+        let base_radius = 990.0;
+        let to_points = |ranges: &[Range<u32>]| -> Vec<_> {
+            gen_points(terrain, ranges)
+                .into_iter()
+                .map(|point| OPoint::from(point.phys_downcast(DVec2::ZERO)))
+                .collect()
+        };
+
+        // The body's center and a single vertex don't make an outline
+        let sliver = to_points(&[0..1]);
+        assert!(sliver.len() < 3);
+        // Plenty of points, but fewer than asked for
+        let patch = to_points(&[0..16]);
+        let strict = TerrainColliderConfig {
+            min_vertices: 64,
+            ..Default::default()
+        };
+
+        for (points, config) in [
+            (&sliver, TerrainColliderConfig::default()),
+            (&patch, strict),
+        ] {
+            let collider = terrain_collider(points, Vec2::ZERO, base_radius, &config);
+            let parts = collider
+                .raw
+                .as_compound()
+                .expect("terrain collider should be a compound")
+                .shapes();
+
+            assert_eq!(parts.len(), 1, "only the base ball should be left");
+            let ball = parts[0].1.as_ball().expect("fallback should be a ball");
+            assert!((ball.radius - base_radius).abs() < 1e-3);
+        }
+
+        let collider = terrain_collider(&patch, Vec2::ZERO, base_radius, &Default::default());
+        let parts = collider
+            .raw
+            .as_compound()
+            .expect("terrain collider should be a compound")
+            .shapes();
+        assert!(parts.len() > 1, "enough points should get decomposed");
     }
 }
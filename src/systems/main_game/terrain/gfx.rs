@@ -5,9 +5,10 @@ use crate::{
         frames::RootSpacePosition,
         terrain::gfx::{LodVectors, PrevFocus},
     },
+    resources::simulation::TerrainMeshConfig,
     terrain::{
         TerrainGen,
-        gfx::{get_focus, get_lod_level_cap},
+        gfx::{Buffers, get_focus, get_lod_level_cap},
     },
 };
 use bevy::{
@@ -15,6 +16,7 @@ use bevy::{
     ecs::query::QueryData,
     mesh::Indices,
     prelude::*,
+    utils::Parallel,
 };
 use core::{
     num::NonZeroU8,
@@ -55,6 +57,7 @@ pub(crate) struct CelestialComponents {
 struct GlobalData {
     zoom: SimCameraZoom,
     cam_pos: RootSpacePosition,
+    config: TerrainMeshConfig,
 }
 
 enum CowMut<'a, T> {
@@ -100,23 +103,31 @@ fn swap_indices(src: &Indices, dest: &mut Indices) {
     }
 }
 
-fn update_gfx_mesh(
-    celestial: CelestialComponentsItem,
-    global: GlobalData,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    commands: &mut Commands,
-) {
+/// A celestial body's freshly generated terrain mesh, waiting
+/// to be written into its mesh asset.
+pub(crate) struct GeneratedMesh {
+    entity: Entity,
+    mesh: AssetId<Mesh>,
+    buffers: Buffers,
+    /// Newly created LoD vectors, for bodies that didn't have any yet.
+    new_lod_vectors: Option<LodVectors>,
+    /// The focus to remember, for bodies that didn't have one yet.
+    new_prev_focus: Option<PrevFocus>,
+}
+
+/// Generates the terrain mesh of a celestial body.
+///
+/// This doesn't touch anything outside of the body's own components,
+/// so it can run for many bodies in parallel.
+fn generate_gfx_mesh(celestial: CelestialComponentsItem, global: GlobalData) -> GeneratedMesh {
     // TODO: Consider celestial rotation
     let new_focus = get_focus(*celestial.pos, 0.0, global.cam_pos);
-    let prev_focus = if let Some(mut f) = celestial.prev_focus {
+    let (prev_focus, new_prev_focus) = if let Some(mut f) = celestial.prev_focus {
         let old = *f;
         f.0 = new_focus;
-        old.0
+        (old.0, None)
     } else {
-        commands
-            .entity(celestial.entity)
-            .insert(PrevFocus(new_focus));
-        f64::NAN
+        (f64::NAN, Some(PrevFocus(new_focus)))
     };
     let camera_space_pos = celestial.pos.0 - global.cam_pos.0;
     let distance_sq = global.cam_pos.0.distance_squared(celestial.pos.0);
@@ -141,22 +152,67 @@ fn update_gfx_mesh(
         lod_vectors.update_lods(&terrain_gen, ending_level, prev_focus, new_focus);
     }
 
-    let buffers =
-        lod_vectors.create_buffers(new_focus, ending_level, camera_space_pos, global.zoom);
+    let buffers = lod_vectors.create_buffers(
+        new_focus,
+        ending_level,
+        global.config.stitch_blend,
+        camera_space_pos,
+        global.zoom,
+    );
+
+    let new_lod_vectors = match lod_vectors {
+        CowMut::Owned(vecs) => Some(vecs),
+        CowMut::Borrowed(_) => None,
+    };
 
-    if let CowMut::Owned(vecs) = lod_vectors {
-        commands.entity(celestial.entity).insert(vecs);
+    GeneratedMesh {
+        entity: celestial.entity,
+        mesh: celestial.mesh.0.id(),
+        buffers,
+        new_lod_vectors,
+        new_prev_focus,
     }
+}
 
-    let Some(mesh) = meshes.get_mut(celestial.mesh) else {
-        error!(
-            "celestial body {} has dangling reference to mesh",
-            celestial.entity
-        );
+/// Writes a generated terrain mesh into its mesh asset.
+fn write_gfx_mesh(
+    generated: GeneratedMesh,
+    aabb: Option<Mut<Aabb>>,
+    meshes: &mut Assets<Mesh>,
+    commands: &mut Commands,
+) {
+    let GeneratedMesh {
+        entity,
+        mesh: mesh_id,
+        buffers,
+        new_lod_vectors,
+        new_prev_focus,
+    } = generated;
+
+    if let Some(vecs) = new_lod_vectors {
+        commands.entity(entity).insert(vecs);
+    }
+    if let Some(prev_focus) = new_prev_focus {
+        commands.entity(entity).insert(prev_focus);
+    }
+
+    let Some(mesh) = meshes.get(mesh_id) else {
+        error!("celestial body {entity} has dangling reference to mesh");
+        return;
+    };
+
+    // Mutably borrowing the mesh marks it as modified, which makes
+    // it get reuploaded to the GPU, so skip it if nothing changed
+    if buffers.matches_mesh(mesh) {
+        return;
+    }
+
+    let Some(mesh) = meshes.get_mut(mesh_id) else {
         return;
     };
 
     mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, buffers.vertices);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, buffers.normals);
     match mesh.indices_mut() {
         Some(indices) => {
             swap_indices(&buffers.indices, indices);
@@ -166,21 +222,35 @@ fn update_gfx_mesh(
         }
     }
 
-    if let Some(aabb) = mesh.compute_aabb() {
-        match celestial.aabb {
-            Some(mut cel_aabb) => *cel_aabb = aabb,
+    if let Some(new_aabb) = mesh.compute_aabb() {
+        match aabb {
+            Some(mut aabb) => *aabb = new_aabb,
             None => {
-                commands.entity(celestial.entity).insert(aabb);
+                commands.entity(entity).insert(new_aabb);
             }
         }
     }
 }
 
+/// Regenerates the terrain meshes of every celestial body, once every
+/// [`TerrainMeshConfig::rebuild_interval`] frames.
+///
+/// The meshes get generated in parallel, one body per task, then
+/// written into their mesh assets on this thread.
 pub(crate) fn update_terrain_gfx(
     mut queries: ParamSet<Queries>,
     mut meshes: ResMut<Assets<Mesh>>,
+    config: Res<TerrainMeshConfig>,
     mut commands: Commands,
+    mut generated: Local<Parallel<Vec<GeneratedMesh>>>,
+    mut frames_until_rebuild: Local<u32>,
 ) {
+    if *frames_until_rebuild > 0 {
+        *frames_until_rebuild -= 1;
+        return;
+    }
+    *frames_until_rebuild = config.rebuild_interval.saturating_sub(1);
+
     let Some((&zoom, &offset, _)) = queries.p0().iter().find(|(_, _, camera)| camera.is_active)
     else {
         #[cfg(feature = "trace")]
@@ -190,9 +260,306 @@ pub(crate) fn update_terrain_gfx(
 
     let cam_pos = offset.immutably().get_root_position(queries.p1());
 
-    let global = GlobalData { zoom, cam_pos };
+    let global = GlobalData {
+        zoom,
+        cam_pos,
+        config: *config,
+    };
+
+    queries.p2().par_iter_mut().for_each_init(
+        || generated.borrow_local_mut(),
+        |local, celestial| local.push(generate_gfx_mesh(celestial, global)),
+    );
+
+    let mut celestials = queries.p2();
+    for mesh in generated.drain() {
+        let aabb = celestials
+            .get_mut(mesh.entity)
+            .ok()
+            .and_then(|celestial| celestial.aabb);
+        write_gfx_mesh(mesh, aabb, &mut meshes, &mut commands);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::{
+        camera::SimCameraBuilder,
+        celestial::{CelestialBodyBuilder, CelestialSurface},
+    };
+    use bevy::{
+        asset::{AssetEvent, RenderAssetUsages},
+        ecs::message::MessageCursor,
+        math::DVec2,
+        mesh::PrimitiveTopology,
+    };
+
+    fn mesh_modified_count(
+        app: &App,
+        cursor: &mut MessageCursor<AssetEvent<Mesh>>,
+        id: AssetId<Mesh>,
+    ) -> usize {
+        let messages = app.world().resource::<Messages<AssetEvent<Mesh>>>();
+        cursor
+            .read(messages)
+            .filter(|event| event.is_modified(id))
+            .count()
+    }
+
+    #[test]
+    fn regenerates_mesh_only_on_change() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        app.init_asset::<Mesh>();
+        app.init_asset::<ColorMaterial>();
+        app.init_resource::<TerrainMeshConfig>();
+        app.add_systems(Update, update_terrain_gfx);
+
+        let mesh = app
+            .world_mut()
+            .resource_mut::<Assets<Mesh>>()
+            .add(Mesh::new(
+                PrimitiveTopology::TriangleList,
+                RenderAssetUsages::all(),
+            ));
+        let material = app
+            .world_mut()
+            .resource_mut::<Assets<ColorMaterial>>()
+            .add(ColorMaterial::from_color(Color::WHITE));
 
-    for celestial in queries.p2() {
-        update_gfx_mesh(celestial, global, &mut meshes, &mut commands);
+        let terrain = Terrain {
+            offset: 1000.0,
+            multiplier: 20.0,
+            ..Default::default()
+        };
+
+        let body = app
+            .world_mut()
+            .spawn(
+                CelestialBodyBuilder {
+                    name: Name::new("Body"),
+                    radius: 1000.0,
+                    mass: 1.0,
+                    angle: 0.0,
+                    mesh: Mesh2d(mesh.clone()),
+                    material: MeshMaterial2d(material),
+                    surface: CelestialSurface::default(),
+                }
+                .build_with_terrain(terrain),
+            )
+            .id();
+
+        let camera = app
+            .world_mut()
+            .spawn(
+                SimCameraBuilder {
+                    offset: SimCameraOffset::Attached {
+                        entity: body,
+                        last_known_pos: RootSpacePosition(DVec2::ZERO),
+                        offset: DVec2::new(0.0, 1000.0),
+                    },
+                    zoom: SimCameraZoom(1.0),
+                    transform: Transform::IDENTITY,
+                }
+                .build(true),
+            )
+            .id();
+
+        app.update();
+
+        let vertex_count = |app: &App| {
+            app.world()
+                .resource::<Assets<Mesh>>()
+                .get(&mesh)
+                .expect("mesh should exist")
+                .count_vertices()
+        };
+        assert_ne!(vertex_count(&app), 0);
+
+        app.update();
+        let mut cursor = app
+            .world()
+            .resource::<Messages<AssetEvent<Mesh>>>()
+            .get_cursor();
+
+        (0..4).for_each(|_| app.update());
+        assert_eq!(
+            mesh_modified_count(&app, &mut cursor, mesh.id()),
+            0,
+            "mesh shouldn't be rewritten while the camera stays still"
+        );
+
+        app.world_mut()
+            .get_mut::<SimCameraZoom>(camera)
+            .expect("camera should have a zoom")
+            .0 = 2.0;
+        (0..2).for_each(|_| app.update());
+        assert_ne!(
+            mesh_modified_count(&app, &mut cursor, mesh.id()),
+            0,
+            "mesh should be rewritten after zooming in"
+        );
+    }
+
+    #[test]
+    fn throttles_mesh_rebuilds() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        app.init_asset::<Mesh>();
+        app.init_asset::<ColorMaterial>();
+        app.insert_resource(TerrainMeshConfig {
+            rebuild_interval: 4,
+            ..Default::default()
+        });
+        app.add_systems(Update, update_terrain_gfx);
+
+        let mesh = app
+            .world_mut()
+            .resource_mut::<Assets<Mesh>>()
+            .add(Mesh::new(
+                PrimitiveTopology::TriangleList,
+                RenderAssetUsages::all(),
+            ));
+        let material = app
+            .world_mut()
+            .resource_mut::<Assets<ColorMaterial>>()
+            .add(ColorMaterial::from_color(Color::WHITE));
+
+        app.world_mut().spawn(
+            CelestialBodyBuilder {
+                name: Name::new("Body"),
+                radius: 1000.0,
+                mass: 1.0,
+                angle: 0.0,
+                mesh: Mesh2d(mesh.clone()),
+                material: MeshMaterial2d(material),
+                surface: CelestialSurface::default(),
+            }
+            .build_with_terrain(Terrain {
+                offset: 1000.0,
+                multiplier: 20.0,
+                ..Default::default()
+            }),
+        );
+        let camera = app
+            .world_mut()
+            .spawn(
+                SimCameraBuilder {
+                    offset: SimCameraOffset::Detached(RootSpacePosition(DVec2::new(0.0, 1100.0))),
+                    zoom: SimCameraZoom(1.0),
+                    transform: Transform::IDENTITY,
+                }
+                .build(true),
+            )
+            .id();
+
+        let mut cursor = app
+            .world()
+            .resource::<Messages<AssetEvent<Mesh>>>()
+            .get_cursor();
+
+        // Zooming in every frame would rebuild the mesh every frame
+        let mut rebuilt = Vec::new();
+        for frame in 0..12 {
+            app.world_mut()
+                .get_mut::<SimCameraZoom>(camera)
+                .expect("camera should have a zoom")
+                .0 = 1.0 + f64::from(frame) * 0.1;
+            app.update();
+
+            if mesh_modified_count(&app, &mut cursor, mesh.id()) > 0 {
+                rebuilt.push(frame);
+            }
+
+            let vertices = app
+                .world()
+                .resource::<Assets<Mesh>>()
+                .get(&mesh)
+                .expect("mesh should exist")
+                .count_vertices();
+            assert_ne!(vertices, 0, "mesh should be kept between rebuilds");
+        }
+
+        assert_eq!(rebuilt, [0, 4, 8]);
+    }
+
+    #[test]
+    #[expect(clippy::cast_precision_loss)]
+    fn generates_many_meshes_in_one_tick() {
+        const BODIES: usize = 16;
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        app.init_asset::<Mesh>();
+        app.init_asset::<ColorMaterial>();
+        app.init_resource::<TerrainMeshConfig>();
+        app.add_systems(Update, update_terrain_gfx);
+
+        let material = app
+            .world_mut()
+            .resource_mut::<Assets<ColorMaterial>>()
+            .add(ColorMaterial::from_color(Color::WHITE));
+
+        let meshes: Vec<Handle<Mesh>> = (0..BODIES)
+            .map(|i| {
+                let mesh = app
+                    .world_mut()
+                    .resource_mut::<Assets<Mesh>>()
+                    .add(Mesh::new(
+                        PrimitiveTopology::TriangleList,
+                        RenderAssetUsages::all(),
+                    ));
+
+                let terrain = Terrain {
+                    seed: i32::try_from(i).unwrap(),
+                    offset: 1000.0,
+                    multiplier: 20.0,
+                    subdivs: 2,
+                    ..Default::default()
+                };
+
+                let body = app
+                    .world_mut()
+                    .spawn(
+                        CelestialBodyBuilder {
+                            name: Name::new(format!("Body {i}")),
+                            radius: 1000.0,
+                            mass: 1.0,
+                            angle: 0.0,
+                            mesh: Mesh2d(mesh.clone()),
+                            material: MeshMaterial2d(material.clone()),
+                            surface: CelestialSurface::default(),
+                        }
+                        .build_with_terrain(terrain),
+                    )
+                    .id();
+
+                app.world_mut()
+                    .get_mut::<RootSpacePosition>(body)
+                    .expect("body should have a position")
+                    .0 = DVec2::new(5000.0 * i as f64, 0.0);
+
+                mesh
+            })
+            .collect();
+
+        app.world_mut().spawn(
+            SimCameraBuilder {
+                offset: SimCameraOffset::Detached(RootSpacePosition(DVec2::new(0.0, 1100.0))),
+                zoom: SimCameraZoom(1.0),
+                transform: Transform::IDENTITY,
+            }
+            .build(true),
+        );
+
+        app.update();
+
+        let assets = app.world().resource::<Assets<Mesh>>();
+        for (i, mesh) in meshes.iter().enumerate() {
+            let mesh = assets.get(mesh).expect("mesh should exist");
+            assert_ne!(mesh.count_vertices(), 0, "body {i} has no vertices");
+            assert!(mesh.indices().is_some(), "body {i} has no indices");
+        }
     }
 }
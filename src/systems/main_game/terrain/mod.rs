@@ -1,2 +1,85 @@
 pub(crate) mod collider;
 pub(crate) mod gfx;
+
+use bevy::prelude::*;
+
+use crate::components::main_game::celestial::{Terrain, TerrainSampler};
+
+/// Rebuilds the [`TerrainSampler`] of every body whose [`Terrain`] changed,
+/// adding it if it's missing.
+pub(crate) fn update_terrain_samplers(
+    mut commands: Commands,
+    query: Query<(Entity, &Terrain, Option<&mut TerrainSampler>), Changed<Terrain>>,
+) {
+    for (entity, &terrain, sampler) in query {
+        match sampler {
+            Some(mut sampler) => *sampler = TerrainSampler::new(terrain),
+            None => {
+                commands.entity(entity).insert(TerrainSampler::new(terrain));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::main_game::celestial::{CelestialBody, SurfaceData},
+        terrain::TerrainGen,
+    };
+    use core::f64::consts::TAU;
+
+    #[test]
+    fn surface_radius_within_terrain_bounds() {
+        let mut app = App::new();
+        app.add_systems(Update, update_terrain_samplers);
+
+        let terrain = Terrain {
+            seed: 7,
+            octaves: 4,
+            frequency: 2.0,
+            gain: 0.5,
+            lacunarity: 2.0,
+            offset: 600_000.0,
+            multiplier: 3_000.0,
+            subdivs: 4,
+        };
+
+        let with_terrain = app.world_mut().spawn(terrain).id();
+        let without_terrain = app
+            .world_mut()
+            .spawn(CelestialBody {
+                base_radius: 1000.0,
+                mass: 1.0,
+            })
+            .id();
+
+        app.update();
+        assert!(
+            app.world()
+                .entity(with_terrain)
+                .contains::<TerrainSampler>()
+        );
+
+        let mut query = app.world_mut().query::<SurfaceData>();
+        let world = app.world();
+
+        let surface = query.get(world, with_terrain).unwrap();
+        let mut radii = (0..4096).map(|i| surface.surface_radius(f64::from(i) * TAU / 4096.0));
+        assert!(radii.all(|radius| {
+            (terrain.offset - terrain.multiplier..=terrain.offset + terrain.multiplier)
+                .contains(&radius)
+        }));
+
+        // Matches the terrain the colliders get generated from
+        assert!(
+            (surface.surface_radius(1.0) - TerrainGen::new(terrain).get_terrain_altitude(1.0))
+                .abs()
+                < f64::EPSILON
+        );
+
+        let surface = query.get(world, without_terrain).unwrap();
+        assert!((surface.surface_radius(2.0) - 1000.0).abs() < f64::EPSILON);
+    }
+}
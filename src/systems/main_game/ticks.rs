@@ -0,0 +1,50 @@
+//! Fixed tick counting, for running expensive systems less often
+
+use bevy::prelude::*;
+
+use crate::resources::simulation::FixedTickCounter;
+
+pub(crate) fn count_fixed_ticks(mut counter: ResMut<FixedTickCounter>) {
+    counter.0 += 1;
+}
+
+/// A run condition that passes on every `n`th fixed tick,
+/// starting from the first one.
+///
+/// Values of 0 are treated as 1, i.e. running every tick.
+pub(crate) fn every_n_ticks(n: u64) -> impl FnMut(Res<FixedTickCounter>) -> bool + Clone {
+    let n = n.max(1);
+    move |counter: Res<FixedTickCounter>| counter.0.is_multiple_of(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default, Resource)]
+    struct Runs(Vec<u64>);
+
+    fn record_run(counter: Res<FixedTickCounter>, mut runs: ResMut<Runs>) {
+        runs.0.push(counter.0);
+    }
+
+    #[test]
+    fn runs_every_n_ticks() {
+        for (n, expected) in [
+            (0, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            (1, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            (4, vec![0, 4, 8]),
+            (20, vec![0]),
+        ] {
+            let mut app = App::new();
+            app.init_resource::<FixedTickCounter>();
+            app.init_resource::<Runs>();
+            app.add_systems(Update, record_run.run_if(every_n_ticks(n)));
+            app.add_systems(Last, count_fixed_ticks);
+
+            (0..10).for_each(|_| app.update());
+
+            assert_eq!(app.world().resource::<Runs>().0, expected, "n = {n}");
+        }
+    }
+}
@@ -1,70 +1,81 @@
 use core::f64::consts::PI;
 
 use crate::{
-    builders::{camera::SimCameraBuilder, celestial::CelestialBodyBuilder, vessel::VesselBuilder},
+    builders::{camera::SimCameraBuilder, solar_system::SystemConfig, vessel::VesselBuilder},
     components::main_game::{
         camera::{SimCamera, SimCameraOffset, SimCameraZoom},
-        celestial::Terrain,
         frames::{RootSpaceLinearVelocity, RootSpacePosition},
         relations::{CelestialParent, RailMode},
     },
-    consts::GRAVITATIONAL_CONSTANT,
-    resources::simulation::ActiveVessel,
+    resources::simulation::{ActiveVessel, GravityConstants},
 };
 use bevy::{asset::RenderAssetUsages, math::DVec2, mesh::PrimitiveTopology, prelude::*};
 use bevy_rapier2d::prelude::*;
 use keplerian_sim::{Orbit2D, OrbitTrait2D};
 
-const CELESTIAL_RADIUS: f64 = 6_371_137.0;
-const CELESTIAL_MASS: f64 = 5.972_168e24;
-const ALTITUDE: f64 = CELESTIAL_RADIUS + 100e3;
+/// How high above the surface the vessel starts out orbiting, in meters.
+const START_ALTITUDE: f64 = 100e3;
 
+/// Spawns the [`SystemConfig`]'s bodies, or the default ones if there's
+/// no config, along with the player's vessel and the camera.
+///
+/// The vessel starts out in a circular orbit around the first top-level
+/// body. A config that can't be spawned falls back to the default system.
 pub(crate) fn init_game(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    gravity: Res<GravityConstants>,
+    config: Option<Res<SystemConfig>>,
 ) {
     // TODO: Load from save
-    let mesh = Mesh::new(
-        PrimitiveTopology::TriangleList,
-        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
-    );
-    let mesh = meshes.add(mesh);
-
     let material = ColorMaterial::from_color(Color::srgba(1.0, 1.0, 1.0, 0.2));
     let material = materials.add(material);
 
-    let body = CelestialBodyBuilder {
-        name: Name::new("Body"),
-        #[expect(clippy::cast_possible_truncation)]
-        radius: CELESTIAL_RADIUS as f32,
-        mass: CELESTIAL_MASS,
-        angle: 0.0,
-        mesh: Mesh2d(mesh),
-        material: MeshMaterial2d(material.clone()),
-    }
-    .build_with_terrain(Terrain {
-        seed: 2401,
-        octaves: 6,
-        frequency: 400.0,
-        gain: 0.4,
-        lacunarity: 0.6,
-        offset: CELESTIAL_RADIUS,
-        multiplier: CELESTIAL_RADIUS * 0.001,
-        subdivs: 6,
+    // Every body needs a mesh of its own for its terrain to get generated into
+    let mut spawn_config = |config: &SystemConfig| {
+        config.spawn(&mut commands, &gravity, |_| {
+            let mesh = Mesh::new(
+                PrimitiveTopology::TriangleList,
+                RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+            );
+            (Mesh2d(meshes.add(mesh)), MeshMaterial2d(material.clone()))
+        })
+    };
+
+    let configured = config.and_then(|config| match spawn_config(&config) {
+        Ok(spawned) => Some((config.into_inner(), spawned)),
+        Err(err) => {
+            error!("Cannot spawn the configured solar system, using the default one: {err}");
+            None
+        }
     });
-    let body = commands.spawn(body).id();
+    let default_config = SystemConfig::default();
+    let (config, spawned) = configured.unwrap_or_else(|| {
+        let spawned =
+            spawn_config(&default_config).expect("the default solar system should always be valid");
+        (&default_config, spawned)
+    });
+
+    let Some((&body, spec)) = spawned.first().zip(config.bodies.first()) else {
+        error!("The solar system has no bodies for the vessel to orbit");
+        return;
+    };
 
-    let orbit = Orbit2D::new_circular(ALTITUDE, 0.0, CELESTIAL_MASS * GRAVITATIONAL_CONSTANT);
+    let orbit = Orbit2D::new_circular(
+        spec.radius + START_ALTITUDE,
+        0.0,
+        spec.mass * gravity.gravitational_constant,
+    );
     let vessel_init_sv = orbit.get_state_vectors_at_true_anomaly(PI / 2.0);
-    let vessel_pos = RootSpacePosition(vessel_init_sv.position);
+    let body_pos = spec.position.unwrap_or(DVec2::ZERO);
+    let vessel_pos = RootSpacePosition(body_pos + vessel_init_sv.position);
     let vessel_vel = RootSpaceLinearVelocity(vessel_init_sv.velocity);
     let vessel_half_x = 10.0;
     let vessel_half_y = 20.0;
 
     let mesh = Mesh2d(meshes.add(Rectangle::new(vessel_half_x * 2.0, vessel_half_y * 2.0)));
 
-    dbg!(vessel_init_sv);
     let vessel = VesselBuilder {
         name: Name::new("Vessel"),
         collider: Collider::cuboid(vessel_half_x, vessel_half_y),
@@ -117,3 +128,104 @@ pub(crate) fn exit_game(mut commands: Commands, sim_objects: Query<Entity, Filte
     commands.remove_resource::<ActiveVessel>();
     commands.remove_resource::<ClearColor>();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builders::solar_system::BodySpec, components::main_game::celestial::CelestialBody,
+    };
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn enter_game(config: Option<SystemConfig>) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        app.init_asset::<Mesh>();
+        app.init_asset::<ColorMaterial>();
+        app.init_resource::<GravityConstants>();
+        if let Some(config) = config {
+            app.insert_resource(config);
+        }
+
+        app.world_mut().run_system_once(init_game).unwrap();
+        app
+    }
+
+    fn bodies(app: &mut App) -> Vec<(String, RootSpacePosition)> {
+        let mut bodies: Vec<_> = app
+            .world_mut()
+            .query_filtered::<(&Name, &RootSpacePosition), With<CelestialBody>>()
+            .iter(app.world())
+            .map(|(name, &pos)| (name.to_string(), pos))
+            .collect();
+        bodies.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        bodies
+    }
+
+    /// Gets the name of the body the active vessel orbits,
+    /// and how far away from it the vessel is.
+    fn vessel_orbit(app: &App) -> (String, f64) {
+        let world = app.world();
+        let active = world.resource::<ActiveVessel>();
+        let parent = world.get::<CelestialParent>(active.entity).unwrap().entity;
+        assert_eq!(parent, active.prev_tick_parent);
+
+        let parent_pos = world.get::<RootSpacePosition>(parent).unwrap();
+        let pos = world.get::<RootSpacePosition>(active.entity).unwrap();
+
+        (
+            world.get::<Name>(parent).unwrap().to_string(),
+            pos.distance_to(*parent_pos),
+        )
+    }
+
+    #[test]
+    fn default_system_without_config() {
+        let mut app = enter_game(None);
+
+        let default = SystemConfig::default();
+        let bodies = bodies(&mut app);
+        assert_eq!(
+            bodies,
+            [(
+                default.bodies[0].name.clone(),
+                RootSpacePosition(DVec2::ZERO)
+            )]
+        );
+
+        let (parent, distance) = vessel_orbit(&app);
+        assert_eq!(parent, default.bodies[0].name);
+        assert!((distance - (default.bodies[0].radius + START_ALTITUDE)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn configured_system_replaces_default() {
+        let body = |name: &str, x: f64| BodySpec {
+            name: name.into(),
+            radius: 1e6,
+            mass: 1e22,
+            position: Some(DVec2::new(x, 0.0)),
+            orbit: None,
+            terrain: None,
+            children: Vec::new(),
+        };
+        let config = SystemConfig {
+            bodies: vec![body("Alpha", -5e9), body("Beta", 5e9)],
+        };
+
+        let mut app = enter_game(Some(config));
+
+        assert_eq!(
+            bodies(&mut app),
+            [
+                ("Alpha".to_owned(), RootSpacePosition(DVec2::new(-5e9, 0.0))),
+                ("Beta".to_owned(), RootSpacePosition(DVec2::new(5e9, 0.0))),
+            ],
+            "only the configured bodies should be spawned, where they're configured"
+        );
+
+        let (parent, distance) = vessel_orbit(&app);
+        assert_eq!(parent, "Alpha");
+        assert!((distance - (1e6 + START_ALTITUDE)).abs() < 1e-3);
+    }
+}
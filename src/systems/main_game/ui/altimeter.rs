@@ -10,7 +10,7 @@ use crate::{
     },
     checked_assign,
     components::main_game::{
-        celestial::{CelestialBody, Terrain},
+        celestial::{CelestialBody, SurfaceData},
         frames::RootSpacePosition,
         ui::altimeter::{
             Altimeter, AltimeterAltitudeText, AltimeterMobileAltitudeText,
@@ -31,7 +31,6 @@ use crate::{
     fl,
     resources::{scene::GameScene, simulation::ActiveVessel, ui::AltimeterMode},
     systems::general::ui_activation::ActivationEvent,
-    terrain::TerrainGen,
 };
 
 fn wrapper(children: &[Entity], commands: &mut Commands) -> Entity {
@@ -299,29 +298,23 @@ pub(crate) fn init_altimeter(
 }
 
 pub(crate) fn calculate_altitude_format(
-    cel_query: Query<(&CelestialBody, &RootSpacePosition, Option<&Terrain>)>,
+    cel_query: Query<(&CelestialBody, &RootSpacePosition, SurfaceData)>,
     active_vessel: Res<ActiveVessel>,
     altimeter_mode: Res<State<AltimeterMode>>,
 ) -> Option<AltitudeFormat> {
-    let Ok((body, body_pos, terrain)) = cel_query.get(active_vessel.prev_tick_parent) else {
+    let Ok((body, body_pos, surface)) = cel_query.get(active_vessel.prev_tick_parent) else {
         return None;
     };
 
     let rel_pos = active_vessel.prev_tick_position.0 - body_pos.0;
     let dist = rel_pos.length();
 
-    let altitude = match (altimeter_mode.get(), terrain) {
-        (AltimeterMode::FromCentre, _) => dist,
-        (AltimeterMode::AboveSeaLevel, _) | (AltimeterMode::AboveGroundLevel, None) => {
-            dist - f64::from(body.base_radius)
-        }
-        (AltimeterMode::AboveGroundLevel, Some(terrain)) => {
+    let altitude = match altimeter_mode.get() {
+        AltimeterMode::FromCentre => dist,
+        AltimeterMode::AboveSeaLevel => dist - f64::from(body.base_radius),
+        AltimeterMode::AboveGroundLevel => {
             // TODO: Consider celestial rotation
-            let theta = rel_pos.to_angle();
-            let terrain = TerrainGen::new(*terrain);
-            let terrain_altitude = terrain.get_terrain_altitude(theta);
-
-            dist - terrain_altitude
+            dist - surface.surface_radius(rel_pos.to_angle())
         }
     };
 
@@ -21,6 +21,7 @@ use crate::{
     components::main_game::{
         frames::{RootSpaceLinearVelocity, RootSpacePosition},
         ui::oribar::{Oribar, OribarIndicator, OribarOverlay},
+        vessel::ControlPoint,
     },
     consts::{
         colors::{ORIBAR_BACKGROUND, scheme::ERROR},
@@ -264,15 +265,18 @@ pub(crate) struct OribarState {
 impl OribarState {
     #[must_use]
     fn new(
-        tf_query: Query<&Transform>,
+        tf_query: Query<(&Transform, Option<&ControlPoint>)>,
         sv_query: Query<(&RootSpacePosition, &RootSpaceLinearVelocity)>,
         active_vessel: Res<ActiveVessel>,
         screen: Single<&Window, With<PrimaryWindow>>,
     ) -> Option<Self> {
-        let Ok(transform) = tf_query.get(active_vessel.entity) else {
+        let Ok((transform, control_point)) = tf_query.get(active_vessel.entity) else {
             return None;
         };
-        let root_rotation = quat_to_rot(transform.rotation);
+        let root_rotation = control_point
+            .copied()
+            .unwrap_or_default()
+            .effective_rotation(quat_to_rot(transform.rotation));
 
         let Ok((parent_pos, parent_vel)) = sv_query.get(active_vessel.prev_tick_parent) else {
             return None;
@@ -344,7 +348,7 @@ impl OribarState {
 #[must_use]
 pub(crate) fn calculate_oribar_state(
     screen: Single<&Window, With<PrimaryWindow>>,
-    tf_query: Query<&Transform>,
+    tf_query: Query<(&Transform, Option<&ControlPoint>)>,
     sv_query: Query<(&RootSpacePosition, &RootSpaceLinearVelocity)>,
     active_vessel: Res<ActiveVessel>,
 ) -> Option<OribarState> {
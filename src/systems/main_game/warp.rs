@@ -0,0 +1,145 @@
+//! Time warp handling
+
+use bevy::{math::DVec2, prelude::*};
+
+#[cfg(feature = "not-headless")]
+use crate::consts::controls::{KB_WARP_FASTER, KB_WARP_SLOWER};
+use crate::{
+    components::main_game::{
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::RailMode,
+    },
+    consts::{MAX_WARP_ANGLE_PER_TICK, MAX_WARP_TO_RATE},
+    messages::warp::WarpTo,
+    resources::simulation::{ActiveVessel, TimeWarp},
+};
+
+pub(crate) fn handle_warp_to(
+    mut reader: MessageReader<WarpTo>,
+    rails: Query<(NameOrEntity, &RailMode)>,
+    time: Res<Time<Fixed>>,
+    mut warp: ResMut<TimeWarp>,
+) {
+    let Some(&WarpTo { vessel, target }) = reader.read().last() else {
+        return;
+    };
+
+    let Ok((name, rail_mode)) = rails.get(vessel) else {
+        warn!("Attempted to warp to an apsis of {vessel}, which has no rail");
+        return;
+    };
+
+    let now = time.elapsed_secs_f64();
+
+    let Some(time_to_apsis) = rail_mode.time_to_apsis(now, target) else {
+        info!("Vessel {name} will never reach its {target:?}; not warping");
+        return;
+    };
+
+    *warp = TimeWarp {
+        rate: MAX_WARP_TO_RATE,
+        until: Some(now + time_to_apsis),
+    };
+}
+
+#[cfg(feature = "not-headless")]
+pub(crate) fn change_warp_rate(mut warp: ResMut<TimeWarp>, keyboard: Res<ButtonInput<KeyCode>>) {
+    if keyboard.any_just_pressed(KB_WARP_FASTER) {
+        warp.step_up();
+    }
+    if keyboard.any_just_pressed(KB_WARP_SLOWER) {
+        warp.step_down();
+    }
+}
+
+/// Steps the [`TimeWarp`] rate down while the active vessel
+/// sweeps around its parent too quickly for it, like when
+/// nearing the periapsis of a steep orbit.
+///
+/// Warps towards a target time are left alone, as they
+/// already slow down on their own.
+pub(crate) fn limit_warp_rate(
+    active: Res<ActiveVessel>,
+    states: Query<(&RootSpacePosition, &RootSpaceLinearVelocity)>,
+    fixed_time: Res<Time<Fixed>>,
+    mut warp: ResMut<TimeWarp>,
+) {
+    if warp.until.is_some() {
+        return;
+    }
+
+    let Ok([(vessel_pos, vessel_vel), (parent_pos, parent_vel)]) =
+        states.get_many([active.entity, active.prev_tick_parent])
+    else {
+        return;
+    };
+
+    let safe_rate = max_safe_warp_rate(
+        vessel_pos.0 - parent_pos.0,
+        vessel_vel.0 - parent_vel.0,
+        fixed_time.timestep().as_secs_f64(),
+    );
+
+    if warp.rate > safe_rate {
+        // Never below real time, even when that's still too fast
+        let limited = TimeWarp::step_at_most(safe_rate);
+        if limited < warp.rate {
+            warp.rate = limited;
+        }
+    }
+}
+
+/// Gets the fastest warp rate at which an object moving at the given
+/// state vectors relative to its parent sweeps at most
+/// [`MAX_WARP_ANGLE_PER_TICK`] around it every `tick_secs` of real time.
+fn max_safe_warp_rate(position: DVec2, velocity: DVec2, tick_secs: f64) -> f64 {
+    // The angular velocity around the parent, which peaks at periapsis
+    let angular_speed = position.perp_dot(velocity).abs() / position.length_squared();
+    let angle_per_tick = angular_speed * tick_secs;
+
+    if angle_per_tick > 0.0 {
+        MAX_WARP_ANGLE_PER_TICK / angle_per_tick
+    } else {
+        f64::INFINITY
+    }
+}
+
+/// Applies the [`TimeWarp`] onto the virtual clock.
+pub(crate) fn apply_time_warp(
+    warp: Res<TimeWarp>,
+    fixed_time: Res<Time<Fixed>>,
+    real_time: Res<Time<Real>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    let rate = match warp.until {
+        Some(until) => {
+            // Aim to land exactly on the target next frame,
+            // assuming the next frame takes as long as this one did
+            let remaining = until - fixed_time.elapsed_secs_f64();
+            let frame_secs = real_time.delta_secs_f64().max(f64::EPSILON);
+            warp.rate.min(remaining / frame_secs).max(1.0)
+        }
+        None => warp.rate,
+    };
+
+    #[expect(clippy::float_cmp)]
+    if virtual_time.relative_speed_f64() != rate {
+        virtual_time.set_relative_speed_f64(rate);
+    }
+}
+
+/// Stops warping once the [`TimeWarp`] target has been reached.
+pub(crate) fn stop_warp_at_target(
+    mut warp: ResMut<TimeWarp>,
+    time: Res<Time>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    let Some(until) = warp.until else {
+        return;
+    };
+
+    if time.elapsed_secs_f64() >= until {
+        *warp = TimeWarp::default();
+        virtual_time.set_relative_speed_f64(warp.rate);
+    }
+}
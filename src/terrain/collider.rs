@@ -3,7 +3,7 @@ use bevy_rapier2d::rapier::prelude::Aabb;
 
 use crate::{
     components::main_game::celestial::Terrain,
-    consts::terrain::{LOD_DIVISIONS, LOD_VERTS},
+    consts::terrain::{LOD_DIVISIONS, LOD_VERTS, MAX_LOD_LEVEL},
     terrain::{TerrainGen, TerrainPoint},
 };
 use core::{
@@ -11,8 +11,13 @@ use core::{
     ops::{Range, RangeInclusive},
 };
 
+/// Gets the amount of vertices in a full revolution at the given LoD level.
+///
+/// `level` must be at most [`MAX_LOD_LEVEL`].
 #[must_use]
 pub(crate) const fn verts_at_lod_level(level: u8) -> u32 {
+    debug_assert!(level <= MAX_LOD_LEVEL);
+
     // compiler explorer asm output showed that the compiler
     // wasn't able to optimize power-of-two powering as best it can
     // therefore this logic is for extra optimization
@@ -97,6 +102,46 @@ pub(crate) fn get_theta_range(
     }
 }
 
+/// Gets the angle, in radians, to pad a vessel's theta range by
+/// on both sides.
+///
+/// `lookahead` is the time, in seconds, until the collider next gets
+/// rebuilt. The padding covers the distance the vessel travels
+/// along the surface in that time, plus `margin_angle`.
+#[must_use]
+pub(crate) fn get_theta_padding(
+    tangential_speed: f64,
+    lookahead: f64,
+    margin_angle: f64,
+    terrain: &Terrain,
+) -> f64 {
+    let conservative_radius = terrain.offset - terrain.multiplier;
+
+    margin_angle + tangential_speed.abs() * lookahead / conservative_radius
+}
+
+/// Widens a theta range, as gotten through [`get_theta_range`],
+/// by `padding` radians on both sides.
+///
+/// The output has the same bounds as the output of
+/// [`get_theta_range`], and never spans more than a full revolution.
+#[must_use]
+pub(crate) fn pad_theta_range(range: RangeInclusive<f64>, padding: f64) -> RangeInclusive<f64> {
+    let (start, end) = range.into_inner();
+
+    let max_padding = (TAU - (end - start)) / 2.0;
+    let padding = padding.clamp(0.0, max_padding.max(0.0));
+
+    let range_min = start - padding;
+    let range_max = end + padding;
+
+    if range_min.is_sign_negative() {
+        (range_min + TAU)..=(range_max + TAU)
+    } else {
+        range_min..=range_max
+    }
+}
+
 /// Converts a theta range into a index range.
 #[must_use]
 fn theta_range_to_idx_range(range: RangeInclusive<f64>, verts: u32) -> Range<u64> {
@@ -120,12 +165,20 @@ fn theta_range_to_idx_range(range: RangeInclusive<f64>, verts: u32) -> Range<u64
 }
 
 /// Wrap the ranges such that things wrap around correctly based on `verts`.
+///
+/// Ranges spanning at least `verts` indices get turned into
+/// a single `0..verts` range covering the whole ring.
 #[must_use]
 fn wrap_ranges(ranges: &[Range<u64>], verts: u32) -> Vec<Range<u32>> {
     let verts_u64 = u64::from(verts);
     let mut wrapped_ranges = Vec::with_capacity(ranges.len());
 
     for range in ranges {
+        if range.end.saturating_sub(range.start) >= verts_u64 {
+            wrapped_ranges.push(0..verts);
+            continue;
+        }
+
         let start = range.start;
         let end_exclusive = range.end;
 
@@ -293,6 +346,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_max_lod_level_verts() {
+        let max_verts = verts_at_lod_level(MAX_LOD_LEVEL);
+
+        assert_eq!(
+            u64::from(max_verts),
+            u64::from(LOD_VERTS) * u64::from(LOD_DIVISIONS).pow(MAX_LOD_LEVEL.into())
+        );
+        assert!(u64::from(max_verts) * u64::from(LOD_DIVISIONS) > u64::from(u32::MAX));
+    }
+
     fn create_terrain(height: f64) -> Terrain {
         Terrain {
             multiplier: height * 0.1,
@@ -371,6 +435,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_speed_widens_theta_range() {
+        let terrain = create_terrain(600_000.0);
+        let verts = verts_at_lod_level(terrain.subdivs);
+        let aabb = Aabb::new(Vec2::splat(-2.0).into(), Vec2::splat(2.0).into());
+        let lookahead = 10.0;
+
+        let range_len = |tangential_speed: f64, rel_pos: DVec2| -> u32 {
+            let range = get_theta_range(aabb, rel_pos, 0.0, &terrain);
+            let padding = get_theta_padding(tangential_speed, lookahead, 0.0, &terrain);
+            let range = pad_theta_range(range, padding);
+
+            gen_idx_ranges(&[range], verts)
+                .iter()
+                .map(|range| range.end - range.start)
+                .sum()
+        };
+
+        for rel_pos in [
+            DVec2::new(600_000.0, 0.0),
+            DVec2::new(0.0, -600_000.0),
+            DVec2::new(600_000.0, -1.0),
+        ] {
+            let stationary = range_len(0.0, rel_pos);
+            let slow = range_len(30.0, rel_pos);
+            let fast = range_len(-2000.0, rel_pos);
+
+            assert!(
+                stationary < fast,
+                "fast vessel got {fast} verts, stationary got {stationary}"
+            );
+            assert!(slow <= fast);
+        }
+    }
+
+    #[test]
+    fn test_pad_theta_range() {
+        let padded = pad_theta_range(0.1..=0.3, 0.2);
+        assert!((*padded.start() - (TAU - 0.1)).abs() < 1e-12);
+        assert!((*padded.end() - (TAU + 0.5)).abs() < 1e-12);
+
+        let padded = pad_theta_range(1.0..=2.0, 10.0);
+        assert!((*padded.end() - *padded.start() - TAU).abs() < 1e-12);
+        assert!((0.0..=TAU).contains(padded.start()));
+
+        assert_eq!(pad_theta_range(1.0..=2.0, 0.0), 1.0..=2.0);
+    }
+
     #[test]
     fn test_wrap_ranges() {
         #[expect(clippy::single_range_in_vec_init)]
@@ -387,6 +499,10 @@ mod tests {
             (vec![12..15], 10, vec![2..5]),
             // 6. Zero-length ranges
             (vec![0..0, 10..10], 10, vec![0..0, 0..0]),
+            // 7. Range longer than verts
+            (vec![7..18], 10, vec![0..10]),
+            // 8. Range exactly verts long, but not starting at zero
+            (vec![13..23], 10, vec![0..10]),
         ];
 
         for (input, verts, expected) in test_cases {
@@ -512,6 +628,124 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_full_ring_idx_ranges() {
+        let terrain = Terrain {
+            offset: 10.0,
+            ..Default::default()
+        };
+        let aabb = Aabb::new(Vec2::splat(-50.0).into(), Vec2::splat(50.0).into());
+
+        for level in 0..4 {
+            let verts = verts_at_lod_level(level);
+
+            for i in 0..16 {
+                let angle = f64::from(i) * TAU / 16.0;
+                let vessel_rel_pos = DVec2::from_angle(angle) * 20.0;
+
+                let theta_range = get_theta_range(aabb, vessel_rel_pos, 1.0, &terrain);
+                let other_range = get_theta_range(aabb, -vessel_rel_pos, 1.0, &terrain);
+
+                #[expect(clippy::single_range_in_vec_init)]
+                let expected = vec![0..verts];
+
+                assert_eq!(gen_idx_ranges(&[theta_range.clone()], verts), expected);
+                assert_eq!(gen_idx_ranges(&[theta_range, other_range], verts), expected);
+            }
+        }
+    }
+
+    fn create_noisy_terrain(subdivs: u8) -> Terrain {
+        Terrain {
+            seed: 1337,
+            octaves: 4,
+            frequency: 2.0,
+            gain: 0.5,
+            lacunarity: 2.0,
+            offset: 600_000.0,
+            multiplier: 5_000.0,
+            subdivs,
+        }
+    }
+
+    /// Checks that the points, minus the central one, sit at the
+    /// angles of the given index ranges, in order.
+    fn assert_points_follow_ranges(points: &[TerrainPoint], ranges: &[Range<u32>], verts: u32) {
+        let expected_len: usize = ranges.iter().map(ExactSizeIterator::len).sum();
+        assert_eq!(points.len(), expected_len + 1);
+        assert_eq!(points[0], TerrainPoint(DVec2::ZERO));
+
+        let expected_thetas = ranges
+            .iter()
+            .flat_map(Clone::clone)
+            .map(|i| index_to_theta(i, verts));
+
+        for (point, expected) in points[1..].iter().zip(expected_thetas) {
+            let theta = point.0.to_angle().rem_euclid(TAU);
+            let diff = (theta - expected).abs();
+
+            assert!(
+                diff.min(TAU - diff) < 1e-9,
+                "point at {theta} rad, expected {expected} rad"
+            );
+        }
+    }
+
+    #[test]
+    fn test_gen_points() {
+        for level in [0, 2, 5] {
+            let terrain = create_noisy_terrain(level);
+            let verts = verts_at_lod_level(level);
+
+            let theta_ranges = [0.5..=0.6, 2.0..=2.25, 2.2..=2.4, 4.0..=4.0];
+            let ranges = gen_idx_ranges(&theta_ranges, verts);
+            let points = gen_points(terrain, &ranges);
+
+            assert_points_follow_ranges(&points, &ranges, verts);
+
+            // Every point should land within one of the requested theta ranges,
+            // give or take the vertex spacing
+            let spacing = TAU / f64::from(verts);
+            for point in &points[1..] {
+                let theta = point.0.to_angle().rem_euclid(TAU);
+
+                assert!(
+                    theta_ranges.iter().any(|range| {
+                        (range.start() - spacing..=range.end() + spacing).contains(&theta)
+                    }),
+                    "point at {theta} rad is outside every range"
+                );
+            }
+
+            // Generation is deterministic
+            assert_eq!(points, gen_points(terrain, &ranges));
+        }
+    }
+
+    #[test]
+    fn test_gen_points_wraparound() {
+        let level = 3;
+        let terrain = create_noisy_terrain(level);
+        let verts = verts_at_lod_level(level);
+
+        let ranges = gen_idx_ranges(&[(TAU - 0.01)..=(TAU + 0.01)], verts);
+
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[0].start, 0);
+        assert_eq!(ranges[1].end, verts);
+
+        let points = gen_points(terrain, &ranges);
+        assert_points_follow_ranges(&points, &ranges, verts);
+
+        // The points just past zero come first, followed by the ones
+        // just before a full revolution
+        let first_len = ranges[0].len();
+        let before_wrap = points[first_len].0.to_angle().rem_euclid(TAU);
+        let after_wrap = points[first_len + 1].0.to_angle().rem_euclid(TAU);
+        assert!(before_wrap < 0.02);
+        assert!(after_wrap > TAU - 0.02);
+    }
+
     #[test]
     fn test_index_buffer() {
         let test_cases = [
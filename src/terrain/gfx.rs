@@ -3,7 +3,11 @@ use crate::{
     consts::terrain::{LOD_DIVISIONS, LOD_VERTS},
     terrain::{TerrainGen, TerrainPoint},
 };
-use bevy::{mesh::Indices, prelude::*};
+use bevy::{
+    math::DVec2,
+    mesh::{Indices, VertexAttributeValues},
+    prelude::*,
+};
 use core::{f64::consts::TAU, num::NonZeroU8};
 
 // Math based off a sketch:
@@ -16,6 +20,8 @@ use core::{f64::consts::TAU, num::NonZeroU8};
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct Buffers {
     pub(crate) vertices: Vec<Vec3>,
+    /// The outward direction of the terrain at each vertex.
+    pub(crate) normals: Vec<Vec3>,
     pub(crate) indices: Indices,
 }
 
@@ -24,9 +30,80 @@ impl Buffers {
     pub(crate) fn empty() -> Self {
         Self {
             vertices: Vec::new(),
+            normals: Vec::new(),
             indices: Indices::U16(vec![]),
         }
     }
+
+    /// Creates the buffers for a triangle fan of terrain points.
+    ///
+    /// `points` must start with the body's center, followed by
+    /// the terrain boundary in counterclockwise order.
+    #[must_use]
+    pub(crate) fn from_fan(
+        points: &[TerrainPoint],
+        indices: Indices,
+        shift: DVec2,
+        zoom: SimCameraZoom,
+    ) -> Self {
+        Self {
+            vertices: points
+                .iter()
+                .map(|point| point.gfx_tf_downcast(shift, zoom))
+                .collect(),
+            normals: fan_normals(points),
+            indices,
+        }
+    }
+
+    /// Checks whether the mesh already holds these exact buffers.
+    #[must_use]
+    pub(crate) fn matches_mesh(&self, mesh: &Mesh) -> bool {
+        let Some(positions) = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+            .and_then(VertexAttributeValues::as_float3)
+        else {
+            return false;
+        };
+
+        mesh.indices() == Some(&self.indices)
+            && mesh.contains_attribute(Mesh::ATTRIBUTE_NORMAL)
+            && positions.len() == self.vertices.len()
+            && positions
+                .iter()
+                .zip(&self.vertices)
+                .all(|(&old, new)| Vec3::from_array(old) == *new)
+    }
+}
+
+/// Estimates the outward normal at every point of a triangle fan.
+///
+/// `points` must start with the body's center, followed by the terrain
+/// boundary in counterclockwise order. Each boundary point's tangent gets
+/// estimated from its two neighbors, then turned a quarter turn clockwise.
+/// As the center has no outward direction, it faces the camera instead.
+///
+/// The normals get computed before downcasting, as neighboring points
+/// far from the camera can get rounded together in 32-bit floats.
+#[must_use]
+pub(crate) fn fan_normals(points: &[TerrainPoint]) -> Vec<Vec3> {
+    let Some((_, ring)) = points.split_first() else {
+        return Vec::new();
+    };
+
+    let ring_normals = (0..ring.len()).map(|i| {
+        let prev = ring[(i + ring.len() - 1) % ring.len()].0;
+        let next = ring[(i + 1) % ring.len()].0;
+        let tangent = next - prev;
+
+        DVec2::new(tangent.y, -tangent.x)
+            .try_normalize()
+            .unwrap_or_else(|| ring[i].0.normalize_or_zero())
+            .as_vec2()
+            .extend(0.0)
+    });
+
+    core::iter::once(Vec3::Z).chain(ring_normals).collect()
 }
 
 impl TerrainGen {
@@ -129,7 +206,7 @@ pub(crate) fn get_focus(
 mod tests {
     use bevy::math::DVec2;
 
-    use crate::components::main_game::{celestial::Terrain, terrain::gfx::LodVectors};
+    use crate::{components::main_game::terrain::gfx::LodVectors, consts::terrain::TEST_TERRAIN};
 
     use super::*;
     use core::{
@@ -137,17 +214,6 @@ mod tests {
         num::NonZeroU8,
     };
 
-    const TEST_TERRAIN: Terrain = Terrain {
-        seed: 0xabcba,
-        octaves: 8,
-        frequency: 1.0,
-        gain: 0.5,
-        lacunarity: 2.0,
-        offset: 20e6,
-        multiplier: 10.0,
-        subdivs: 8,
-    };
-
     #[test]
     fn determinism() {
         let terrain_gen = TerrainGen::new(TEST_TERRAIN);
@@ -254,6 +320,7 @@ mod tests {
         let buffers = vecs.create_buffers(
             FOCUS,
             TEST_TERRAIN.subdivs.into(),
+            0,
             DVec2::ZERO,
             SimCameraZoom(1.0),
         );
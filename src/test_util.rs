@@ -0,0 +1,198 @@
+//! Helpers for driving the game logic from tests and benchmarks.
+
+use bevy::{math::DVec2, prelude::*, time::run_fixed_main_schedule};
+use bevy_rapier2d::{
+    na::OPoint,
+    prelude::{AdditionalMassProperties, Collider},
+};
+
+use crate::{
+    builders::{
+        celestial::{CelestialBodyBuilder, CelestialSurface},
+        vessel::VesselBuilder,
+    },
+    components::main_game::{
+        camera::SimCameraZoom,
+        celestial::Terrain,
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::{CelestialParent, ParentBody, RailMode},
+        terrain::gfx::LodVectors,
+        vessel::{Vessel, VesselPart},
+    },
+    resources::simulation::TerrainColliderConfig,
+    systems::main_game::terrain::collider::terrain_collider,
+    terrain::{
+        TerrainGen,
+        collider::{gen_idx_ranges, gen_points, get_theta_range, verts_at_lod_level},
+    },
+};
+
+pub use crate::consts::terrain::TEST_TERRAIN;
+
+/// Runs exactly one fixed tick of the app.
+///
+/// Unlike [`App::update`], this doesn't depend on how much virtual time
+/// passes per update. [`Time<Virtual>`] only gets advanced by however much
+/// the fixed clock still needs for its next tick, and only the
+/// [`FixedMain`] schedule gets run, so other schedules such as [`Update`]
+/// are skipped.
+pub fn step_fixed(app: &mut App) {
+    let world = app.world_mut();
+
+    let fixed = world.resource::<Time<Fixed>>();
+    let needed = fixed.timestep().saturating_sub(fixed.overstep());
+
+    world.resource_mut::<Time<Virtual>>().advance_by(needed);
+    run_fixed_main_schedule(world);
+}
+
+/// A one-meter ball of a vessel weighing a kilogram, sitting still
+/// in the given state with no rail of its own.
+///
+/// The mesh and material are left as the default handles.
+/// Use struct update syntax to change anything else.
+#[must_use]
+pub fn vessel_builder(
+    name: impl Into<Name>,
+    parent: Entity,
+    position: RootSpacePosition,
+    linvel: RootSpaceLinearVelocity,
+) -> VesselBuilder<ColorMaterial> {
+    VesselBuilder {
+        name: name.into(),
+        collider: Collider::ball(1.0),
+        mass: AdditionalMassProperties::Mass(1.0),
+        parent: CelestialParent { entity: parent },
+        rail_mode: RailMode::None,
+        position,
+        linvel,
+        mesh: Mesh2d::default(),
+        material: MeshMaterial2d::default(),
+        angvel: 0.0,
+        angle: 0.0,
+    }
+}
+
+/// An unrotated celestial body with the default surface and handles.
+#[must_use]
+pub fn body_builder(
+    name: impl Into<Name>,
+    radius: f32,
+    mass: f64,
+) -> CelestialBodyBuilder<ColorMaterial> {
+    CelestialBodyBuilder {
+        name: name.into(),
+        radius,
+        mass,
+        angle: 0.0,
+        mesh: Mesh2d::default(),
+        material: MeshMaterial2d::default(),
+        surface: CelestialSurface::default(),
+    }
+}
+
+/// The root part of a vessel, with its own `shape` weighing
+/// `mass` kilograms and no other parts attached yet.
+#[must_use]
+pub fn root_part(shape: Collider, mass: f32) -> impl Bundle {
+    (
+        Vessel,
+        VesselPart {
+            offset: Vec2::ZERO,
+            angle: 0.0,
+            shape: shape.clone(),
+            mass,
+        },
+        shape,
+        AdditionalMassProperties::Mass(mass),
+    )
+}
+
+/// A part attached to the `root` part of a vessel.
+#[must_use]
+pub fn attached_part(root: Entity, part: VesselPart) -> impl Bundle {
+    (ParentBody { entity: root }, part, Transform::default())
+}
+
+/// A terrain's LoD levels, generated ahead of time so that
+/// building meshes out of them can be measured on its own.
+pub struct TerrainLods {
+    vectors: LodVectors,
+    focus: f64,
+}
+
+impl TerrainLods {
+    /// Generates every LoD level of the terrain, for a camera
+    /// looking at the surface from `focus` radians.
+    #[must_use]
+    pub fn new(terrain: Terrain, focus: f64) -> Self {
+        let terrain_gen = TerrainGen::new(terrain);
+
+        Self {
+            vectors: LodVectors::new_full(&terrain_gen, terrain.subdivs, focus),
+            focus,
+        }
+    }
+
+    /// Builds the terrain mesh's buffers, down to `max_level`.
+    ///
+    /// See [`TerrainMeshConfig`][crate::resources::simulation::TerrainMeshConfig]
+    /// for what `stitch_blend` does.
+    ///
+    /// # Output
+    /// The amount of vertices in the mesh.
+    #[must_use]
+    pub fn build_mesh(&self, max_level: Option<u8>, stitch_blend: u32) -> usize {
+        self.vectors
+            .create_buffers(
+                self.focus,
+                max_level,
+                stitch_blend,
+                DVec2::ZERO,
+                SimCameraZoom(1.0),
+            )
+            .vertices
+            .len()
+    }
+}
+
+/// Builds the terrain collider under a square vessel `vessel_size`
+/// meters wide, sitting on the surface along the +X axis.
+///
+/// This goes through the same steps as the terrain colliders in game,
+/// minus the padding for how fast the vessel moves.
+///
+/// # Output
+/// The collider, along with the amount of terrain vertices in it.
+#[must_use]
+pub fn terrain_collider_under(
+    terrain: Terrain,
+    vessel_size: f32,
+    config: &TerrainColliderConfig,
+) -> (Collider, usize) {
+    let aabb = Collider::cuboid(vessel_size / 2.0, vessel_size / 2.0)
+        .raw
+        .compute_local_aabb();
+    let vessel_pos = DVec2::new(terrain.offset, 0.0);
+
+    let range = get_theta_range(aabb, vessel_pos, 0.0, &terrain);
+    let idx_ranges = gen_idx_ranges(&[range], verts_at_lod_level(terrain.subdivs));
+    let terrain_pts = gen_points(terrain, &idx_ranges);
+
+    // Rigid space is centered on the vessel
+    let rigid_pos = -vessel_pos;
+    let points: Vec<_> = terrain_pts
+        .iter()
+        .map(|point| OPoint::from(point.phys_downcast(rigid_pos)))
+        .collect();
+
+    #[expect(clippy::cast_possible_truncation)]
+    let collider = terrain_collider(
+        &points,
+        rigid_pos.as_vec2(),
+        (terrain.offset - terrain.multiplier) as f32,
+        config,
+    );
+
+    (collider, terrain_pts.len())
+}
@@ -0,0 +1,88 @@
+use bevy::{ecs::system::RunSystemOnce, math::DVec2, prelude::*};
+use bevy_rapier2d::prelude::*;
+use hack_club_space_program::{
+    components::main_game::frames::{RootSpaceLinearVelocity, RootSpacePosition},
+    resources::simulation::{ActiveVessel, PhysicsConfig},
+    test_util,
+};
+
+use crate::common::TestAppConfig;
+
+mod common;
+
+/// Sets up a massless body with two vessels resting on its surface,
+/// overlapping each other and the body.
+///
+/// # Output
+/// The body and both vessels.
+fn setup(physics: PhysicsConfig) -> (App, [Entity; 3]) {
+    let mut app = common::setup(TestAppConfig {
+        physics,
+        ..TestAppConfig::DEFAULT
+    });
+
+    let body = app
+        .world_mut()
+        .spawn(test_util::body_builder("Body", 10.0, 0.0).build_without_terrain())
+        .id();
+
+    let mut spawn_vessel = |name: &str, position: DVec2| {
+        app.world_mut()
+            .spawn(
+                test_util::vessel_builder(
+                    name,
+                    body,
+                    RootSpacePosition(position),
+                    RootSpaceLinearVelocity(DVec2::ZERO),
+                )
+                .build_rigid(),
+            )
+            .id()
+    };
+
+    let first = spawn_vessel("First", DVec2::new(0.0, 10.5));
+    let second = spawn_vessel("Second", DVec2::new(0.5, 10.5));
+
+    app.insert_resource(ActiveVessel {
+        entity: first,
+        prev_tick_parent: body,
+        prev_tick_position: RootSpacePosition(DVec2::new(0.0, 10.5)),
+        prev_tick_velocity: RootSpaceLinearVelocity(DVec2::ZERO),
+    });
+
+    common::run_for_ticks(&mut app, 1);
+
+    (app, [body, first, second])
+}
+
+fn in_contact(app: &mut App, a: Entity, b: Entity) -> bool {
+    app.world_mut()
+        .run_system_once(move |context: ReadRapierContext| {
+            context
+                .single()
+                .unwrap()
+                .contact_pair(a, b)
+                .is_some_and(|pair| pair.has_any_active_contact())
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_vessels_pass_through_each_other() {
+    let (mut app, [body, first, second]) = setup(PhysicsConfig::DEFAULT);
+
+    assert!(!in_contact(&mut app, first, second));
+    assert!(in_contact(&mut app, first, body));
+    assert!(in_contact(&mut app, second, body));
+}
+
+#[test]
+fn test_vessel_collisions_can_be_enabled() {
+    let (mut app, [body, first, second]) = setup(PhysicsConfig {
+        vessel_collisions: true,
+        ..PhysicsConfig::DEFAULT
+    });
+
+    assert!(in_contact(&mut app, first, second));
+    assert!(in_contact(&mut app, first, body));
+}
@@ -11,10 +11,19 @@ use bevy::{
     time::TimeUpdateStrategy,
 };
 use hack_club_space_program::{
-    components::main_game::frames::{RootSpaceLinearVelocity, RootSpacePosition},
+    builders::vessel::VesselBuilder,
+    components::main_game::{
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::RailMode,
+    },
     plugins::main_game::logic::GameLogicPlugin,
-    resources::scene::GameScene,
+    resources::{
+        scene::GameScene,
+        simulation::{ActiveVessel, PhysicsConfig},
+    },
+    test_util::step_fixed,
 };
+use keplerian_sim::{Orbit2D, OrbitTrait2D};
 
 fn setup_time(
     mut commands: Commands,
@@ -34,7 +43,7 @@ pub(crate) fn enable_backtrace() {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq)]
 pub(crate) struct TestAppConfig {
     /// Whether or not the app's itme should increase
     /// every time `app.update()` is called.
@@ -44,6 +53,8 @@ pub(crate) struct TestAppConfig {
     /// What to set the app's current game scene to
     /// (for logic purposes).
     pub(crate) game_scene: Option<GameScene>,
+    /// The physics tuning to build the game logic with.
+    pub(crate) physics: PhysicsConfig,
 }
 
 impl TestAppConfig {
@@ -51,6 +62,7 @@ impl TestAppConfig {
         forward_time_on_update: true,
         log_level: None,
         game_scene: Some(GameScene::InGame),
+        physics: PhysicsConfig::DEFAULT,
     };
 }
 
@@ -73,7 +85,7 @@ pub(crate) fn setup(config: TestAppConfig) -> App {
     enable_backtrace();
 
     let mut app = App::new();
-    app.add_plugins((MinimalPlugins, GameLogicPlugin));
+    app.add_plugins((MinimalPlugins, GameLogicPlugin::with_config(config.physics)));
     if let Some(level) = config.log_level {
         app.add_plugins(LogPlugin {
             level,
@@ -110,11 +122,22 @@ pub(crate) fn empty_mesh_material(app: &mut App) -> (Mesh2d, MeshMaterial2d<Colo
     (Mesh2d(mesh), MeshMaterial2d(material))
 }
 
+/// Spawns the vessel as a loaded rigid body and makes it the active vessel.
+pub(crate) fn spawn_active_vessel(app: &mut App, builder: VesselBuilder<ColorMaterial>) -> Entity {
+    let (parent, position, linvel) = (builder.parent.entity, builder.position, builder.linvel);
+    let entity = app.world_mut().spawn(builder.build_rigid()).id();
+
+    app.insert_resource(ActiveVessel {
+        entity,
+        prev_tick_parent: parent,
+        prev_tick_position: position,
+        prev_tick_velocity: linvel,
+    });
+    entity
+}
+
 pub(crate) fn assert_sv(entity: EntityRef, pos: RootSpacePosition, vel: RootSpaceLinearVelocity) {
-    let name = entity
-        .get::<Name>()
-        .map(std::convert::Into::into)
-        .unwrap_or(entity.id().to_string());
+    let name = entity_name(entity);
     assert_eq!(
         entity.get::<RootSpacePosition>().copied(),
         Some(pos),
@@ -161,32 +184,24 @@ macro_rules! assert_almost_eq {
     };
 }
 
+fn entity_name(entity: EntityRef) -> String {
+    entity
+        .get::<Name>()
+        .map(std::convert::Into::into)
+        .unwrap_or(entity.id().to_string())
+}
+
 /// Tolerance is a fractional error that can be tolerated.
-pub(crate) fn assert_sv_close(
-    entity: EntityRef,
-    pos: RootSpacePosition,
-    vel: RootSpaceLinearVelocity,
-    tolerance: f64,
-) {
+pub(crate) fn assert_pos_close(entity: EntityRef, pos: RootSpacePosition, tolerance: f64) {
     let actual_pos = entity
         .get::<RootSpacePosition>()
         .copied()
         .expect("entity should have root pos");
-    let actual_vel = entity
-        .get::<RootSpaceLinearVelocity>()
-        .copied()
-        .expect("entity should have root vel");
 
     let dpos = actual_pos.0 - pos.0;
-    let dvel = actual_vel.0 - vel.0;
-
     let rel_dpos = dpos.length() / ((actual_pos.0 + pos.0).length() / 2.0);
-    let rel_dvel = dvel.length() / ((actual_vel.0 + vel.0).length());
 
-    let name = entity
-        .get::<Name>()
-        .map(std::convert::Into::into)
-        .unwrap_or(entity.id().to_string());
+    let name = entity_name(entity);
 
     assert!(
         !(actual_pos != pos && rel_dpos > tolerance),
@@ -197,6 +212,19 @@ pub(crate) fn assert_sv_close(
         got: {actual_pos}
         dif: {dpos}"
     );
+}
+
+/// Tolerance is a fractional error that can be tolerated.
+pub(crate) fn assert_vel_close(entity: EntityRef, vel: RootSpaceLinearVelocity, tolerance: f64) {
+    let actual_vel = entity
+        .get::<RootSpaceLinearVelocity>()
+        .copied()
+        .expect("entity should have root vel");
+
+    let dvel = actual_vel.0 - vel.0;
+    let rel_dvel = dvel.length() / ((actual_vel.0 + vel.0).length());
+
+    let name = entity_name(entity);
 
     assert!(
         !(actual_vel != vel && rel_dvel > tolerance),
@@ -209,6 +237,134 @@ pub(crate) fn assert_sv_close(
     );
 }
 
+/// Tolerance is a fractional error that can be tolerated.
+pub(crate) fn assert_sv_close(
+    entity: EntityRef,
+    pos: RootSpacePosition,
+    vel: RootSpaceLinearVelocity,
+    tolerance: f64,
+) {
+    assert_pos_close(entity, pos, tolerance);
+    assert_vel_close(entity, vel, tolerance);
+}
+
+/// Checks that the entity is on an orbit rail with the same shape as
+/// `orbit`, and that it's at the same place along it at `time`.
+///
+/// Tolerance is a fractional error that can be tolerated, except for
+/// the eccentricity, where it's an absolute one.
+pub(crate) fn assert_orbit_close(entity: EntityRef, orbit: &Orbit2D, time: f64, tolerance: f64) {
+    let name = entity_name(entity);
+
+    let rail_mode = entity
+        .get::<RailMode>()
+        .copied()
+        .expect("entity should have rail mode");
+    let Some(actual) = rail_mode.as_orbit() else {
+        panic!("rail mode of {name} should be orbit, found {rail_mode:?}");
+    };
+
+    let rel_diff = |actual: f64, expected: f64| {
+        if actual == expected {
+            0.0
+        } else {
+            ((actual - expected) / actual.abs().max(expected.abs())).abs()
+        }
+    };
+
+    let periapsis_diff = rel_diff(actual.get_periapsis(), orbit.get_periapsis());
+    assert!(
+        periapsis_diff <= tolerance,
+        "periapsis mismatch for {name}: exp {}, got {}",
+        orbit.get_periapsis(),
+        actual.get_periapsis()
+    );
+
+    let eccentricity_diff = (actual.get_eccentricity() - orbit.get_eccentricity()).abs();
+    assert!(
+        eccentricity_diff <= tolerance,
+        "eccentricity mismatch for {name}: exp {}, got {}",
+        orbit.get_eccentricity(),
+        actual.get_eccentricity()
+    );
+
+    let expected_pos = orbit.get_state_vectors_at_time(time).position;
+    let actual_pos = actual.get_state_vectors_at_time(time).position;
+    let pos_diff = (actual_pos - expected_pos).length() / expected_pos.length();
+    assert!(
+        pos_diff <= tolerance,
+        "position along orbit mismatch for {name} at {time}s: exp {expected_pos}, got {actual_pos}"
+    );
+}
+
+/// Runs `ticks` fixed ticks of the app.
+pub(crate) fn run_for_ticks(app: &mut App, ticks: u32) {
+    for _ in 0..ticks {
+        step_fixed(app);
+    }
+}
+
+/// Runs `ticks` fixed ticks, then checks the entity's state vectors
+/// with [`assert_sv_close`].
+pub(crate) fn assert_sv_after(
+    app: &mut App,
+    entity: Entity,
+    ticks: u32,
+    pos: RootSpacePosition,
+    vel: RootSpaceLinearVelocity,
+    tolerance: f64,
+) {
+    run_for_ticks(app, ticks);
+    assert_sv_close(app.world().entity(entity), pos, vel, tolerance);
+}
+
+/// Runs `ticks` fixed ticks, then checks the entity's position
+/// with [`assert_pos_close`].
+pub(crate) fn assert_pos_after(
+    app: &mut App,
+    entity: Entity,
+    ticks: u32,
+    pos: RootSpacePosition,
+    tolerance: f64,
+) {
+    run_for_ticks(app, ticks);
+    assert_pos_close(app.world().entity(entity), pos, tolerance);
+}
+
+/// Runs `ticks` fixed ticks, then checks the entity's velocity
+/// with [`assert_vel_close`].
+pub(crate) fn assert_vel_after(
+    app: &mut App,
+    entity: Entity,
+    ticks: u32,
+    vel: RootSpaceLinearVelocity,
+    tolerance: f64,
+) {
+    run_for_ticks(app, ticks);
+    assert_vel_close(app.world().entity(entity), vel, tolerance);
+}
+
+/// Runs `ticks` fixed ticks, then checks the entity's orbit rail
+/// with [`assert_orbit_close`] at the current fixed time.
+pub(crate) fn assert_orbit_after(
+    app: &mut App,
+    entity: Entity,
+    ticks: u32,
+    orbit: &Orbit2D,
+    tolerance: f64,
+) {
+    run_for_ticks(app, ticks);
+
+    let time = app.world().resource::<Time<Fixed>>().elapsed_secs_f64();
+    assert_orbit_close(app.world().entity(entity), orbit, time, tolerance);
+}
+
+/// Gets the fixed time `ticks` fixed ticks from now, in seconds.
+pub(crate) fn fixed_time_after(app: &App, ticks: u32) -> f64 {
+    let fixed = app.world().resource::<Time<Fixed>>();
+    (fixed.elapsed() + fixed.timestep() * ticks).as_secs_f64()
+}
+
 /// Trait for collection of assertions.
 pub(crate) trait Assertions {
     type ExtraData: Copy;
@@ -0,0 +1,80 @@
+use bevy::{math::DVec2, prelude::*};
+use bevy_rapier2d::prelude::*;
+use hack_club_space_program::{
+    builders::vessel::VesselBuilder,
+    components::main_game::{
+        celestial::Terrain,
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        vessel::CrashTolerance,
+    },
+    resources::simulation::ActiveVessel,
+    test_util,
+};
+
+mod common;
+
+const BODY_RADIUS: f64 = 1000.0;
+
+#[test]
+fn test_high_speed_crash_destroys_vessel() {
+    let mut app = common::setup_default();
+
+    let body = app
+        .world_mut()
+        .spawn(
+            #[expect(clippy::cast_possible_truncation)]
+            test_util::body_builder("Body", BODY_RADIUS as f32, 0.0).build_with_terrain(Terrain {
+                seed: 1,
+                octaves: 3,
+                frequency: 2.0,
+                gain: 0.5,
+                lacunarity: 1.0,
+                offset: BODY_RADIUS,
+                multiplier: 10.0,
+                subdivs: 4,
+            }),
+        )
+        .id();
+
+    let vessel_pos = RootSpacePosition(DVec2::new(0.0, BODY_RADIUS + 20.0));
+    let vessel_vel = RootSpaceLinearVelocity(DVec2::new(0.0, -50.0));
+
+    let vessel = app
+        .world_mut()
+        .spawn((
+            VesselBuilder {
+                collider: Collider::ball(0.5),
+                ..test_util::vessel_builder("Vessel", body, vessel_pos, vessel_vel)
+            }
+            .build_rigid(),
+            CrashTolerance {
+                max_impact_speed: 20.0,
+            },
+        ))
+        .id();
+
+    app.insert_resource(ActiveVessel {
+        entity: vessel,
+        prev_tick_parent: body,
+        prev_tick_position: vessel_pos,
+        prev_tick_velocity: vessel_vel,
+    });
+
+    // It takes about 40 ticks to reach the surface
+    for _ in 0..200 {
+        app.update();
+
+        if app.world().get_entity(vessel).is_err() {
+            break;
+        }
+    }
+
+    assert!(
+        app.world().get_entity(vessel).is_err(),
+        "vessel should have been destroyed on impact"
+    );
+    assert!(
+        !app.world().contains_resource::<ActiveVessel>(),
+        "there's no vessel left to switch to"
+    );
+}
@@ -0,0 +1,156 @@
+use bevy::{math::DVec2, prelude::*};
+use bevy_rapier2d::prelude::*;
+use hack_club_space_program::{
+    builders::vessel::{DebrisBuilder, VesselBuilder},
+    components::main_game::{
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::{CelestialParent, RailMode},
+    },
+    consts::GRAVITATIONAL_CONSTANT,
+    resources::simulation::DebrisLimit,
+    test_util,
+};
+
+mod common;
+
+const BODY_RADIUS: f64 = 1000.0;
+
+/// A surface gravity of about 10 m/s².
+const BODY_MASS: f64 = 1e7 / GRAVITATIONAL_CONSTANT;
+
+struct Scene {
+    app: App,
+    body: Entity,
+}
+
+impl Scene {
+    /// Sets up a body with the active vessel hovering high above it.
+    fn new() -> Self {
+        let mut app = common::setup_default();
+
+        let body = app
+            .world_mut()
+            .spawn(
+                #[expect(clippy::cast_possible_truncation)]
+                test_util::body_builder("Body", BODY_RADIUS as f32, BODY_MASS)
+                    .build_without_terrain(),
+            )
+            .id();
+
+        common::spawn_active_vessel(
+            &mut app,
+            test_util::vessel_builder(
+                "Vessel",
+                body,
+                RootSpacePosition(DVec2::new(0.0, BODY_RADIUS + 300.0)),
+                RootSpaceLinearVelocity(DVec2::ZERO),
+            ),
+        );
+
+        Self { app, body }
+    }
+
+    fn spawn_debris(&mut self, position: DVec2) -> Entity {
+        let bundle = DebrisBuilder {
+            name: Name::new("Debris"),
+            collider: Collider::ball(0.5),
+            mass: AdditionalMassProperties::Mass(0.1),
+            parent: CelestialParent { entity: self.body },
+            position: RootSpacePosition(position),
+            linvel: RootSpaceLinearVelocity(DVec2::ZERO),
+            angvel: 0.0,
+            angle: 0.0,
+            mesh: Mesh2d::default(),
+            material: MeshMaterial2d::<ColorMaterial>::default(),
+        }
+        .build();
+
+        self.app.world_mut().spawn(bundle).id()
+    }
+}
+
+#[test]
+fn test_debris_falls_and_lands() {
+    let mut scene = Scene::new();
+    let debris = scene.spawn_debris(DVec2::new(0.0, BODY_RADIUS + 20.0));
+
+    // Falling 20 m at 10 m/s² takes 2 s
+    common::run_for_ticks(&mut scene.app, 64);
+
+    let world = scene.app.world();
+    let height = world.get::<RootSpacePosition>(debris).unwrap().0.y - BODY_RADIUS;
+    assert!(height < 20.0 - 2.0, "only fell to {height} m after 1 s");
+    assert!(world.get::<RailMode>(debris).unwrap().is_orbit());
+
+    common::run_for_ticks(&mut scene.app, 64 * 3);
+
+    let world = scene.app.world();
+    let height = world.get::<RootSpacePosition>(debris).unwrap().0.y - BODY_RADIUS;
+    assert!((0.0..1.0).contains(&height), "debris at {height} m");
+    assert!(
+        world
+            .get::<RootSpaceLinearVelocity>(debris)
+            .unwrap()
+            .0
+            .length()
+            < 0.5
+    );
+    assert!(world.get::<RailMode>(debris).unwrap().is_surface());
+}
+
+#[test]
+fn test_debris_unloads_sooner() {
+    let mut scene = Scene::new();
+
+    // Past the debris unload distance, but within the vessel one
+    let position = DVec2::new(1000.0, BODY_RADIUS + 300.0);
+    let debris = scene.spawn_debris(position);
+    let vessel = scene
+        .app
+        .world_mut()
+        .spawn(
+            VesselBuilder {
+                collider: Collider::ball(0.5),
+                mass: AdditionalMassProperties::Mass(0.1),
+                ..test_util::vessel_builder(
+                    "Other vessel",
+                    scene.body,
+                    RootSpacePosition(position),
+                    RootSpaceLinearVelocity(DVec2::ZERO),
+                )
+            }
+            .build_rigid(),
+        )
+        .id();
+
+    common::run_for_ticks(&mut scene.app, 1);
+
+    let is_loaded = |entity| {
+        !scene
+            .app
+            .world()
+            .entity(entity)
+            .contains::<RigidBodyDisabled>()
+    };
+    assert!(!is_loaded(debris));
+    assert!(is_loaded(vessel));
+}
+
+#[test]
+fn test_oldest_debris_despawned_past_limit() {
+    let mut scene = Scene::new();
+    scene.app.insert_resource(DebrisLimit { max_count: 3 });
+
+    let debris: Vec<_> = (0..5)
+        .map(|i| {
+            let entity = scene.spawn_debris(DVec2::new(f64::from(i) * 5.0, BODY_RADIUS + 100.0));
+            common::run_for_ticks(&mut scene.app, 1);
+            entity
+        })
+        .collect();
+
+    let exists = |entity| scene.app.world().get_entity(entity).is_ok();
+    assert!(!exists(debris[0]));
+    assert!(!exists(debris[1]));
+    assert!(debris[2..].iter().all(|&entity| exists(entity)));
+}
@@ -0,0 +1,107 @@
+use bevy::{math::DVec2, prelude::*};
+use bevy_rapier2d::prelude::*;
+use hack_club_space_program::{
+    builders::vessel::VesselBuilder,
+    components::main_game::{
+        celestial::Atmosphere,
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        vessel::DragProfile,
+    },
+    resources::simulation::GravityConstants,
+    test_util,
+};
+
+mod common;
+
+const BODY_RADIUS: f64 = 1e5;
+const SEA_LEVEL_DENSITY: f64 = 1.2;
+
+#[test]
+fn test_falling_vessel_reaches_terminal_velocity() {
+    let mut app = common::setup_default();
+
+    // Gravity of about 1 m/s^2 at the surface, with air that's just as
+    // thick all the way up
+    let gravitational_constant = app
+        .world()
+        .resource::<GravityConstants>()
+        .gravitational_constant;
+    let body = app
+        .world_mut()
+        .spawn((
+            #[expect(clippy::cast_possible_truncation)]
+            test_util::body_builder(
+                "Body",
+                BODY_RADIUS as f32,
+                BODY_RADIUS.powi(2) / gravitational_constant,
+            )
+            .build_without_terrain(),
+            Atmosphere {
+                scale_height: 1e12,
+                sea_level_density: SEA_LEVEL_DENSITY,
+                max_altitude: 1e4,
+            },
+        ))
+        .id();
+
+    let vessel_pos = RootSpacePosition(DVec2::new(0.0, BODY_RADIUS + 1000.0));
+    let vessel_vel = RootSpaceLinearVelocity(DVec2::ZERO);
+
+    let vessel = common::spawn_active_vessel(
+        &mut app,
+        VesselBuilder {
+            collider: Collider::ball(0.5),
+            ..test_util::vessel_builder("Vessel", body, vessel_pos, vessel_vel)
+        },
+    );
+
+    let terminal_velocity = |app: &App| {
+        let world = app.world();
+        let mass = f64::from(world.get::<ReadMassProperties>(vessel).unwrap().get().mass);
+        let drag = world.get::<DragProfile>(vessel).unwrap();
+        let radius = world.get::<RootSpacePosition>(vessel).unwrap().0.length();
+        let gravity = BODY_RADIUS.powi(2) / radius.powi(2);
+
+        (2.0 * mass * gravity / (SEA_LEVEL_DENSITY * drag.drag_coefficient * drag.reference_area))
+            .sqrt()
+    };
+    let speed = |app: &App| {
+        app.world()
+            .get::<RootSpaceLinearVelocity>(vessel)
+            .unwrap()
+            .0
+            .length()
+    };
+
+    // Drag only grows until it cancels out gravity,
+    // so the vessel shouldn't overshoot on its way there
+    let mut last_speed = 0.0;
+    for _ in 0..20 {
+        common::run_for_ticks(&mut app, 64);
+
+        let speed = speed(&app);
+        assert!(
+            speed >= last_speed - 1e-6,
+            "slowed from {last_speed} to {speed}"
+        );
+        assert!(speed <= terminal_velocity(&app) * 1.001);
+        last_speed = speed;
+    }
+
+    let expected = terminal_velocity(&app);
+    assert!(
+        (last_speed / expected - 1.0).abs() < 1e-2,
+        "fell at {last_speed} m/s instead of {expected} m/s"
+    );
+
+    let force = app.world().get::<ExternalForce>(vessel).unwrap().force;
+    let vel = app
+        .world()
+        .get::<RootSpaceLinearVelocity>(vessel)
+        .unwrap()
+        .0;
+    assert!(
+        force.as_dvec2().normalize().dot(vel.normalize()) < -0.999,
+        "drag should push against the fall"
+    );
+}
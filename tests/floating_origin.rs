@@ -0,0 +1,134 @@
+use bevy::{math::DVec2, prelude::*};
+use hack_club_space_program::{
+    builders::vessel::VesselBuilder,
+    components::main_game::{
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::RailMode,
+    },
+    consts::GRAVITATIONAL_CONSTANT,
+    orbit::orbit_from_elements,
+    resources::simulation::{ActiveVessel, FloatingOrigin},
+    test_util,
+};
+
+mod common;
+
+/// Far enough from the origin for precision to matter.
+const BODY_POS: DVec2 = DVec2::new(1e9, -5e8);
+
+/// Sets up a body far from the origin, with a loaded vessel orbiting it
+/// and an unloaded one further out.
+///
+/// # Output
+/// The body, the loaded vessel and the unloaded vessel.
+fn setup(floating_origin: Option<FloatingOrigin>) -> (App, [Entity; 3]) {
+    let mut app = common::setup_default();
+    if let Some(floating_origin) = floating_origin {
+        app.insert_resource(floating_origin);
+    }
+
+    let body_mass = 4e6 * core::f64::consts::PI.powi(2) / GRAVITATIONAL_CONSTANT;
+    let body_mu = body_mass * GRAVITATIONAL_CONSTANT;
+
+    let body = app
+        .world_mut()
+        .spawn(test_util::body_builder("Body", 10.0, body_mass).build_without_terrain())
+        .id();
+    app.world_mut()
+        .get_mut::<RootSpacePosition>(body)
+        .unwrap()
+        .0 = BODY_POS;
+
+    let vessel_pos = RootSpacePosition(BODY_POS + DVec2::new(1000.0, 0.0));
+    let vessel_vel = RootSpaceLinearVelocity(DVec2::new(0.0, (body_mu / 1000.0).sqrt()));
+
+    let builder = |name: &str, rail_mode| VesselBuilder {
+        rail_mode,
+        ..test_util::vessel_builder(name, body, vessel_pos, vessel_vel)
+    };
+
+    let vessel = app
+        .world_mut()
+        .spawn(builder("Loaded", RailMode::None).build_rigid())
+        .id();
+    // Far enough away to stay unloaded
+    let distant = app
+        .world_mut()
+        .spawn(
+            builder(
+                "Unloaded",
+                RailMode::Orbit(orbit_from_elements(5e4, 0.2, 1.0, 2.0, body_mu)),
+            )
+            .build_on_rails(),
+        )
+        .id();
+
+    app.insert_resource(ActiveVessel {
+        entity: vessel,
+        prev_tick_parent: body,
+        prev_tick_position: vessel_pos,
+        prev_tick_velocity: vessel_vel,
+    });
+
+    (app, [body, vessel, distant])
+}
+
+fn state(app: &App, entity: Entity) -> (DVec2, DVec2) {
+    let world = app.world();
+    (
+        world.get::<RootSpacePosition>(entity).unwrap().0,
+        world.get::<RootSpaceLinearVelocity>(entity).unwrap().0,
+    )
+}
+
+#[test]
+fn test_rebasing_keeps_relative_states() {
+    const TICKS: usize = 100;
+    const THRESHOLD: f64 = 50.0;
+
+    let (mut fixed, entities) = setup(None);
+    let (mut floating, floating_entities) = setup(Some(FloatingOrigin::new(THRESHOLD)));
+
+    let mut offsets = Vec::new();
+
+    for _ in 0..TICKS {
+        common::run_for_ticks(&mut fixed, 1);
+        common::run_for_ticks(&mut floating, 1);
+
+        let origin = floating.world().resource::<FloatingOrigin>();
+        offsets.push(origin.offset);
+
+        // Each tick moves the vessel a few meters at most
+        let (vessel_pos, _) = state(&floating, floating_entities[1]);
+        assert!(vessel_pos.length() < THRESHOLD + 10.0);
+
+        let (body_pos, body_vel) = state(&fixed, entities[0]);
+        let (floating_body_pos, floating_body_vel) = state(&floating, floating_entities[0]);
+
+        for (entity, floating_entity) in entities.into_iter().zip(floating_entities) {
+            let (pos, vel) = state(&fixed, entity);
+            let (floating_pos, floating_vel) = state(&floating, floating_entity);
+
+            let rel_pos = pos - body_pos;
+            let floating_rel_pos = floating_pos - floating_body_pos;
+            assert!(
+                rel_pos.distance(floating_rel_pos) < 1e-4,
+                "{entity} is at {floating_rel_pos} from the body, expected {rel_pos}"
+            );
+            assert!(
+                (vel - body_vel).distance(floating_vel - floating_body_vel) < 1e-5,
+                "{entity} moves at {} relative to the body, expected {}",
+                floating_vel - floating_body_vel,
+                vel - body_vel
+            );
+
+            assert!((floating_pos + origin.offset).distance(pos) < 1e-4);
+        }
+    }
+
+    offsets.dedup();
+    assert!(
+        offsets.len() > 2,
+        "the origin should have moved several times, but moved to {offsets:?}"
+    );
+}
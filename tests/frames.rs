@@ -4,15 +4,15 @@ use crate::common::{Assertions, AssertionsCollection};
 use bevy::{math::DVec2, prelude::*};
 use bevy_rapier2d::prelude::*;
 use hack_club_space_program::{
-    builders::{camera::SimCameraBuilder, celestial::CelestialBodyBuilder, vessel::VesselBuilder},
+    builders::{camera::SimCameraBuilder, vessel::VesselBuilder},
     components::main_game::{
         camera::{SimCameraOffset, SimCameraZoom},
         frames::{
             CameraSpaceTransform, RigidSpaceVelocity, RootSpaceLinearVelocity, RootSpacePosition,
         },
-        relations::{CelestialParent, RailMode},
     },
     resources::simulation::ActiveVessel,
+    test_util,
 };
 use std::sync::LazyLock;
 
@@ -233,21 +233,9 @@ fn reference_frames() {
 
     let mut app = common::setup_default();
 
-    let (mesh, material) = common::empty_mesh_material(&mut app);
-
     let body = app
         .world_mut()
-        .spawn(
-            CelestialBodyBuilder {
-                name: Name::new("Body"),
-                radius: 1.0 / 4.0,
-                mass: 0.0,
-                angle: 0.0,
-                mesh: mesh.clone(),
-                material: material.clone(),
-            }
-            .build_without_terrain(),
-        )
+        .spawn(test_util::body_builder("Body", 1.0 / 4.0, 0.0).build_without_terrain())
         .id();
 
     let vessel_pos = RootSpacePosition(DVec2::new(0.5, 1.5));
@@ -257,17 +245,9 @@ fn reference_frames() {
         .world_mut()
         .spawn(
             VesselBuilder {
-                name: Name::new("Vessel"),
                 collider: Collider::ball(1.0 / 8.0),
                 mass: AdditionalMassProperties::Mass(1e4),
-                rail_mode: RailMode::None,
-                parent: CelestialParent { entity: body },
-                position: vessel_pos,
-                linvel: vessel_vel,
-                mesh: mesh.clone(),
-                material: material.clone(),
-                angvel: 0.0,
-                angle: 0.0,
+                ..test_util::vessel_builder("Vessel", body, vessel_pos, vessel_vel)
             }
             .build_rigid(),
         )
@@ -341,43 +321,21 @@ fn reference_frame_fixed_cam() {
 
     let mut app = common::setup_default();
 
-    let (mesh, material) = common::empty_mesh_material(&mut app);
-
     let body = app
         .world_mut()
-        .spawn(
-            CelestialBodyBuilder {
-                name: Name::new("Body"),
-                radius: 1.0 / 4.0,
-                mass: 0.0,
-                angle: 0.0,
-                mesh,
-                material,
-            }
-            .build_without_terrain(),
-        )
+        .spawn(test_util::body_builder("Body", 1.0 / 4.0, 0.0).build_without_terrain())
         .id();
 
     let vessel_pos = RootSpacePosition(DVec2::new(0.5, 1.5));
     let vessel_vel = RootSpaceLinearVelocity(DVec2::new(1.0, 0.0));
 
-    let (mesh, material) = common::empty_mesh_material(&mut app);
-
     let vessel = app
         .world_mut()
         .spawn(
             VesselBuilder {
-                name: Name::new("Vessel"),
                 collider: Collider::ball(1.0 / 8.0),
                 mass: AdditionalMassProperties::Mass(1e4),
-                parent: CelestialParent { entity: body },
-                rail_mode: RailMode::None,
-                position: vessel_pos,
-                linvel: vessel_vel,
-                angvel: 0.0,
-                angle: 0.0,
-                mesh,
-                material,
+                ..test_util::vessel_builder("Vessel", body, vessel_pos, vessel_vel)
             }
             .build_rigid(),
         )
@@ -0,0 +1,99 @@
+use bevy::{math::DVec2, prelude::*};
+use hack_club_space_program::{
+    builders::vessel::VesselBuilder,
+    components::main_game::{
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::RailMode,
+    },
+    consts::GRAVITATIONAL_CONSTANT,
+    orbit::orbit_from_elements,
+    resources::simulation::{ActiveVessel, SimPaused},
+    test_util,
+};
+
+mod common;
+
+fn positions(app: &mut App) -> Vec<(Entity, RootSpacePosition)> {
+    let mut positions: Vec<_> = app
+        .world_mut()
+        .query::<(Entity, &RootSpacePosition)>()
+        .iter(app.world())
+        .map(|(entity, &pos)| (entity, pos))
+        .collect();
+    positions.sort_unstable_by_key(|&(entity, _)| entity);
+    positions
+}
+
+#[test]
+fn test_pause_freezes_positions() {
+    let mut app = common::setup_default();
+
+    let body_mass = 4e6 * core::f64::consts::PI.powi(2) / GRAVITATIONAL_CONSTANT;
+    let body_mu = body_mass * GRAVITATIONAL_CONSTANT;
+
+    let body = app
+        .world_mut()
+        .spawn(test_util::body_builder("Body", 10.0, body_mass).build_without_terrain())
+        .id();
+
+    let vessel_pos = RootSpacePosition(DVec2::new(1000.0, 0.0));
+    let vessel_vel = RootSpaceLinearVelocity(DVec2::new(0.0, (body_mu / 1000.0).sqrt()));
+
+    let spawn_vessel = |app: &mut App, name: &str, rail_mode: RailMode| {
+        let builder = VesselBuilder {
+            rail_mode,
+            ..test_util::vessel_builder(name, body, vessel_pos, vessel_vel)
+        };
+
+        if rail_mode.is_orbit() {
+            app.world_mut().spawn(builder.build_on_rails()).id()
+        } else {
+            app.world_mut().spawn(builder.build_rigid()).id()
+        }
+    };
+
+    let vessel = spawn_vessel(&mut app, "Rigid vessel", RailMode::None);
+    // Far enough away to stay unloaded
+    spawn_vessel(
+        &mut app,
+        "Distant vessel",
+        RailMode::Orbit(orbit_from_elements(5e4, 0.2, 1.0, 2.0, body_mu)),
+    );
+
+    app.insert_resource(ActiveVessel {
+        entity: vessel,
+        prev_tick_parent: body,
+        prev_tick_position: vessel_pos,
+        prev_tick_velocity: vessel_vel,
+    });
+
+    // Let everything get placed before pausing
+    common::run_for_ticks(&mut app, 2);
+    app.insert_resource(SimPaused(true));
+    // The pause gets applied onto the clocks during the update
+    app.update();
+
+    let before = positions(&mut app);
+    let elapsed = app.world().resource::<Time<Fixed>>().elapsed();
+
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(
+        app.world().resource::<Time<Fixed>>().elapsed(),
+        elapsed,
+        "simulation time shouldn't pass while paused"
+    );
+
+    // Running ticks by hand doesn't move or push the rigid vessel either
+    let world = app.world();
+    let frozen_pos = *world.get::<RootSpacePosition>(vessel).unwrap();
+    let frozen_vel = *world.get::<RootSpaceLinearVelocity>(vessel).unwrap();
+    common::assert_pos_after(&mut app, vessel, 5, frozen_pos, 0.0);
+    common::assert_vel_after(&mut app, vessel, 5, frozen_vel, 0.0);
+    assert_eq!(positions(&mut app), before);
+
+    app.insert_resource(SimPaused(false));
+    common::run_for_ticks(&mut app, 10);
+    assert_ne!(positions(&mut app), before);
+}
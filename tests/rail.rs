@@ -7,11 +7,16 @@ use bevy_rapier2d::prelude::*;
 use hack_club_space_program::{
     builders::{celestial::CelestialBodyBuilder, vessel::VesselBuilder},
     components::main_game::{
-        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        celestial::{CelestialBody, CelestialSpin, Terrain, TerrainSampler},
+        frames::{
+            RootSpaceAngle, RootSpaceAngularVelocity, RootSpaceLinearVelocity, RootSpacePosition,
+        },
         relations::{CelestialParent, RailMode, SurfaceAttachment},
     },
     consts::GRAVITATIONAL_CONSTANT,
-    resources::simulation::ActiveVessel,
+    orbit::{barycentric_gravitational_parameter, orbit_from_elements},
+    resources::simulation::{ActiveVessel, GravityConstants},
+    test_util::{self, step_fixed},
 };
 use keplerian_sim::{CompactOrbit2D, Orbit2D, OrbitTrait2D, StateVectors2D};
 
@@ -23,57 +28,21 @@ mod common;
 fn test_writing_to_orbit_rails() {
     let mut app = common::setup_default();
 
-    let (mesh, material) = common::empty_mesh_material(&mut app);
-
     let body_mass = 10.0;
     let body_mu = body_mass * GRAVITATIONAL_CONSTANT;
 
     let body = app
         .world_mut()
-        .spawn(
-            CelestialBodyBuilder {
-                name: Name::new("Body"),
-                mass: body_mass,
-                radius: 10.0,
-                mesh,
-                material,
-                angle: 0.0,
-            }
-            .build_without_terrain(),
-        )
+        .spawn(test_util::body_builder("Body", 10.0, body_mass).build_without_terrain())
         .id();
 
     let vessel_pos = RootSpacePosition(DVec2::new(0.0, 12.0));
     let vessel_vel = RootSpaceLinearVelocity(DVec2::new(1.0, 0.0));
 
-    let (mesh, material) = common::empty_mesh_material(&mut app);
-
-    let vessel = app
-        .world_mut()
-        .spawn(
-            VesselBuilder {
-                name: Name::new("Vessel"),
-                angle: 0.0,
-                angvel: 0.0,
-                collider: Collider::ball(1.0),
-                linvel: vessel_vel,
-                mass: AdditionalMassProperties::Mass(1.0),
-                parent: CelestialParent { entity: body },
-                rail_mode: RailMode::None,
-                position: vessel_pos,
-                mesh,
-                material,
-            }
-            .build_rigid(),
-        )
-        .id();
-
-    app.insert_resource(ActiveVessel {
-        entity: vessel,
-        prev_tick_parent: body,
-        prev_tick_position: vessel_pos,
-        prev_tick_velocity: vessel_vel,
-    });
+    let vessel = common::spawn_active_vessel(
+        &mut app,
+        test_util::vessel_builder("Vessel", body, vessel_pos, vessel_vel),
+    );
 
     app.update();
 
@@ -107,59 +76,61 @@ fn test_writing_to_orbit_rails() {
 }
 
 #[test]
-fn test_writing_to_surface_rails() {
+fn test_unit_gravitational_constant() {
     let mut app = common::setup_default();
+    app.insert_resource(GravityConstants {
+        gravitational_constant: 1.0,
+    });
 
-    let (mesh, material) = common::empty_mesh_material(&mut app);
+    let body = app
+        .world_mut()
+        .spawn(test_util::body_builder("Body", 1.0, 100.0).build_without_terrain())
+        .id();
+
+    // Circular orbit with radius 4 around μ = 100
+    let vessel_pos = RootSpacePosition(DVec2::new(4.0, 0.0));
+    let vessel_vel = RootSpaceLinearVelocity(DVec2::new(0.0, 5.0));
+
+    let vessel = common::spawn_active_vessel(
+        &mut app,
+        VesselBuilder {
+            collider: Collider::ball(0.1),
+            ..test_util::vessel_builder("Vessel", body, vessel_pos, vessel_vel)
+        },
+    );
+
+    app.update();
+
+    let orbit = app
+        .world()
+        .get::<RailMode>(vessel)
+        .expect("vessel should have rail mode")
+        .as_orbit()
+        .expect("vessel rail mode should be orbit");
+
+    assert!((orbit.get_gravitational_parameter() - 100.0).abs() < 1e-12);
+    assert!(orbit.get_eccentricity() < 1e-2);
+    assert!((orbit.get_semi_major_axis() - 4.0).abs() < 4e-2);
+}
+
+#[test]
+fn test_writing_to_surface_rails() {
+    let mut app = common::setup_default();
 
     let body_mass = 10.0;
 
     let body = app
         .world_mut()
-        .spawn(
-            CelestialBodyBuilder {
-                name: Name::new("Body"),
-                mass: body_mass,
-                radius: 10.0,
-                angle: 0.0,
-                mesh,
-                material,
-            }
-            .build_without_terrain(),
-        )
+        .spawn(test_util::body_builder("Body", 10.0, body_mass).build_without_terrain())
         .id();
 
     let vessel_pos = RootSpacePosition(DVec2::new(0.0, 11.0));
     let vessel_vel = RootSpaceLinearVelocity(DVec2::new(0.0, 0.0));
 
-    let (mesh, material) = common::empty_mesh_material(&mut app);
-
-    let vessel = app
-        .world_mut()
-        .spawn(
-            VesselBuilder {
-                name: Name::new("Vessel"),
-                angle: 0.0,
-                angvel: 0.0,
-                collider: Collider::ball(1.0),
-                linvel: vessel_vel,
-                mass: AdditionalMassProperties::Mass(1.0),
-                parent: CelestialParent { entity: body },
-                position: vessel_pos,
-                rail_mode: RailMode::None,
-                mesh,
-                material,
-            }
-            .build_rigid(),
-        )
-        .id();
-
-    app.insert_resource(ActiveVessel {
-        entity: vessel,
-        prev_tick_parent: body,
-        prev_tick_position: vessel_pos,
-        prev_tick_velocity: vessel_vel,
-    });
+    let vessel = common::spawn_active_vessel(
+        &mut app,
+        test_util::vessel_builder("Vessel", body, vessel_pos, vessel_vel),
+    );
 
     app.update();
 
@@ -186,6 +157,207 @@ fn test_writing_to_surface_rails() {
     );
 }
 
+#[test]
+fn test_landed_attachment_settles() {
+    const MAX_TICKS: usize = 512;
+    const STABLE_TICKS: usize = 64;
+
+    let mut app = common::setup_default();
+    app.insert_resource(GravityConstants {
+        gravitational_constant: 1.0,
+    });
+
+    // 10 m/s² at the surface, which is close to flat for the vessel
+    let body = app
+        .world_mut()
+        .spawn(test_util::body_builder("Body", 316.0, 1e6).build_without_terrain())
+        .id();
+
+    let vessel_pos = RootSpacePosition(DVec2::new(0.0, 317.5));
+    let vessel_vel = RootSpaceLinearVelocity(DVec2::ZERO);
+
+    let vessel = common::spawn_active_vessel(
+        &mut app,
+        VesselBuilder {
+            collider: Collider::cuboid(1.0, 1.0),
+            ..test_util::vessel_builder("Vessel", body, vessel_pos, vessel_vel)
+        },
+    );
+
+    let mut last = None;
+    let mut stable_ticks = 0;
+
+    for _ in 0..MAX_TICKS {
+        step_fixed(&mut app);
+
+        let attachment = app
+            .world()
+            .get::<RailMode>(vessel)
+            .expect("vessel should have rail mode")
+            .as_attachment();
+
+        if attachment.is_some() && attachment == last {
+            stable_ticks += 1;
+        } else {
+            stable_ticks = 0;
+        }
+        last = attachment;
+
+        if stable_ticks >= STABLE_TICKS {
+            break;
+        }
+    }
+
+    assert!(
+        stable_ticks >= STABLE_TICKS,
+        "attachment didn't settle within {MAX_TICKS} ticks, last: {last:?}"
+    );
+
+    let attachment = last.expect("vessel should be landed");
+    assert!((attachment.angle - PI / 2.0).abs() < 1e-3);
+    assert!((attachment.radius - 317.0).abs() < 0.1);
+}
+
+#[test]
+fn test_on_rails_not_integrated() {
+    const BODY_MASS: f64 = 1e20;
+
+    let mut app = common::setup_default();
+
+    let body = app
+        .world_mut()
+        .spawn(test_util::body_builder("Body", 1e6, BODY_MASS).build_without_terrain())
+        .id();
+
+    let orbit = Orbit2D::new_circular(2e6, 0.3, BODY_MASS * GRAVITATIONAL_CONSTANT);
+
+    let railed = app
+        .world_mut()
+        .spawn(
+            VesselBuilder {
+                rail_mode: RailMode::Orbit(orbit),
+                ..test_util::vessel_builder(
+                    "Railed",
+                    body,
+                    RootSpacePosition(DVec2::NAN),
+                    RootSpaceLinearVelocity(DVec2::NAN),
+                )
+            }
+            .build_on_rails(),
+        )
+        .id();
+
+    let active_pos = RootSpacePosition(DVec2::new(0.0, -3e6));
+    let active_vel = RootSpaceLinearVelocity(DVec2::ZERO);
+
+    let active = app
+        .world_mut()
+        .spawn(test_util::vessel_builder("Active", body, active_pos, active_vel).build_rigid())
+        .id();
+
+    app.insert_resource(ActiveVessel {
+        entity: active,
+        prev_tick_parent: body,
+        prev_tick_position: active_pos,
+        prev_tick_velocity: active_vel,
+    });
+
+    for _ in 0..500 {
+        step_fixed(&mut app);
+
+        let time = app.world().resource::<Time<Fixed>>().elapsed_secs_f64();
+        let expected_sv = orbit.get_state_vectors_at_time(time);
+
+        assert_sv_close(
+            app.world().entity(railed),
+            RootSpacePosition(expected_sv.position),
+            RootSpaceLinearVelocity(expected_sv.velocity),
+            1e-9,
+        );
+    }
+
+    let time = app.world().resource::<Time<Fixed>>().elapsed_secs_f64();
+    common::assert_orbit_close(app.world().entity(railed), &orbit, time, 1e-12);
+}
+
+#[test]
+fn test_earth_moon_from_physical() {
+    const EARTH_RADIUS: f64 = 6.371e6;
+    const EARTH_MASS: f64 = 5.972e24;
+    const MOON_RADIUS: f64 = 1.737e6;
+    const MOON_MASS: f64 = 7.342e22;
+
+    let mut app = common::setup_default();
+
+    let (earth, earth_orbit) =
+        CelestialBodyBuilder::from_physical("Earth", EARTH_RADIUS, EARTH_MASS, None);
+    assert!(earth_orbit.is_none(), "earth shouldn't orbit anything");
+
+    let (mesh, material) = common::empty_mesh_material(&mut app);
+    let earth = app
+        .world_mut()
+        .spawn(
+            CelestialBodyBuilder {
+                mesh,
+                material,
+                ..earth
+            }
+            .build_without_terrain(),
+        )
+        .id();
+
+    let earth_mu = EARTH_MASS * GRAVITATIONAL_CONSTANT;
+    let moon_orbit = orbit_from_elements(3.844e8, 0.0549, 0.0, 1.0, earth_mu);
+
+    let (moon, orbit_components) = CelestialBodyBuilder::<ColorMaterial>::from_physical(
+        "Moon",
+        MOON_RADIUS,
+        MOON_MASS,
+        Some((earth, moon_orbit)),
+    );
+    let orbit_components = orbit_components.expect("moon should orbit the earth");
+    let moon = app
+        .world_mut()
+        .spawn(moon.build_without_terrain())
+        .insert(orbit_components)
+        .id();
+
+    let moon_ref = app.world().entity(moon);
+    assert_eq!(moon_ref.get::<CelestialParent>().unwrap().entity, earth);
+    let moon_body = moon_ref.get::<CelestialBody>().unwrap();
+    assert!(
+        (moon_body.mass - MOON_MASS).abs() < f64::EPSILON,
+        "mass should be set up from the physical mass"
+    );
+    assert!((f64::from(moon_body.base_radius) / MOON_RADIUS - 1.0).abs() < 1e-6);
+
+    let active_pos = RootSpacePosition(DVec2::new(EARTH_RADIUS + 1e6, 0.0));
+    let active_vel = RootSpaceLinearVelocity(DVec2::new(0.0, (earth_mu / active_pos.0.x).sqrt()));
+
+    let active = app
+        .world_mut()
+        .spawn(test_util::vessel_builder("Active", earth, active_pos, active_vel).build_rigid())
+        .id();
+
+    app.insert_resource(ActiveVessel {
+        entity: active,
+        prev_tick_parent: earth,
+        prev_tick_position: active_pos,
+        prev_tick_velocity: active_vel,
+    });
+
+    let expected_sv = moon_orbit.get_state_vectors_at_time(common::fixed_time_after(&app, 10));
+
+    common::assert_sv_after(
+        &mut app,
+        moon,
+        10,
+        RootSpacePosition(expected_sv.position),
+        RootSpaceLinearVelocity(expected_sv.velocity),
+        1e-9,
+    );
+}
+
 /// Environment:
 /// - Alpha (1e6 radius)
 ///     - Alpharove (π radians, 1e6 alt) => (-1e6 0) (0 0)
@@ -225,62 +397,45 @@ fn test_rail_to_sv() {
 
     let mut app = common::setup_default();
 
-    let (mesh, material) = common::empty_mesh_material(&mut app);
-
     let alpha = app
         .world_mut()
         .spawn(
-            CelestialBodyBuilder {
-                name: Name::new("Alpha"),
-                radius: ALPHA_RADIUS as f32,
-                mass: ALPHA_MASS,
-                angle: 0.0,
-                mesh: mesh.clone(),
-                material: material.clone(),
-            }
-            .build_without_terrain(),
+            test_util::body_builder("Alpha", ALPHA_RADIUS as f32, ALPHA_MASS)
+                .build_without_terrain(),
         )
         .id();
 
-    let (mesh, material) = common::empty_mesh_material(&mut app);
-
     let alpharove = app
         .world_mut()
         .spawn(
             VesselBuilder {
-                name: Name::new("AlphaRove"),
                 collider: Collider::ball(0.01),
                 mass: AdditionalMassProperties::Mass(0.1),
-                parent: CelestialParent { entity: alpha },
                 rail_mode: RailMode::Surface(ALPHAROVE_ATTACHMENT),
-                position: RootSpacePosition(DVec2::NAN),
-                linvel: RootSpaceLinearVelocity(DVec2::NAN),
-                angvel: 0.0,
-                angle: 0.0,
-                mesh,
-                material,
+                ..test_util::vessel_builder(
+                    "AlphaRove",
+                    alpha,
+                    RootSpacePosition(DVec2::NAN),
+                    RootSpaceLinearVelocity(DVec2::NAN),
+                )
             }
             .build_on_rails(),
         )
         .id();
 
-    let (mesh, material) = common::empty_mesh_material(&mut app);
-
     let alphasat = app
         .world_mut()
         .spawn(
             VesselBuilder {
-                name: Name::new("AlphaSat"),
                 collider: Collider::ball(0.01),
                 mass: AdditionalMassProperties::Mass(0.1),
-                parent: CelestialParent { entity: alpha },
                 rail_mode: RailMode::Orbit(*ALPHASAT_ORBIT),
-                position: RootSpacePosition(DVec2::NAN),
-                linvel: RootSpaceLinearVelocity(DVec2::NAN),
-                angvel: 0.0,
-                angle: 0.0,
-                mesh: mesh.clone(),
-                material: material.clone(),
+                ..test_util::vessel_builder(
+                    "AlphaSat",
+                    alpha,
+                    RootSpacePosition(DVec2::NAN),
+                    RootSpaceLinearVelocity(DVec2::NAN),
+                )
             }
             .build_on_rails(),
         )
@@ -289,15 +444,7 @@ fn test_rail_to_sv() {
     let beta = app
         .world_mut()
         .spawn(
-            CelestialBodyBuilder {
-                name: Name::new("Beta"),
-                radius: BETA_RADIUS as f32,
-                mass: BETA_MASS,
-                angle: 0.0,
-                mesh: mesh.clone(),
-                material: material.clone(),
-            }
-            .build_without_terrain(),
+            test_util::body_builder("Beta", BETA_RADIUS as f32, BETA_MASS).build_without_terrain(),
         )
         .insert((
             CelestialParent { entity: alpha },
@@ -305,45 +452,31 @@ fn test_rail_to_sv() {
         ))
         .id();
 
-    let (mesh, material) = common::empty_mesh_material(&mut app);
-
     let betarove = app
         .world_mut()
         .spawn(
             VesselBuilder {
-                name: Name::new("BetaRove"),
                 collider: Collider::ball(0.01),
                 mass: AdditionalMassProperties::Mass(0.1),
-                parent: CelestialParent { entity: beta },
                 rail_mode: RailMode::Surface(BETAROVE_ATTACHMENT),
-                position: RootSpacePosition(DVec2::NAN),
-                linvel: RootSpaceLinearVelocity(DVec2::NAN),
-                angvel: 0.0,
-                angle: 0.0,
-                mesh,
-                material,
+                ..test_util::vessel_builder(
+                    "BetaRove",
+                    beta,
+                    RootSpacePosition(DVec2::NAN),
+                    RootSpaceLinearVelocity(DVec2::NAN),
+                )
             }
             .build_on_rails(),
         )
         .id();
 
-    let (mesh, material) = common::empty_mesh_material(&mut app);
-
     let betabase = app
         .world_mut()
         .spawn(
             VesselBuilder {
-                name: Name::new("BetaBase"),
                 collider: Collider::ball(0.0),
                 mass: AdditionalMassProperties::Mass(0.0),
-                parent: CelestialParent { entity: beta },
-                rail_mode: RailMode::None,
-                position: *BETABASE_POS,
-                linvel: *BETABASE_VEL,
-                angvel: 0.0,
-                angle: 0.0,
-                mesh,
-                material,
+                ..test_util::vessel_builder("BetaBase", beta, *BETABASE_POS, *BETABASE_VEL)
             }
             .build_rigid(),
         )
@@ -459,7 +592,7 @@ fn test_rail_to_sv() {
     );
 
     // Long term testing
-    (0..1000).for_each(|_| app.update());
+    (0..1000).for_each(|_| step_fixed(&mut app));
 
     let beta_ref = app.world().get_entity(beta).expect("beta should exist");
     let beta_pos = beta_ref
@@ -484,3 +617,409 @@ fn test_rail_to_sv() {
         1e-7,
     );
 }
+
+/// Tests that a loaded vessel resting on an on-rails body
+/// doesn't slowly drift away from it over many ticks.
+#[test]
+#[expect(clippy::cast_possible_truncation)]
+fn test_loaded_vessel_keeps_up_with_parent() {
+    const ALPHA_RADIUS: f64 = 1e6;
+    const ALPHA_MASS: f64 = 1e20;
+    const BETA_RADIUS: f64 = 1e5;
+    const BETA_MASS: f64 = 1e18;
+    const TICKS: usize = 10_000;
+
+    let beta_orbit = Orbit2D::new_circular(2e5, 0.0, ALPHA_MASS * GRAVITATIONAL_CONSTANT);
+    let betabase_pos = RootSpacePosition(DVec2::new(beta_orbit.get_periapsis() + BETA_RADIUS, 0.0));
+    let betabase_vel = RootSpaceLinearVelocity(beta_orbit.get_velocity_at_time(0.0));
+
+    let mut app = common::setup_default();
+
+    let alpha = app
+        .world_mut()
+        .spawn(
+            test_util::body_builder("Alpha", ALPHA_RADIUS as f32, ALPHA_MASS)
+                .build_without_terrain(),
+        )
+        .id();
+
+    let beta = app
+        .world_mut()
+        .spawn(
+            test_util::body_builder("Beta", BETA_RADIUS as f32, BETA_MASS).build_without_terrain(),
+        )
+        .insert((
+            CelestialParent { entity: alpha },
+            RailMode::Orbit(beta_orbit),
+        ))
+        .id();
+
+    let betabase = common::spawn_active_vessel(
+        &mut app,
+        VesselBuilder {
+            collider: Collider::ball(0.0),
+            mass: AdditionalMassProperties::Mass(0.0),
+            ..test_util::vessel_builder("BetaBase", beta, betabase_pos, betabase_vel)
+        },
+    );
+
+    (0..TICKS).for_each(|_| step_fixed(&mut app));
+
+    let beta_pos = app
+        .world()
+        .get::<RootSpacePosition>(beta)
+        .copied()
+        .expect("beta should have pos");
+    let betabase_pos = app
+        .world()
+        .get::<RootSpacePosition>(betabase)
+        .copied()
+        .expect("betabase should have pos");
+
+    let drift = (betabase_pos.0 - beta_pos.0 - DVec2::new(BETA_RADIUS, 0.0)).length();
+    assert!(
+        drift < 1.0,
+        "betabase drifted {drift} m away from its spot on beta after {TICKS} ticks"
+    );
+}
+
+#[test]
+fn test_binary_stars_orbit_barycenter() {
+    const STAR_MASS: f64 = 1e24;
+    const SEPARATION: f64 = 2e6;
+
+    let mut app = common::setup_default();
+
+    let mu = barycentric_gravitational_parameter(STAR_MASS, STAR_MASS, GRAVITATIONAL_CONSTANT);
+    let stars = [("Alpha", 0.0), ("Beta", PI)].map(|(name, arg_pe)| {
+        let rail = RailMode::Orbit(orbit_from_elements(SEPARATION / 2.0, 0.0, arg_pe, 0.0, mu));
+
+        let star = app
+            .world_mut()
+            .spawn((
+                test_util::body_builder(name, 1e5, STAR_MASS).build_without_terrain(),
+                rail,
+            ))
+            .id();
+
+        (star, rail)
+    });
+
+    // About a 25th of a revolution
+    common::run_for_ticks(&mut app, 64 * 60);
+
+    let world = app.world();
+    let time = world.resource::<Time<Fixed>>().elapsed_secs_f64();
+    let [(alpha_pos, alpha_vel), (beta_pos, beta_vel)] = stars.map(|(star, rail)| {
+        let pos = world.get::<RootSpacePosition>(star).unwrap().0;
+        let vel = world.get::<RootSpaceLinearVelocity>(star).unwrap().0;
+
+        let (expected_pos, expected_vel) = rail.relative_state_at(time, 0.0);
+        assert!(
+            pos.distance(expected_pos) < 1e-3,
+            "star should be at {expected_pos} around the midpoint, found {pos}"
+        );
+        assert!(vel.distance(expected_vel) < 1e-6);
+
+        (pos, vel)
+    });
+
+    assert!((alpha_pos + beta_pos).length() < 1e-3, "the midpoint moved");
+    assert!((alpha_vel + beta_vel).length() < 1e-6);
+    assert!((alpha_pos.distance(beta_pos) - SEPARATION).abs() < 1e-3);
+    assert!(
+        alpha_pos.angle_to(DVec2::X).abs() > 0.1,
+        "stars should've moved around the midpoint"
+    );
+
+    // Each star pulls the other in at the speed it needs to stay circular
+    let pull = GRAVITATIONAL_CONSTANT * STAR_MASS / SEPARATION.powi(2);
+    let centripetal = alpha_vel.length_squared() / (SEPARATION / 2.0);
+    assert!((centripetal / pull - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_surface_attachments_rest_on_ground() {
+    // Rotating the body turns its terrain along with it
+    for body_angle in [0.0, 0.7] {
+        surface_attachments_rest_on_ground(body_angle);
+    }
+}
+
+fn surface_attachments_rest_on_ground(body_angle: f32) {
+    const TERRAIN: Terrain = Terrain {
+        seed: 1,
+        octaves: 3,
+        frequency: 2.0,
+        gain: 0.5,
+        lacunarity: 1.0,
+        offset: 1e6,
+        multiplier: 1000.0,
+        subdivs: 4,
+    };
+    const SUNK_ANGLE: f64 = 1.0;
+    const PERCHED_ANGLE: f64 = 2.0;
+
+    let mut app = common::setup_default();
+
+    let body = app
+        .world_mut()
+        .spawn(
+            #[expect(clippy::cast_possible_truncation)]
+            CelestialBodyBuilder {
+                angle: body_angle,
+                ..test_util::body_builder("Body", TERRAIN.offset as f32, 0.0)
+            }
+            .build_with_terrain(TERRAIN),
+        )
+        .id();
+
+    let mut spawn_vessel = |name: &'static str, rail_mode: RailMode, position: DVec2| {
+        let builder = VesselBuilder {
+            rail_mode,
+            ..test_util::vessel_builder(
+                name,
+                body,
+                RootSpacePosition(position),
+                RootSpaceLinearVelocity(DVec2::ZERO),
+            )
+        };
+
+        if rail_mode.is_none() {
+            app.world_mut().spawn(builder.build_rigid()).id()
+        } else {
+            app.world_mut().spawn(builder.build_on_rails()).id()
+        }
+    };
+
+    let sunk = spawn_vessel(
+        "Sunk",
+        RailMode::Surface(SurfaceAttachment {
+            angle: SUNK_ANGLE,
+            radius: TERRAIN.offset - TERRAIN.multiplier - 50.0,
+        }),
+        DVec2::ZERO,
+    );
+    let perched = spawn_vessel(
+        "Perched",
+        RailMode::Surface(SurfaceAttachment {
+            angle: PERCHED_ANGLE,
+            radius: TERRAIN.offset + TERRAIN.multiplier + 50.0,
+        }),
+        DVec2::ZERO,
+    );
+
+    // Far enough away that neither of the attached vessels gets loaded
+    let active_pos = DVec2::new(0.0, -3e6);
+    let active = spawn_vessel("Active", RailMode::None, active_pos);
+
+    app.insert_resource(ActiveVessel {
+        entity: active,
+        prev_tick_parent: body,
+        prev_tick_position: RootSpacePosition(active_pos),
+        prev_tick_velocity: RootSpaceLinearVelocity(DVec2::ZERO),
+    });
+
+    for _ in 0..4 {
+        step_fixed(&mut app);
+    }
+
+    let world = app.world();
+    let altitude = |entity: Entity| {
+        let body_pos = world
+            .get::<RootSpacePosition>(body)
+            .expect("body should have pos");
+        let pos = world
+            .get::<RootSpacePosition>(entity)
+            .expect("vessel should have pos");
+
+        (pos.0 - body_pos.0).length()
+    };
+    let sampler = world
+        .get::<TerrainSampler>(body)
+        .expect("body should have a terrain sampler");
+
+    for (name, entity, angle) in [
+        ("sunk", sunk, SUNK_ANGLE),
+        ("perched", perched, PERCHED_ANGLE),
+    ] {
+        let radius = altitude(entity);
+        let surface_radius = sampler.surface_radius(angle - f64::from(body_angle));
+        assert!(
+            (radius - surface_radius).abs() < 1e-3,
+            "{name} vessel is at radius {radius}, but the surface is at {surface_radius} \
+            (body angle {body_angle})"
+        );
+    }
+}
+
+#[test]
+fn test_active_vessel_going_on_rails() {
+    const BODY_MASS: f64 = 1e20;
+    const ORBIT_RADIUS: f64 = 2e5;
+
+    let mut app = common::setup_default();
+
+    let body = app
+        .world_mut()
+        .spawn(test_util::body_builder("Body", 1e5, BODY_MASS).build_without_terrain())
+        .id();
+
+    let mu = BODY_MASS * GRAVITATIONAL_CONSTANT;
+    let vessel_pos = RootSpacePosition(DVec2::new(0.0, ORBIT_RADIUS));
+    let vessel_vel = RootSpaceLinearVelocity(DVec2::new(-(mu / ORBIT_RADIUS).sqrt(), 0.0));
+
+    let vessel = common::spawn_active_vessel(
+        &mut app,
+        test_util::vessel_builder("Vessel", body, vessel_pos, vessel_vel),
+    );
+
+    common::run_for_ticks(&mut app, 5);
+
+    // Put the active vessel on rails along the orbit it's on
+    let world = app.world();
+    let now = world.resource::<Time<Fixed>>().elapsed_secs_f64();
+    let body_pos = *world.get::<RootSpacePosition>(body).unwrap();
+    let body_vel = *world.get::<RootSpaceLinearVelocity>(body).unwrap();
+    let orbit = world
+        .get::<RootSpacePosition>(vessel)
+        .unwrap()
+        .relative_to(
+            *world.get::<RootSpaceLinearVelocity>(vessel).unwrap(),
+            body_pos,
+            body_vel,
+        )
+        .to_cached_orbit(mu, now);
+
+    app.world_mut()
+        .entity_mut(vessel)
+        .insert((RailMode::Orbit(orbit), RigidBodyDisabled));
+
+    for tick in 0..10 {
+        common::run_for_ticks(&mut app, 1);
+
+        let world = app.world();
+        let pos = *world.get::<RootSpacePosition>(vessel).unwrap();
+        let vel = *world.get::<RootSpaceLinearVelocity>(vessel).unwrap();
+        let active = world.resource::<ActiveVessel>();
+
+        assert!(
+            pos.is_finite() && vel.is_finite(),
+            "tick {tick}: {pos} {vel}"
+        );
+        assert_eq!(
+            active.prev_tick_position, pos,
+            "tick {tick}: rigid space isn't centered on the active vessel"
+        );
+        assert_eq!(active.prev_tick_velocity, vel);
+        assert_eq!(active.prev_tick_parent, body);
+
+        // No jumps from being recentered on a stale spot
+        let now = world.resource::<Time<Fixed>>().elapsed_secs_f64();
+        let expected = orbit.get_state_vectors_at_time(now);
+        let rel_pos = pos.0 - world.get::<RootSpacePosition>(body).unwrap().0;
+        assert!(
+            rel_pos.distance(expected.position) < 1.0,
+            "tick {tick}: vessel is at {rel_pos}, expected {}",
+            expected.position
+        );
+    }
+
+    // And it keeps going along the same orbit
+    common::assert_orbit_after(&mut app, vessel, 10, &orbit, 1e-6);
+}
+
+#[test]
+fn test_landed_vessel_keeps_spin_off_rails() {
+    const BODY_MASS: f64 = 1e20;
+    const BODY_RADIUS: f64 = 1e6;
+    const SPIN: f64 = 1e-5;
+
+    let mut app = common::setup_default();
+
+    let body = app
+        .world_mut()
+        .spawn((
+            #[expect(clippy::cast_possible_truncation)]
+            test_util::body_builder("Body", BODY_RADIUS as f32, BODY_MASS).build_without_terrain(),
+            CelestialSpin {
+                angular_velocity: SPIN,
+            },
+        ))
+        .id();
+
+    // Without any ground to touch, nothing but the spin
+    // it comes off rails with turns the vessel
+    app.world_mut().entity_mut(body).remove::<Collider>();
+
+    let landed = app
+        .world_mut()
+        .spawn(
+            VesselBuilder {
+                rail_mode: RailMode::Surface(SurfaceAttachment {
+                    angle: PI / 2.0,
+                    radius: BODY_RADIUS,
+                }),
+                ..test_util::vessel_builder(
+                    "Landed",
+                    body,
+                    RootSpacePosition(DVec2::new(0.0, BODY_RADIUS)),
+                    RootSpaceLinearVelocity(DVec2::ZERO),
+                )
+            }
+            .build_on_rails(),
+        )
+        .id();
+
+    common::spawn_active_vessel(
+        &mut app,
+        test_util::vessel_builder(
+            "Active",
+            body,
+            RootSpacePosition(DVec2::new(0.0, -3e6)),
+            RootSpaceLinearVelocity(DVec2::ZERO),
+        ),
+    );
+
+    common::run_for_ticks(&mut app, 64);
+
+    let world = app.world();
+    assert!(world.entity(landed).contains::<RigidBodyDisabled>());
+    assert_eq!(
+        world.get::<RootSpaceAngularVelocity>(landed),
+        Some(&RootSpaceAngularVelocity(SPIN)),
+        "landed vessels should turn along with the ground"
+    );
+    let angle = world.get::<RootSpaceAngle>(landed).unwrap().0;
+    assert!((angle - SPIN).abs() < 1e-12, "turned to {angle} rad in 1 s");
+    let vel = world.get::<RootSpaceLinearVelocity>(landed).unwrap().0;
+    assert!(
+        vel.distance(DVec2::new(-SPIN * BODY_RADIUS, 0.0)) < 1e-9,
+        "moving at {vel} with the ground"
+    );
+
+    // Switching over to the landed vessel takes it off rails
+    let landed_pos = *world.get::<RootSpacePosition>(landed).unwrap();
+    let landed_vel = *world.get::<RootSpaceLinearVelocity>(landed).unwrap();
+    app.insert_resource(ActiveVessel {
+        entity: landed,
+        prev_tick_parent: body,
+        prev_tick_position: landed_pos,
+        prev_tick_velocity: landed_vel,
+    });
+
+    common::run_for_ticks(&mut app, 64);
+
+    let world = app.world();
+    assert!(!world.entity(landed).contains::<RigidBodyDisabled>());
+    let angvel = world.get::<RootSpaceAngularVelocity>(landed).unwrap().0;
+    assert!(
+        (angvel - SPIN).abs() < 1e-9,
+        "spinning at {angvel} rad/s off rails"
+    );
+    let angle = world.get::<RootSpaceAngle>(landed).unwrap().0;
+    assert!(
+        (angle - 2.0 * SPIN).abs() < 1e-6,
+        "turned to {angle} rad in 2 s"
+    );
+}
@@ -0,0 +1,158 @@
+use bevy::{ecs::message::MessageCursor, math::DVec2, prelude::*};
+use hack_club_space_program::{
+    components::main_game::{
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::{CelestialParent, SoiMembers},
+    },
+    messages::relations::SoiChanged,
+    resources::simulation::ActiveVessel,
+    test_util,
+};
+
+mod common;
+
+fn read_soi_changes(app: &App, cursor: &mut MessageCursor<SoiChanged>) -> Vec<SoiChanged> {
+    let messages = app.world().resource::<Messages<SoiChanged>>();
+    cursor.read(messages).copied().collect()
+}
+
+#[test]
+fn test_soi_changed_messages() {
+    let mut app = common::setup_default();
+
+    let spawn_body = |app: &mut App, name: &'static str| {
+        app.world_mut()
+            .spawn(test_util::body_builder(name, 10.0, 0.0).build_without_terrain())
+            .id()
+    };
+
+    let alpha = spawn_body(&mut app, "Alpha");
+    let beta = spawn_body(&mut app, "Beta");
+    let moon = spawn_body(&mut app, "Moon");
+    app.world_mut()
+        .entity_mut(moon)
+        .insert(CelestialParent { entity: alpha });
+
+    let vessel_pos = RootSpacePosition(DVec2::new(0.0, 1000.0));
+    let vessel_vel = RootSpaceLinearVelocity(DVec2::ZERO);
+
+    let vessel = common::spawn_active_vessel(
+        &mut app,
+        test_util::vessel_builder("Vessel", alpha, vessel_pos, vessel_vel),
+    );
+
+    let mut cursor = app.world().resource::<Messages<SoiChanged>>().get_cursor();
+
+    app.update();
+
+    assert!(
+        read_soi_changes(&app, &mut cursor).is_empty(),
+        "spawning shouldn't count as an SOI change"
+    );
+
+    app.world_mut()
+        .entity_mut(vessel)
+        .insert(CelestialParent { entity: beta });
+    app.world_mut()
+        .entity_mut(moon)
+        .insert(CelestialParent { entity: beta });
+
+    app.update();
+
+    let mut changes = read_soi_changes(&app, &mut cursor);
+    changes.sort_by_key(|change| change.vessel);
+
+    let mut expected = vec![
+        SoiChanged {
+            vessel,
+            from: alpha,
+            to: beta,
+        },
+        SoiChanged {
+            vessel: moon,
+            from: alpha,
+            to: beta,
+        },
+    ];
+    expected.sort_by_key(|change| change.vessel);
+
+    assert_eq!(changes, expected);
+
+    (0..4).for_each(|_| app.update());
+
+    assert!(
+        read_soi_changes(&app, &mut cursor).is_empty(),
+        "SOI changes should only be sent once per transition"
+    );
+}
+
+#[test]
+fn test_soi_members_follow_parents() {
+    let mut app = common::setup_default();
+
+    let spawn_body = |app: &mut App, name: &'static str| {
+        app.world_mut()
+            .spawn(test_util::body_builder(name, 10.0, 0.0).build_without_terrain())
+            .id()
+    };
+
+    let alpha = spawn_body(&mut app, "Alpha");
+    let beta = spawn_body(&mut app, "Beta");
+    let moon = spawn_body(&mut app, "Moon");
+    app.world_mut()
+        .entity_mut(moon)
+        .insert(CelestialParent { entity: alpha });
+
+    let spawn_vessel = |app: &mut App, parent: Entity, x: f64| {
+        app.world_mut()
+            .spawn(
+                test_util::vessel_builder(
+                    "Vessel",
+                    parent,
+                    RootSpacePosition(DVec2::new(x, 1000.0)),
+                    RootSpaceLinearVelocity(DVec2::ZERO),
+                )
+                .build_rigid(),
+            )
+            .id()
+    };
+
+    let first = spawn_vessel(&mut app, alpha, 0.0);
+    let second = spawn_vessel(&mut app, alpha, 1000.0);
+    let third = spawn_vessel(&mut app, beta, 2000.0);
+
+    app.insert_resource(ActiveVessel {
+        entity: first,
+        prev_tick_parent: alpha,
+        prev_tick_position: RootSpacePosition(DVec2::new(0.0, 1000.0)),
+        prev_tick_velocity: RootSpaceLinearVelocity(DVec2::ZERO),
+    });
+
+    let members = |app: &App, body: Entity| {
+        let mut members = app.world().get::<SoiMembers>(body).unwrap().to_vec();
+        members.sort();
+        members
+    };
+    let sorted = |mut entities: Vec<Entity>| {
+        entities.sort();
+        entities
+    };
+
+    common::run_for_ticks(&mut app, 1);
+
+    // The moon orbits Alpha, but isn't a vessel
+    assert_eq!(members(&app, alpha), sorted(vec![first, second]));
+    assert_eq!(members(&app, beta), [third]);
+    assert!(members(&app, moon).is_empty());
+
+    app.world_mut()
+        .entity_mut(second)
+        .insert(CelestialParent { entity: beta });
+    app.world_mut().despawn(third);
+
+    common::run_for_ticks(&mut app, 1);
+
+    assert_eq!(members(&app, alpha), [first]);
+    assert_eq!(members(&app, beta), [second]);
+    assert!(members(&app, moon).is_empty());
+}
@@ -0,0 +1,204 @@
+use bevy::{math::DVec2, prelude::*};
+use hack_club_space_program::{
+    builders::solar_system::{BodySpec, OrbitSpec, SystemConfig, SystemConfigError},
+    components::main_game::{
+        celestial::CelestialBody,
+        frames::RootSpacePosition,
+        relations::{CelestialChildren, CelestialParent, RailMode},
+    },
+    consts::GRAVITATIONAL_CONSTANT,
+    resources::simulation::GravityConstants,
+};
+use keplerian_sim::OrbitTrait2D;
+
+mod common;
+
+const SUN_MASS: f64 = 1.989e30;
+const PLANET_MASS: f64 = 5.972e24;
+const MOON_MASS: f64 = 7.342e22;
+
+fn three_body_config(moon_distance: f64) -> SystemConfig {
+    let moon = BodySpec {
+        name: "Moon".into(),
+        radius: 1.737e6,
+        mass: MOON_MASS,
+        position: None,
+        orbit: Some(OrbitSpec {
+            semi_major_axis: moon_distance,
+            eccentricity: 0.05,
+            arg_pe: 0.0,
+            mean_anomaly: 1.0,
+        }),
+        terrain: None,
+        children: Vec::new(),
+    };
+
+    let planet = BodySpec {
+        name: "Planet".into(),
+        radius: 6.371e6,
+        mass: PLANET_MASS,
+        position: None,
+        orbit: Some(OrbitSpec {
+            semi_major_axis: 1.496e11,
+            eccentricity: 0.0167,
+            arg_pe: 0.5,
+            mean_anomaly: 0.0,
+        }),
+        terrain: None,
+        children: vec![moon],
+    };
+
+    SystemConfig {
+        bodies: vec![BodySpec {
+            name: "Sun".into(),
+            radius: 6.957e8,
+            mass: SUN_MASS,
+            position: None,
+            orbit: None,
+            terrain: None,
+            children: vec![planet],
+        }],
+    }
+}
+
+fn spawn_config(app: &mut App, config: &SystemConfig) -> Result<Vec<Entity>, SystemConfigError> {
+    let (mesh, material) = common::empty_mesh_material(app);
+    let constants = *app.world().resource::<GravityConstants>();
+
+    let world = app.world_mut();
+    let spawned = config.spawn(&mut world.commands(), &constants, |_| {
+        (mesh.clone(), material.clone())
+    });
+    world.flush();
+
+    spawned
+}
+
+#[test]
+fn test_three_body_hierarchy() {
+    let mut app = common::setup_default();
+
+    let spawned = spawn_config(&mut app, &three_body_config(3.844e8)).unwrap();
+    let [sun, planet, moon] = spawned[..] else {
+        panic!("expected 3 bodies, got {}", spawned.len());
+    };
+
+    app.update();
+
+    let world = app.world();
+    let name = |entity| world.get::<Name>(entity).unwrap().as_str();
+    assert_eq!(
+        [name(sun), name(planet), name(moon)],
+        ["Sun", "Planet", "Moon"]
+    );
+
+    assert!(world.get::<CelestialParent>(sun).is_none());
+    assert!(matches!(
+        world.get::<RailMode>(sun),
+        None | Some(RailMode::None)
+    ));
+    assert_eq!(world.get::<CelestialParent>(planet).unwrap().entity, sun);
+    assert_eq!(world.get::<CelestialParent>(moon).unwrap().entity, planet);
+
+    let children = |entity| {
+        world
+            .get::<CelestialChildren>(entity)
+            .map(|children| children.to_vec())
+            .unwrap_or_default()
+    };
+    assert_eq!(children(sun), [planet]);
+    assert_eq!(children(planet), [moon]);
+    assert!(children(moon).is_empty());
+
+    for (entity, parent_mass, semi_major_axis) in
+        [(planet, SUN_MASS, 1.496e11), (moon, PLANET_MASS, 3.844e8)]
+    {
+        let orbit = world
+            .get::<RailMode>(entity)
+            .and_then(RailMode::as_orbit)
+            .expect("child bodies should be on orbit rails");
+
+        let mu = parent_mass * GRAVITATIONAL_CONSTANT;
+        assert!((orbit.get_gravitational_parameter() / mu - 1.0).abs() < 1e-9);
+        assert!((orbit.get_semi_major_axis() / semi_major_axis - 1.0).abs() < 1e-9);
+    }
+
+    let moon_body = world.get::<CelestialBody>(moon).unwrap();
+    assert!((moon_body.mass - MOON_MASS).abs() < f64::EPSILON);
+}
+
+#[test]
+fn test_top_level_bodies_get_placed() {
+    let mut app = common::setup_default();
+
+    let mut config = three_body_config(3.844e8);
+    let sun_pos = DVec2::new(-2e9, 3e9);
+    config.bodies[0].position = Some(sun_pos);
+
+    let spawned = spawn_config(&mut app, &config).unwrap();
+    let [sun, planet, ..] = spawned[..] else {
+        panic!("expected 3 bodies, got {}", spawned.len());
+    };
+
+    common::run_for_ticks(&mut app, 1);
+
+    let world = app.world();
+    assert_eq!(
+        *world.get::<RootSpacePosition>(sun).unwrap(),
+        RootSpacePosition(sun_pos)
+    );
+
+    // The planet's orbit is around wherever the sun is
+    let planet_pos = world.get::<RootSpacePosition>(planet).unwrap();
+    let distance = planet_pos.distance_to(RootSpacePosition(sun_pos));
+    assert!((1.4e11..1.53e11).contains(&distance), "{distance} m away");
+}
+
+#[test]
+fn test_invalid_config_spawns_nothing() {
+    let mut app = common::setup_default();
+
+    // Far past the planet's sphere of influence, of about 9.2e8 m
+    let err = spawn_config(&mut app, &three_body_config(2e9)).unwrap_err();
+    let SystemConfigError::OutsideSoi {
+        body, parent, soi, ..
+    } = &err
+    else {
+        panic!("expected an SOI error, got {err:?}");
+    };
+    assert_eq!((body.as_str(), parent.as_str()), ("Moon", "Planet"));
+    assert!((soi / 9.25e8 - 1.0).abs() < 1e-2, "SOI of {soi} m");
+    assert!(err.to_string().contains("sphere of influence"));
+
+    let err = spawn_config(&mut app, &three_body_config(5e6)).unwrap_err();
+    assert!(matches!(err, SystemConfigError::IntersectsParent { .. }));
+
+    let mut orphan = three_body_config(3.844e8);
+    orphan.bodies[0].children[0].orbit = None;
+    let err = spawn_config(&mut app, &orphan).unwrap_err();
+    assert_eq!(
+        err,
+        SystemConfigError::MissingOrbit {
+            body: "Planet".into(),
+            parent: "Sun".into(),
+        }
+    );
+
+    let mut misplaced = three_body_config(3.844e8);
+    misplaced.bodies[0].children[0].position = Some(DVec2::new(1e11, 0.0));
+    let err = spawn_config(&mut app, &misplaced).unwrap_err();
+    assert_eq!(
+        err,
+        SystemConfigError::PositionWithParent {
+            body: "Planet".into(),
+            parent: "Sun".into(),
+        }
+    );
+
+    let bodies = app
+        .world_mut()
+        .query::<&CelestialBody>()
+        .iter(app.world())
+        .count();
+    assert_eq!(bodies, 0);
+}
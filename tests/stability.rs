@@ -1,14 +1,14 @@
 use bevy::prelude::*;
-use bevy_rapier2d::prelude::{AdditionalMassProperties, Collider};
+use bevy_rapier2d::prelude::Collider;
 use hack_club_space_program::{
-    builders::{celestial::CelestialBodyBuilder, vessel::VesselBuilder},
+    builders::vessel::VesselBuilder,
     components::main_game::{
         celestial::Terrain,
         frames::{RootSpaceLinearVelocity, RootSpacePosition},
-        relations::{CelestialParent, RailMode},
     },
     consts::{GRAVITATIONAL_CONSTANT, GRAVITY_MIN_RADIUS},
     resources::simulation::ActiveVessel,
+    test_util,
 };
 use keplerian_sim::{Orbit2D, OrbitTrait2D};
 
@@ -46,30 +46,22 @@ impl OrbitSim {
 fn test_orbit_stability() {
     let mut app = common::setup_default();
 
-    let (mesh, material) = common::empty_mesh_material(&mut app);
-
     let body = app
         .world_mut()
         .spawn(
             #[expect(clippy::cast_possible_truncation)]
-            CelestialBodyBuilder {
-                name: Name::new("Earth"),
-                mesh: mesh.clone(),
-                material: material.clone(),
-                angle: 0.0,
-                mass: BODY_MASS,
-                radius: BODY_RADIUS as f32,
-            }
-            .build_with_terrain(Terrain {
-                frequency: 2.0,
-                gain: 0.5,
-                seed: 17,
-                lacunarity: 1.0,
-                octaves: 3,
-                offset: 1.0,
-                multiplier: 1.0,
-                subdivs: 4,
-            }),
+            test_util::body_builder("Earth", BODY_RADIUS as f32, BODY_MASS).build_with_terrain(
+                Terrain {
+                    frequency: 2.0,
+                    gain: 0.5,
+                    seed: 17,
+                    lacunarity: 1.0,
+                    octaves: 3,
+                    offset: 1.0,
+                    multiplier: 1.0,
+                    subdivs: 4,
+                },
+            ),
         )
         .id();
 
@@ -82,17 +74,13 @@ fn test_orbit_stability() {
         .world_mut()
         .spawn(
             VesselBuilder {
-                name: Name::new("Satellite"),
                 collider: Collider::ball(0.0),
-                mass: AdditionalMassProperties::Mass(1.0),
-                parent: CelestialParent { entity: body },
-                rail_mode: RailMode::None,
-                position: RootSpacePosition(vessel_init_sv.position),
-                linvel: RootSpaceLinearVelocity(vessel_init_sv.velocity),
-                mesh,
-                material,
-                angvel: 0.0,
-                angle: 0.0,
+                ..test_util::vessel_builder(
+                    "Satellite",
+                    body,
+                    RootSpacePosition(vessel_init_sv.position),
+                    RootSpaceLinearVelocity(vessel_init_sv.velocity),
+                )
             }
             .build_rigid(),
         )
@@ -175,30 +163,22 @@ fn test_simplified_model() {
 fn test_equal_to_simplified() {
     let mut app = common::setup_default();
 
-    let (mesh, material) = common::empty_mesh_material(&mut app);
-
     let body = app
         .world_mut()
         .spawn(
             #[expect(clippy::cast_possible_truncation)]
-            CelestialBodyBuilder {
-                name: Name::new("Earth"),
-                mesh: mesh.clone(),
-                material: material.clone(),
-                angle: 0.0,
-                mass: BODY_MASS,
-                radius: BODY_RADIUS as f32,
-            }
-            .build_with_terrain(Terrain {
-                frequency: 2.0,
-                gain: 0.5,
-                seed: 17,
-                lacunarity: 1.0,
-                octaves: 3,
-                offset: 1.0,
-                multiplier: 1.0,
-                subdivs: 4,
-            }),
+            test_util::body_builder("Earth", BODY_RADIUS as f32, BODY_MASS).build_with_terrain(
+                Terrain {
+                    frequency: 2.0,
+                    gain: 0.5,
+                    seed: 17,
+                    lacunarity: 1.0,
+                    octaves: 3,
+                    offset: 1.0,
+                    multiplier: 1.0,
+                    subdivs: 4,
+                },
+            ),
         )
         .id();
 
@@ -216,18 +196,15 @@ fn test_equal_to_simplified() {
         .world_mut()
         .spawn(
             VesselBuilder {
-                name: Name::new("Satellite"),
                 // collider: Collider::ball(0.0),
                 collider: Collider::cuboid(2.0, 4.0),
-                mass: AdditionalMassProperties::Mass(1.0),
-                parent: CelestialParent { entity: body },
-                rail_mode: RailMode::None,
-                position: RootSpacePosition(vessel_init_sv.position),
-                linvel: RootSpaceLinearVelocity(vessel_init_sv.velocity),
-                mesh,
-                material,
                 angvel: 0.5,
-                angle: 0.0,
+                ..test_util::vessel_builder(
+                    "Satellite",
+                    body,
+                    RootSpacePosition(vessel_init_sv.position),
+                    RootSpaceLinearVelocity(vessel_init_sv.velocity),
+                )
             }
             .build_rigid(),
         )
@@ -0,0 +1,106 @@
+use bevy::{ecs::message::MessageCursor, math::DVec2, prelude::*};
+use hack_club_space_program::{
+    components::main_game::{
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::RailMode,
+        vessel::{Engine, OrbitalVelocity, SurfaceVelocity},
+    },
+    messages::telemetry::TelemetryFrame,
+    resources::simulation::{ActiveVessel, TelemetryEnabled},
+    test_util,
+};
+
+mod common;
+
+fn read_frames(app: &App, cursor: &mut MessageCursor<TelemetryFrame>) -> Vec<TelemetryFrame> {
+    let messages = app.world().resource::<Messages<TelemetryFrame>>();
+    cursor.read(messages).copied().collect()
+}
+
+#[test]
+fn test_telemetry_frames() {
+    let mut app = common::setup_default();
+
+    let body = app
+        .world_mut()
+        .spawn(test_util::body_builder("Body", 10.0, 0.0).build_without_terrain())
+        .id();
+
+    let vessel_pos = RootSpacePosition(DVec2::new(0.0, 1000.0));
+    let vessel_vel = RootSpaceLinearVelocity(DVec2::new(5.0, 0.0));
+
+    let vessel = app
+        .world_mut()
+        .spawn((
+            test_util::vessel_builder("Vessel", body, vessel_pos, vessel_vel).build_rigid(),
+            Engine {
+                max_thrust: 1.0,
+                fuel: 30.0,
+            },
+        ))
+        .id();
+
+    app.insert_resource(ActiveVessel {
+        entity: vessel,
+        prev_tick_parent: body,
+        prev_tick_position: vessel_pos,
+        prev_tick_velocity: vessel_vel,
+    });
+
+    let mut cursor = app
+        .world()
+        .resource::<Messages<TelemetryFrame>>()
+        .get_cursor();
+
+    app.update();
+
+    assert!(
+        read_frames(&app, &mut cursor).is_empty(),
+        "telemetry should be opt-in"
+    );
+
+    app.insert_resource(TelemetryEnabled);
+
+    let mut last_time = None;
+
+    for _ in 0..8 {
+        app.update();
+
+        let frames = read_frames(&app, &mut cursor);
+        assert_eq!(frames.len(), 1, "expected exactly one frame per tick");
+        let frame = frames[0];
+
+        let entity = app.world().entity(vessel);
+        let position = *entity.get::<RootSpacePosition>().unwrap();
+        let velocity = *entity.get::<RootSpaceLinearVelocity>().unwrap();
+
+        assert_eq!(frame.vessel, vessel);
+        assert_eq!(frame.parent, body);
+        assert_eq!(frame.position, position);
+        assert_eq!(frame.velocity, velocity);
+        assert_eq!(frame.rel_position, position.0);
+        assert_eq!(frame.rel_velocity, velocity.0);
+        assert_eq!(
+            frame.orbital_velocity,
+            *entity.get::<OrbitalVelocity>().unwrap()
+        );
+        assert_eq!(frame.rail_mode, *entity.get::<RailMode>().unwrap());
+        assert!((frame.altitude - (position.0.length() - 10.0)).abs() < 1e-9);
+        assert_eq!(
+            frame.surface_velocity,
+            *entity.get::<SurfaceVelocity>().unwrap()
+        );
+        assert!(!frame.landed, "vessel is nowhere near the surface");
+        assert!((frame.fuel - entity.get::<Engine>().unwrap().fuel).abs() < 1e-12);
+
+        if let Some(last_time) = last_time {
+            assert!(frame.time > last_time, "time should advance every frame");
+        }
+        last_time = Some(frame.time);
+    }
+
+    app.world_mut().remove_resource::<TelemetryEnabled>();
+    app.update();
+
+    assert!(read_frames(&app, &mut cursor).is_empty());
+}
@@ -0,0 +1,151 @@
+use bevy::{math::DVec2, prelude::*};
+use bevy_rapier2d::{plugin::RapierContextColliders, prelude::*};
+use hack_club_space_program::{
+    builders::{
+        celestial::{CelestialBodyBuilder, CelestialSurface},
+        vessel::VesselBuilder,
+    },
+    components::main_game::{
+        celestial::Terrain,
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+    },
+    resources::simulation::PhysicsConfig,
+    test_util,
+};
+
+use crate::common::TestAppConfig;
+
+mod common;
+
+const BODY_RADIUS: f64 = 1000.0;
+
+#[test]
+fn test_terrain_collider_interval() {
+    let mut app = common::setup(TestAppConfig {
+        physics: PhysicsConfig {
+            terrain_collider_interval: 4,
+            ..PhysicsConfig::DEFAULT
+        },
+        ..TestAppConfig::DEFAULT
+    });
+
+    let body = app
+        .world_mut()
+        .spawn(
+            #[expect(clippy::cast_possible_truncation)]
+            test_util::body_builder("Body", BODY_RADIUS as f32, 0.0).build_with_terrain(Terrain {
+                seed: 1,
+                octaves: 3,
+                frequency: 2.0,
+                gain: 0.5,
+                lacunarity: 1.0,
+                offset: BODY_RADIUS,
+                multiplier: 10.0,
+                subdivs: 4,
+            }),
+        )
+        .id();
+
+    let vessel_pos = RootSpacePosition(DVec2::new(0.0, BODY_RADIUS + 9.0));
+    let vessel_vel = RootSpaceLinearVelocity(DVec2::ZERO);
+
+    common::spawn_active_vessel(
+        &mut app,
+        VesselBuilder {
+            collider: Collider::ball(0.5),
+            ..test_util::vessel_builder("Vessel", body, vessel_pos, vessel_vel)
+        },
+    );
+
+    // Let spawning-related change detection settle
+    app.update();
+
+    let mut rebuilt_ticks = vec![];
+
+    for tick in 0..12 {
+        app.update();
+
+        let collider = app
+            .world()
+            .entity(body)
+            .get_ref::<Collider>()
+            .expect("body should have a collider");
+
+        if collider.is_changed() {
+            rebuilt_ticks.push(tick);
+        }
+
+        assert!(
+            collider.as_compound().is_some(),
+            "terrain collider should persist between rebuilds (tick {tick})"
+        );
+    }
+
+    assert_eq!(
+        rebuilt_ticks.len(),
+        3,
+        "collider should be rebuilt once every four ticks, got {rebuilt_ticks:?}"
+    );
+    assert!(
+        rebuilt_ticks.windows(2).all(|pair| pair[1] - pair[0] == 4),
+        "collider should be rebuilt once every four ticks, got {rebuilt_ticks:?}"
+    );
+}
+
+#[test]
+fn test_terrain_collider_keeps_surface_material() {
+    let mut app = common::setup_default();
+
+    let body = app
+        .world_mut()
+        .spawn(
+            #[expect(clippy::cast_possible_truncation)]
+            CelestialBodyBuilder {
+                surface: CelestialSurface::new(0.05, 0.3),
+                ..test_util::body_builder("Icy body", BODY_RADIUS as f32, 0.0)
+            }
+            .build_with_terrain(Terrain {
+                seed: 1,
+                octaves: 3,
+                frequency: 2.0,
+                gain: 0.5,
+                lacunarity: 1.0,
+                offset: BODY_RADIUS,
+                multiplier: 10.0,
+                subdivs: 4,
+            }),
+        )
+        .id();
+
+    let vessel_pos = RootSpacePosition(DVec2::new(0.0, BODY_RADIUS + 9.0));
+    let vessel_vel = RootSpaceLinearVelocity(DVec2::ZERO);
+
+    let vessel = common::spawn_active_vessel(
+        &mut app,
+        VesselBuilder {
+            collider: Collider::ball(0.5),
+            ..test_util::vessel_builder("Vessel", body, vessel_pos, vessel_vel)
+        },
+    );
+
+    // Give the terrain collider time to get rebuilt around the vessel
+    common::run_for_ticks(&mut app, 4);
+
+    let handle = app
+        .world()
+        .get::<RapierColliderHandle>(body)
+        .expect("body collider should be in the physics world")
+        .0;
+    let colliders = app
+        .world_mut()
+        .query::<&RapierContextColliders>()
+        .single(app.world())
+        .expect("there should be a single Rapier context");
+    let raw = colliders
+        .colliders
+        .get(handle)
+        .expect("body collider should be in the collider set");
+
+    assert!((raw.friction() - 0.05).abs() < 1e-6);
+    assert!((raw.restitution() - 0.3).abs() < 1e-6);
+}
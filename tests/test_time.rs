@@ -3,6 +3,7 @@
 use core::sync::atomic::{AtomicU8, Ordering};
 
 use bevy::prelude::*;
+use hack_club_space_program::{resources::simulation::FixedTickCounter, test_util::step_fixed};
 
 mod common;
 
@@ -33,3 +34,31 @@ fn time_works() {
 
     assert_eq!(TICKS.load(Ordering::SeqCst), 2);
 }
+
+#[test]
+fn step_fixed_runs_one_tick() {
+    let mut app = common::setup(common::TestAppConfig {
+        forward_time_on_update: false,
+        ..common::TestAppConfig::DEFAULT
+    });
+
+    let ticks = |app: &App| app.world().resource::<FixedTickCounter>().0;
+    let elapsed = |app: &App| app.world().resource::<Time<Fixed>>().elapsed();
+    let timestep = app.world().resource::<Time<Fixed>>().timestep();
+
+    let start_ticks = ticks(&app);
+    let start_elapsed = elapsed(&app);
+
+    // Updating without time passing runs no ticks at all
+    app.update();
+    assert_eq!(ticks(&app), start_ticks);
+
+    for i in 1..=3 {
+        step_fixed(&mut app);
+        assert_eq!(ticks(&app), start_ticks + i);
+        assert_eq!(
+            elapsed(&app),
+            start_elapsed + timestep * u32::try_from(i).unwrap()
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use bevy::{math::DVec2, prelude::*};
+use hack_club_space_program::{
+    components::main_game::{
+        frames::{RootSpaceLinearVelocity, RootSpacePosition},
+        relations::RailMode,
+    },
+    consts::{GRAVITATIONAL_CONSTANT, WARP_RATES},
+    messages::warp::WarpTo,
+    orbit::ApsisTarget,
+    resources::simulation::TimeWarp,
+    test_util,
+};
+use keplerian_sim::OrbitTrait2D;
+
+mod common;
+
+const MAX_UPDATES: usize = 10_000;
+
+fn distance_from_body(app: &App, vessel: Entity, body: Entity) -> f64 {
+    let world = app.world();
+    let vessel_pos = world.get::<RootSpacePosition>(vessel).unwrap();
+    let body_pos = world.get::<RootSpacePosition>(body).unwrap();
+    vessel_pos.distance(body_pos.0)
+}
+
+fn warp_to(app: &mut App, vessel: Entity, target: ApsisTarget) {
+    app.world_mut().write_message(WarpTo { vessel, target });
+
+    app.update();
+    assert!(
+        app.world().resource::<TimeWarp>().until.is_some(),
+        "warp should've started"
+    );
+
+    for _ in 0..MAX_UPDATES {
+        app.update();
+
+        if app.world().resource::<TimeWarp>().until.is_none() {
+            // Let the virtual clock pick up the reset rate
+            app.update();
+            return;
+        }
+    }
+
+    panic!("warp never stopped");
+}
+
+#[test]
+fn test_warp_to_apsis() {
+    let mut app = common::setup_default();
+
+    // ~100s period at 1km
+    let body_mass = 4e6 * core::f64::consts::PI.powi(2) / GRAVITATIONAL_CONSTANT;
+    let body_mu = body_mass * GRAVITATIONAL_CONSTANT;
+
+    let body = app
+        .world_mut()
+        .spawn(test_util::body_builder("Body", 10.0, body_mass).build_without_terrain())
+        .id();
+
+    let circular_speed = (body_mu / 1000.0).sqrt();
+    let vessel_pos = RootSpacePosition(DVec2::new(1000.0, 0.0));
+    let vessel_vel = RootSpaceLinearVelocity(DVec2::new(0.0, 1.2 * circular_speed));
+
+    let vessel = common::spawn_active_vessel(
+        &mut app,
+        test_util::vessel_builder("Vessel", body, vessel_pos, vessel_vel),
+    );
+
+    app.update();
+
+    let orbit = app
+        .world()
+        .get::<RailMode>(vessel)
+        .unwrap()
+        .as_orbit()
+        .expect("vessel should be orbiting");
+
+    warp_to(&mut app, vessel, ApsisTarget::Apoapsis);
+
+    let distance = distance_from_body(&app, vessel, body);
+    assert!(
+        (distance - orbit.get_apoapsis()).abs() < 1e-3 * orbit.get_apoapsis(),
+        "expected to be at apoapsis {}, got {distance}",
+        orbit.get_apoapsis()
+    );
+    assert!(
+        (app.world().resource::<Time<Virtual>>().relative_speed_f64() - 1.0).abs() < 1e-12,
+        "warp should be stopped"
+    );
+
+    warp_to(&mut app, vessel, ApsisTarget::Periapsis);
+
+    let distance = distance_from_body(&app, vessel, body);
+    assert!(
+        (distance - orbit.get_periapsis()).abs() < 1e-3 * orbit.get_periapsis(),
+        "expected to be at periapsis {}, got {distance}",
+        orbit.get_periapsis()
+    );
+}
+
+/// Spawns a vessel at the given state around a body with
+/// a 100s period at 1km, and tries to warp at the fastest rate.
+///
+/// # Output
+/// The warp rate left after a few updates.
+fn warp_rate_around_body(position: DVec2, velocity: DVec2) -> f64 {
+    let mut app = common::setup_default();
+
+    let body_mass = 4e6 * core::f64::consts::PI.powi(2) / GRAVITATIONAL_CONSTANT;
+
+    let body = app
+        .world_mut()
+        .spawn(test_util::body_builder("Body", 10.0, body_mass).build_without_terrain())
+        .id();
+
+    common::spawn_active_vessel(
+        &mut app,
+        test_util::vessel_builder(
+            "Vessel",
+            body,
+            RootSpacePosition(position),
+            RootSpaceLinearVelocity(velocity),
+        ),
+    );
+    app.insert_resource(TimeWarp {
+        rate: WARP_RATES[WARP_RATES.len() - 1],
+        until: None,
+    });
+
+    for _ in 0..3 {
+        app.update();
+    }
+
+    app.world().resource::<TimeWarp>().rate
+}
+
+#[test]
+fn test_warp_limited_near_periapsis() {
+    let body_mu = 4e6 * core::f64::consts::PI.powi(2);
+
+    let high_radius = 1e6;
+    let high_rate = warp_rate_around_body(
+        DVec2::new(high_radius, 0.0),
+        DVec2::new(0.0, (body_mu / high_radius).sqrt()),
+    );
+    assert!(
+        (high_rate - WARP_RATES[WARP_RATES.len() - 1]).abs() < f64::EPSILON,
+        "a high circular orbit shouldn't limit warping, but it got limited to {high_rate}x"
+    );
+
+    // At the periapsis of an orbit from 1e6m down to 100m
+    let (periapsis, apoapsis) = (100.0, 1e6);
+    let periapsis_speed = (2.0 * body_mu * apoapsis / (periapsis * (periapsis + apoapsis))).sqrt();
+    let low_rate =
+        warp_rate_around_body(DVec2::new(periapsis, 0.0), DVec2::new(0.0, periapsis_speed));
+    assert!(
+        low_rate < high_rate,
+        "a low periapsis should limit warping more than a high circular orbit"
+    );
+    assert!(
+        WARP_RATES.contains(&low_rate),
+        "warping got limited to {low_rate}x, which isn't one of the steps"
+    );
+}